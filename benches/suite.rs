@@ -0,0 +1,22 @@
+use std::time::Duration;
+
+use blitzbeaver::benchmark::{benchmarks, BenchmarkData};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn bench_suite(c: &mut Criterion) {
+    let data = BenchmarkData::generate(500, 4, 0).unwrap();
+
+    let mut group = c.benchmark_group("blitzbeaver");
+    group
+        .sample_size(20)
+        .warm_up_time(Duration::from_millis(500))
+        .measurement_time(Duration::from_secs(2));
+    for mut benchmark in benchmarks(&data) {
+        let name = benchmark.name.clone();
+        group.bench_function(name, |b| b.iter(|| benchmark.run()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_suite);
+criterion_main!(benches);
@@ -7,11 +7,20 @@ from .blitzbeaver import (
     DistanceMetricConfig,
     ResolverConfig,
     RecordScorerConfig,
+    CompositeFeatureConfig,
     MemoryConfig,
     TrackerRecordDiagnostics,
     TrackerFrameDiagnostics,
     TrackerDiagnostics,
+    CacheDiagnostics,
+    WorkerFrameDiagnostics,
+    FramePerformanceDiagnostics,
+    PersistentCacheDiagnostics,
+    PerformanceDiagnostics,
     NormalizationConfig,
+    GeneratorConfig,
+    GeneratorEventsConfig,
+    GeneratorNoiseConfig,
     Diagnostics,
     compute_median_word,
     compute_words_clusters,
@@ -23,12 +32,19 @@ from .literals import (
     ID,
     DistanceMetric,
     MemoryStrategy,
+    MedianStrategy,
     ResolvingStrategy,
     RecordScorer,
+    GraphFormat,
+    SearchObjective,
+    SearchStrategy,
 )
 from .logger import setup_logger, LogLevel
 from .tracking import execute_tracking
 from .normalization import execute_normalization
+from .search import execute_search
+from .generator import generate_census, SyntheticCensus
+from .benchmark import benchmark_suite
 from .exceptions import (
     BlitzBeaverException,
     InvalidConfigException,
@@ -44,14 +60,20 @@ from .config import config
 from .serialization import (
     serialize_distance_metric_config,
     serialize_record_scorer_config,
+    serialize_composite_feature_config,
     serialize_memory_config,
     serialize_resolver_config,
     serialize_tracker_config,
     serialize_tracking_config,
+    serialize_normalization_config,
+    serialize_record_schema,
     deserialize_distance_metric_config,
     deserialize_record_scorer_config,
+    deserialize_composite_feature_config,
     deserialize_memory_config,
     deserialize_resolver_config,
     deserialize_tracker_config,
     deserialize_tracking_config,
+    deserialize_normalization_config,
+    deserialize_record_schema,
 )
@@ -0,0 +1,51 @@
+import polars as pl
+
+from .blitzbeaver import benchmark_suite as _benchmark_suite
+from .logger import setup_logger, LogLevel
+
+
+def benchmark_suite(
+    num_households: int = 500,
+    num_frames: int = 4,
+    seed: int = 0,
+    num_samples: int = 20,
+    warm_up_time: float = 0.5,
+    measurement_time: float = 2.0,
+    filter: str | None = None,
+    log_level: LogLevel = "info",
+) -> pl.DataFrame:
+    """
+    Runs the benchmark suite on a synthetic census: every distance metric,
+    the precomputation of the cache, the median word, the UPGMA clustering
+    and a full run of the tracking engine.
+
+    The results can be compared across versions to catch performance regressions.
+
+    Args:
+        num_households: Number of households of the synthetic census
+        num_frames: Number of frames of the synthetic census (at least 2)
+        seed: Seed of the synthetic census
+        num_samples: Number of samples measured for each benchmark
+        warm_up_time: Duration (in seconds) each benchmark is run before being measured
+        measurement_time: Duration (in seconds) of the samples of each benchmark
+        filter: Only runs the benchmarks whose name contains the filter,
+            e.g. "distance/" or "engine"
+        log_level: The log level to set the logger to, defaults to "info".
+            This will not overwrite the logger if it has already been set up.
+
+    Returns:
+        One row per benchmark with the columns name, num_samples,
+        iterations_per_sample and the statistics of the duration of one run
+        in nanoseconds: mean_ns, median_ns, std_dev_ns, min_ns, max_ns.
+    """
+    setup_logger(log_level)
+
+    return _benchmark_suite(
+        num_households,
+        num_frames,
+        seed,
+        num_samples,
+        warm_up_time,
+        measurement_time,
+        filter,
+    )
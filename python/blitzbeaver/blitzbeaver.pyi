@@ -5,6 +5,7 @@ from .literals import (
     ResolvingStrategy,
     DistanceMetric,
     MemoryStrategy,
+    MedianStrategy,
     RecordScorer,
     ID,
 )
@@ -46,6 +47,20 @@ class RecordSchema:
     fields: list[FieldSchema]
 
     def __init__(self, fields: list[FieldSchema]) -> None: ...
+    @staticmethod
+    def from_json(content: str) -> "RecordSchema": ...
+    @staticmethod
+    def from_toml(content: str) -> "RecordSchema": ...
+    def to_json(self) -> str: ...
+    def to_toml(self) -> str: ...
+    def validate(self) -> None:
+        """
+        Validates the schema: it must have at least one field
+        and the names of the fields must be unique.
+
+        Raises:
+            ValueError: If the schema is invalid.
+        """
 
 # Config
 
@@ -59,8 +74,9 @@ class TrackingConfig:
     Number of threads to use for the tracking process.
     This defines the total number of threads that will be created
     for the tracking process.
-    Note that one thread is the supervisor thread and will not
-    have much work to do.
+    Note that in the "workers" engine mode, one thread is the supervisor
+    thread and will not have much work to do, in the "work-stealing"
+    engine mode all threads score the trackers.
     """
     tracker: "TrackerConfig"
     """
@@ -74,6 +90,20 @@ class TrackingConfig:
     """
     Configuration of the resolver.
     """
+    engine_mode: str | None
+    """
+    Parallelization mode of the engine, one of:
+    - "workers" (default): each worker thread manages a fixed share
+      of the trackers.
+    - "work-stealing": at each frame, the trackers are split into chunks
+      that the threads take as soon as they are idle, which balances
+      the load when some trackers are much costlier than others.
+    """
+    chunk_size: int | None
+    """
+    Number of trackers per chunk in the "work-stealing" engine mode,
+    by default a few chunks per thread.
+    """
 
     def __init__(
         self,
@@ -81,7 +111,24 @@ class TrackingConfig:
         tracker: "TrackerConfig",
         distance_metric: "DistanceMetricConfig",
         resolver: "ResolverConfig",
+        engine_mode: str | None = None,
+        chunk_size: int | None = None,
     ) -> None: ...
+    @staticmethod
+    def from_json(content: str) -> "TrackingConfig": ...
+    @staticmethod
+    def from_toml(content: str) -> "TrackingConfig": ...
+    def to_json(self) -> str: ...
+    def to_toml(self) -> str: ...
+    def validate(self, record_schema: RecordSchema) -> None:
+        """
+        Validates the configuration against the record schema.
+
+        Raises:
+            ValueError: If the configuration is invalid, the message names
+                the path of the invalid attribute, e.g.
+                `tracker.memories[1].multiword_distance_metric`.
+        """
 
 class ResolverConfig:
     """
@@ -92,8 +139,34 @@ class ResolverConfig:
 
     resolving_strategy: ResolvingStrategy
     """Resolving strategy"""
+    num_hypotheses: int | None
+    """
+    Maximal number of hypotheses kept by each tracker,
+    only used for the multi-hypothesis resolving strategy.
+    """
+    hypotheses_depth: int | None
+    """
+    Number of frames a tracker can defer its decision before committing
+    the path of its best hypothesis, only used for the multi-hypothesis
+    resolving strategy.
+    """
+    ambiguity_margin: float | None
+    """
+    When set, a match is rejected if the best and second-best scores
+    for the record or for the tracker are closer than the margin,
+    the tracker then doesn't match with any record for the frame.
+    A record rejected for the closeness of its trackers is left
+    unmatched, no new tracker is created for it.
+    The rejected matches are recorded in the resolving diagnostics.
+    """
 
-    def __init__(self, resolving_strategy: ResolvingStrategy) -> None: ...
+    def __init__(
+        self,
+        resolving_strategy: ResolvingStrategy,
+        num_hypotheses: int | None = None,
+        hypotheses_depth: int | None = None,
+        ambiguity_margin: float | None = None,
+    ) -> None: ...
 
 class DistanceMetricConfig:
     """
@@ -129,6 +202,13 @@ class DistanceMetricConfig:
     In case of `lv_multiword` distance metric, separator to use to split
     the string into multiple words.
     """
+    persistent_cache_bytes: int | None
+    """
+    Memory budget, in bytes, of the persistent cache of each feature.
+    The persistent cache keeps the distances across frames, it is
+    shared between the workers, the least recently used distances are
+    evicted when it is full. No persistent cache if None.
+    """
 
     def __init__(
         self,
@@ -138,6 +218,7 @@ class DistanceMetricConfig:
         lv_edit_weights: list[float] | None = None,
         lv_substring_weight: float | None = None,
         lv_multiword_separator: str | None = None,
+        persistent_cache_bytes: int | None = None,
     ) -> None: ...
 
 class MemoryConfig:
@@ -156,12 +237,35 @@ class MemoryConfig:
     """
     In case of multiword strategy, distance metric to use for computing the distance with the clusters
     """
+    median_strategy: MedianStrategy | None
+    """
+    In case of median strategy (median, ls-median, mw-median), how the median word is computed:
+
+    - graphemes (default): most frequent grapheme at each position once the words are aligned,
+      a fast approximation of the median word.
+    - set-median: the seen word with the minimal sum of distances to the seen words.
+    - refined-median: local search around the set median for a word, not necessarily
+      seen, with a smaller sum of distances.
+
+    The weighted median strategies (weighted-median, mw-weighted-median) only support graphemes.
+    """
+    recency_decay: float | None
+    """
+    In case of weighted median strategy, factor (between 0 and 1) applied to the weight
+    of a word for each frame since it was seen, 1 by default (no decay).
+
+    The weight of a word is the score of the record it comes from, multiplied
+    by the decay for each frame since then. With mw-weighted-median, only the
+    frames where the word of the memory is matched are counted.
+    """
 
     def __init__(
         self,
         memory_strategy: MemoryStrategy,
         multiword_threshold_match: float | None = None,
         multiword_distance_metric: DistanceMetricConfig | None = None,
+        median_strategy: MedianStrategy | None = None,
+        recency_decay: float | None = None,
     ) -> None: ...
 
 class RecordScorerConfig:
@@ -183,12 +287,64 @@ class RecordScorerConfig:
     to take into account in case some features are missing in the record. The min weight ratio is the minimum
     ratio of the total weights to take into account.
     """
+    missing_record_distances: list[float | None] | None
+    """
+    For each feature (field), distance to use when the element of the record is missing
+    (but not the one of the tracker memory). None means that the distance stays missing.
+    """
+    missing_memory_distances: list[float | None] | None
+    """
+    For each feature (field), distance to use when the tracker memory is empty
+    (but the element of the record is not). None means that the distance stays missing.
+    """
+    missing_both_distances: list[float | None] | None
+    """
+    For each feature (field), distance to use when both the element of the record
+    and the tracker memory are missing. None means that the distance stays missing.
+    """
+    composite_features: list["CompositeFeatureConfig"] | None
+    """
+    Composite features, each one gives a bonus to the score of a record when
+    all of its fields match with the tracker memory.
+    """
 
     def __init__(
         self,
         record_scorer: RecordScorer,
         weights: list[float] | None = None,
         min_weight_ratio: float | None = None,
+        missing_record_distances: list[float | None] | None = None,
+        missing_memory_distances: list[float | None] | None = None,
+        missing_both_distances: list[float | None] | None = None,
+        composite_features: list["CompositeFeatureConfig"] | None = None,
+    ) -> None: ...
+
+class CompositeFeatureConfig:
+    """
+    Configuration of a composite feature.
+    Combines multiple related fields (ex: spouse name and children names of
+    a household), their agreement is treated as extra evidence instead of
+    independent features.
+    """
+
+    fields: list[str]
+    """Names of the fields composing the feature"""
+    threshold_match: float
+    """
+    Minimal distance for a field to be considered as matching with the
+    tracker memory. In case of multi-strings field, at least one of the
+    strings must match.
+    """
+    bonus: float
+    """
+    Bonus added to the score of the record when all the fields match.
+    """
+
+    def __init__(
+        self,
+        fields: list[str],
+        threshold_match: float,
+        bonus: float,
     ) -> None: ...
 
 class TrackerConfig:
@@ -248,6 +404,17 @@ class NormalizationConfig:
     """
     Distance metric configuration
     """
+    median_strategy: MedianStrategy | None
+    """
+    How the median word of each cluster is computed, see `MemoryConfig.median_strategy`,
+    graphemes by default.
+    """
+    weighted_median: bool | None
+    """
+    Whether each word votes for the median word of its cluster with the score of
+    the link to its record, the first record of a chain counts fully.
+    Only supported by the graphemes median strategy, False by default.
+    """
 
     def __init__(
         self,
@@ -255,7 +422,114 @@ class NormalizationConfig:
         min_cluster_size: int,
         infer_missing_clusters: bool,
         distance_metric: DistanceMetricConfig,
+        median_strategy: MedianStrategy | None = None,
+        weighted_median: bool | None = None,
     ) -> None: ...
+    @staticmethod
+    def from_json(content: str) -> "NormalizationConfig": ...
+    @staticmethod
+    def from_toml(content: str) -> "NormalizationConfig": ...
+    def to_json(self) -> str: ...
+    def to_toml(self) -> str: ...
+    def validate(self) -> None:
+        """
+        Validates the configuration.
+
+        Raises:
+            ValueError: If the configuration is invalid.
+        """
+
+# Generator
+
+class GeneratorEventsConfig:
+    """
+    Probabilities of the events of the population between two frames,
+    for each person (or household for the moves and immigration).
+    """
+
+    birth_rate: float
+    death_rate: float
+    marriage_rate: float
+    move_rate: float
+    occupation_change_rate: float
+    leave_home_rate: float
+    """
+    Probability of an adult child to leave home and become
+    the head of a new household.
+    """
+    immigration_rate: float
+    """
+    Number of households arriving, relative to the number of households.
+    """
+
+    def __init__(
+        self,
+        birth_rate: float = 0.1,
+        death_rate: float = 0.02,
+        marriage_rate: float = 0.08,
+        move_rate: float = 0.1,
+        occupation_change_rate: float = 0.05,
+        leave_home_rate: float = 0.1,
+        immigration_rate: float = 0.03,
+    ) -> None: ...
+
+class GeneratorNoiseConfig:
+    """
+    Transcription noise applied to the records.
+    """
+
+    ocr_confusion_rate: float
+    """
+    Probability of each letter to be confused with a similar letter (ex: "n" and "u").
+    """
+    letter_drop_rate: float
+    """
+    Probability of each letter to be dropped.
+    """
+    missing_field_rate: float
+    """
+    Probability of each field to be missing.
+    """
+
+    def __init__(
+        self,
+        ocr_confusion_rate: float = 0.02,
+        letter_drop_rate: float = 0.01,
+        missing_field_rate: float = 0.02,
+    ) -> None: ...
+
+class GeneratorConfig:
+    """
+    Configuration of the synthetic census generator.
+    """
+
+    num_frames: int
+    num_households: int
+    """
+    Number of households in the first frame.
+    """
+    seed: int
+    events: GeneratorEventsConfig
+    noise: GeneratorNoiseConfig
+
+    def __init__(
+        self,
+        num_frames: int,
+        num_households: int,
+        seed: int = 0,
+        events: GeneratorEventsConfig | None = None,
+        noise: GeneratorNoiseConfig | None = None,
+    ) -> None: ...
+    @staticmethod
+    def from_json(content: str) -> "GeneratorConfig": ...
+    def to_json(self) -> str: ...
+    def validate(self) -> None:
+        """
+        Validates the configuration.
+
+        Raises:
+            ValueError: If the configuration is invalid.
+        """
 
 # Tracking graph
 
@@ -267,12 +541,66 @@ class ChainNode:
     frame_idx: int
     record_idx: int
 
+class ChainLink:
+    """
+    Matching information of a link between two chain nodes,
+    that is how confident the tracker was when it matched the record.
+    """
+
+    score: float
+    """Score of the record"""
+    distances: list[float | None]
+    """
+    For each feature, distance between the record and
+    the tracker's memory
+    """
+    margin: float | None
+    """
+    Difference between the score of the record and the best score
+    of the other records of interest of the tracker, None if there
+    was no other record.
+    """
+
 class GraphNode:
     """
     Internal class
     """
 
     outs: list[tuple[ID, ChainNode]]
+    links: list[tuple[ID, ChainLink]]
+
+class GraphEdit:
+    """
+    Manual edit of the tracking graph, the IDs of the chains created
+    by the edit are part of the edit such that it can be replayed.
+    """
+
+    class Split(GraphEdit):
+        """Splits the chain at the frame"""
+
+        id: ID
+        frame_idx: int
+        new_id: ID
+
+    class Merge(GraphEdit):
+        """Merges the other chain into the chain"""
+
+        id: ID
+        other_id: ID
+
+    class Detach(GraphEdit):
+        """Detaches the node of the chain at the frame into a new chain"""
+
+        id: ID
+        frame_idx: int
+        new_id: ID
+
+    class Reassign(GraphEdit):
+        """Reassigns the node of the chain at the frame to the target chain"""
+
+        id: ID
+        frame_idx: int
+        target_id: ID
 
 class TrackingGraph:
     """
@@ -281,6 +609,7 @@ class TrackingGraph:
 
     root: GraphNode
     matrix: list[list[GraphNode]]
+    edits: list[GraphEdit]
 
     def get_tracking_chain(self, id: ID) -> list[ChainNode]:
         """
@@ -289,6 +618,87 @@ class TrackingGraph:
         Builds the tracking chain with the given ID.
         """
 
+    def get_links_below(self, threshold: float) -> list[tuple[ID, ChainNode, ChainLink]]:
+        """
+        Internal method
+
+        Returns all the links with a score below the threshold.
+        """
+
+    def to_graphml(
+        self,
+        record_schema: RecordSchema | None = None,
+        dataframes: list[pl.DataFrame] | None = None,
+        chains_ids: set[ID] | None = None,
+        frame_range: tuple[int, int] | None = None,
+    ) -> str:
+        """
+        Internal method
+
+        Exports the graph in the GraphML format.
+        """
+
+    def to_dot(
+        self,
+        record_schema: RecordSchema | None = None,
+        dataframes: list[pl.DataFrame] | None = None,
+        chains_ids: set[ID] | None = None,
+        frame_range: tuple[int, int] | None = None,
+    ) -> str:
+        """
+        Internal method
+
+        Exports the graph in the DOT format.
+        """
+
+    def to_jsonl(
+        self,
+        record_schema: RecordSchema | None = None,
+        dataframes: list[pl.DataFrame] | None = None,
+        chains_ids: set[ID] | None = None,
+        frame_range: tuple[int, int] | None = None,
+    ) -> str:
+        """
+        Internal method
+
+        Exports the graph in the JSON-lines format.
+        """
+
+    def split_chain(self, id: ID, frame_idx: int) -> ID:
+        """
+        Internal method
+
+        Splits the tracking chain at the frame, returns the ID of the new chain.
+        """
+
+    def merge_chains(self, id: ID, other_id: ID) -> None:
+        """
+        Internal method
+
+        Merges the other tracking chain into the tracking chain.
+        """
+
+    def detach_node(self, id: ID, frame_idx: int) -> ID:
+        """
+        Internal method
+
+        Detaches the node at the frame into a new chain, returns its ID.
+        """
+
+    def reassign_node(self, id: ID, frame_idx: int, target_id: ID) -> None:
+        """
+        Internal method
+
+        Reassigns the node at the frame to the target chain.
+        """
+
+    def replay_edits(self, edits: list[GraphEdit]) -> None:
+        """
+        Internal method
+
+        Applies the edits in order.
+        """
+
 # Diagnostics
 
 class TrackerRecordDiagnostics:
@@ -344,6 +754,27 @@ class TrackerDiagnostics:
     where the tracker is alive.
     """
 
+class AmbiguousMatchDiagnostics:
+    """
+    Match between a tracker and a record rejected because
+    the best and second-best scores were closer than the
+    ambiguity margin.
+    """
+
+    tracker_id: ID
+    """ID of the tracker"""
+    record_idx: int
+    """Index of the record in the frame"""
+    ambiguity: str
+    """
+    "record" when the ambiguity is between the trackers of the record,
+    "tracker" when it is between the records of the tracker
+    """
+    best_score: float
+    """Best score"""
+    second_score: float
+    """Second-best score"""
+
 class ResolvingDiagnostics:
     """
     Diagnostic information about the resolving process
@@ -361,6 +792,119 @@ class ResolvingDiagnostics:
     """
     Histogram of the number of match for each tracker
     """
+    ambiguous_matches: list[AmbiguousMatchDiagnostics]
+    """
+    Matches rejected because of the ambiguity margin
+    """
+
+class CacheDiagnostics:
+    """
+    Statistics of the cache of the distance calculator of a feature.
+    """
+
+    feature_idx: int
+    """Index of the feature"""
+    computation_count: int
+    """Number of distances requested to the distance calculator"""
+    cache_hit_count: int
+    """Number of distances found in the cache"""
+    cache_size: int
+    """Number of distances precomputed in the cache"""
+
+    def hit_rate(self) -> float:
+        """
+        Returns the ratio of the distances found in the cache,
+        0 if no distance was requested.
+        """
+
+class WorkerFrameDiagnostics:
+    """
+    Performance of a tracking worker for a frame,
+    the times are in seconds.
+    """
+
+    worker_idx: int
+    """Index of the worker, in the "work-stealing" mode the main thread is 0"""
+    num_trackers: int
+    """Number of trackers processed by the worker"""
+    num_chunks: int
+    """Number of chunks of trackers processed, always 1 in the "workers" mode"""
+    setup_caches_time: float
+    """Time spent precomputing the caches"""
+    scoring_time: float
+    """Time spent scoring the records"""
+    caches: list[CacheDiagnostics]
+    """Statistics of the caches, one per feature"""
+
+    def busy_time(self) -> float:
+        """
+        Returns the time the worker spent working on the frame.
+        """
+
+class FramePerformanceDiagnostics:
+    """
+    Performance of the tracking engine for a frame,
+    the times are in seconds.
+    """
+
+    frame_idx: int
+    """Index of the frame"""
+    scoring_time: float
+    """Wall time of the scoring of all the trackers"""
+    resolving_time: float
+    """Time spent in the resolving process"""
+    workers: list[WorkerFrameDiagnostics]
+    """Performance of each worker"""
+
+    def load_imbalance(self) -> float:
+        """
+        Returns the ratio between the busiest worker time and the
+        average worker time, 1 when the load is perfectly balanced.
+        """
+
+class PersistentCacheDiagnostics:
+    """
+    Statistics of the persistent cache of a feature,
+    over the whole tracking process.
+    """
+
+    feature_idx: int
+    """Index of the feature"""
+    capacity: int
+    """Maximum number of distances in the cache"""
+    size: int
+    """Number of distances in the cache"""
+    lookup_count: int
+    """Number of distances looked up in the cache"""
+    hit_count: int
+    """Number of distances found in the cache"""
+    insertion_count: int
+    """Number of distances inserted in the cache"""
+    eviction_count: int
+    """Number of distances evicted from the cache"""
+
+    def hit_rate(self) -> float:
+        """
+        Returns the ratio of the lookups found in the cache,
+        0 if there was no lookup.
+        """
+
+class PerformanceDiagnostics:
+    """
+    Performance of the tracking engine, it allows to
+    tune the caching threshold of the distance metric.
+    """
+
+    frames: list[FramePerformanceDiagnostics]
+    """Performance for each processed frame"""
+    persistent_caches: list[PersistentCacheDiagnostics]
+    """Statistics of the persistent caches, empty if they are disabled"""
+
+    def cache_totals(self) -> list[CacheDiagnostics]:
+        """
+        Returns the statistics of the caches of each feature
+        summed over all the workers and frames.
+        """
 
 class Diagnostics:
     """
@@ -374,6 +918,10 @@ class Diagnostics:
     Diagnostic information about the resolving process
     for each frame.
     """
+    performance: PerformanceDiagnostics
+    """
+    Cache statistics and timings of the tracking engine.
+    """
 
     def get_tracker(self, id: ID) -> TrackerDiagnostics | None:
         """
@@ -435,6 +983,8 @@ def execute_tracking_process(
     tracking_config: TrackingConfig,
     record_schema: RecordSchema,
     dataframes: list[pl.DataFrame],
+    must_links: list[tuple[tuple[int, int], tuple[int, int]]] | None = None,
+    cannot_links: list[tuple[tuple[int, int], tuple[int, int]]] | None = None,
 ) -> tuple[TrackingGraph, Diagnostics]:
     """
     Internal function
@@ -442,6 +992,46 @@ def execute_tracking_process(
     Main entry point for the tracking process.
     """
 
+def benchmark_suite(
+    num_households: int = 500,
+    num_frames: int = 4,
+    seed: int = 0,
+    num_samples: int = 20,
+    warm_up_time: float = 0.5,
+    measurement_time: float = 2.0,
+    filter: str | None = None,
+) -> pl.DataFrame:
+    """
+    Internal function
+
+    Runs the benchmark suite on a synthetic census.
+    """
+
+def generate_census_process(
+    generator_config: GeneratorConfig,
+) -> tuple[RecordSchema, list[pl.DataFrame], list[list[tuple[int, int]]]]:
+    """
+    Internal function
+
+    Main entry point for the census generator.
+    """
+
+def execute_search_process(
+    base_config: TrackingConfig,
+    record_schema: RecordSchema,
+    dataframes: list[pl.DataFrame],
+    search_space: str,
+    objective: str,
+    ground_truth: list[list[tuple[int, int]]] | None = None,
+    parallel_runs: int = 1,
+    results_path: str | None = None,
+) -> pl.DataFrame:
+    """
+    Internal function
+
+    Main entry point for the hyperparameter search, the search space is given in JSON.
+    """
+
 def execute_normalization_process(
     normalization_config: NormalizationConfig,
     record_schema: RecordSchema,
@@ -454,12 +1044,40 @@ def execute_normalization_process(
     Main entry point for the normalization process.
     """
 
-def compute_median_word(words: list[str]) -> str | None:
+def export_tracking_graph(
+    tracking_graph: TrackingGraph,
+    record_schema: RecordSchema | None = None,
+    dataframes: list[pl.DataFrame] | None = None,
+    normalized_dataframes: list[pl.DataFrame] | None = None,
+) -> pl.DataFrame:
+    """
+    Exports the tracking graph to a long-format DataFrame, with one row per chain node.
+
+    Args:
+        tracking_graph: Tracking graph
+        record_schema: Record schema, required if dataframes are given
+        dataframes: List of DataFrames containing the records
+        normalized_dataframes: List of DataFrames containing the normalized records
+
+    Returns:
+        DataFrame with the columns chain_id, frame_idx, record_idx, link_score
+        and the fields of the records, if any.
+    """
+
+def compute_median_word(
+    words: list[str],
+    median_strategy: MedianStrategy | None = None,
+    weights: list[float] | None = None,
+) -> str | None:
     """
     Computes the median word from a list of words.
 
     Args:
         words: List of words
+        median_strategy: How the median word is computed, see
+                        `MemoryConfig.median_strategy`, graphemes by default.
+        weights: Weight of each word (e.g. confidence or recency), each word
+                        votes with its weight, only supported by graphemes.
 
     Returns:
         The median word or None if the list is empty.
@@ -489,6 +1107,8 @@ def normalize_words(
     threshold_match: float,
     min_cluster_size: int,
     infer_missing_clusters: bool,
+    median_strategy: MedianStrategy | None = None,
+    weights: list[float] | None = None,
 ) -> list[str | None]:
     """
     Normalizes a list of words using clustering.
@@ -499,6 +1119,10 @@ def normalize_words(
         threshold_match: Threshold for a word to be considered a match
                         with a cluster.
         min_cluster_size: Minimum size of a cluster to be considered
+        median_strategy: How the median word of each cluster is computed,
+                        graphemes by default.
+        weights: Weight of each word, each word votes for the median word of
+                        its cluster with its weight, only supported by graphemes.
 
     Returns:
         List of normalized words
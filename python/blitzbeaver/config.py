@@ -45,16 +45,6 @@ def _get_default_num_threads() -> int:
     return len(os.sched_getaffinity(0))
 
 
-def _check_record_scorer_config(
-    record_scorer_config: RecordScorerConfig, num_features: int
-) -> None:
-    if record_scorer_config.weights is not None:
-        if len(record_scorer_config.weights) != num_features:
-            raise ValueError(
-                "The length of the RecordScorerConfig.weights must be equal to the number of features."
-            )
-
-
 def config(
     record_schema: RecordSchema,
     distance_metric_config: DistanceMetricConfig | None = None,
@@ -65,6 +55,7 @@ def config(
     interest_threshold: float | None = None,
     limit_no_match_streak: int | None = None,
     num_threads: int | None = None,
+    engine_mode: str | None = None,
 ) -> TrackingConfig:
     """
     Builds a TrackingConfig object with the given configuration.
@@ -83,9 +74,14 @@ def config(
         interest_threshold: TrackerConfig.interest_threshold.
         limit_no_match_streak: interest_threshold.limit_no_match_streak
         num_threads: The number of threads to use.
+        engine_mode: TrackingConfig.engine_mode.
 
     Returns:
         A TrackingConfig object with the given configuration.
+
+    Raises:
+        ValueError: If the configuration is invalid, the message names
+            the path of the invalid attribute.
     """
 
     if distance_metric_config is None:
@@ -112,9 +108,7 @@ def config(
         else:
             memories.append(multistring_memory_config)
 
-    _check_record_scorer_config(record_scorer_config, len(record_schema.fields))
-
-    return TrackingConfig(
+    tracking_config = TrackingConfig(
         num_threads=num_threads,
         tracker=TrackerConfig(
             interest_threshold=interest_threshold,
@@ -124,4 +118,7 @@ def config(
         ),
         distance_metric=distance_metric_config,
         resolver=resolver_config,
+        engine_mode=engine_mode,
     )
+    tracking_config.validate(record_schema)
+    return tracking_config
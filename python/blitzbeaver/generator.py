@@ -0,0 +1,43 @@
+from dataclasses import dataclass
+
+import polars as pl
+
+from .blitzbeaver import (
+    GeneratorConfig,
+    RecordSchema,
+    generate_census_process,
+)
+
+
+@dataclass
+class SyntheticCensus:
+    """
+    Generated census with known ground truth.
+    """
+
+    record_schema: RecordSchema
+    dataframes: list[pl.DataFrame]
+    chains: list[list[tuple[int, int]]]
+    """
+    True chains, as lists of (frame index, record index).
+    """
+
+
+def generate_census(generator_config: GeneratorConfig) -> SyntheticCensus:
+    """
+    Generates a synthetic census: simulates a population of households over
+    the frames (births, deaths, marriages, moves, occupation changes, children
+    leaving home and immigration) and applies the transcription noise to the
+    records. The generation is deterministic given the seed.
+
+    Each record is the head of a household, with the fields address, firstname,
+    lastname, origin, occupation and children.
+
+    Args:
+        generator_config: Generator configuration
+
+    Returns:
+        The record schema, one DataFrame per frame and the true chains
+    """
+    record_schema, dataframes, chains = generate_census_process(generator_config)
+    return SyntheticCensus(record_schema, dataframes, chains)
@@ -4,10 +4,11 @@ ID = int
 
 Element = str | list[str] | None
 
-ResolvingStrategy = Literal["simple", "best-match"]
+ResolvingStrategy = Literal["simple", "best-match", "multi-hypothesis"]
 DistanceMetric = Literal[
     "lv",
     "lv_opti",
+    "lv_bitparallel",
     "lv_edit",
     "lv_substring",
     "lv_multiword",
@@ -16,10 +17,16 @@ MemoryStrategy = Literal[
     "bruteforce",
     "mostfrequent",
     "median",
+    "weighted-median",
     "ls-bruteforce",
     "ls-mostfrequent",
     "ls-median",
     "mw-mostfrequent",
     "mw-median",
+    "mw-weighted-median",
 ]
+MedianStrategy = Literal["graphemes", "set-median", "refined-median"]
 RecordScorer = Literal["average", "weighted-average", "weighted-quadratic"]
+GraphFormat = Literal["graphml", "dot", "jsonl"]
+SearchObjective = Literal["chain-length", "match-ratio", "ground-truth"]
+SearchStrategy = Literal["grid", "random"]
@@ -0,0 +1,109 @@
+import json
+from typing import Any
+
+import polars as pl
+
+from .blitzbeaver import (
+    TrackingConfig,
+    RecordSchema,
+    execute_search_process,
+)
+from .exceptions import InvalidConfigException
+from .literals import SearchObjective, SearchStrategy
+from .logger import setup_logger, LogLevel
+
+SearchValues = list[Any] | tuple[int, int] | tuple[float, float]
+
+
+def _serialize_search_values(path: str, values: SearchValues) -> dict:
+    if isinstance(values, list):
+        return {"choice": values}
+    if isinstance(values, tuple) and len(values) == 2:
+        if all(isinstance(v, int) for v in values):
+            return {"int_uniform": list(values)}
+        return {"uniform": [float(v) for v in values]}
+    raise InvalidConfigException(
+        f"{path}: expected a list of values or a (low, high) range"
+    )
+
+
+def execute_search(
+    base_config: TrackingConfig,
+    record_schema: RecordSchema,
+    dataframes: list[pl.DataFrame],
+    parameters: dict[str, SearchValues],
+    strategy: SearchStrategy = "grid",
+    num_samples: int = 10,
+    seed: int = 0,
+    objective: SearchObjective = "chain-length",
+    ground_truth: list[list[tuple[int, int]]] | None = None,
+    parallel_runs: int = 1,
+    results_path: str | None = None,
+    log_level: LogLevel = "info",
+) -> pl.DataFrame:
+    """
+    Executes a hyperparameter search over tracking configurations.
+
+    Each configuration is the base configuration with the parameters set,
+    the tracking process is run with it and its result is scored. The frames
+    and the distances between the frequent words are shared by all the runs.
+
+    Args:
+        base_config: Tracking configuration the parameters are applied to
+        record_schema: Record schema
+        dataframes: List of DataFrames containing the records
+        parameters: Values of each parameter, keyed by the path of the attribute
+            in the tracking configuration, e.g. "tracker.interest_threshold" or
+            "tracker.memories[*].memory_strategy". The values are either a list
+            of values or a (low, high) range, ranges of floats are only
+            supported by the random search.
+        strategy: "grid" to try all the combinations, "random" to draw
+            `num_samples` combinations given the seed.
+        num_samples: Number of combinations of the random search
+        seed: Seed of the random search
+        objective: Score of the tracking graph, the higher the better:
+            "chain-length" (average length of the chains), "match-ratio"
+            (ratio of records matched minus the ratio of conflicts) or
+            "ground-truth" (F1 score of the links against the true chains).
+        ground_truth: True chains, as lists of (frame index, record index),
+            required by the "ground-truth" objective.
+        parallel_runs: Number of tracking processes run at the same time,
+            they share the `num_threads` of the configuration.
+        results_path: Path of a JSON lines file the results are appended to,
+            the configurations already present in it are not run again such
+            that an interrupted search can be resumed.
+        log_level: The log level to set the logger to, defaults to "info".
+            This will not overwrite the logger if it has already been set up.
+
+    Returns:
+        The results ranked from the best score to the worst, with the columns
+        rank, score, duration_secs, one column per parameter (values as JSON)
+        and config (as JSON).
+
+    Raises:
+        InvalidConfigException: If the values of a parameter are neither a list
+            nor a range.
+    """
+    setup_logger(log_level)
+
+    search_space = {
+        "strategy": strategy,
+        "parameters": [
+            {"path": path, "values": _serialize_search_values(path, values)}
+            for path, values in parameters.items()
+        ],
+    }
+    if strategy == "random":
+        search_space["num_samples"] = num_samples
+        search_space["seed"] = seed
+
+    return execute_search_process(
+        base_config,
+        record_schema,
+        dataframes,
+        json.dumps(search_space),
+        objective,
+        ground_truth,
+        parallel_runs,
+        results_path,
+    )
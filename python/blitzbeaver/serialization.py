@@ -1,9 +1,14 @@
+import json
+
 from .blitzbeaver import (
+    RecordSchema,
+    NormalizationConfig,
     TrackingConfig,
     TrackerConfig,
     DistanceMetricConfig,
     ResolverConfig,
     RecordScorerConfig,
+    CompositeFeatureConfig,
     MemoryConfig,
 )
 from .exceptions import InvalidConfigException
@@ -26,6 +31,24 @@ def serialize_distance_metric_config(c: DistanceMetricConfig) -> dict:
         "lv_edit_weights": c.lv_edit_weights,
         "lv_substring_weight": c.lv_substring_weight,
         "lv_multiword_separator": c.lv_multiword_separator,
+        "persistent_cache_bytes": c.persistent_cache_bytes,
+    }
+
+
+def serialize_composite_feature_config(c: CompositeFeatureConfig) -> dict:
+    """
+    Serializes the CompositeFeatureConfig object to a dictionary.
+
+    Args:
+        c: The CompositeFeatureConfig object to serialize.
+
+    Returns:
+        A dictionary representation of the CompositeFeatureConfig object.
+    """
+    return {
+        "fields": c.fields,
+        "threshold_match": c.threshold_match,
+        "bonus": c.bonus,
     }
 
 
@@ -43,6 +66,14 @@ def serialize_record_scorer_config(c: RecordScorerConfig) -> dict:
         "record_scorer": c.record_scorer,
         "weights": c.weights,
         "min_weight_ratio": c.min_weight_ratio,
+        "missing_record_distances": c.missing_record_distances,
+        "missing_memory_distances": c.missing_memory_distances,
+        "missing_both_distances": c.missing_both_distances,
+        "composite_features": (
+            None
+            if c.composite_features is None
+            else [serialize_composite_feature_config(a) for a in c.composite_features]
+        ),
     }
 
 
@@ -64,6 +95,8 @@ def serialize_memory_config(c: MemoryConfig) -> dict:
             if c.multiword_distance_metric is None
             else serialize_distance_metric_config(c.multiword_distance_metric)
         ),
+        "median_strategy": c.median_strategy,
+        "recency_decay": c.recency_decay,
     }
 
 
@@ -77,7 +110,12 @@ def serialize_resolver_config(c: ResolverConfig) -> dict:
     Returns:
         A dictionary representation of the ResolverConfig object.
     """
-    return {"resolving_strategy": c.resolving_strategy}
+    return {
+        "resolving_strategy": c.resolving_strategy,
+        "num_hypotheses": c.num_hypotheses,
+        "hypotheses_depth": c.hypotheses_depth,
+        "ambiguity_margin": c.ambiguity_margin,
+    }
 
 
 def serialize_tracker_config(c: TrackerConfig) -> dict:
@@ -108,12 +146,33 @@ def serialize_tracking_config(c: TrackingConfig) -> dict:
     Returns:
         A dictionary representation of the TrackingConfig object.
     """
-    return {
-        "num_threads": c.num_threads,
-        "tracker": serialize_tracker_config(c.tracker),
-        "distance_metric": serialize_distance_metric_config(c.distance_metric),
-        "resolver": serialize_resolver_config(c.resolver),
-    }
+    return json.loads(c.to_json())
+
+
+def serialize_normalization_config(c: NormalizationConfig) -> dict:
+    """
+    Serializes the NormalizationConfig object to a dictionary.
+
+    Args:
+        c: The NormalizationConfig object to serialize.
+
+    Returns:
+        A dictionary representation of the NormalizationConfig object.
+    """
+    return json.loads(c.to_json())
+
+
+def serialize_record_schema(s: RecordSchema) -> dict:
+    """
+    Serializes the RecordSchema object to a dictionary.
+
+    Args:
+        s: The RecordSchema object to serialize.
+
+    Returns:
+        A dictionary representation of the RecordSchema object.
+    """
+    return json.loads(s.to_json())
 
 
 def deserialize_distance_metric_config(d: dict) -> DistanceMetricConfig:
@@ -134,11 +193,32 @@ def deserialize_distance_metric_config(d: dict) -> DistanceMetricConfig:
             lv_edit_weights=d.get("lv_edit_weights"),
             lv_substring_weight=d.get("lv_substring_weight"),
             lv_multiword_separator=d.get("lv_multiword_separator"),
+            persistent_cache_bytes=d.get("persistent_cache_bytes"),
         )
     except KeyError as e:
         raise InvalidConfigException(f"Missing key in DistanceMetricConfig: {e}")
 
 
+def deserialize_composite_feature_config(d: dict) -> CompositeFeatureConfig:
+    """
+    Deserializes a dictionary to a CompositeFeatureConfig object.
+
+    Args:
+        d: The dictionary to deserialize.
+
+    Returns:
+        A CompositeFeatureConfig object.
+    """
+    try:
+        return CompositeFeatureConfig(
+            fields=d["fields"],
+            threshold_match=d["threshold_match"],
+            bonus=d["bonus"],
+        )
+    except KeyError as e:
+        raise InvalidConfigException(f"Missing key in CompositeFeatureConfig: {e}")
+
+
 def deserialize_record_scorer_config(d: dict) -> RecordScorerConfig:
     """
     Deserializes a dictionary to a RecordScorerConfig object.
@@ -154,6 +234,17 @@ def deserialize_record_scorer_config(d: dict) -> RecordScorerConfig:
             record_scorer=d["record_scorer"],
             weights=d.get("weights"),
             min_weight_ratio=d.get("min_weight_ratio"),
+            missing_record_distances=d.get("missing_record_distances"),
+            missing_memory_distances=d.get("missing_memory_distances"),
+            missing_both_distances=d.get("missing_both_distances"),
+            composite_features=(
+                None
+                if d.get("composite_features") is None
+                else [
+                    deserialize_composite_feature_config(a)
+                    for a in d["composite_features"]
+                ]
+            ),
         )
     except KeyError as e:
         raise InvalidConfigException(f"Missing key in RecordScorerConfig: {e}")
@@ -178,6 +269,8 @@ def deserialize_memory_config(d: dict) -> MemoryConfig:
                 if d.get("multiword_distance_metric") is None
                 else deserialize_distance_metric_config(d["multiword_distance_metric"])
             ),
+            median_strategy=d.get("median_strategy"),
+            recency_decay=d.get("recency_decay"),
         )
     except KeyError as e:
         raise InvalidConfigException(f"Missing key in MemoryConfig: {e}")
@@ -194,7 +287,12 @@ def deserialize_resolver_config(d: dict) -> ResolverConfig:
         A ResolverConfig object.
     """
     try:
-        return ResolverConfig(resolving_strategy=d["resolving_strategy"])
+        return ResolverConfig(
+            resolving_strategy=d["resolving_strategy"],
+            num_hypotheses=d.get("num_hypotheses"),
+            hypotheses_depth=d.get("hypotheses_depth"),
+            ambiguity_margin=d.get("ambiguity_margin"),
+        )
     except KeyError as e:
         raise InvalidConfigException(f"Missing key in ResolverConfig: {e}")
 
@@ -229,13 +327,51 @@ def deserialize_tracking_config(d: dict) -> TrackingConfig:
 
     Returns:
         A TrackingConfig object.
+
+    Raises:
+        InvalidConfigException: If the dictionary is not a valid configuration,
+            the message names the path of the invalid attribute.
     """
     try:
-        return TrackingConfig(
-            num_threads=d["num_threads"],
-            tracker=deserialize_tracker_config(d["tracker"]),
-            distance_metric=deserialize_distance_metric_config(d["distance_metric"]),
-            resolver=deserialize_resolver_config(d["resolver"]),
-        )
-    except KeyError as e:
-        raise InvalidConfigException(f"Missing key in TrackingConfig: {e}")
+        return TrackingConfig.from_json(json.dumps(d))
+    except ValueError as e:
+        raise InvalidConfigException(f"Invalid TrackingConfig: {e}")
+
+
+def deserialize_normalization_config(d: dict) -> NormalizationConfig:
+    """
+    Deserializes a dictionary to a NormalizationConfig object.
+
+    Args:
+        d: The dictionary to deserialize.
+
+    Returns:
+        A NormalizationConfig object.
+
+    Raises:
+        InvalidConfigException: If the dictionary is not a valid configuration,
+            the message names the path of the invalid attribute.
+    """
+    try:
+        return NormalizationConfig.from_json(json.dumps(d))
+    except ValueError as e:
+        raise InvalidConfigException(f"Invalid NormalizationConfig: {e}")
+
+
+def deserialize_record_schema(d: dict) -> RecordSchema:
+    """
+    Deserializes a dictionary to a RecordSchema object.
+
+    Args:
+        d: The dictionary to deserialize.
+
+    Returns:
+        A RecordSchema object.
+
+    Raises:
+        InvalidConfigException: If the dictionary is not a valid record schema.
+    """
+    try:
+        return RecordSchema.from_json(json.dumps(d))
+    except ValueError as e:
+        raise InvalidConfigException(f"Invalid RecordSchema: {e}")
@@ -14,6 +14,8 @@ def execute_tracking(
     record_schema: RecordSchema,
     dataframes: list[pl.DataFrame],
     log_level: LogLevel = "info",
+    must_links: list[tuple[tuple[int, int], tuple[int, int]]] | None = None,
+    cannot_links: list[tuple[tuple[int, int], tuple[int, int]]] | None = None,
 ) -> TrackingGraph:
     """
     Executes the tracking process, builds the tracking graph.
@@ -26,6 +28,11 @@ def execute_tracking(
         dataframes: List of DataFrames containing the records
         log_level: The log level to set the logger to, defaults to "info".
             This will not overwrite the logger if it has already been set up.
+        must_links: Links that must be part of the tracking chains, each link
+            is a pair of (frame index, record index) tuples, from a record to
+            the next record of the same chain.
+        cannot_links: Links that must not be part of the tracking chains,
+            in the same format as the must-links.
 
     Returns:
         The tracking graph built by the tracking process
@@ -36,6 +43,8 @@ def execute_tracking(
         tracking_config,
         record_schema,
         dataframes,
+        must_links,
+        cannot_links,
     )
 
     return TrackingGraph(raw_graph, diagnostics)
@@ -7,8 +7,11 @@ from .blitzbeaver import (
     RecordSchema,
     TrackingGraph as _TrackingGraph,
     ChainNode,
+    ChainLink,
+    GraphEdit,
+    export_tracking_graph,
 )
-from .literals import ID, Element
+from .literals import ID, Element, GraphFormat
 
 
 class MaterializedTrackerFrame:
@@ -143,6 +146,234 @@ class TrackingGraph:
         self.diagnostics = diagnostics
         self.trackers_ids = [id for id, _ in self._raw.root.outs]
 
+    def get_links_below(self, threshold: float) -> list[tuple[ID, ChainNode, ChainLink]]:
+        """
+        Returns all the links of the graph with a score below the threshold
+
+        Each link holds the matching information of a record in a tracking
+        chain: its score, the distances of each field and the margin with the
+        runner-up record. This doesn't require the diagnostics.
+
+        Args:
+            threshold: Score threshold
+
+        Returns:
+            List of tuples of the ID of the tracking chain, the chain node
+            the link leads to and the link.
+        """
+        return self._raw.get_links_below(threshold)
+
+    @property
+    def edits(self) -> list[GraphEdit]:
+        """
+        Edits applied to the tracking graph, in order
+        """
+        return self._raw.edits
+
+    def split_chain(self, id: ID, frame_idx: int) -> ID:
+        """
+        Splits a tracking chain at a frame
+
+        The nodes of the chain from the frame onward form a new chain.
+
+        Args:
+            id: ID of the tracking chain
+            frame_idx: Index of the first frame of the new chain
+
+        Returns:
+            ID of the new tracking chain
+
+        Raises:
+            ValueError: If the chain doesn't exist or has no node on
+                both sides of the frame.
+        """
+        new_id = self._raw.split_chain(id, frame_idx)
+        self._update_trackers_ids()
+        return new_id
+
+    def merge_chains(self, id: ID, other_id: ID) -> None:
+        """
+        Merges two tracking chains
+
+        The nodes of the other chain are moved to the chain.
+
+        Args:
+            id: ID of the tracking chain
+            other_id: ID of the tracking chain to merge into the chain
+
+        Raises:
+            ValueError: If a chain doesn't exist or both chains have a
+                node in the same frame.
+        """
+        self._raw.merge_chains(id, other_id)
+        self._update_trackers_ids()
+
+    def detach_node(self, id: ID, frame_idx: int) -> ID:
+        """
+        Detaches the node of a tracking chain into a new chain
+
+        Args:
+            id: ID of the tracking chain
+            frame_idx: Index of the frame of the node
+
+        Returns:
+            ID of the new tracking chain
+
+        Raises:
+            ValueError: If the chain doesn't exist, has no node in the
+                frame or has a single node.
+        """
+        new_id = self._raw.detach_node(id, frame_idx)
+        self._update_trackers_ids()
+        return new_id
+
+    def reassign_node(self, id: ID, frame_idx: int, target_id: ID) -> None:
+        """
+        Reassigns the node of a tracking chain to another chain
+
+        The chain is removed if the node was its only node.
+
+        Args:
+            id: ID of the tracking chain
+            frame_idx: Index of the frame of the node
+            target_id: ID of the tracking chain to reassign the node to
+
+        Raises:
+            ValueError: If a chain doesn't exist, the chain has no node in the
+                frame or the target chain already has a node in the frame.
+        """
+        self._raw.reassign_node(id, frame_idx, target_id)
+        self._update_trackers_ids()
+
+    def replay_edits(self, edits: list[GraphEdit]) -> None:
+        """
+        Replays edits on the tracking graph
+
+        Typically used to apply the edits of another tracking graph
+        resulting from the same tracking process.
+
+        Args:
+            edits: Edits to apply, in order
+
+        Raises:
+            ValueError: If an edit is invalid, the edits before it
+                are applied.
+        """
+        try:
+            self._raw.replay_edits(edits)
+        finally:
+            self._update_trackers_ids()
+
+    def _update_trackers_ids(self) -> None:
+        self.trackers_ids = [id for id, _ in self._raw.root.outs]
+
+    def _get_edited_chains_ids(self) -> set[ID]:
+        ids = set()
+        for edit in self._raw.edits:
+            for attr in ("id", "other_id", "new_id", "target_id"):
+                if hasattr(edit, attr):
+                    ids.add(getattr(edit, attr))
+        return ids
+
+    def to_dataframe(
+        self,
+        record_schema: RecordSchema | None = None,
+        dataframes: list[pl.DataFrame] | None = None,
+        normalized_dataframes: list[pl.DataFrame] | None = None,
+    ) -> pl.DataFrame:
+        """
+        Exports the tracking graph to a long-format DataFrame
+
+        The DataFrame has one row per chain node, with the columns chain_id,
+        frame_idx, record_idx and link_score, the score of the link of the node
+        (null for the first node of a chain). If the dataframes are given,
+        the fields of the record schema are joined from them, and the fields
+        of the normalized records are joined as well, suffixed with "_normalized".
+
+        Args:
+            record_schema: Record schema, required if dataframes are given
+            dataframes: List of DataFrames containing the records
+            normalized_dataframes: List of DataFrames containing the normalized records
+
+        Returns:
+            DataFrame with one row per chain node
+        """
+        return export_tracking_graph(
+            self._raw, record_schema, dataframes, normalized_dataframes
+        )
+
+    def to_dot(
+        self,
+        record_schema: RecordSchema | None = None,
+        dataframes: list[pl.DataFrame] | None = None,
+        chains_ids: list[ID] | None = None,
+        frame_range: tuple[int, int] | None = None,
+    ) -> str:
+        """
+        Exports the tracking graph in the DOT format
+
+        Nodes are identified by "<frame_idx>_<record_idx>", the nodes
+        of a frame are on the same rank and the edges are labelled
+        with the link score.
+
+        Args:
+            record_schema: Record schema, required if dataframes are given
+            dataframes: List of DataFrames containing the records, their
+                fields are used as node labels.
+            chains_ids: IDs of the tracking chains to export, all if None
+            frame_range: Inclusive range of frames to export, all if None
+
+        Returns:
+            DOT source of the graph
+        """
+        return self._raw.to_dot(
+            record_schema,
+            dataframes,
+            None if chains_ids is None else set(chains_ids),
+            frame_range,
+        )
+
+    def write_graph(
+        self,
+        filepath: str,
+        format: GraphFormat,
+        record_schema: RecordSchema | None = None,
+        dataframes: list[pl.DataFrame] | None = None,
+        chains_ids: list[ID] | None = None,
+        frame_range: tuple[int, int] | None = None,
+    ) -> None:
+        """
+        Writes the tracking graph to a file
+
+        Supported formats are GraphML (e.g. for Gephi), DOT (for Graphviz) and
+        JSON-lines, where each line is either a node or an edge, with a "type" field.
+        The node attributes are the fields of the records if the dataframes are given.
+
+        Args:
+            filepath: Path of the file
+            format: Format of the file
+            record_schema: Record schema, required if dataframes are given
+            dataframes: List of DataFrames containing the records
+            chains_ids: IDs of the tracking chains to export, all if None
+            frame_range: Inclusive range of frames to export, all if None
+        """
+        exporters = {
+            "graphml": self._raw.to_graphml,
+            "dot": self._raw.to_dot,
+            "jsonl": self._raw.to_jsonl,
+        }
+        if format not in exporters:
+            raise ValueError(f"Unknown graph format {format}")
+
+        content = exporters[format](
+            record_schema,
+            dataframes,
+            None if chains_ids is None else set(chains_ids),
+            frame_range,
+        )
+        with open(filepath, "w", encoding="utf-8") as file:
+            file.write(content)
+
     def materialize_tracking_chain(
         self,
         id: ID,
@@ -200,6 +431,22 @@ class TrackingGraph:
         ]
 
         tracker_diagnostics = self.diagnostics.get_tracker(id)
+
+        # the diagnostics don't match the chain once it is edited,
+        # only the frames of the chain nodes are materialized
+        if tracker_diagnostics is None or id in self._get_edited_chains_ids():
+            frames.extend(
+                MaterializedTrackerFrame(
+                    frame_idx=ch.frame_idx,
+                    record_idx=ch.record_idx,
+                    record=get_record(ch),
+                    normalized_record=get_normalized_record(ch),
+                    frame_diagnostic=None,
+                )
+                for ch in chain_nodes[1:]
+            )
+            return MaterializedTrackingChain(id, frames, record_schema)
+
         for frame in tracker_diagnostics.frames:
             # there may or may not be a matching record for this frame
             ch = map_frame_ch.get(frame.frame_idx)
@@ -0,0 +1,42 @@
+import polars as pl
+
+import blitzbeaver as bb
+
+
+def build_tracking_graph() -> bb.TrackingGraph:
+    record_schema = bb.RecordSchema(
+        [bb.FieldSchema("name", bb.ElementType.String)],
+    )
+    tracking_config = bb.config(record_schema, num_threads=1)
+    dataframes = [
+        pl.DataFrame({"name": ["alice", "bob"]}),
+        pl.DataFrame({"name": ["alice", "bob"]}),
+    ]
+    return bb.execute_tracking(
+        tracking_config, record_schema, dataframes, log_level="warn"
+    )
+
+
+def test_raw_graph_fields():
+    graph = build_tracking_graph()
+    raw = graph._raw
+
+    assert len(raw.root.outs) == 2
+    assert [len(row) for row in raw.matrix] == [2, 2]
+    assert raw.edits == []
+
+    for _, node in raw.root.outs:
+        assert node.frame_idx == 0
+        assert len(raw.matrix[0][node.record_idx].outs) == 1
+
+
+def test_raw_graph_edits():
+    graph = build_tracking_graph()
+    id = graph.trackers_ids[0]
+
+    new_id = graph.split_chain(id, 1)
+
+    assert len(graph._raw.edits) == 1
+    assert graph._raw.edits[0].new_id == new_id
+    assert len(graph.edits) == 1
+    assert len(graph._raw.root.outs) == 3
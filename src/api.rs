@@ -1,29 +1,65 @@
 mod api;
 mod beaver;
+#[cfg(feature = "benchmark")]
+mod benchmark;
 mod casting;
 mod config;
 mod diagnostics;
 mod evaluation;
+mod generator;
+mod graph_export;
 mod schema;
+mod search;
+mod serialization;
 mod tracking_graph;
+mod validation;
 
 pub use api::{
-    compute_median_word, compute_words_clusters, execute_normalization_process,
-    execute_tracking_process, normalize_words, setup_logger,
+    compute_median_word, compute_words_clusters, execute_normalization, execute_tracking,
+    execute_tracking_out_of_core, initialize_logger, normalize_words, run_tracking_engine,
+};
+#[cfg(feature = "python")]
+pub use api::{
+    compute_median_word_process, compute_words_clusters_process, execute_normalization_process,
+    execute_tracking_process, export_tracking_graph, normalize_words_process, setup_logger,
 };
 pub use beaver::BeaverFile;
-pub use casting::{build_tracking_engine, cast_to_frame};
+#[cfg(feature = "benchmark")]
+pub use benchmark::benchmark_results_to_dataframe;
+#[cfg(all(feature = "benchmark", feature = "python"))]
+pub use benchmark::benchmark_suite;
+#[cfg(feature = "benchmark")]
+pub use casting::cast_generator_config;
+pub use casting::{
+    build_distance_calculators, build_tracking_engine, build_tracking_engine_with_shared_caches,
+    min_num_threads, RawLink,
+};
 pub use config::{
-    DistanceMetricConfig, MemoryConfig, NormalizationConfig, RecordScorerConfig, ResolverConfig,
-    TrackerConfig, TrackingConfig,
+    CompositeFeatureConfig, DistanceMetricConfig, MemoryConfig, NormalizationConfig,
+    RecordScorerConfig, ResolverConfig, TrackerConfig, TrackingConfig,
 };
 pub use diagnostics::{
-    Diagnostics, ResolvingDiagnostics, TrackerDiagnostics, TrackerFrameDiagnostics,
-    TrackerRecordDiagnostics,
+    AmbiguousMatchDiagnostics, CacheDiagnostics, Diagnostics, FramePerformanceDiagnostics,
+    PerformanceDiagnostics, PersistentCacheDiagnostics, ResolvingDiagnostics, TrackerDiagnostics,
+    TrackerFrameDiagnostics, TrackerRecordDiagnostics, WorkerFrameDiagnostics,
 };
 pub use evaluation::{
     evaluate_tracking_chain_length, evaluate_tracking_graph_properties, EvalMetricChainLength,
     EvalMetricGraphProperties,
 };
+#[cfg(feature = "python")]
+pub use generator::generate_census_process;
+pub use generator::{
+    census_record_schema, generate_census, GeneratorConfig, GeneratorEventsConfig,
+    GeneratorNoiseConfig, SyntheticCensus,
+};
+pub use graph_export::GraphExportFilter;
 pub use schema::{ElementType, FieldSchema, RecordSchema};
-pub use tracking_graph::{ChainNode, GraphNode, TrackingGraph};
+#[cfg(feature = "python")]
+pub use search::execute_search_process;
+pub use search::{
+    execute_search, search_results_to_dataframe, SearchObjective, SearchOptions, SearchParameter,
+    SearchResult, SearchSpace, SearchValues,
+};
+pub use serialization::{deserialize_config, serialize_config, ConfigFormat};
+pub use tracking_graph::{ChainLink, ChainNode, GraphEdit, GraphNode, TrackingGraph};
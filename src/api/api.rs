@@ -1,9 +1,18 @@
+use std::{path::Path, sync::Arc};
+
 use crate::{
-    distances, logger,
+    distances::{self, MedianWordStrategy},
+    engine::{FrameLoader, FrameStore, SpillStore, TrackingEngine},
+    error::{BlitzBeaverError, Result},
+    frame::Frame,
+    logger,
     normalization::{self, InternalNormalizationConfig, NormalizationEngine, Normalizer},
     word::Word,
 };
+use polars::prelude::DataFrame;
+#[cfg(feature = "python")]
 use pyo3::{pyfunction, PyResult};
+#[cfg(feature = "python")]
 use pyo3_polars::PyDataFrame;
 
 use super::{
@@ -11,144 +20,729 @@ use super::{
     TrackingConfig, TrackingGraph,
 };
 
+/// Casts the dataframes to frames, the index of each frame is its position.
+pub fn cast_to_frames(
+    record_schema: &RecordSchema,
+    dataframes: &[DataFrame],
+) -> Result<Vec<Frame>> {
+    dataframes
+        .iter()
+        .enumerate()
+        .map(|(i, dataframe)| casting::cast_to_frame(i, record_schema, dataframe))
+        .collect()
+}
+
+#[cfg(feature = "python")]
+fn into_dataframes(dataframes: Vec<PyDataFrame>) -> Vec<DataFrame> {
+    dataframes.into_iter().map(|d| d.0).collect()
+}
+
+#[cfg(feature = "python")]
 #[pyfunction]
 pub fn setup_logger(log_level: String) {
     logger::initialize_logger(&log_level);
 }
 
-#[pyfunction]
-pub fn execute_tracking_process(
+/// Initializes the logger of the library with the given level
+/// (trace, debug, info, warn or error).
+pub fn initialize_logger(log_level: &str) {
+    logger::initialize_logger(log_level);
+}
+
+/// Runs the tracking process on the dataframes, one dataframe per frame
+/// in chronological order, the must-links and cannot-links are given as
+/// `((frame_idx, record_idx), (frame_idx, record_idx))`.
+///
+/// # Errors
+/// Returns an error if the configuration is invalid, it is validated before
+/// anything else, or if the dataframes don't match the record schema.
+pub fn execute_tracking(
     tracking_config: &TrackingConfig,
     record_schema: &RecordSchema,
-    dataframes: Vec<PyDataFrame>,
-) -> PyResult<(TrackingGraph, Diagnostics)> {
-    let mut frames = Vec::new();
-    for i in 0..dataframes.len() {
-        let frame = casting::cast_to_frame(i, record_schema, &dataframes[i])?;
-        frames.push(frame);
+    dataframes: &[DataFrame],
+    must_links: &[casting::RawLink],
+    cannot_links: &[casting::RawLink],
+) -> Result<(TrackingGraph, Diagnostics)> {
+    record_schema.validate()?;
+    tracking_config.validate(record_schema)?;
+
+    let frames = cast_to_frames(record_schema, dataframes)?;
+    let constraints = casting::cast_resolving_constraints(must_links, cannot_links, &frames)?;
+
+    let tracking_engine = casting::build_tracking_engine(
+        tracking_config,
+        record_schema,
+        FrameStore::in_memory(Arc::new(frames)),
+        constraints,
+    )?;
+
+    run_tracking_engine(tracking_engine)
+}
+
+/// Frame loader reading each frame as a dataframe, which is then cast to a frame,
+/// the records of the links are checked as the frames are loaded.
+struct DataFrameLoader<F> {
+    record_schema: RecordSchema,
+    num_frames: usize,
+    read_dataframe: F,
+    must_links: Vec<casting::RawLink>,
+    cannot_links: Vec<casting::RawLink>,
+}
+
+impl<F> FrameLoader for DataFrameLoader<F>
+where
+    F: Fn(usize) -> Result<DataFrame> + Send + Sync,
+{
+    fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    fn load_frame(&self, frame_idx: usize) -> Result<Frame> {
+        let dataframe = (self.read_dataframe)(frame_idx)?;
+        let frame = casting::cast_to_frame(frame_idx, &self.record_schema, &dataframe)?;
+        casting::check_links_records(&self.must_links, &self.cannot_links, &frame)?;
+        Ok(frame)
     }
+}
+
+/// Runs the tracking process out-of-core, for datasets that don't fit in memory.
+///
+/// The frames are read on demand with `read_dataframe`, given the index of
+/// the frame, and only the last `frame_window` frames stay in memory, the words
+/// of the frames out of the window are freed once no tracker holds them.
+/// The chains and diagnostics of the dead trackers are spilled to a temporary
+/// store in `spill_dir`, they are read back to build the tracking graph.
+/// The must-links and cannot-links are given as in `execute_tracking`.
+///
+/// This mode is available from the Rust API and the command line, not from Python.
+///
+/// # Errors
+/// Returns an error if the configuration is invalid, if a dataframe can't
+/// be read or doesn't match the record schema, if a link is invalid (its records
+/// are checked when their frame is read), or if the spill store fails.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_tracking_out_of_core<F>(
+    tracking_config: &TrackingConfig,
+    record_schema: &RecordSchema,
+    num_frames: usize,
+    read_dataframe: F,
+    must_links: &[casting::RawLink],
+    cannot_links: &[casting::RawLink],
+    frame_window: usize,
+    spill_dir: &Path,
+) -> Result<(TrackingGraph, Diagnostics)>
+where
+    F: Fn(usize) -> Result<DataFrame> + Send + Sync + 'static,
+{
+    record_schema.validate()?;
+    tracking_config.validate(record_schema)?;
 
-    let mut tracking_engine =
-        casting::build_tracking_engine(tracking_config, record_schema, frames)?;
+    let constraints =
+        casting::cast_lazy_resolving_constraints(must_links, cannot_links, num_frames)?;
+    let loader = DataFrameLoader {
+        record_schema: record_schema.clone(),
+        num_frames,
+        read_dataframe,
+        must_links: must_links.to_vec(),
+        cannot_links: cannot_links.to_vec(),
+    };
+    let tracking_engine = casting::build_tracking_engine(
+        tracking_config,
+        record_schema,
+        FrameStore::lazy(Box::new(loader), frame_window),
+        constraints,
+    )?
+    .with_spill_store(SpillStore::create(spill_dir)?);
 
-    for frame_idx in 1..dataframes.len() {
+    run_tracking_engine(tracking_engine)
+}
+
+/// Processes all the frames with the tracking engine, builds the tracking graph.
+///
+/// # Errors
+/// Returns an error if a frame can't be loaded or the dead trackers
+/// can't be spilled, only in the out-of-core mode.
+pub fn run_tracking_engine(
+    mut tracking_engine: TrackingEngine,
+) -> Result<(TrackingGraph, Diagnostics)> {
+    for frame_idx in 1..tracking_engine.frames().num_frames() {
         log::debug!("processing frame {}...", frame_idx);
-        tracking_engine.process_next_frame();
+        tracking_engine.process_next_frame()?;
     }
 
-    let tracking_chains = tracking_engine.stop();
+    let tracking_chains = tracking_engine.stop()?;
     let tracking_graph =
-        TrackingGraph::from_tracking_chains(tracking_engine.frames(), tracking_chains);
+        TrackingGraph::from_frame_sizes(&tracking_engine.frames().frame_sizes(), tracking_chains);
 
     Ok((tracking_graph, tracking_engine.take_diagnostics()))
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-pub fn execute_normalization_process(
-    normalization_config: &NormalizationConfig,
-    record_schema: RecordSchema,
-    tracking_graph: TrackingGraph,
+#[pyo3(signature = (tracking_config, record_schema, dataframes, must_links=None, cannot_links=None))]
+pub fn execute_tracking_process(
+    tracking_config: &TrackingConfig,
+    record_schema: &RecordSchema,
     dataframes: Vec<PyDataFrame>,
-) -> PyResult<Vec<PyDataFrame>> {
-    let mut frames = Vec::new();
-    for i in 0..dataframes.len() {
-        let frame = casting::cast_to_frame(i, &record_schema, &dataframes[i])?;
-        frames.push(frame);
-    }
+    must_links: Option<Vec<casting::RawLink>>,
+    cannot_links: Option<Vec<casting::RawLink>>,
+) -> PyResult<(TrackingGraph, Diagnostics)> {
+    Ok(execute_tracking(
+        tracking_config,
+        record_schema,
+        &into_dataframes(dataframes),
+        &must_links.unwrap_or_default(),
+        &cannot_links.unwrap_or_default(),
+    )?)
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (tracking_graph, record_schema=None, dataframes=None, normalized_dataframes=None))]
+pub fn export_tracking_graph(
+    tracking_graph: &TrackingGraph,
+    record_schema: Option<&RecordSchema>,
+    dataframes: Option<Vec<PyDataFrame>>,
+    normalized_dataframes: Option<Vec<PyDataFrame>>,
+) -> PyResult<PyDataFrame> {
+    let dataframes = dataframes.map(into_dataframes);
+    let normalized_dataframes = normalized_dataframes.map(into_dataframes);
+    Ok(PyDataFrame(casting::cast_tracking_chains_to_dataframe(
+        &tracking_graph.build_tracking_chains(),
+        record_schema,
+        dataframes.as_deref(),
+        normalized_dataframes.as_deref(),
+    )?))
+}
+
+/// Runs the normalization process on the dataframes, returns the normalized dataframes.
+///
+/// # Errors
+/// Returns an error if the configuration is invalid or if the dataframes
+/// don't match the record schema.
+pub fn execute_normalization(
+    normalization_config: &NormalizationConfig,
+    record_schema: &RecordSchema,
+    tracking_graph: &TrackingGraph,
+    dataframes: &[DataFrame],
+) -> Result<Vec<DataFrame>> {
+    normalization_config.validate()?;
 
+    let frames = cast_to_frames(record_schema, dataframes)?;
     let distance_calculator =
         casting::build_distance_calculator(&normalization_config.distance_metric)?;
 
     let normalizer = Normalizer::new(
-        casting::cast_normalization_config(&normalization_config),
+        casting::cast_normalization_config(normalization_config)?,
         distance_calculator,
     );
 
-    let engine =
-        NormalizationEngine::new(frames, tracking_graph, record_schema.clone(), normalizer);
+    let engine = NormalizationEngine::new(
+        frames,
+        tracking_graph.clone(),
+        record_schema.clone(),
+        normalizer,
+    );
 
-    let normalized_frames = engine.normalize();
-    let mut normalized_dataframes = Vec::new();
-    for frame in normalized_frames.iter() {
-        let frame = casting::cast_to_dataframe(&record_schema, frame)?;
-        normalized_dataframes.push(frame);
-    }
-    Ok(normalized_dataframes)
+    engine
+        .normalize()?
+        .iter()
+        .map(|frame| casting::cast_to_dataframe(record_schema, frame))
+        .collect()
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-pub fn compute_median_word(words: Vec<String>) -> Option<String> {
+pub fn execute_normalization_process(
+    normalization_config: &NormalizationConfig,
+    record_schema: RecordSchema,
+    tracking_graph: TrackingGraph,
+    dataframes: Vec<PyDataFrame>,
+) -> PyResult<Vec<PyDataFrame>> {
+    let normalized_dataframes = execute_normalization(
+        normalization_config,
+        &record_schema,
+        &tracking_graph,
+        &into_dataframes(dataframes),
+    )?;
+    Ok(normalized_dataframes.into_iter().map(PyDataFrame).collect())
+}
+
+/// Checks the weights of the words of a weighted median, if any.
+///
+/// # Errors
+/// Returns an error if there isn't one weight per word or if the median strategy
+/// doesn't support the weights.
+fn check_median_weights(
+    weights: Option<&[f32]>,
+    num_words: usize,
+    median_strategy: MedianWordStrategy,
+) -> Result<()> {
+    let Some(weights) = weights else {
+        return Ok(());
+    };
+    if weights.len() != num_words {
+        return Err(BlitzBeaverError::InvalidData(format!(
+            "expected {} weights, one per word, got {}",
+            num_words,
+            weights.len()
+        )));
+    }
+    if median_strategy != MedianWordStrategy::Graphemes {
+        return Err(BlitzBeaverError::InvalidConfig(
+            "the weighted median only supports the graphemes median strategy".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Computes the median word of the words with the given median strategy
+/// (graphemes by default), each word can be given a weight, returns `None`
+/// if there are no words.
+///
+/// # Errors
+/// Returns an error if a word is invalid, if the median strategy is unknown
+/// or if the weights are invalid.
+pub fn compute_median_word(
+    words: &[String],
+    median_strategy: &Option<String>,
+    weights: Option<&[f32]>,
+) -> Result<Option<String>> {
     let words = words
-        .into_iter()
-        .map(|w| Word::new(w))
-        .collect::<Vec<Word>>();
-    let median_word = distances::compute_median_word(&words.iter().map(|w| w).collect());
-    median_word.map(|w| w.raw)
+        .iter()
+        .cloned()
+        .map(Word::try_new)
+        .collect::<Result<Vec<Word>>>()?;
+    let median_strategy = casting::cast_median_word_strategy(median_strategy)?;
+    check_median_weights(weights, words.len(), median_strategy)?;
+    let median_word = match weights {
+        None => distances::compute_median_word_with_strategy(
+            &words.iter().collect::<Vec<_>>(),
+            median_strategy,
+        )?,
+        Some(weights) => distances::compute_weighted_median_word(
+            &words
+                .iter()
+                .zip(weights.iter().copied())
+                .collect::<Vec<_>>(),
+        )?,
+    };
+    Ok(median_word.map(|w| w.raw().to_string()))
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-pub fn compute_words_clusters(
+#[pyo3(name = "compute_median_word", signature = (words, median_strategy=None, weights=None))]
+pub fn compute_median_word_process(
     words: Vec<String>,
-    distance_metric_config: DistanceMetricConfig,
+    median_strategy: Option<String>,
+    weights: Option<Vec<f32>>,
+) -> PyResult<Option<String>> {
+    Ok(compute_median_word(
+        &words,
+        &median_strategy,
+        weights.as_deref(),
+    )?)
+}
+
+/// Groups the words in clusters of words matching each other
+/// (see `threshold_match`), returns the words of each cluster.
+///
+/// # Errors
+/// Returns an error if a word or the distance metric configuration is invalid.
+pub fn compute_words_clusters(
+    words: &[String],
+    distance_metric_config: &DistanceMetricConfig,
     threshold_match: f32,
-) -> PyResult<Vec<Vec<String>>> {
+) -> Result<Vec<Vec<String>>> {
     let words = words
-        .into_iter()
-        .map(|w| Word::new(w))
-        .collect::<Vec<Word>>();
+        .iter()
+        .cloned()
+        .map(Word::try_new)
+        .collect::<Result<Vec<Word>>>()?;
 
-    let mut distance_calculator = casting::build_distance_calculator(&distance_metric_config)?;
+    let mut distance_calculator = casting::build_distance_calculator(distance_metric_config)?;
     let clusters_sets = normalization::compute_words_clusters(
         &mut distance_calculator,
         words.iter().collect(),
         threshold_match,
     );
 
-    let mut clusters = Vec::new();
-    for cluster in clusters_sets.into_iter() {
-        let mut cluster_words = Vec::new();
-        for i in cluster.iter() {
-            cluster_words.push(&words[i]);
-        }
-        clusters.push(cluster_words);
-    }
-
-    Ok(clusters
-        .iter()
-        .map(|cluster| cluster.iter().map(|w| w.raw.clone()).collect())
+    Ok(clusters_sets
+        .into_iter()
+        .map(|cluster| cluster.iter().map(|i| words[i].raw().to_string()).collect())
         .collect())
 }
 
+#[cfg(feature = "python")]
 #[pyfunction]
-pub fn normalize_words(
-    words: Vec<Option<String>>,
+#[pyo3(name = "compute_words_clusters")]
+pub fn compute_words_clusters_process(
+    words: Vec<String>,
     distance_metric_config: DistanceMetricConfig,
     threshold_match: f32,
+) -> PyResult<Vec<Vec<String>>> {
+    Ok(compute_words_clusters(
+        &words,
+        &distance_metric_config,
+        threshold_match,
+    )?)
+}
+
+/// Normalizes the words, each word is replaced by the median word of its
+/// cluster, the missing words are kept missing unless `infer_missing_clusters`
+/// is set.
+///
+/// # Errors
+/// Returns an error if a word, the distance metric configuration, the median
+/// strategy or the weights are invalid.
+#[allow(clippy::too_many_arguments)]
+pub fn normalize_words(
+    words: &[Option<String>],
+    distance_metric_config: &DistanceMetricConfig,
+    threshold_match: f32,
     min_cluster_size: usize,
     infer_missing_clusters: bool,
-) -> PyResult<Vec<Option<String>>> {
+    median_strategy: &Option<String>,
+    weights: Option<&[f32]>,
+) -> Result<Vec<Option<String>>> {
+    let median_strategy = casting::cast_median_word_strategy(median_strategy)?;
+    check_median_weights(weights, words.len(), median_strategy)?;
     let words = words
-        .into_iter()
-        .map(|w| w.map(|w| Word::new(w)))
-        .collect::<Vec<Option<Word>>>();
+        .iter()
+        .map(|w| w.clone().map(Word::try_new).transpose())
+        .collect::<Result<Vec<Option<Word>>>>()?;
 
-    let distance_calculator = casting::build_distance_calculator(&distance_metric_config)?;
+    let distance_calculator = casting::build_distance_calculator(distance_metric_config)?;
     let mut normalizer = Normalizer::new(
         InternalNormalizationConfig {
             threshold_cluster_match: threshold_match,
-            min_cluster_size: min_cluster_size,
+            min_cluster_size,
             infer_missing_clusters,
+            median_strategy,
+            weighted_median: weights.is_some(),
         },
         distance_calculator,
     );
 
-    let normalized_words = normalizer.normalize_words(words.iter().map(|w| w.as_ref()).collect());
-
+    let weights = weights.map_or_else(|| vec![1.; words.len()], <[f32]>::to_vec);
+    let normalized_words =
+        normalizer.normalize_words(words.iter().map(|w| w.as_ref()).collect(), &weights)?;
     Ok(normalized_words
         .iter()
-        .map(|w| match w {
-            None => None,
-            Some(w) => Some(w.raw.clone()),
-        })
+        .map(|w| w.as_ref().map(|w| w.raw().to_string()))
         .collect())
 }
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "normalize_words", signature = (words, distance_metric_config, threshold_match, min_cluster_size, infer_missing_clusters, median_strategy=None, weights=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn normalize_words_process(
+    words: Vec<Option<String>>,
+    distance_metric_config: DistanceMetricConfig,
+    threshold_match: f32,
+    min_cluster_size: usize,
+    infer_missing_clusters: bool,
+    median_strategy: Option<String>,
+    weights: Option<Vec<f32>>,
+) -> PyResult<Vec<Option<String>>> {
+    Ok(normalize_words(
+        &words,
+        &distance_metric_config,
+        threshold_match,
+        min_cluster_size,
+        infer_missing_clusters,
+        &median_strategy,
+        weights.as_deref(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::df;
+
+    use crate::{
+        error::BlitzBeaverError,
+        test_utils::{
+            build_name_dataframes, build_name_record_schema, build_sorted_chains,
+            build_tracking_config,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_execute_tracking() {
+        let record_schema = build_name_record_schema();
+        let dataframes = build_name_dataframes(&[&["alice", "bob"], &["bobb", "alise"]]);
+
+        let (tracking_graph, _) = execute_tracking(
+            &build_tracking_config(2),
+            &record_schema,
+            &dataframes,
+            &[],
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            build_sorted_chains(&tracking_graph),
+            vec![vec![(0, 0), (1, 1)], vec![(0, 1), (1, 0)]]
+        );
+
+        let result = execute_tracking(
+            &build_tracking_config(1),
+            &record_schema,
+            &dataframes,
+            &[],
+            &[],
+        );
+        assert!(matches!(result, Err(BlitzBeaverError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_performance_diagnostics() {
+        let record_schema = build_name_record_schema();
+        let dataframes =
+            build_name_dataframes(&[&["alice", "bob"], &["bobb", "alise"], &["bob", "alice"]]);
+
+        let (_, diagnostics) = execute_tracking(
+            &build_tracking_config(3),
+            &record_schema,
+            &dataframes,
+            &[],
+            &[],
+        )
+        .unwrap();
+
+        let frames = &diagnostics.performance.frames;
+        assert_eq!(
+            frames.iter().map(|f| f.frame_idx).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        for frame in frames.iter() {
+            assert_eq!(
+                frame
+                    .workers
+                    .iter()
+                    .map(|w| w.worker_idx)
+                    .collect::<Vec<_>>(),
+                vec![0, 1]
+            );
+            assert_eq!(
+                frame.workers.iter().map(|w| w.num_trackers).sum::<usize>(),
+                2
+            );
+            assert!(frame.workers.iter().all(|w| w.caches.len() == 1));
+        }
+
+        // every tracker computes the distance between every record of the frame
+        // and every value of its memory: 2 x 2 x 1 then 2 x 2 x 2
+        let totals = diagnostics.performance.cache_totals();
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].computation_count, 12);
+    }
+
+    #[test]
+    fn test_persistent_cache() {
+        let record_schema = build_name_record_schema();
+        let dataframes =
+            build_name_dataframes(&[&["alice", "bob"], &["bobb", "alise"], &["bob", "alice"]]);
+
+        let tracking_config = build_tracking_config(3);
+        let (tracking_graph, diagnostics) =
+            execute_tracking(&tracking_config, &record_schema, &dataframes, &[], &[]).unwrap();
+        let chains = build_sorted_chains(&tracking_graph);
+        assert!(diagnostics.performance.persistent_caches.is_empty());
+
+        let mut tracking_config = build_tracking_config(3);
+        tracking_config.distance_metric.persistent_cache_bytes = Some(1 << 20);
+        let (tracking_graph, diagnostics) =
+            execute_tracking(&tracking_config, &record_schema, &dataframes, &[], &[]).unwrap();
+        assert_eq!(build_sorted_chains(&tracking_graph), chains);
+
+        // the distances alice-alise and bob-bobb of the second frame are reused
+        let persistent_caches = &diagnostics.performance.persistent_caches;
+        assert_eq!(persistent_caches.len(), 1);
+        assert!(persistent_caches[0].hit_count >= 2);
+        assert_eq!(persistent_caches[0].eviction_count, 0);
+    }
+
+    #[test]
+    fn test_work_stealing_engine() {
+        let record_schema = build_name_record_schema();
+        let dataframes = build_name_dataframes(&[
+            &["alice", "bob", "carol"],
+            &["bobb", "alise", "karol"],
+            &["bob", "alice", "carole"],
+        ]);
+
+        let (tracking_graph, diagnostics) = execute_tracking(
+            &build_tracking_config(3),
+            &record_schema,
+            &dataframes,
+            &[],
+            &[],
+        )
+        .unwrap();
+        let chains = build_sorted_chains(&tracking_graph);
+        let computation_count = diagnostics.performance.cache_totals()[0].computation_count;
+
+        // the main thread takes part, a single thread is enough
+        for (num_threads, chunk_size) in [(1, None), (3, None), (3, Some(1))] {
+            let mut tracking_config = build_tracking_config(num_threads);
+            tracking_config.engine_mode = Some("work-stealing".to_string());
+            tracking_config.chunk_size = chunk_size;
+            let (tracking_graph, diagnostics) =
+                execute_tracking(&tracking_config, &record_schema, &dataframes, &[], &[]).unwrap();
+            assert_eq!(build_sorted_chains(&tracking_graph), chains);
+
+            for frame in diagnostics.performance.frames.iter() {
+                assert_eq!(frame.workers.len(), num_threads);
+                assert_eq!(
+                    frame.workers.iter().map(|w| w.num_trackers).sum::<usize>(),
+                    3
+                );
+                if chunk_size == Some(1) {
+                    assert_eq!(frame.workers.iter().map(|w| w.num_chunks).sum::<usize>(), 3);
+                }
+                assert!(frame.load_imbalance() >= 1.0);
+            }
+            assert_eq!(
+                diagnostics.performance.cache_totals()[0].computation_count,
+                computation_count
+            );
+        }
+    }
+
+    #[test]
+    fn test_execute_tracking_out_of_core() {
+        let record_schema = build_name_record_schema();
+        // the tracker of carol dies after 3 frames without match
+        let dataframes = build_name_dataframes(&[
+            &["alice", "bob", "carol"],
+            &["bobb", "alise"],
+            &["bob", "alice"],
+            &["alice", "bob"],
+            &["bob", "alise"],
+        ]);
+
+        let tracking_config = build_tracking_config(2);
+        let (tracking_graph, diagnostics) =
+            execute_tracking(&tracking_config, &record_schema, &dataframes, &[], &[]).unwrap();
+        let chains = build_sorted_chains(&tracking_graph);
+        assert_eq!(chains[2].len(), 1);
+
+        let spill_dir = std::env::temp_dir();
+        let execute_out_of_core =
+            |must_links: &[casting::RawLink], cannot_links: &[casting::RawLink], frame_window| {
+                let frames = dataframes.clone();
+                execute_tracking_out_of_core(
+                    &tracking_config,
+                    &record_schema,
+                    frames.len(),
+                    move |frame_idx| Ok(frames[frame_idx].clone()),
+                    must_links,
+                    cannot_links,
+                    frame_window,
+                    &spill_dir,
+                )
+            };
+        for frame_window in [1, 2] {
+            let (tracking_graph, ooc_diagnostics) =
+                execute_out_of_core(&[], &[], frame_window).unwrap();
+            assert_eq!(tracking_graph.matrix.len(), dataframes.len());
+            assert_eq!(build_sorted_chains(&tracking_graph), chains);
+            assert_eq!(ooc_diagnostics.trackers.len(), diagnostics.trackers.len());
+        }
+
+        // the tracker of carol waits for its must-link target
+        let must_links = [((0, 2), (4, 0))];
+        let cannot_links = [((0, 0), (1, 1))];
+        let (tracking_graph, _) = execute_tracking(
+            &tracking_config,
+            &record_schema,
+            &dataframes,
+            &must_links,
+            &cannot_links,
+        )
+        .unwrap();
+        let chains = build_sorted_chains(&tracking_graph);
+        assert!(chains.contains(&vec![(0, 2), (4, 0)]));
+        let (tracking_graph, _) = execute_out_of_core(&must_links, &cannot_links, 1).unwrap();
+        assert_eq!(build_sorted_chains(&tracking_graph), chains);
+
+        // the records of the links are checked when their frame is read
+        for must_links in [[((0, 0), (9, 0))], [((0, 0), (2, 5))]] {
+            let result = execute_out_of_core(&must_links, &[], 1);
+            assert!(matches!(result, Err(BlitzBeaverError::InvalidData(_))));
+        }
+
+        let result = execute_tracking_out_of_core(
+            &tracking_config,
+            &record_schema,
+            2,
+            |_| Ok(df!("age" => [1]).unwrap()),
+            &[],
+            &[],
+            2,
+            &spill_dir,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_words_functions() {
+        let distance_metric_config = DistanceMetricConfig {
+            metric: "lv".to_string(),
+            caching_threshold: 4,
+            use_sigmoid: false,
+            lv_edit_weights: None,
+            lv_substring_weight: None,
+            lv_multiword_separator: None,
+            persistent_cache_bytes: None,
+        };
+        let words = ["alice", "alise", "alice", "bob"].map(String::from);
+
+        assert_eq!(
+            compute_median_word(&words[..3], &None, None).unwrap(),
+            Some("alice".to_string())
+        );
+        assert_eq!(
+            compute_median_word(&words[..2], &None, Some(&[0.2, 0.8])).unwrap(),
+            Some("alise".to_string())
+        );
+        assert!(compute_median_word(&words, &None, Some(&[1.])).is_err());
+        assert!(compute_median_word(&words, &Some("set".to_string()), Some(&[1.; 4])).is_err());
+
+        let mut clusters = compute_words_clusters(&words, &distance_metric_config, 0.7).unwrap();
+        clusters.sort();
+        assert_eq!(
+            clusters,
+            vec![
+                vec![
+                    "alice".to_string(),
+                    "alise".to_string(),
+                    "alice".to_string()
+                ],
+                vec!["bob".to_string()],
+            ]
+        );
+
+        let normalized_words = normalize_words(
+            &words.clone().map(Some),
+            &distance_metric_config,
+            0.7,
+            1,
+            false,
+            &None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            normalized_words,
+            ["alice", "alice", "alice", "bob"].map(|w| Some(w.to_string()))
+        );
+    }
+}
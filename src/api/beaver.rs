@@ -1,39 +1,128 @@
-use pyo3::{exceptions::PyValueError, pyclass, pymethods, types::PyBytes, Bound, PyResult, Python};
+#[cfg(feature = "python")]
+use pyo3::{pyclass, pymethods, types::PyBytes, Bound, PyResult, Python};
 use serde::{Deserialize, Serialize};
 
+use crate::error::{BlitzBeaverError, Result};
+
 use super::{Diagnostics, TrackingGraph};
 
-#[pyclass]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Magic bytes at the start of a beaver file, followed by the format version.
+///
+/// The files of the version 1 have no header, they start with the
+/// serialized content directly.
+const BEAVER_MAGIC: &[u8; 6] = b"BEAVER";
+
+/// Version of the format of the beaver files, it must be incremented
+/// whenever the serialized layout of the content changes.
+///
+/// * 1: blitzbeaver 1.0.0, no header.
+/// * 2: links of the graph nodes, edits of the tracking graph and
+///   performance diagnostics.
+const BEAVER_VERSION: u32 = 2;
+
+#[cfg_attr(feature = "python", pyclass)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct BeaverFile {
     tracking_graph: Option<TrackingGraph>,
     diagnostics: Option<Diagnostics>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl BeaverFile {
     #[new]
     fn py_new() -> Self {
-        Self {
-            tracking_graph: None,
-            diagnostics: None,
-        }
+        Self::default()
     }
 
-    /// Deserialize a beaver file from bytes.
     #[staticmethod]
-    pub fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
-        bincode::deserialize(bytes)
-            .map_err(|_| PyValueError::new_err("failed to deserialize beaver file"))
+    #[pyo3(name = "from_bytes")]
+    fn py_from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        Ok(Self::from_bytes(bytes)?)
     }
 
     /// Serialize the beaver file to bytes.
     pub fn to_bytes<'a>(&self, py: Python<'a>) -> PyResult<Bound<'a, PyBytes>> {
-        let bytes = bincode::serialize(self)
-            .map_err(|_| PyValueError::new_err("failed to serialize beaver file"))?;
+        let bytes = self.serialize()?;
         Ok(PyBytes::new_bound(py, &bytes))
     }
 
+    #[pyo3(name = "set_tracking_graph")]
+    fn py_set_tracking_graph(&mut self, tracking_graph: TrackingGraph) {
+        self.set_tracking_graph(tracking_graph);
+    }
+
+    #[pyo3(name = "take_tracking_graph")]
+    fn py_take_tracking_graph(&mut self) -> PyResult<TrackingGraph> {
+        Ok(self.take_tracking_graph()?)
+    }
+
+    #[pyo3(name = "set_diagnostics")]
+    fn py_set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        self.set_diagnostics(diagnostics);
+    }
+
+    #[pyo3(name = "take_diagnostics")]
+    fn py_take_diagnostics(&mut self) -> PyResult<Diagnostics> {
+        Ok(self.take_diagnostics()?)
+    }
+}
+
+impl BeaverFile {
+    pub fn new(tracking_graph: TrackingGraph, diagnostics: Diagnostics) -> Self {
+        Self {
+            tracking_graph: Some(tracking_graph),
+            diagnostics: Some(diagnostics),
+        }
+    }
+
+    /// Deserialize a beaver file from bytes.
+    ///
+    /// # Errors
+    /// Returns a Serialization error if the format version of the file is not
+    /// the current one or if the content can't be deserialized.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let version = Self::read_version(bytes)?;
+        if version != BEAVER_VERSION {
+            return Err(BlitzBeaverError::Serialization(format!(
+                "unsupported .beaver version {}, expected version {}, \
+                the tracking must be run again to produce the file",
+                version, BEAVER_VERSION
+            )));
+        }
+        let content = &bytes[BEAVER_MAGIC.len() + size_of::<u32>()..];
+        bincode::deserialize(content).map_err(|_| {
+            BlitzBeaverError::Serialization("failed to deserialize beaver file".to_string())
+        })
+    }
+
+    /// Reads the format version from the header of the beaver file,
+    /// the files without header are of the version 1.
+    fn read_version(bytes: &[u8]) -> Result<u32> {
+        let Some(header) = bytes.strip_prefix(BEAVER_MAGIC) else {
+            return Ok(1);
+        };
+        let version = header
+            .get(..size_of::<u32>())
+            .ok_or_else(|| {
+                BlitzBeaverError::Serialization("truncated header of the beaver file".to_string())
+            })?
+            .try_into()
+            .unwrap();
+        Ok(u32::from_le_bytes(version))
+    }
+
+    /// Serialize the beaver file to a vector of bytes, with a header
+    /// holding the format version.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let mut bytes = BEAVER_MAGIC.to_vec();
+        bytes.extend_from_slice(&BEAVER_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, self).map_err(|_| {
+            BlitzBeaverError::Serialization("failed to serialize beaver file".to_string())
+        })?;
+        Ok(bytes)
+    }
+
     /// Set the tracking graph in the beaver file.
     pub fn set_tracking_graph(&mut self, tracking_graph: TrackingGraph) {
         self.tracking_graph = Some(tracking_graph);
@@ -42,14 +131,12 @@ impl BeaverFile {
     /// Take the tracking graph from the beaver file.
     ///
     /// This will remove the tracking graph from the beaver file.
-    pub fn take_tracking_graph(&mut self) -> PyResult<TrackingGraph> {
-        if self.tracking_graph.is_some() {
-            Ok(std::mem::replace(&mut self.tracking_graph, None).unwrap())
-        } else {
-            Err(PyValueError::new_err(
-                "the beaver file does not contain a tracking graph",
-            ))
-        }
+    pub fn take_tracking_graph(&mut self) -> Result<TrackingGraph> {
+        self.tracking_graph.take().ok_or_else(|| {
+            BlitzBeaverError::InvalidData(
+                "the beaver file does not contain a tracking graph".to_string(),
+            )
+        })
     }
 
     /// Set the diagnostics in the beaver file.
@@ -60,13 +147,42 @@ impl BeaverFile {
     /// Take the diagnostics from the beaver file.
     ///
     /// This will remove the diagnostics from the beaver file.
-    pub fn take_diagnostics(&mut self) -> PyResult<Diagnostics> {
-        if self.diagnostics.is_some() {
-            Ok(std::mem::replace(&mut self.diagnostics, None).unwrap())
-        } else {
-            Err(PyValueError::new_err(
-                "the beaver file does not contain diagnostics",
-            ))
-        }
+    pub fn take_diagnostics(&mut self) -> Result<Diagnostics> {
+        self.diagnostics.take().ok_or_else(|| {
+            BlitzBeaverError::InvalidData(
+                "the beaver file does not contain diagnostics".to_string(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beaver_version() {
+        let bytes = BeaverFile::default().serialize().unwrap();
+        assert!(bytes.starts_with(BEAVER_MAGIC));
+        let mut beaver_file = BeaverFile::from_bytes(&bytes).unwrap();
+        assert!(beaver_file.take_tracking_graph().is_err());
+
+        // the files of the version 1 have no header
+        let legacy_bytes = bincode::serialize(&BeaverFile::default()).unwrap();
+        let error = BeaverFile::from_bytes(&legacy_bytes).unwrap_err();
+        assert!(error.to_string().contains("unsupported .beaver version 1"));
+
+        let mut future_bytes = BEAVER_MAGIC.to_vec();
+        future_bytes.extend_from_slice(&(BEAVER_VERSION + 1).to_le_bytes());
+        let error = BeaverFile::from_bytes(&future_bytes).unwrap_err();
+        assert!(error.to_string().contains(&format!(
+            "unsupported .beaver version {}",
+            BEAVER_VERSION + 1
+        )));
+
+        assert!(matches!(
+            BeaverFile::from_bytes(&BEAVER_MAGIC[..]),
+            Err(BlitzBeaverError::Serialization(_))
+        ));
     }
 }
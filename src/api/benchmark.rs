@@ -0,0 +1,69 @@
+use polars::prelude::{Column, DataFrame};
+#[cfg(feature = "python")]
+use pyo3::{pyfunction, PyResult};
+#[cfg(feature = "python")]
+use pyo3_polars::PyDataFrame;
+#[cfg(feature = "python")]
+use std::time::Duration;
+
+#[cfg(feature = "python")]
+use crate::benchmark::{run_benchmark_suite, BenchmarkData, BenchmarkOptions};
+use crate::{benchmark::BenchmarkResult, error::Result};
+
+/// Builds a dataframe of the benchmark results, one row per benchmark with
+/// the statistics of the duration of one run in nanoseconds.
+///
+/// # Errors
+/// Returns an error if the dataframe can't be built.
+pub fn benchmark_results_to_dataframe(results: &[BenchmarkResult]) -> Result<DataFrame> {
+    let column = |name: &str, f: fn(&BenchmarkResult) -> f64| {
+        Column::new(name.into(), results.iter().map(f).collect::<Vec<f64>>())
+    };
+    Ok(DataFrame::new(vec![
+        Column::new(
+            "name".into(),
+            results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "num_samples".into(),
+            results
+                .iter()
+                .map(|r| r.num_samples as u64)
+                .collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "iterations_per_sample".into(),
+            results
+                .iter()
+                .map(|r| r.iterations_per_sample)
+                .collect::<Vec<_>>(),
+        ),
+        column("mean_ns", |r| r.mean_ns),
+        column("median_ns", |r| r.median_ns),
+        column("std_dev_ns", |r| r.std_dev_ns),
+        column("min_ns", |r| r.min_ns),
+        column("max_ns", |r| r.max_ns),
+    ])?)
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (num_households=500, num_frames=4, seed=0, num_samples=20, warm_up_time=0.5, measurement_time=2.0, filter=None))]
+pub fn benchmark_suite(
+    num_households: usize,
+    num_frames: usize,
+    seed: u64,
+    num_samples: usize,
+    warm_up_time: f64,
+    measurement_time: f64,
+    filter: Option<&str>,
+) -> PyResult<PyDataFrame> {
+    let data = BenchmarkData::generate(num_households, num_frames, seed)?;
+    let options = BenchmarkOptions {
+        num_samples,
+        warm_up_time: Duration::from_secs_f64(warm_up_time),
+        measurement_time: Duration::from_secs_f64(measurement_time),
+    };
+    let results = run_benchmark_suite(&data, &options, filter);
+    Ok(PyDataFrame(benchmark_results_to_dataframe(&results)?))
+}
@@ -1,63 +1,161 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
 use polars::{
     frame::DataFrame,
-    prelude::{Column, NamedFrom},
+    prelude::{Column, IdxCa, IdxSize, NamedFrom},
     series::Series,
 };
-use pyo3::{exceptions::PyValueError, PyResult};
-use pyo3_polars::{error::PyPolarsErr, PyDataFrame};
 
 use crate::{
-    distances::{CachedDistanceCalculator, InternalDistanceMetricConfig},
-    engine::{EngineConfig, TrackingEngine},
+    distances::{
+        CachedDistanceCalculator, DistanceMatrix, InternalDistanceMetricConfig, MedianWordStrategy,
+        PersistentDistanceCache,
+    },
+    engine::{EngineConfig, EngineMode, FrameStore, TrackingEngine},
+    error::{BlitzBeaverError, Result},
     frame::{Element, Frame},
+    generator::{EventRates, NoiseModel},
     normalization::InternalNormalizationConfig,
-    resolvers::{BestMatchResolvingStrategy, Resolver, ResolvingStrategy, SimpleResolvingStrategy},
-    trackers::{InternalTrackerConfig, TrackerMemoryConfig, TrackerRecordScorerConfig},
+    resolvers::{
+        BestMatchResolvingStrategy, MultiHypothesisResolvingStrategy, Resolver,
+        ResolvingConstraints, ResolvingStrategy, SimpleResolvingStrategy,
+    },
+    trackers::{
+        InternalTrackerConfig, MissingFieldDistances, TrackerCompositeFeatureConfig,
+        TrackerMemoryConfig, TrackerRecordScorerConfig, TrackingChain,
+    },
     word::Word,
 };
 
 use super::{
-    config::{MemoryConfig, RecordScorerConfig},
-    DistanceMetricConfig, ElementType, FieldSchema, NormalizationConfig, RecordSchema,
-    ResolverConfig, TrackerConfig, TrackingConfig,
+    config::{CompositeFeatureConfig, MemoryConfig, RecordScorerConfig},
+    ChainNode, DistanceMetricConfig, ElementType, FieldSchema, GeneratorConfig,
+    NormalizationConfig, RecordSchema, ResolverConfig, TrackerConfig, TrackingConfig,
 };
 
+pub(super) const METRIC_LV: &str = "lv";
+pub(super) const METRIC_LV_OPTI: &str = "lv_opti";
+pub(super) const METRIC_LV_BITPARALLEL: &str = "lv_bitparallel";
+pub(super) const METRIC_LV_EDIT: &str = "lv_edit";
+pub(super) const METRIC_LV_SUBSTRING: &str = "lv_substring";
+pub(super) const METRIC_LV_MULTIWORD: &str = "lv_multiword";
+/// Names of the distance metrics of DistanceMetricConfig.metric
+pub(super) const DISTANCE_METRICS: [&str; 6] = [
+    METRIC_LV,
+    METRIC_LV_OPTI,
+    METRIC_LV_BITPARALLEL,
+    METRIC_LV_EDIT,
+    METRIC_LV_SUBSTRING,
+    METRIC_LV_MULTIWORD,
+];
+
+pub(super) const MEMORY_BRUTEFORCE: &str = "bruteforce";
+pub(super) const MEMORY_MOSTFREQUENT: &str = "mostfrequent";
+pub(super) const MEMORY_MEDIAN: &str = "median";
+pub(super) const MEMORY_WEIGHTED_MEDIAN: &str = "weighted-median";
+pub(super) const MEMORY_LS_BRUTEFORCE: &str = "ls-bruteforce";
+pub(super) const MEMORY_LS_MOSTFREQUENT: &str = "ls-mostfrequent";
+pub(super) const MEMORY_LS_MEDIAN: &str = "ls-median";
+pub(super) const MEMORY_MW_MOSTFREQUENT: &str = "mw-mostfrequent";
+pub(super) const MEMORY_MW_MEDIAN: &str = "mw-median";
+pub(super) const MEMORY_MW_WEIGHTED_MEDIAN: &str = "mw-weighted-median";
+/// Names of the memory strategies of MemoryConfig.memory_strategy
+pub(super) const MEMORY_STRATEGIES: [&str; 10] = [
+    MEMORY_BRUTEFORCE,
+    MEMORY_MOSTFREQUENT,
+    MEMORY_MEDIAN,
+    MEMORY_WEIGHTED_MEDIAN,
+    MEMORY_LS_BRUTEFORCE,
+    MEMORY_LS_MOSTFREQUENT,
+    MEMORY_LS_MEDIAN,
+    MEMORY_MW_MOSTFREQUENT,
+    MEMORY_MW_MEDIAN,
+    MEMORY_MW_WEIGHTED_MEDIAN,
+];
+/// Names of the memory strategies that weight the words of the median word
+pub(super) const WEIGHTED_MEMORY_STRATEGIES: [&str; 2] =
+    [MEMORY_WEIGHTED_MEDIAN, MEMORY_MW_WEIGHTED_MEDIAN];
+/// Names of the memory strategies of multi-strings fields
+pub(super) const MULTIWORD_MEMORY_STRATEGIES: [&str; 3] = [
+    MEMORY_MW_MOSTFREQUENT,
+    MEMORY_MW_MEDIAN,
+    MEMORY_MW_WEIGHTED_MEDIAN,
+];
+
+pub(super) const MEDIAN_GRAPHEMES: &str = "graphemes";
+pub(super) const MEDIAN_SET_MEDIAN: &str = "set-median";
+pub(super) const MEDIAN_REFINED_MEDIAN: &str = "refined-median";
+/// Names of the median word strategies
+pub(super) const MEDIAN_STRATEGIES: [&str; 3] =
+    [MEDIAN_GRAPHEMES, MEDIAN_SET_MEDIAN, MEDIAN_REFINED_MEDIAN];
+
+pub(super) const SCORER_AVERAGE: &str = "average";
+pub(super) const SCORER_WEIGHTED_AVERAGE: &str = "weighted-average";
+pub(super) const SCORER_WEIGHTED_QUADRATIC: &str = "weighted-quadratic";
+/// Names of the record scorers of RecordScorerConfig.record_scorer
+pub(super) const RECORD_SCORERS: [&str; 3] = [
+    SCORER_AVERAGE,
+    SCORER_WEIGHTED_AVERAGE,
+    SCORER_WEIGHTED_QUADRATIC,
+];
+
+pub(super) const RESOLVING_SIMPLE: &str = "simple";
+pub(super) const RESOLVING_BEST_MATCH: &str = "best-match";
+pub(super) const RESOLVING_MULTI_HYPOTHESIS: &str = "multi-hypothesis";
+/// Names of the resolving strategies of ResolverConfig.resolving_strategy
+pub(super) const RESOLVING_STRATEGIES: [&str; 3] = [
+    RESOLVING_SIMPLE,
+    RESOLVING_BEST_MATCH,
+    RESOLVING_MULTI_HYPOTHESIS,
+];
+
+pub(super) const ENGINE_WORKERS: &str = "workers";
+pub(super) const ENGINE_WORK_STEALING: &str = "work-stealing";
+/// Names of the engine modes of TrackingConfig.engine_mode
+pub(super) const ENGINE_MODES: [&str; 2] = [ENGINE_WORKERS, ENGINE_WORK_STEALING];
+
 /// Casts a polars series to a vector of Word elements.
 ///
+/// The words are interned, each unique value is stored once.
+///
 /// # Errors
-/// Returns a PyPolarsErr if the series cannot be cast to a string series.
-fn cast_to_string_column(serie: &Series) -> PyResult<Vec<Element>> {
-    Ok(serie
-        .str()
-        .map_err(PyPolarsErr::from)?
+/// Returns an error if the series cannot be cast to a string series,
+/// or if all the word IDs have been used.
+fn cast_to_string_column(serie: &Series) -> Result<Vec<Element>> {
+    serie
+        .str()?
         .iter()
         .map(|v| match v {
-            None => Element::None,
-            Some(v) => Element::Word(Word::new(v.to_string())),
+            None => Ok(Element::None),
+            Some(v) => Ok(Element::Word(Word::try_new(v)?)),
         })
-        .collect())
+        .collect()
 }
 
 /// Casts a polars series to a vector of MultiWords elements.
 ///
 /// # Errors
-/// Returns a PyPolarsErr if the series cannot be cast to a list of string series.
+/// Returns an error if the series cannot be cast to a list of string series.
 /// Returns a PyValueError if a None value is found in the list.
-fn cast_to_multistrings_column(serie: &Series) -> PyResult<Vec<Element>> {
+/// Returns an error if all the word IDs have been used.
+fn cast_to_multistrings_column(serie: &Series) -> Result<Vec<Element>> {
     let mut elements = Vec::new();
 
-    for cell in serie.list().map_err(PyPolarsErr::from)?.into_iter() {
+    for cell in serie.list()?.into_iter() {
         match cell {
             Some(cell) => {
                 let mut words = Vec::new();
 
-                for v in cell.str().map_err(PyPolarsErr::from)?.into_iter() {
+                for v in cell.str()?.into_iter() {
                     match v {
                         Some(value) => {
-                            words.push(Word::new(value.to_string()));
+                            words.push(Word::try_new(value)?);
                         }
                         None => {
-                            return Err(PyValueError::new_err(format!(
+                            return Err(BlitzBeaverError::InvalidData(format!(
                                 "None value in list[str] cell: {:?}",
                                 cell
                             )));
@@ -77,8 +175,8 @@ fn cast_to_multistrings_column(serie: &Series) -> PyResult<Vec<Element>> {
 /// Casts a polars series to a vector of elements based on the field schema.
 ///
 /// # Errors
-/// Returns PyPolarsErr or PyValueError if the series cannot be cast to the specified type.
-fn cast_to_frame_column(field_schema: &FieldSchema, serie: &Series) -> PyResult<Vec<Element>> {
+/// Returns an error if the series cannot be cast to the specified type.
+fn cast_to_frame_column(field_schema: &FieldSchema, serie: &Series) -> Result<Vec<Element>> {
     match &field_schema.dtype {
         ElementType::String => cast_to_string_column(serie),
         ElementType::MultiStrings => cast_to_multistrings_column(serie),
@@ -88,20 +186,17 @@ fn cast_to_frame_column(field_schema: &FieldSchema, serie: &Series) -> PyResult<
 /// Casts a polars dataframe to a Frame.
 ///
 /// # Errors
-/// Returns PyPolarsErr or PyValueError if the dataframe cannot be cast to a Frame.
+/// Returns an error if the dataframe cannot be cast to a Frame.
 pub fn cast_to_frame(
     frame_idx: usize,
     record_schema: &RecordSchema,
-    dataframe: &PyDataFrame,
-) -> PyResult<Frame> {
+    dataframe: &DataFrame,
+) -> Result<Frame> {
     let mut columns = Vec::new();
     for field_schema in record_schema.fields.iter() {
-        let column = dataframe
-            .0
-            .column(&field_schema.name)
-            .map_err(PyPolarsErr::from)?;
-        let series = column.as_series().ok_or(PyValueError::new_err(
-            "Internal error: invalid polars column",
+        let column = dataframe.column(&field_schema.name)?;
+        let series = column.as_series().ok_or(BlitzBeaverError::InvalidData(
+            "Internal error: invalid polars column".to_string(),
         ))?;
 
         columns.push(cast_to_frame_column(field_schema, series)?);
@@ -113,8 +208,8 @@ pub fn cast_to_frame(
 /// Casts a frame to a polars dataframe.
 ///
 /// # Errors
-/// Returns PyPolarsErr if the frame cannot be cast to a DataFrame.
-pub fn cast_to_dataframe(record_schema: &RecordSchema, frame: &Frame) -> PyResult<PyDataFrame> {
+/// Returns an error if the frame cannot be cast to a DataFrame.
+pub fn cast_to_dataframe(record_schema: &RecordSchema, frame: &Frame) -> Result<DataFrame> {
     let mut columns = Vec::new();
     for (i, field_schema) in record_schema.fields.iter().enumerate() {
         let column = frame.column(i);
@@ -123,7 +218,7 @@ pub fn cast_to_dataframe(record_schema: &RecordSchema, frame: &Frame) -> PyResul
                 field_schema.name.as_str().into(),
                 column
                     .iter()
-                    .map(|e| e.as_word().map(|w| w.raw.clone()))
+                    .map(|e| e.as_word().map(|w| w.raw().to_string()))
                     .collect::<Vec<_>>(),
             ),
             ElementType::MultiStrings => {
@@ -132,10 +227,7 @@ pub fn cast_to_dataframe(record_schema: &RecordSchema, frame: &Frame) -> PyResul
                     .map(|e| {
                         Series::new(
                             "".into(),
-                            e.as_multiword()
-                                .iter()
-                                .map(|w| w.raw.as_str())
-                                .collect::<Vec<_>>(),
+                            e.as_multiword().iter().map(|w| w.raw()).collect::<Vec<_>>(),
                         )
                     })
                     .collect::<Vec<_>>();
@@ -146,77 +238,394 @@ pub fn cast_to_dataframe(record_schema: &RecordSchema, frame: &Frame) -> PyResul
         columns.push(series);
     }
 
-    let df = DataFrame::new(columns).map_err(PyPolarsErr::from)?;
+    let df = DataFrame::new(columns)?;
+
+    Ok(df)
+}
+
+/// Takes the records of the chain nodes from the dataframes, in the order of the nodes.
+///
+/// The columns are the fields of the record schema, their names are suffixed with the suffix.
+///
+/// # Errors
+/// Returns an error if the fields can't be selected from the dataframes.
+/// Returns an error if a chain node references a missing frame or record.
+fn take_chain_records(
+    nodes: &[ChainNode],
+    record_schema: &RecordSchema,
+    dataframes: &[DataFrame],
+    suffix: &str,
+) -> Result<Vec<Column>> {
+    let names = record_schema
+        .fields
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect::<Vec<_>>();
+
+    // concatenates the frames such that the records can be taken at once
+    let mut offsets = Vec::with_capacity(dataframes.len());
+    let mut records = DataFrame::empty();
+    for (i, dataframe) in dataframes.iter().enumerate() {
+        let selected = dataframe.select(names.iter().copied())?;
+        offsets.push(records.height());
+        if i == 0 {
+            records = selected;
+        } else {
+            records.vstack_mut(&selected)?;
+        }
+    }
+    offsets.push(records.height());
+
+    let mut indices = Vec::with_capacity(nodes.len());
+    for cn in nodes.iter() {
+        if cn.frame_idx >= dataframes.len()
+            || cn.record_idx >= offsets[cn.frame_idx + 1] - offsets[cn.frame_idx]
+        {
+            return Err(BlitzBeaverError::InvalidData(format!(
+                "Record {} of frame {} not found in the dataframes",
+                cn.record_idx, cn.frame_idx
+            )));
+        }
+        indices.push((offsets[cn.frame_idx] + cn.record_idx) as IdxSize);
+    }
+
+    let mut columns = records
+        .take(&IdxCa::from_vec("".into(), indices))?
+        .take_columns();
+    for (column, name) in columns.iter_mut().zip(names.iter()) {
+        column.rename(format!("{}{}", name, suffix).into());
+    }
+    Ok(columns)
+}
+
+/// Casts tracking chains to a long-format polars dataframe, with one row per chain node.
+///
+/// The columns are the chain ID, frame index, record index and score of the link of
+/// the node, followed by the fields of the records taken from the dataframes, if any,
+/// and the fields of the normalized records, suffixed with `_normalized`, if any.
+///
+/// # Errors
+/// Returns an error if the records can't be taken from the dataframes.
+pub fn cast_tracking_chains_to_dataframe(
+    chains: &[TrackingChain],
+    record_schema: Option<&RecordSchema>,
+    dataframes: Option<&[DataFrame]>,
+    normalized_dataframes: Option<&[DataFrame]>,
+) -> Result<DataFrame> {
+    let num_nodes = chains.iter().map(|c| c.nodes.len()).sum();
+    let mut chains_ids = Vec::with_capacity(num_nodes);
+    let mut nodes = Vec::with_capacity(num_nodes);
+    let mut scores = Vec::with_capacity(num_nodes);
+
+    for chain in chains.iter() {
+        for (cn, link) in chain.nodes.iter().zip(chain.links.iter()) {
+            chains_ids.push(chain.id);
+            nodes.push(*cn);
+            scores.push(link.as_ref().map(|l| l.score));
+        }
+    }
+
+    let mut columns = vec![
+        Column::new("chain_id".into(), chains_ids),
+        Column::new(
+            "frame_idx".into(),
+            nodes
+                .iter()
+                .map(|cn| cn.frame_idx as u64)
+                .collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "record_idx".into(),
+            nodes
+                .iter()
+                .map(|cn| cn.record_idx as u64)
+                .collect::<Vec<_>>(),
+        ),
+        Column::new("link_score".into(), scores),
+    ];
+
+    for (dataframes, suffix) in [(dataframes, ""), (normalized_dataframes, "_normalized")] {
+        if let Some(dataframes) = dataframes {
+            let record_schema = record_schema.ok_or_else(|| {
+                BlitzBeaverError::InvalidData(
+                    "A record schema is required to export the records".to_string(),
+                )
+            })?;
+            columns.extend(take_chain_records(
+                &nodes,
+                record_schema,
+                dataframes,
+                suffix,
+            )?);
+        }
+    }
+
+    let df = DataFrame::new(columns)?;
 
-    Ok(PyDataFrame(df))
+    Ok(df)
 }
 
 /// Get an optional attribute from a configuration.
 ///
 /// # Errors
-/// Returns PyValueError if the attribute is missing.
-fn get_optional_attribute<T>(value: Option<T>, attribute: &str, context: &str) -> PyResult<T> {
+/// Returns an error if the attribute is missing.
+fn get_optional_attribute<T>(value: Option<T>, attribute: &str, context: &str) -> Result<T> {
     value.ok_or_else(|| {
-        PyValueError::new_err(format!("Missing {} attribute in {}", attribute, context))
+        BlitzBeaverError::InvalidConfig(format!("Missing {} attribute in {}", attribute, context))
     })
 }
 
 /// Builds a tracking engine from the given configuration and frames.
 ///
 /// # Errors
-/// Returns PyValueError if the configuration is invalid.
+/// Returns an error if the configuration is invalid or the first frame can't be loaded.
 pub fn build_tracking_engine(
     config: &TrackingConfig,
     record_schema: &RecordSchema,
-    frames: Vec<Frame>,
-) -> PyResult<TrackingEngine> {
-    Ok(TrackingEngine::new(
+    frames: FrameStore,
+    constraints: ResolvingConstraints,
+) -> Result<TrackingEngine> {
+    build_tracking_engine_with_shared_caches(config, record_schema, frames, constraints, &[])
+}
+
+/// Builds a tracking engine whose distance calculators look up the given
+/// shared caches, one per feature, before computing a distance.
+///
+/// If the distance metric has a memory budget for the persistent cache,
+/// each feature gets its own persistent cache, shared between the workers.
+///
+/// # Errors
+/// Returns an error if the configuration is invalid or the first frame can't be loaded.
+pub fn build_tracking_engine_with_shared_caches(
+    config: &TrackingConfig,
+    record_schema: &RecordSchema,
+    frames: FrameStore,
+    constraints: ResolvingConstraints,
+    shared_caches: &[Arc<DistanceMatrix>],
+) -> Result<TrackingEngine> {
+    let mut distance_calculators =
+        build_distance_calculators(&config.distance_metric, record_schema)?;
+    if !shared_caches.is_empty() {
+        distance_calculators = distance_calculators
+            .into_iter()
+            .zip(shared_caches.iter())
+            .map(|(d, cache)| d.with_shared_cache(Arc::clone(cache)))
+            .collect();
+    }
+    if let Some(memory_budget) = config.distance_metric.persistent_cache_bytes {
+        distance_calculators = distance_calculators
+            .into_iter()
+            .map(|d| {
+                d.with_persistent_cache(Arc::new(PersistentDistanceCache::with_memory_budget(
+                    memory_budget,
+                )))
+            })
+            .collect();
+    }
+
+    TrackingEngine::new(
         frames,
-        cast_engine_config(config)?,
-        build_resolver(&config.resolver)?,
-        build_distance_calculators(&config.distance_metric, record_schema)?,
+        cast_engine_config(config, record_schema)?,
+        build_resolver(&config.resolver, constraints)?,
+        distance_calculators,
+    )
+}
+
+/// Link between two records, as ((frame index, record index), (frame index, record index)).
+pub type RawLink = ((usize, usize), (usize, usize));
+
+fn unknown_record_error(frame_idx: usize, record_idx: usize, context: &str) -> BlitzBeaverError {
+    BlitzBeaverError::InvalidData(format!(
+        "Invalid {}: unknown record ({}, {})",
+        context, frame_idx, record_idx
+    ))
+}
+
+/// Checks that a link references existing records and goes forward in time,
+/// the number of records of a frame is None if the frame isn't loaded yet.
+///
+/// # Errors
+/// Returns an error if the link is invalid.
+fn cast_link(
+    link: RawLink,
+    num_frames: usize,
+    num_records: &impl Fn(usize) -> Option<usize>,
+    context: &str,
+) -> Result<(ChainNode, ChainNode)> {
+    let source = ChainNode::new(link.0 .0, link.0 .1);
+    let target = ChainNode::new(link.1 .0, link.1 .1);
+    for node in [source, target] {
+        if node.frame_idx >= num_frames
+            || num_records(node.frame_idx).is_some_and(|n| node.record_idx >= n)
+        {
+            return Err(unknown_record_error(
+                node.frame_idx,
+                node.record_idx,
+                context,
+            ));
+        }
+    }
+    if source.frame_idx >= target.frame_idx {
+        return Err(BlitzBeaverError::InvalidData(format!(
+            "Invalid {}: ({}, {}) -> ({}, {}) doesn't go forward in time",
+            context, source.frame_idx, source.record_idx, target.frame_idx, target.record_idx
+        )));
+    }
+    Ok((source, target))
+}
+
+/// Casts the must-links and cannot-links to resolving constraints.
+///
+/// # Errors
+/// Returns an error if a link is invalid or if the links are conflicting.
+fn cast_links(
+    must_links: &[RawLink],
+    cannot_links: &[RawLink],
+    num_frames: usize,
+    num_records: impl Fn(usize) -> Option<usize>,
+) -> Result<ResolvingConstraints> {
+    let mut casted_must_links = HashMap::new();
+    for link in must_links.iter() {
+        let (source, target) = cast_link(*link, num_frames, &num_records, "must-link")?;
+        if let Some(other) = casted_must_links.insert(source, target) {
+            if other != target {
+                return Err(BlitzBeaverError::InvalidData(format!(
+                    "Conflicting must-links from ({}, {})",
+                    source.frame_idx, source.record_idx
+                )));
+            }
+        }
+    }
+
+    let mut casted_cannot_links = HashSet::new();
+    for link in cannot_links.iter() {
+        let (source, target) = cast_link(*link, num_frames, &num_records, "cannot-link")?;
+        if casted_must_links.get(&source) == Some(&target) {
+            return Err(BlitzBeaverError::InvalidData(format!(
+                "Conflicting must-link and cannot-link ({}, {}) -> ({}, {})",
+                source.frame_idx, source.record_idx, target.frame_idx, target.record_idx
+            )));
+        }
+        casted_cannot_links.insert((source, target));
+    }
+
+    Ok(ResolvingConstraints::new(
+        casted_must_links,
+        casted_cannot_links,
     ))
 }
 
+/// Casts the must-links and cannot-links to resolving constraints.
+///
+/// # Errors
+/// Returns an error if a link is invalid or if the links are conflicting.
+pub fn cast_resolving_constraints(
+    must_links: &[RawLink],
+    cannot_links: &[RawLink],
+    frames: &[Frame],
+) -> Result<ResolvingConstraints> {
+    cast_links(must_links, cannot_links, frames.len(), |frame_idx| {
+        Some(frames[frame_idx].num_records())
+    })
+}
+
+/// Casts the must-links and cannot-links to resolving constraints, before the
+/// frames are loaded: the records of the links are checked when their frame
+/// is loaded, see `check_links_records`.
+///
+/// # Errors
+/// Returns an error if a link is invalid or if the links are conflicting.
+pub fn cast_lazy_resolving_constraints(
+    must_links: &[RawLink],
+    cannot_links: &[RawLink],
+    num_frames: usize,
+) -> Result<ResolvingConstraints> {
+    cast_links(must_links, cannot_links, num_frames, |_| None)
+}
+
+/// Checks that the links reference existing records of the frame.
+///
+/// # Errors
+/// Returns an error if a record of the frame doesn't exist.
+pub fn check_links_records(
+    must_links: &[RawLink],
+    cannot_links: &[RawLink],
+    frame: &Frame,
+) -> Result<()> {
+    for (links, context) in [(must_links, "must-link"), (cannot_links, "cannot-link")] {
+        for link in links.iter() {
+            for (frame_idx, record_idx) in [link.0, link.1] {
+                if frame_idx == frame.idx() && record_idx >= frame.num_records() {
+                    return Err(unknown_record_error(frame_idx, record_idx, context));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Builds a resolver from the given configuration.
 ///
 /// # Errors
-/// Returns PyValueError if the configuration is invalid.
-fn build_resolver(resolver_config: &ResolverConfig) -> PyResult<Resolver> {
+/// Returns an error if the configuration is invalid.
+fn build_resolver(
+    resolver_config: &ResolverConfig,
+    constraints: ResolvingConstraints,
+) -> Result<Resolver> {
     let resolving_strategy: Box<dyn ResolvingStrategy> =
         match resolver_config.resolving_strategy.as_str() {
-            "simple" => Box::new(SimpleResolvingStrategy {}),
-            "best-match" => Box::new(BestMatchResolvingStrategy {}),
+            RESOLVING_SIMPLE => Box::new(SimpleResolvingStrategy {}),
+            RESOLVING_BEST_MATCH => Box::new(BestMatchResolvingStrategy {}),
+            RESOLVING_MULTI_HYPOTHESIS => Box::new(MultiHypothesisResolvingStrategy::new(
+                get_optional_attribute(
+                    resolver_config.num_hypotheses,
+                    "num_hypotheses",
+                    "ResolverConfig",
+                )?,
+                get_optional_attribute(
+                    resolver_config.hypotheses_depth,
+                    "hypotheses_depth",
+                    "ResolverConfig",
+                )?,
+            )),
             v => {
-                return Err(PyValueError::new_err(format!(
+                return Err(BlitzBeaverError::InvalidConfig(format!(
                     "Invalid resolving strategy: {}",
                     v
                 )));
             }
         };
 
-    Ok(Resolver::new(resolving_strategy))
+    Ok(Resolver::new(
+        resolving_strategy,
+        resolver_config.ambiguity_margin,
+        constraints,
+    ))
 }
 
 fn cast_distance_metric_config(
     distance_metric_config: &DistanceMetricConfig,
-) -> PyResult<InternalDistanceMetricConfig> {
+) -> Result<InternalDistanceMetricConfig> {
     match distance_metric_config.metric.as_str() {
-        "lv" => Ok(InternalDistanceMetricConfig::Lv(
+        METRIC_LV => Ok(InternalDistanceMetricConfig::Lv(
+            distance_metric_config.use_sigmoid,
+        )),
+        METRIC_LV_OPTI => Ok(InternalDistanceMetricConfig::LvOpti(
             distance_metric_config.use_sigmoid,
         )),
-        "lv_opti" => Ok(InternalDistanceMetricConfig::LvOpti(
+        METRIC_LV_BITPARALLEL => Ok(InternalDistanceMetricConfig::LvBitParallel(
             distance_metric_config.use_sigmoid,
         )),
-        "lv_edit" => {
+        METRIC_LV_EDIT => {
             let weights = get_optional_attribute(
                 distance_metric_config.lv_edit_weights.clone(),
                 "lv_edit_weights",
                 "DistanceMetricConfig",
             )?;
             if weights.len() != 3 {
-                return Err(PyValueError::new_err(
-                    "lv_edit_weights attribute must have 3 weights in DistanceMetricConfig",
+                return Err(BlitzBeaverError::InvalidConfig(
+                    "lv_edit_weights attribute must have 3 weights in DistanceMetricConfig"
+                        .to_string(),
                 ));
             }
 
@@ -227,7 +636,7 @@ fn cast_distance_metric_config(
                 distance_metric_config.use_sigmoid,
             ))
         }
-        "lv_substring" => Ok(InternalDistanceMetricConfig::LvSubstring(
+        METRIC_LV_SUBSTRING => Ok(InternalDistanceMetricConfig::LvSubstring(
             get_optional_attribute(
                 distance_metric_config.lv_substring_weight,
                 "lv_substring_weight",
@@ -235,7 +644,7 @@ fn cast_distance_metric_config(
             )?,
             distance_metric_config.use_sigmoid,
         )),
-        "lv_multiword" => {
+        METRIC_LV_MULTIWORD => {
             let separator = get_optional_attribute(
                 distance_metric_config.lv_multiword_separator.clone(),
                 "lv_multiword_separator",
@@ -247,7 +656,7 @@ fn cast_distance_metric_config(
                 distance_metric_config.use_sigmoid,
             ))
         }
-        v => Err(PyValueError::new_err(format!(
+        v => Err(BlitzBeaverError::InvalidConfig(format!(
             "Invalid distance metric: {}",
             v
         ))),
@@ -257,10 +666,10 @@ fn cast_distance_metric_config(
 /// Builds a distance calculator from the given configuration.
 ///
 /// # Errors
-/// Returns PyValueError if the configuration is invalid.
+/// Returns an error if the configuration is invalid.
 pub fn build_distance_calculator(
     distance_metric_config: &DistanceMetricConfig,
-) -> PyResult<CachedDistanceCalculator> {
+) -> Result<CachedDistanceCalculator> {
     let internal_distance_metric_config = cast_distance_metric_config(distance_metric_config)?;
     Ok(CachedDistanceCalculator::new(
         internal_distance_metric_config.make_metric(),
@@ -271,11 +680,11 @@ pub fn build_distance_calculator(
 /// Builds a list of distance calculators from the given configuration and record schema.
 ///
 /// # Errors
-/// Returns PyValueError if the configuration is invalid.
-fn build_distance_calculators(
+/// Returns an error if the configuration is invalid.
+pub fn build_distance_calculators(
     distance_metric_config: &DistanceMetricConfig,
     record_schema: &RecordSchema,
-) -> PyResult<Vec<CachedDistanceCalculator>> {
+) -> Result<Vec<CachedDistanceCalculator>> {
     let internal_distance_metric_config = cast_distance_metric_config(distance_metric_config)?;
     let mut distance_calculators = Vec::new();
     for _ in record_schema.fields.iter() {
@@ -291,13 +700,13 @@ fn build_distance_calculators(
 /// Cast a RecordScorerConfig to a TrackerRecordScorer.
 ///
 /// # Errors
-/// Returns PyValueError if the configuration is invalid.
+/// Returns an error if the configuration is invalid.
 fn cast_record_scorer_config(
     record_scorer_config: &RecordScorerConfig,
-) -> PyResult<TrackerRecordScorerConfig> {
+) -> Result<TrackerRecordScorerConfig> {
     Ok(match record_scorer_config.record_scorer.as_str() {
-        "average" => TrackerRecordScorerConfig::Average,
-        "weighted-average" => TrackerRecordScorerConfig::WeightedAverage(
+        SCORER_AVERAGE => TrackerRecordScorerConfig::Average,
+        SCORER_WEIGHTED_AVERAGE => TrackerRecordScorerConfig::WeightedAverage(
             get_optional_attribute(
                 record_scorer_config.weights.clone(),
                 "weights",
@@ -309,7 +718,7 @@ fn cast_record_scorer_config(
                 "RecordScorerConfig",
             )?,
         ),
-        "weighted-quadratic" => TrackerRecordScorerConfig::WeightedQuadratic(
+        SCORER_WEIGHTED_QUADRATIC => TrackerRecordScorerConfig::WeightedQuadratic(
             get_optional_attribute(
                 record_scorer_config.weights.clone(),
                 "weights",
@@ -322,7 +731,7 @@ fn cast_record_scorer_config(
             )?,
         ),
         v => {
-            return Err(PyValueError::new_err(format!(
+            return Err(BlitzBeaverError::InvalidConfig(format!(
                 "Invalid record scorer: {}",
                 v
             )))
@@ -330,21 +739,141 @@ fn cast_record_scorer_config(
     })
 }
 
+/// Cast an optional list of missing distances, checking that it
+/// has one value per feature.
+///
+/// # Errors
+/// Returns an error if the list doesn't have one value per feature.
+fn cast_missing_distances(
+    missing_distances: &Option<Vec<Option<f32>>>,
+    attribute: &str,
+    num_features: usize,
+) -> Result<Vec<Option<f32>>> {
+    match missing_distances {
+        None => Ok(Vec::new()),
+        Some(values) => {
+            if values.len() != num_features {
+                return Err(BlitzBeaverError::InvalidConfig(format!(
+                    "{} attribute must have one value per field in RecordScorerConfig",
+                    attribute
+                )));
+            }
+            Ok(values.clone())
+        }
+    }
+}
+
+/// Cast the missing distances of a RecordScorerConfig to MissingFieldDistances.
+///
+/// # Errors
+/// Returns an error if the configuration is invalid.
+fn cast_missing_field_distances(
+    record_scorer_config: &RecordScorerConfig,
+    num_features: usize,
+) -> Result<MissingFieldDistances> {
+    Ok(MissingFieldDistances::new(
+        cast_missing_distances(
+            &record_scorer_config.missing_record_distances,
+            "missing_record_distances",
+            num_features,
+        )?,
+        cast_missing_distances(
+            &record_scorer_config.missing_memory_distances,
+            "missing_memory_distances",
+            num_features,
+        )?,
+        cast_missing_distances(
+            &record_scorer_config.missing_both_distances,
+            "missing_both_distances",
+            num_features,
+        )?,
+    ))
+}
+
+/// Cast a CompositeFeatureConfig to a TrackerCompositeFeatureConfig,
+/// the fields are resolved to their index in the record schema.
+///
+/// # Errors
+/// Returns an error if a field is not in the record schema.
+fn cast_composite_feature_config(
+    composite_feature_config: &CompositeFeatureConfig,
+    record_schema: &RecordSchema,
+) -> Result<TrackerCompositeFeatureConfig> {
+    let mut feature_idxs = Vec::new();
+    for field in composite_feature_config.fields.iter() {
+        let feature_idx = record_schema
+            .fields
+            .iter()
+            .position(|f| f.name == *field)
+            .ok_or_else(|| {
+                BlitzBeaverError::InvalidConfig(format!(
+                    "Unknown field {} in CompositeFeatureConfig",
+                    field
+                ))
+            })?;
+        feature_idxs.push(feature_idx);
+    }
+
+    Ok(TrackerCompositeFeatureConfig {
+        feature_idxs,
+        threshold_match: composite_feature_config.threshold_match,
+        bonus: composite_feature_config.bonus,
+    })
+}
+
+/// Returns the minimum number of threads of the engine mode of the configuration.
+pub fn min_num_threads(config: &TrackingConfig) -> usize {
+    match config.engine_mode.as_deref() {
+        Some(ENGINE_WORK_STEALING) => 1,
+        // one thread runs the engine, the others the workers
+        _ => 2,
+    }
+}
+
 /// Cast a TrackingConfig to an EngineConfig.
 ///
 /// # Errors
-/// Returns PyValueError if the configuration is invalid.
-fn cast_engine_config(config: &TrackingConfig) -> PyResult<EngineConfig> {
+/// Returns an error if the configuration is invalid.
+fn cast_engine_config(
+    config: &TrackingConfig,
+    record_schema: &RecordSchema,
+) -> Result<EngineConfig> {
+    let mode = match config.engine_mode.as_deref().unwrap_or(ENGINE_WORKERS) {
+        ENGINE_WORKERS => {
+            // one thread runs the engine, the others the workers
+            if config.num_threads < 2 {
+                return Err(BlitzBeaverError::InvalidConfig(
+                    "num_threads must be at least 2 in TrackingConfig".to_string(),
+                ));
+            }
+            EngineMode::Workers
+        }
+        ENGINE_WORK_STEALING => {
+            if config.num_threads < 1 {
+                return Err(BlitzBeaverError::InvalidConfig(
+                    "num_threads must be at least 1 in TrackingConfig".to_string(),
+                ));
+            }
+            EngineMode::WorkStealing(config.chunk_size)
+        }
+        mode => {
+            return Err(BlitzBeaverError::InvalidConfig(format!(
+                "Invalid engine mode: {}",
+                mode
+            )))
+        }
+    };
     Ok(EngineConfig {
         num_threads: config.num_threads,
-        tracker_config: cast_tracker_config(&config.tracker)?,
+        mode,
+        tracker_config: cast_tracker_config(&config.tracker, record_schema)?,
     })
 }
 
 fn cast_multiword_memory_config(
     memory_config: &MemoryConfig,
     tracker_memory_config: TrackerMemoryConfig,
-) -> PyResult<TrackerMemoryConfig> {
+) -> Result<TrackerMemoryConfig> {
     Ok(TrackerMemoryConfig::MultiWord(
         Box::new(tracker_memory_config),
         cast_distance_metric_config(&get_optional_attribute(
@@ -360,24 +889,48 @@ fn cast_multiword_memory_config(
     ))
 }
 
-fn cast_memory_config(memory_config: &MemoryConfig) -> PyResult<TrackerMemoryConfig> {
+/// Casts the median word strategy, the most frequent graphemes if None.
+///
+/// # Errors
+/// Returns an error if the strategy is unknown.
+pub fn cast_median_word_strategy(median_strategy: &Option<String>) -> Result<MedianWordStrategy> {
+    Ok(match median_strategy.as_deref() {
+        None | Some(MEDIAN_GRAPHEMES) => MedianWordStrategy::Graphemes,
+        Some(MEDIAN_SET_MEDIAN) => MedianWordStrategy::SetMedian,
+        Some(MEDIAN_REFINED_MEDIAN) => MedianWordStrategy::RefinedMedian,
+        Some(v) => {
+            return Err(BlitzBeaverError::InvalidConfig(format!(
+                "Invalid median strategy: {}",
+                v
+            )))
+        }
+    })
+}
+
+fn cast_memory_config(memory_config: &MemoryConfig) -> Result<TrackerMemoryConfig> {
+    let median =
+        TrackerMemoryConfig::Median(cast_median_word_strategy(&memory_config.median_strategy)?);
+    let weighted_median =
+        TrackerMemoryConfig::WeightedMedian(memory_config.recency_decay.unwrap_or(1.));
     Ok(match memory_config.memory_strategy.as_str() {
-        "bruteforce" => TrackerMemoryConfig::BruteForce,
-        "mostfrequent" => TrackerMemoryConfig::MostFrequent,
-        "median" => TrackerMemoryConfig::Median,
-        "ls-bruteforce" => {
+        MEMORY_BRUTEFORCE => TrackerMemoryConfig::BruteForce,
+        MEMORY_MOSTFREQUENT => TrackerMemoryConfig::MostFrequent,
+        MEMORY_MEDIAN => median,
+        MEMORY_WEIGHTED_MEDIAN => weighted_median,
+        MEMORY_LS_BRUTEFORCE => {
             TrackerMemoryConfig::LongShortTerm(Box::new(TrackerMemoryConfig::BruteForce))
         }
-        "ls-mostfrequent" => {
+        MEMORY_LS_MOSTFREQUENT => {
             TrackerMemoryConfig::LongShortTerm(Box::new(TrackerMemoryConfig::MostFrequent))
         }
-        "ls-median" => TrackerMemoryConfig::LongShortTerm(Box::new(TrackerMemoryConfig::Median)),
-        "mw-mostfrequent" => {
+        MEMORY_LS_MEDIAN => TrackerMemoryConfig::LongShortTerm(Box::new(median)),
+        MEMORY_MW_MOSTFREQUENT => {
             cast_multiword_memory_config(memory_config, TrackerMemoryConfig::MostFrequent)?
         }
-        "mw-median" => cast_multiword_memory_config(memory_config, TrackerMemoryConfig::Median)?,
+        MEMORY_MW_MEDIAN => cast_multiword_memory_config(memory_config, median)?,
+        MEMORY_MW_WEIGHTED_MEDIAN => cast_multiword_memory_config(memory_config, weighted_median)?,
         v => {
-            return Err(PyValueError::new_err(format!(
+            return Err(BlitzBeaverError::InvalidConfig(format!(
                 "Invalid tracker memory strategy: {}",
                 v
             )))
@@ -388,27 +941,138 @@ fn cast_memory_config(memory_config: &MemoryConfig) -> PyResult<TrackerMemoryCon
 /// Cast a TrackerConfig to an InternalTrackerConfig.
 ///
 /// # Errors
-/// Returns PyValueError if the configuration is invalid.
-fn cast_tracker_config(tracker_config: &TrackerConfig) -> PyResult<InternalTrackerConfig> {
+/// Returns an error if the configuration is invalid.
+fn cast_tracker_config(
+    tracker_config: &TrackerConfig,
+    record_schema: &RecordSchema,
+) -> Result<InternalTrackerConfig> {
     let mut memory_configs = Vec::new();
     for memory_config in tracker_config.memories.iter() {
         memory_configs.push(cast_memory_config(memory_config)?);
     }
 
+    let mut composite_features = Vec::new();
+    for composite_feature_config in tracker_config
+        .record_scorer
+        .composite_features
+        .iter()
+        .flatten()
+    {
+        composite_features.push(cast_composite_feature_config(
+            composite_feature_config,
+            record_schema,
+        )?);
+    }
+
     Ok(InternalTrackerConfig {
         interest_threshold: tracker_config.interest_threshold,
         limit_no_match_streak: tracker_config.limit_no_match_streak,
         memory_configs,
         record_scorer: cast_record_scorer_config(&tracker_config.record_scorer)?,
+        missing_distances: cast_missing_field_distances(
+            &tracker_config.record_scorer,
+            tracker_config.memories.len(),
+        )?,
+        composite_features,
     })
 }
 
+/// Cast a NormalizationConfig to an InternalNormalizationConfig.
+///
+/// # Errors
+/// Returns an error if the median strategy is unknown.
 pub fn cast_normalization_config(
     normalization_config: &NormalizationConfig,
-) -> InternalNormalizationConfig {
-    InternalNormalizationConfig {
+) -> Result<InternalNormalizationConfig> {
+    Ok(InternalNormalizationConfig {
         threshold_cluster_match: normalization_config.threshold_cluster_match,
         min_cluster_size: normalization_config.min_cluster_size,
         infer_missing_clusters: normalization_config.infer_missing_clusters,
+        median_strategy: cast_median_word_strategy(&normalization_config.median_strategy)?,
+        weighted_median: normalization_config.weighted_median.unwrap_or(false),
+    })
+}
+
+/// Casts a GeneratorConfig to the event rates of the population and the noise model.
+pub fn cast_generator_config(generator_config: &GeneratorConfig) -> (EventRates, NoiseModel) {
+    let events = &generator_config.events;
+    let noise = &generator_config.noise;
+    (
+        EventRates {
+            birth_rate: events.birth_rate,
+            death_rate: events.death_rate,
+            marriage_rate: events.marriage_rate,
+            move_rate: events.move_rate,
+            occupation_change_rate: events.occupation_change_rate,
+            leave_home_rate: events.leave_home_rate,
+            immigration_rate: events.immigration_rate,
+        },
+        NoiseModel {
+            ocr_confusion_rate: noise.ocr_confusion_rate,
+            letter_drop_rate: noise.letter_drop_rate,
+            missing_field_rate: noise.missing_field_rate,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::df;
+
+    use crate::api::ChainLink;
+
+    use super::*;
+
+    #[test]
+    fn test_cast_tracking_chains_to_dataframe() {
+        let record_schema = RecordSchema {
+            fields: vec![FieldSchema {
+                name: "name".to_string(),
+                dtype: ElementType::String,
+            }],
+        };
+        let dataframes = vec![
+            df!("name" => ["a0", "b0"], "other" => [0, 1]).unwrap(),
+            df!("name" => ["b1", "a1"], "other" => [2, 3]).unwrap(),
+        ];
+        let chains = [
+            TrackingChain::new(
+                7,
+                vec![ChainNode::new(0, 0), ChainNode::new(1, 1)],
+                vec![None, Some(ChainLink::new(0.8, vec![Some(0.8)], None))],
+            ),
+            TrackingChain::new(9, vec![ChainNode::new(1, 0)], vec![None]),
+        ];
+
+        let df = cast_tracking_chains_to_dataframe(
+            &chains,
+            Some(&record_schema),
+            Some(&dataframes),
+            None,
+        )
+        .unwrap();
+
+        let expected = df!(
+            "chain_id" => [7u64, 7, 9],
+            "frame_idx" => [0u64, 1, 1],
+            "record_idx" => [0u64, 1, 0],
+            "link_score" => [None, Some(0.8f32), None],
+            "name" => ["a0", "a1", "b1"],
+        )
+        .unwrap();
+        assert!(df.equals_missing(&expected));
+
+        let invalid_chains = [TrackingChain::new(
+            7,
+            vec![ChainNode::new(1, 2)],
+            vec![None],
+        )];
+        assert!(cast_tracking_chains_to_dataframe(
+            &invalid_chains,
+            Some(&record_schema),
+            Some(&dataframes),
+            None,
+        )
+        .is_err());
     }
 }
@@ -1,72 +1,130 @@
-use pyo3::{pyclass, pymethods};
+#[cfg(feature = "python")]
+use pyo3::{pyclass, pymethods, PyResult};
+use serde::{Deserialize, Serialize};
 
-#[pyclass(frozen)]
-#[derive(Debug, Clone)]
+use crate::error::Result;
+
+#[cfg(feature = "python")]
+use super::{deserialize_config, serialize_config, ConfigFormat};
+use super::{validation, RecordSchema};
+
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackingConfig {
-    #[pyo3(get)]
     pub num_threads: usize,
-    #[pyo3(get)]
     pub tracker: TrackerConfig,
-    #[pyo3(get)]
     pub distance_metric: DistanceMetricConfig,
-    #[pyo3(get)]
     pub resolver: ResolverConfig,
+    pub engine_mode: Option<String>,
+    pub chunk_size: Option<usize>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl TrackingConfig {
     #[new]
+    #[pyo3(signature = (num_threads, tracker, distance_metric, resolver, engine_mode=None, chunk_size=None))]
     pub fn py_new(
         num_threads: usize,
         tracker: TrackerConfig,
         distance_metric: DistanceMetricConfig,
         resolver: ResolverConfig,
+        engine_mode: Option<String>,
+        chunk_size: Option<usize>,
     ) -> Self {
         Self {
             num_threads,
             tracker,
             distance_metric,
             resolver,
+            engine_mode,
+            chunk_size,
         }
     }
+
+    #[staticmethod]
+    fn from_json(content: &str) -> PyResult<Self> {
+        Ok(deserialize_config(content, ConfigFormat::Json)?)
+    }
+
+    #[staticmethod]
+    fn from_toml(content: &str) -> PyResult<Self> {
+        Ok(deserialize_config(content, ConfigFormat::Toml)?)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(serialize_config(self, ConfigFormat::Json)?)
+    }
+
+    fn to_toml(&self) -> PyResult<String> {
+        Ok(serialize_config(self, ConfigFormat::Toml)?)
+    }
+
+    #[pyo3(name = "validate")]
+    fn py_validate(&self, record_schema: &RecordSchema) -> PyResult<()> {
+        Ok(self.validate(record_schema)?)
+    }
+}
+
+impl TrackingConfig {
+    /// Validates the configuration against the record schema.
+    ///
+    /// # Errors
+    /// Returns an InvalidConfig error naming the path of the invalid attribute,
+    /// e.g. `tracker.memories[3].multiword_distance_metric`.
+    pub fn validate(&self, record_schema: &RecordSchema) -> Result<()> {
+        validation::validate_tracking_config(self, record_schema)
+    }
 }
 
-#[pyclass(frozen)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolverConfig {
-    #[pyo3(get)]
     pub resolving_strategy: String,
+    pub num_hypotheses: Option<usize>,
+    pub hypotheses_depth: Option<usize>,
+    pub ambiguity_margin: Option<f32>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl ResolverConfig {
     #[new]
-    pub fn py_new(resolving_strategy: String) -> Self {
-        Self { resolving_strategy }
+    #[pyo3(signature = (resolving_strategy, num_hypotheses=None, hypotheses_depth=None, ambiguity_margin=None))]
+    pub fn py_new(
+        resolving_strategy: String,
+        num_hypotheses: Option<usize>,
+        hypotheses_depth: Option<usize>,
+        ambiguity_margin: Option<f32>,
+    ) -> Self {
+        Self {
+            resolving_strategy,
+            num_hypotheses,
+            hypotheses_depth,
+            ambiguity_margin,
+        }
     }
 }
 
-#[pyclass(frozen)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DistanceMetricConfig {
-    #[pyo3(get)]
     pub metric: String,
-    #[pyo3(get)]
     pub caching_threshold: u32,
-    #[pyo3(get)]
     pub use_sigmoid: bool,
-    #[pyo3(get)]
     pub lv_edit_weights: Option<Vec<f32>>,
-    #[pyo3(get)]
     pub lv_substring_weight: Option<f32>,
-    #[pyo3(get)]
     pub lv_multiword_separator: Option<String>,
+    /// Memory budget, in bytes, of the persistent cache of each feature,
+    /// no persistent cache if None.
+    pub persistent_cache_bytes: Option<usize>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl DistanceMetricConfig {
     #[new]
-    #[pyo3(signature = (metric, caching_threshold, use_sigmoid, lv_edit_weights=None, lv_substring_weight=None, lv_multiword_separator=None))]
+    #[pyo3(signature = (metric, caching_threshold, use_sigmoid, lv_edit_weights=None, lv_substring_weight=None, lv_multiword_separator=None, persistent_cache_bytes=None))]
     pub fn py_new(
         metric: String,
         caching_threshold: u32,
@@ -74,6 +132,7 @@ impl DistanceMetricConfig {
         lv_edit_weights: Option<Vec<f32>>,
         lv_substring_weight: Option<f32>,
         lv_multiword_separator: Option<String>,
+        persistent_cache_bytes: Option<usize>,
     ) -> Self {
         Self {
             metric,
@@ -82,79 +141,112 @@ impl DistanceMetricConfig {
             lv_edit_weights,
             lv_substring_weight,
             lv_multiword_separator,
+            persistent_cache_bytes,
         }
     }
 }
 
-#[pyclass(frozen)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryConfig {
-    #[pyo3(get)]
     pub memory_strategy: String,
-    #[pyo3(get)]
     pub multiword_threshold_match: Option<f32>,
-    #[pyo3(get)]
     pub multiword_distance_metric: Option<DistanceMetricConfig>,
+    pub median_strategy: Option<String>,
+    pub recency_decay: Option<f32>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl MemoryConfig {
     #[new]
-    #[pyo3(signature = (memory_strategy, multiword_threshold_match=None, multiword_distance_metric=None))]
+    #[pyo3(signature = (memory_strategy, multiword_threshold_match=None, multiword_distance_metric=None, median_strategy=None, recency_decay=None))]
     pub fn py_new(
         memory_strategy: String,
         multiword_threshold_match: Option<f32>,
         multiword_distance_metric: Option<DistanceMetricConfig>,
+        median_strategy: Option<String>,
+        recency_decay: Option<f32>,
     ) -> Self {
         Self {
             memory_strategy,
             multiword_threshold_match,
             multiword_distance_metric,
+            median_strategy,
+            recency_decay,
         }
     }
 }
 
-#[pyclass(frozen)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordScorerConfig {
-    #[pyo3(get)]
     pub record_scorer: String,
-    #[pyo3(get)]
     pub weights: Option<Vec<f32>>,
-    #[pyo3(get)]
     pub min_weight_ratio: Option<f32>,
+    pub missing_record_distances: Option<Vec<Option<f32>>>,
+    pub missing_memory_distances: Option<Vec<Option<f32>>>,
+    pub missing_both_distances: Option<Vec<Option<f32>>>,
+    pub composite_features: Option<Vec<CompositeFeatureConfig>>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl RecordScorerConfig {
     #[new]
-    #[pyo3(signature = (record_scorer, weights=None, min_weight_ratio=None))]
+    #[pyo3(signature = (record_scorer, weights=None, min_weight_ratio=None, missing_record_distances=None, missing_memory_distances=None, missing_both_distances=None, composite_features=None))]
     pub fn py_new(
         record_scorer: String,
         weights: Option<Vec<f32>>,
         min_weight_ratio: Option<f32>,
+        missing_record_distances: Option<Vec<Option<f32>>>,
+        missing_memory_distances: Option<Vec<Option<f32>>>,
+        missing_both_distances: Option<Vec<Option<f32>>>,
+        composite_features: Option<Vec<CompositeFeatureConfig>>,
     ) -> Self {
         Self {
             record_scorer,
             weights,
             min_weight_ratio,
+            missing_record_distances,
+            missing_memory_distances,
+            missing_both_distances,
+            composite_features,
         }
     }
 }
 
-#[pyclass(frozen)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeFeatureConfig {
+    pub fields: Vec<String>,
+    pub threshold_match: f32,
+    pub bonus: f32,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl CompositeFeatureConfig {
+    #[new]
+    pub fn py_new(fields: Vec<String>, threshold_match: f32, bonus: f32) -> Self {
+        Self {
+            fields,
+            threshold_match,
+            bonus,
+        }
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackerConfig {
-    #[pyo3(get)]
     pub interest_threshold: f32,
-    #[pyo3(get)]
     pub limit_no_match_streak: usize,
-    #[pyo3(get)]
     pub memories: Vec<MemoryConfig>,
-    #[pyo3(get)]
     pub record_scorer: RecordScorerConfig,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl TrackerConfig {
     #[new]
@@ -173,33 +265,70 @@ impl TrackerConfig {
     }
 }
 
-#[pyclass(frozen)]
-#[derive(Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NormalizationConfig {
-    #[pyo3(get)]
     pub threshold_cluster_match: f32,
-    #[pyo3(get)]
     pub min_cluster_size: usize,
-    #[pyo3(get)]
     pub infer_missing_clusters: bool,
-    #[pyo3(get)]
     pub distance_metric: DistanceMetricConfig,
+    pub median_strategy: Option<String>,
+    pub weighted_median: Option<bool>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl NormalizationConfig {
     #[new]
+    #[pyo3(signature = (threshold_cluster_match, min_cluster_size, infer_missing_clusters, distance_metric, median_strategy=None, weighted_median=None))]
     pub fn py_new(
         threshold_cluster_match: f32,
         min_cluster_size: usize,
         infer_missing_clusters: bool,
         distance_metric: DistanceMetricConfig,
+        median_strategy: Option<String>,
+        weighted_median: Option<bool>,
     ) -> Self {
         Self {
             threshold_cluster_match,
             min_cluster_size,
             infer_missing_clusters,
             distance_metric,
+            median_strategy,
+            weighted_median,
         }
     }
+
+    #[staticmethod]
+    fn from_json(content: &str) -> PyResult<Self> {
+        Ok(deserialize_config(content, ConfigFormat::Json)?)
+    }
+
+    #[staticmethod]
+    fn from_toml(content: &str) -> PyResult<Self> {
+        Ok(deserialize_config(content, ConfigFormat::Toml)?)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(serialize_config(self, ConfigFormat::Json)?)
+    }
+
+    fn to_toml(&self) -> PyResult<String> {
+        Ok(serialize_config(self, ConfigFormat::Toml)?)
+    }
+
+    #[pyo3(name = "validate")]
+    fn py_validate(&self) -> PyResult<()> {
+        Ok(self.validate()?)
+    }
+}
+
+impl NormalizationConfig {
+    /// Validates the configuration.
+    ///
+    /// # Errors
+    /// Returns an InvalidConfig error naming the path of the invalid attribute.
+    pub fn validate(&self) -> Result<()> {
+        validation::validate_normalization_config(self)
+    }
 }
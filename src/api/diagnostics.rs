@@ -1,18 +1,19 @@
 use std::collections::HashMap;
 
+#[cfg(feature = "python")]
 use pyo3::{pyclass, pymethods};
 use serde::{Deserialize, Serialize};
 
-use crate::id::ID;
+use crate::{
+    distances::{PersistentCacheStats, TraceCachedDistanceCalculator},
+    id::ID,
+};
 
-#[pyclass(frozen)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TrackerRecordDiagnostics {
-    #[pyo3(get)]
     pub record_idx: usize,
-    #[pyo3(get)]
     pub record_score: f32,
-    #[pyo3(get)]
     pub distances: Vec<Option<f32>>,
 }
 
@@ -26,14 +27,11 @@ impl TrackerRecordDiagnostics {
     }
 }
 
-#[pyclass(frozen)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TrackerFrameDiagnostics {
-    #[pyo3(get)]
     pub frame_idx: usize,
-    #[pyo3(get)]
     pub records: Vec<TrackerRecordDiagnostics>,
-    #[pyo3(get)]
     pub memory: Vec<Vec<String>>,
 }
 
@@ -47,12 +45,10 @@ impl TrackerFrameDiagnostics {
     }
 }
 
-#[pyclass(frozen)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TrackerDiagnostics {
-    #[pyo3(get)]
     pub id: ID,
-    #[pyo3(get)]
     pub frames: Vec<TrackerFrameDiagnostics>,
 }
 
@@ -65,13 +61,44 @@ impl TrackerDiagnostics {
     }
 }
 
-#[pyclass(frozen)]
+/// Match between a tracker and a record that was rejected because
+/// the best and second-best scores were too close.
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AmbiguousMatchDiagnostics {
+    pub tracker_id: ID,
+    pub record_idx: usize,
+    /// Either "record" when the ambiguity is between the trackers of the record
+    /// or "tracker" when it is between the records of the tracker.
+    pub ambiguity: String,
+    pub best_score: f32,
+    pub second_score: f32,
+}
+
+impl AmbiguousMatchDiagnostics {
+    pub fn new(
+        tracker_id: ID,
+        record_idx: usize,
+        ambiguity: &str,
+        best_score: f32,
+        second_score: f32,
+    ) -> Self {
+        Self {
+            tracker_id,
+            record_idx,
+            ambiguity: ambiguity.to_string(),
+            best_score,
+            second_score,
+        }
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ResolvingDiagnostics {
-    #[pyo3(get)]
     pub histogram_record_matchs: Vec<usize>,
-    #[pyo3(get)]
     pub histogram_tracker_matchs: Vec<usize>,
+    pub ambiguous_matches: Vec<AmbiguousMatchDiagnostics>,
 }
 
 impl ResolvingDiagnostics {
@@ -79,21 +106,226 @@ impl ResolvingDiagnostics {
         Self {
             histogram_record_matchs: Vec::new(),
             histogram_tracker_matchs: Vec::new(),
+            ambiguous_matches: Vec::new(),
         }
     }
 }
 
-#[pyclass(frozen)]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Statistics of the cache of the distance calculator of a feature.
+///
+/// The computation count is the number of distances requested to the
+/// calculator, the cache hit count the number of them found in the cache
+/// and the cache size the number of distances precomputed.
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CacheDiagnostics {
+    pub feature_idx: usize,
+    pub computation_count: u64,
+    pub cache_hit_count: u64,
+    pub cache_size: usize,
+}
+
+impl CacheDiagnostics {
+    pub fn new(feature_idx: usize, trace: TraceCachedDistanceCalculator) -> Self {
+        Self {
+            feature_idx,
+            computation_count: trace.computation_count,
+            cache_hit_count: trace.cache_hit_count,
+            cache_size: trace.cache_size,
+        }
+    }
+
+    /// Returns the ratio of the distances found in the cache,
+    /// 0 if no distance was requested.
+    pub fn hit_rate(&self) -> f32 {
+        if self.computation_count == 0 {
+            return 0.0;
+        }
+        self.cache_hit_count as f32 / self.computation_count as f32
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl CacheDiagnostics {
+    #[pyo3(name = "hit_rate")]
+    fn py_hit_rate(&self) -> f32 {
+        self.hit_rate()
+    }
+}
+
+/// Performance of a worker for a frame, the times are in seconds.
+///
+/// In the work-stealing mode, a worker is one of the threads of the pool
+/// and the main thread is the worker 0.
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct WorkerFrameDiagnostics {
+    pub worker_idx: usize,
+    pub num_trackers: usize,
+    /// Number of chunks of trackers processed, always 1 in the workers mode.
+    pub num_chunks: usize,
+    pub setup_caches_time: f64,
+    pub scoring_time: f64,
+    /// Statistics of the caches, one per feature.
+    pub caches: Vec<CacheDiagnostics>,
+}
+
+/// Performance of the engine for a frame, the times are in seconds.
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FramePerformanceDiagnostics {
+    pub frame_idx: usize,
+    /// Wall time of the scoring of all the trackers.
+    pub scoring_time: f64,
+    pub resolving_time: f64,
+    pub workers: Vec<WorkerFrameDiagnostics>,
+}
+
+impl WorkerFrameDiagnostics {
+    /// Returns the time the worker spent working on the frame.
+    pub fn busy_time(&self) -> f64 {
+        self.setup_caches_time + self.scoring_time
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl WorkerFrameDiagnostics {
+    #[pyo3(name = "busy_time")]
+    fn py_busy_time(&self) -> f64 {
+        self.busy_time()
+    }
+}
+
+impl FramePerformanceDiagnostics {
+    /// Returns the ratio between the busiest worker time and the average
+    /// worker time, 1 when the load is perfectly balanced.
+    pub fn load_imbalance(&self) -> f64 {
+        let max_time = self
+            .workers
+            .iter()
+            .map(|w| w.busy_time())
+            .fold(0.0, f64::max);
+        let tot_time = self.workers.iter().map(|w| w.busy_time()).sum::<f64>();
+        if tot_time == 0.0 {
+            return 1.0;
+        }
+        max_time * self.workers.len() as f64 / tot_time
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FramePerformanceDiagnostics {
+    #[pyo3(name = "load_imbalance")]
+    fn py_load_imbalance(&self) -> f64 {
+        self.load_imbalance()
+    }
+}
+
+/// Statistics of the persistent cache of a feature, over the whole tracking process.
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PersistentCacheDiagnostics {
+    pub feature_idx: usize,
+    pub capacity: usize,
+    pub size: usize,
+    pub lookup_count: u64,
+    pub hit_count: u64,
+    pub insertion_count: u64,
+    pub eviction_count: u64,
+}
+
+impl PersistentCacheDiagnostics {
+    pub fn new(feature_idx: usize, stats: PersistentCacheStats) -> Self {
+        Self {
+            feature_idx,
+            capacity: stats.capacity,
+            size: stats.size,
+            lookup_count: stats.lookup_count,
+            hit_count: stats.hit_count,
+            insertion_count: stats.insertion_count,
+            eviction_count: stats.eviction_count,
+        }
+    }
+
+    /// Returns the ratio of the lookups found in the cache,
+    /// 0 if there was no lookup.
+    pub fn hit_rate(&self) -> f32 {
+        if self.lookup_count == 0 {
+            return 0.0;
+        }
+        self.hit_count as f32 / self.lookup_count as f32
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PersistentCacheDiagnostics {
+    #[pyo3(name = "hit_rate")]
+    fn py_hit_rate(&self) -> f32 {
+        self.hit_rate()
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PerformanceDiagnostics {
+    pub frames: Vec<FramePerformanceDiagnostics>,
+    /// Statistics of the persistent caches, empty if they are disabled.
+    pub persistent_caches: Vec<PersistentCacheDiagnostics>,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl PerformanceDiagnostics {
+    #[pyo3(name = "cache_totals")]
+    fn py_cache_totals(&self) -> Vec<CacheDiagnostics> {
+        self.cache_totals()
+    }
+}
+
+impl PerformanceDiagnostics {
+    pub fn new() -> Self {
+        Self {
+            frames: Vec::new(),
+            persistent_caches: Vec::new(),
+        }
+    }
+
+    /// Returns the statistics of the caches of each feature summed
+    /// over all the workers and frames.
+    pub fn cache_totals(&self) -> Vec<CacheDiagnostics> {
+        let mut totals: Vec<CacheDiagnostics> = Vec::new();
+        for worker in self.frames.iter().flat_map(|f| f.workers.iter()) {
+            for cache in worker.caches.iter() {
+                if totals.len() <= cache.feature_idx {
+                    totals.resize_with(cache.feature_idx + 1, CacheDiagnostics::default);
+                }
+                let total = &mut totals[cache.feature_idx];
+                total.feature_idx = cache.feature_idx;
+                total.computation_count += cache.computation_count;
+                total.cache_hit_count += cache.cache_hit_count;
+                total.cache_size += cache.cache_size;
+            }
+        }
+        totals
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Diagnostics {
     /// Note: do not expose the trackers to Python directly
     /// as the hashmap is very large and causes very
     /// significant performance issues.
     pub trackers: HashMap<ID, TrackerDiagnostics>,
-    #[pyo3(get)]
     pub resolvings: Vec<ResolvingDiagnostics>,
+    pub performance: PerformanceDiagnostics,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl Diagnostics {
     /// Python function
@@ -105,6 +337,16 @@ impl Diagnostics {
     pub fn get_tracker<'a>(&self, id: ID) -> Option<TrackerDiagnostics> {
         self.trackers.get(&id).map(|t| t.clone())
     }
+
+    #[getter]
+    fn resolvings(&self) -> Vec<ResolvingDiagnostics> {
+        self.resolvings.clone()
+    }
+
+    #[getter]
+    fn performance(&self) -> PerformanceDiagnostics {
+        self.performance.clone()
+    }
 }
 
 impl Diagnostics {
@@ -112,6 +354,71 @@ impl Diagnostics {
         Self {
             trackers: HashMap::new(),
             resolvings: Vec::new(),
+            performance: PerformanceDiagnostics::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_totals() {
+        let cache = |feature_idx, computation_count, cache_hit_count| CacheDiagnostics {
+            feature_idx,
+            computation_count,
+            cache_hit_count,
+            cache_size: 2,
+        };
+        let worker = |worker_idx| WorkerFrameDiagnostics {
+            worker_idx,
+            caches: vec![cache(0, 10, 5), cache(1, 4, 0)],
+            ..Default::default()
+        };
+        let performance = PerformanceDiagnostics {
+            frames: vec![
+                FramePerformanceDiagnostics {
+                    frame_idx: 1,
+                    scoring_time: 0.0,
+                    resolving_time: 0.0,
+                    workers: vec![worker(0), worker(1)],
+                },
+                FramePerformanceDiagnostics {
+                    frame_idx: 2,
+                    scoring_time: 0.0,
+                    resolving_time: 0.0,
+                    workers: vec![worker(0)],
+                },
+            ],
+            persistent_caches: Vec::new(),
+        };
+
+        let totals = performance.cache_totals();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].computation_count, 30);
+        assert_eq!(totals[0].cache_hit_count, 15);
+        assert_eq!(totals[0].cache_size, 6);
+        assert_eq!(totals[0].hit_rate(), 0.5);
+        assert_eq!(totals[1].feature_idx, 1);
+        assert_eq!(totals[1].hit_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_load_imbalance() {
+        let worker = |setup_caches_time, scoring_time| WorkerFrameDiagnostics {
+            setup_caches_time,
+            scoring_time,
+            ..Default::default()
+        };
+        let mut frame = FramePerformanceDiagnostics::default();
+        assert_eq!(frame.load_imbalance(), 1.0);
+
+        frame.workers = vec![worker(0.5, 1.5), worker(0.0, 2.0)];
+        assert_eq!(frame.load_imbalance(), 1.0);
+
+        // the busiest worker takes 3 times the average time
+        frame.workers = vec![worker(1.0, 2.0), worker(0.0, 0.0), worker(0.0, 0.0)];
+        assert_eq!(frame.load_imbalance(), 3.0);
+    }
+}
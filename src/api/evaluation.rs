@@ -1,3 +1,4 @@
+#[cfg(feature = "python")]
 use pyo3::{pyclass, pyfunction, pymethods};
 
 use crate::evaluation;
@@ -7,21 +8,17 @@ use super::TrackingGraph;
 /// EvalMetricChainLength
 ///
 /// Metrics of the length of the tracking chains.
-#[pyclass(frozen)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
 #[derive(Debug, Clone)]
 pub struct EvalMetricChainLength {
-    #[pyo3(get)]
     pub average: f32,
-    #[pyo3(get)]
     pub median: f32,
-    #[pyo3(get)]
     pub max: f32,
-    #[pyo3(get)]
     pub min: f32,
-    #[pyo3(get)]
     pub histogram: Vec<u32>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl EvalMetricChainLength {
     pub fn __repr__(&self) -> String {
@@ -43,21 +40,19 @@ impl EvalMetricChainLength {
 /// EvalMetricGraphProperties
 ///
 /// Metrics of the properties of the tracking graph.
-#[pyclass(frozen)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
 #[derive(Debug, Clone)]
 pub struct EvalMetricGraphProperties {
     /// For each frame, the ratio of nodes (records) that have matched with an existing tracking chain.
-    #[pyo3(get)]
     pub records_match_ratios: Vec<f32>,
     /// For each frame, the ratio of trackers that have match with a node (record).
-    #[pyo3(get)]
     pub trackers_match_ratios: Vec<f32>,
     /// For each frame, the ratio of nodes (records) that have conflicts, a conflict occurs
     /// when multiple tracking chains match to the same node.
-    #[pyo3(get)]
     pub conflict_ratios: Vec<f32>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl EvalMetricGraphProperties {
     pub fn __repr__(&self) -> String {
@@ -82,12 +77,12 @@ impl EvalMetricGraphProperties {
     }
 }
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn evaluate_tracking_chain_length(graph: &TrackingGraph) -> EvalMetricChainLength {
     evaluation::eval_tracking_chain_length(graph)
 }
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn evaluate_tracking_graph_properties(graph: &TrackingGraph) -> EvalMetricGraphProperties {
     evaluation::eval_tracking_graph_properties(graph)
 }
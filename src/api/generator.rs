@@ -0,0 +1,265 @@
+use polars::prelude::DataFrame;
+#[cfg(feature = "python")]
+use pyo3::{pyclass, pyfunction, pymethods, PyResult};
+#[cfg(feature = "python")]
+use pyo3_polars::PyDataFrame;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Result, generator};
+
+use super::{casting, validation, ElementType, FieldSchema, RecordSchema};
+#[cfg(feature = "python")]
+use super::{deserialize_config, serialize_config, ConfigFormat};
+
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratorConfig {
+    pub num_frames: usize,
+    pub num_households: usize,
+    pub seed: u64,
+    pub events: GeneratorEventsConfig,
+    pub noise: GeneratorNoiseConfig,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl GeneratorConfig {
+    #[new]
+    #[pyo3(signature = (num_frames, num_households, seed=0, events=None, noise=None))]
+    pub fn py_new(
+        num_frames: usize,
+        num_households: usize,
+        seed: u64,
+        events: Option<GeneratorEventsConfig>,
+        noise: Option<GeneratorNoiseConfig>,
+    ) -> Self {
+        Self {
+            num_frames,
+            num_households,
+            seed,
+            events: events.unwrap_or_default(),
+            noise: noise.unwrap_or_default(),
+        }
+    }
+
+    #[staticmethod]
+    fn from_json(content: &str) -> PyResult<Self> {
+        Ok(deserialize_config(content, ConfigFormat::Json)?)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(serialize_config(self, ConfigFormat::Json)?)
+    }
+
+    #[pyo3(name = "validate")]
+    fn py_validate(&self) -> PyResult<()> {
+        Ok(self.validate()?)
+    }
+}
+
+impl GeneratorConfig {
+    /// Validates the configuration, the rates must be between 0 and 1.
+    ///
+    /// # Errors
+    /// Returns an InvalidConfig error naming the path of the invalid attribute.
+    pub fn validate(&self) -> Result<()> {
+        validation::validate_generator_config(self)
+    }
+}
+
+/// GeneratorEventsConfig
+///
+/// Probabilities of the events between two frames, for each person
+/// (or household for the moves and immigration).
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratorEventsConfig {
+    pub birth_rate: f32,
+    pub death_rate: f32,
+    pub marriage_rate: f32,
+    pub move_rate: f32,
+    pub occupation_change_rate: f32,
+    pub leave_home_rate: f32,
+    pub immigration_rate: f32,
+}
+
+impl Default for GeneratorEventsConfig {
+    fn default() -> Self {
+        Self {
+            birth_rate: 0.1,
+            death_rate: 0.02,
+            marriage_rate: 0.08,
+            move_rate: 0.1,
+            occupation_change_rate: 0.05,
+            leave_home_rate: 0.1,
+            immigration_rate: 0.03,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl GeneratorEventsConfig {
+    #[new]
+    #[pyo3(signature = (birth_rate=0.1, death_rate=0.02, marriage_rate=0.08, move_rate=0.1, occupation_change_rate=0.05, leave_home_rate=0.1, immigration_rate=0.03))]
+    pub fn py_new(
+        birth_rate: f32,
+        death_rate: f32,
+        marriage_rate: f32,
+        move_rate: f32,
+        occupation_change_rate: f32,
+        leave_home_rate: f32,
+        immigration_rate: f32,
+    ) -> Self {
+        Self {
+            birth_rate,
+            death_rate,
+            marriage_rate,
+            move_rate,
+            occupation_change_rate,
+            leave_home_rate,
+            immigration_rate,
+        }
+    }
+}
+
+/// GeneratorNoiseConfig
+///
+/// Transcription noise: probability of each letter to be confused with a similar
+/// letter or dropped, probability of each field to be missing.
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratorNoiseConfig {
+    pub ocr_confusion_rate: f32,
+    pub letter_drop_rate: f32,
+    pub missing_field_rate: f32,
+}
+
+impl Default for GeneratorNoiseConfig {
+    fn default() -> Self {
+        Self {
+            ocr_confusion_rate: 0.02,
+            letter_drop_rate: 0.01,
+            missing_field_rate: 0.02,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl GeneratorNoiseConfig {
+    #[new]
+    #[pyo3(signature = (ocr_confusion_rate=0.02, letter_drop_rate=0.01, missing_field_rate=0.02))]
+    pub fn py_new(ocr_confusion_rate: f32, letter_drop_rate: f32, missing_field_rate: f32) -> Self {
+        Self {
+            ocr_confusion_rate,
+            letter_drop_rate,
+            missing_field_rate,
+        }
+    }
+}
+
+/// SyntheticCensus
+///
+/// Generated census: one dataframe per frame and the true chains,
+/// as lists of `(frame_idx, record_idx)`.
+#[derive(Debug, Clone)]
+pub struct SyntheticCensus {
+    pub record_schema: RecordSchema,
+    pub dataframes: Vec<DataFrame>,
+    pub chains: Vec<Vec<(usize, usize)>>,
+}
+
+/// Returns the record schema of the generated census.
+pub fn census_record_schema() -> RecordSchema {
+    RecordSchema::new(
+        generator::CENSUS_FIELDS
+            .iter()
+            .map(|name| {
+                let dtype = match *name {
+                    "children" => ElementType::MultiStrings,
+                    _ => ElementType::String,
+                };
+                FieldSchema::new(name.to_string(), dtype)
+            })
+            .collect(),
+    )
+}
+
+/// Generates a synthetic census with known ground truth: simulates a population
+/// over the frames and applies the transcription noise to the records.
+///
+/// The generation is deterministic given the seed of the configuration.
+///
+/// # Errors
+/// Returns an error if the configuration is invalid or if all the word IDs
+/// have been used.
+pub fn generate_census(generator_config: &GeneratorConfig) -> Result<SyntheticCensus> {
+    generator_config.validate()?;
+
+    let (rates, noise) = casting::cast_generator_config(generator_config);
+    let synthetic = generator::generate_frames(
+        generator_config.num_frames,
+        generator_config.num_households,
+        rates,
+        &noise,
+        generator_config.seed,
+    )?;
+
+    let record_schema = census_record_schema();
+    let dataframes = synthetic
+        .frames
+        .iter()
+        .map(|frame| casting::cast_to_dataframe(&record_schema, frame))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(SyntheticCensus {
+        record_schema,
+        dataframes,
+        chains: synthetic.chains,
+    })
+}
+
+/// Record schema, dataframes and true chains of the generated census.
+#[cfg(feature = "python")]
+type PySyntheticCensus = (RecordSchema, Vec<PyDataFrame>, Vec<Vec<(usize, usize)>>);
+
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn generate_census_process(generator_config: &GeneratorConfig) -> PyResult<PySyntheticCensus> {
+    let census = generate_census(generator_config)?;
+    Ok((
+        census.record_schema,
+        census.dataframes.into_iter().map(PyDataFrame).collect(),
+        census.chains,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_census() {
+        let mut generator_config = GeneratorConfig {
+            num_frames: 4,
+            num_households: 30,
+            seed: 7,
+            events: GeneratorEventsConfig::default(),
+            noise: GeneratorNoiseConfig::default(),
+        };
+        let census = generate_census(&generator_config).unwrap();
+        assert_eq!(census.dataframes.len(), 4);
+        assert_eq!(census.dataframes[0].height(), 30);
+        assert_eq!(
+            census.dataframes[0].get_column_names(),
+            generator::CENSUS_FIELDS.to_vec()
+        );
+        let num_nodes = census.chains.iter().map(|c| c.len()).sum::<usize>();
+        let num_records = census.dataframes.iter().map(|d| d.height()).sum::<usize>();
+        assert_eq!(num_nodes, num_records);
+
+        generator_config.noise.letter_drop_rate = 1.5;
+        assert!(generate_census(&generator_config).is_err());
+    }
+}
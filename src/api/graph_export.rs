@@ -0,0 +1,383 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::{
+    frame::{Element, Frame},
+    id::ID,
+    trackers::TrackingChain,
+};
+
+use super::{ChainNode, RecordSchema};
+
+/// GraphExportFilter
+///
+/// Restricts the exported graph to some tracking chains and/or to a range of frames.
+#[derive(Debug, Clone, Default)]
+pub struct GraphExportFilter {
+    chains_ids: Option<HashSet<ID>>,
+    frame_range: Option<(usize, usize)>,
+}
+
+impl GraphExportFilter {
+    /// Creates a new filter, the frame range is inclusive.
+    pub fn new(chains_ids: Option<HashSet<ID>>, frame_range: Option<(usize, usize)>) -> Self {
+        Self {
+            chains_ids,
+            frame_range,
+        }
+    }
+
+    fn contains_chain(&self, id: ID) -> bool {
+        match &self.chains_ids {
+            Some(ids) => ids.contains(&id),
+            None => true,
+        }
+    }
+
+    fn contains_node(&self, node: &ChainNode) -> bool {
+        match self.frame_range {
+            Some((start, end)) => node.frame_idx >= start && node.frame_idx <= end,
+            None => true,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ExportNode {
+    id: String,
+    chain_id: ID,
+    frame_idx: usize,
+    record_idx: usize,
+    fields: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct ExportEdge {
+    source: String,
+    target: String,
+    chain_id: ID,
+    score: Option<f32>,
+}
+
+/// ExportGraph
+///
+/// Flattened view of (a subset of) the tracking graph, with the nodes
+/// and edges of the tracking chains, used to export the graph in
+/// various text formats.
+///
+/// The nodes are identified by `<frame_idx>_<record_idx>`, their attributes
+/// are the fields of the record, if the records are given.
+pub struct ExportGraph {
+    nodes: Vec<ExportNode>,
+    edges: Vec<ExportEdge>,
+    fields: Vec<String>,
+}
+
+impl ExportGraph {
+    pub fn new(
+        chains: &[TrackingChain],
+        filter: &GraphExportFilter,
+        records: Option<(&RecordSchema, &[Frame])>,
+    ) -> Self {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        let mut visited = HashSet::new();
+
+        for chain in chains.iter().filter(|c| filter.contains_chain(c.id)) {
+            let mut prev_node: Option<&ChainNode> = None;
+
+            for (cn, link) in chain.nodes.iter().zip(chain.links.iter()) {
+                if !filter.contains_node(cn) {
+                    prev_node = None;
+                    continue;
+                }
+                if visited.insert(*cn) {
+                    nodes.push(ExportNode {
+                        id: Self::node_id(cn),
+                        chain_id: chain.id,
+                        frame_idx: cn.frame_idx,
+                        record_idx: cn.record_idx,
+                        fields: Self::node_fields(cn, records),
+                    });
+                }
+                if let Some(prev_node) = prev_node {
+                    edges.push(ExportEdge {
+                        source: Self::node_id(prev_node),
+                        target: Self::node_id(cn),
+                        chain_id: chain.id,
+                        score: link.as_ref().map(|l| l.score),
+                    });
+                }
+                prev_node = Some(cn);
+            }
+        }
+        nodes.sort_by_key(|n| (n.frame_idx, n.record_idx));
+
+        let fields = match records {
+            Some((record_schema, _)) => record_schema
+                .fields
+                .iter()
+                .map(|f| f.name.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Self {
+            nodes,
+            edges,
+            fields,
+        }
+    }
+
+    fn node_id(node: &ChainNode) -> String {
+        format!("{}_{}", node.frame_idx, node.record_idx)
+    }
+
+    /// Returns the non-missing fields of the record of the node.
+    fn node_fields(
+        node: &ChainNode,
+        records: Option<(&RecordSchema, &[Frame])>,
+    ) -> BTreeMap<String, String> {
+        let mut fields = BTreeMap::new();
+        let (record_schema, frames) = match records {
+            Some(records) => records,
+            None => return fields,
+        };
+        let record = frames[node.frame_idx].record(node.record_idx);
+
+        for (i, field_schema) in record_schema.fields.iter().enumerate() {
+            let value = match record.element(i) {
+                Element::Word(word) => word.raw().to_string(),
+                Element::MultiWords(words) => {
+                    words.iter().map(|w| w.raw()).collect::<Vec<_>>().join(" ")
+                }
+                Element::None => continue,
+            };
+            fields.insert(field_schema.name.clone(), value);
+        }
+        fields
+    }
+
+    /// Exports the graph in the GraphML format.
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str(
+            "  <key id=\"chain_id\" for=\"all\" attr.name=\"chain_id\" attr.type=\"string\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"frame_idx\" for=\"node\" attr.name=\"frame_idx\" attr.type=\"int\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"record_idx\" for=\"node\" attr.name=\"record_idx\" attr.type=\"int\"/>\n",
+        );
+        out.push_str(
+            "  <key id=\"score\" for=\"edge\" attr.name=\"score\" attr.type=\"double\"/>\n",
+        );
+        for (i, field) in self.fields.iter().enumerate() {
+            out.push_str(&format!(
+                "  <key id=\"field{}\" for=\"node\" attr.name=\"{}\" attr.type=\"string\"/>\n",
+                i,
+                escape_xml(field)
+            ));
+        }
+        out.push_str("  <graph id=\"tracking_graph\" edgedefault=\"directed\">\n");
+
+        for node in self.nodes.iter() {
+            out.push_str(&format!("    <node id=\"{}\">\n", node.id));
+            out.push_str(&format!(
+                "      <data key=\"chain_id\">{}</data>\n",
+                node.chain_id
+            ));
+            out.push_str(&format!(
+                "      <data key=\"frame_idx\">{}</data>\n",
+                node.frame_idx
+            ));
+            out.push_str(&format!(
+                "      <data key=\"record_idx\">{}</data>\n",
+                node.record_idx
+            ));
+            for (i, field) in self.fields.iter().enumerate() {
+                if let Some(value) = node.fields.get(field) {
+                    out.push_str(&format!(
+                        "      <data key=\"field{}\">{}</data>\n",
+                        i,
+                        escape_xml(value)
+                    ));
+                }
+            }
+            out.push_str("    </node>\n");
+        }
+        for edge in self.edges.iter() {
+            out.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\">\n",
+                edge.source, edge.target
+            ));
+            out.push_str(&format!(
+                "      <data key=\"chain_id\">{}</data>\n",
+                edge.chain_id
+            ));
+            if let Some(score) = edge.score {
+                out.push_str(&format!("      <data key=\"score\">{}</data>\n", score));
+            }
+            out.push_str("    </edge>\n");
+        }
+
+        out.push_str("  </graph>\n");
+        out.push_str("</graphml>\n");
+        out
+    }
+
+    /// Exports the graph in the DOT format, the nodes of a frame are on the same rank.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        out.push_str("digraph tracking_graph {\n");
+        out.push_str("  rankdir=LR;\n");
+        out.push_str("  node [shape=box];\n");
+
+        for node in self.nodes.iter() {
+            let mut label = format!("{}:{}", node.frame_idx, node.record_idx);
+            for field in self.fields.iter() {
+                if let Some(value) = node.fields.get(field) {
+                    label.push_str(&format!("\n{}={}", field, value));
+                }
+            }
+            out.push_str(&format!(
+                "  \"{}\" [label=\"{}\"];\n",
+                node.id,
+                escape_dot(&label)
+            ));
+        }
+
+        let mut start = 0;
+        while start < self.nodes.len() {
+            let frame_idx = self.nodes[start].frame_idx;
+            let end = start
+                + self.nodes[start..]
+                    .iter()
+                    .take_while(|n| n.frame_idx == frame_idx)
+                    .count();
+            let ids = self.nodes[start..end]
+                .iter()
+                .map(|n| format!("\"{}\"", n.id))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("  {{ rank=same; {}; }}\n", ids.join("; ")));
+            start = end;
+        }
+
+        for edge in self.edges.iter() {
+            let label = match edge.score {
+                Some(score) => format!(" label=\"{:.3}\"", score),
+                None => String::new(),
+            };
+            out.push_str(&format!(
+                "  \"{}\" -> \"{}\" [chain_id=\"{}\"{}];\n",
+                edge.source, edge.target, edge.chain_id, label
+            ));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Exports the graph in the JSON-lines format, one line per node then
+    /// one line per edge, each line has a `type` field, either "node" or "edge".
+    pub fn to_jsonl(&self) -> String {
+        #[derive(Serialize)]
+        #[serde(tag = "type", rename_all = "lowercase")]
+        enum Line<'a> {
+            Node(&'a ExportNode),
+            Edge(&'a ExportEdge),
+        }
+
+        let lines = self
+            .nodes
+            .iter()
+            .map(Line::Node)
+            .chain(self.edges.iter().map(Line::Edge));
+
+        let mut out = String::new();
+        for line in lines {
+            // the serialization of these types can't fail
+            out.push_str(&serde_json::to_string(&line).unwrap());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn escape_dot(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{ChainLink, ElementType, FieldSchema},
+        word::Word,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_export_graph() {
+        let record_schema = RecordSchema {
+            fields: vec![FieldSchema {
+                name: "name".to_string(),
+                dtype: ElementType::String,
+            }],
+        };
+        let frames = [
+            Frame::new(0, vec![vec![Element::Word(Word::new("a<b".to_string()))]]),
+            Frame::new(1, vec![vec![Element::None]]),
+            Frame::new(2, vec![vec![Element::None]]),
+        ];
+        let chains = [TrackingChain::new(
+            3,
+            vec![
+                ChainNode::new(0, 0),
+                ChainNode::new(1, 0),
+                ChainNode::new(2, 0),
+            ],
+            vec![None, Some(ChainLink::new(0.5, vec![Some(0.5)], None)), None],
+        )];
+
+        let filter = GraphExportFilter::new(None, Some((0, 1)));
+        let graph = ExportGraph::new(&chains, &filter, Some((&record_schema, &frames)));
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains("<data key=\"field0\">a&lt;b</data>"));
+        assert!(graphml.contains("<edge source=\"0_0\" target=\"1_0\">"));
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"0_0\" -> \"1_0\" [chain_id=\"3\" label=\"0.500\"];"));
+
+        let jsonl = graph.to_jsonl();
+        let lines = jsonl.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[2],
+            "{\"type\":\"edge\",\"source\":\"0_0\",\"target\":\"1_0\",\"chain_id\":3,\"score\":0.5}"
+        );
+
+        let filter = GraphExportFilter::new(Some(HashSet::from([4])), None);
+        let graph = ExportGraph::new(&chains, &filter, None);
+        assert!(graph.nodes.is_empty());
+    }
+}
@@ -1,7 +1,16 @@
-use pyo3::{pyclass, pymethods};
+#[cfg(feature = "python")]
+use pyo3::{pyclass, pymethods, PyResult};
+use serde::{Deserialize, Serialize};
 
-#[pyclass(eq, eq_int)]
-#[derive(PartialEq, Clone, Copy)]
+use crate::error::Result;
+
+use super::validation;
+#[cfg(feature = "python")]
+use super::{deserialize_config, serialize_config, ConfigFormat};
+
+#[cfg_attr(feature = "python", pyclass(eq, eq_int))]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ElementType {
     String,
     MultiStrings,
@@ -16,18 +25,56 @@ impl ElementType {
     }
 }
 
-#[pyclass(frozen)]
-#[derive(Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordSchema {
-    #[pyo3(get)]
     pub fields: Vec<FieldSchema>,
 }
 
+impl RecordSchema {
+    pub fn new(fields: Vec<FieldSchema>) -> Self {
+        Self { fields }
+    }
+
+    /// Validates the schema, it must have at least one field
+    /// and the names of the fields must be unique.
+    ///
+    /// # Errors
+    /// Returns an InvalidConfig error naming the invalid field.
+    pub fn validate(&self) -> Result<()> {
+        validation::validate_record_schema(self)
+    }
+}
+
+#[cfg(feature = "python")]
 #[pymethods]
 impl RecordSchema {
     #[new]
     fn py_new(fields: Vec<FieldSchema>) -> Self {
-        Self { fields }
+        Self::new(fields)
+    }
+
+    #[staticmethod]
+    fn from_json(content: &str) -> PyResult<Self> {
+        Ok(deserialize_config(content, ConfigFormat::Json)?)
+    }
+
+    #[staticmethod]
+    fn from_toml(content: &str) -> PyResult<Self> {
+        Ok(deserialize_config(content, ConfigFormat::Toml)?)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(serialize_config(self, ConfigFormat::Json)?)
+    }
+
+    fn to_toml(&self) -> PyResult<String> {
+        Ok(serialize_config(self, ConfigFormat::Toml)?)
+    }
+
+    #[pyo3(name = "validate")]
+    fn py_validate(&self) -> PyResult<()> {
+        Ok(self.validate()?)
     }
 
     fn __repr__(&self) -> String {
@@ -42,19 +89,15 @@ impl RecordSchema {
     }
 }
 
-#[pyclass(frozen)]
-#[derive(Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldSchema {
-    #[pyo3(get)]
     pub name: String,
-    #[pyo3(get)]
     pub dtype: ElementType,
 }
 
-#[pymethods]
 impl FieldSchema {
-    #[new]
-    fn py_new(name: String, dtype: ElementType) -> Self {
+    pub fn new(name: String, dtype: ElementType) -> Self {
         Self { name, dtype }
     }
 
@@ -66,3 +109,17 @@ impl FieldSchema {
         )
     }
 }
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl FieldSchema {
+    #[new]
+    fn py_new(name: String, dtype: ElementType) -> Self {
+        Self::new(name, dtype)
+    }
+
+    #[pyo3(name = "__repr__")]
+    fn py_repr(&self) -> String {
+        self.__repr__()
+    }
+}
@@ -0,0 +1,463 @@
+use std::{path::PathBuf, sync::Arc};
+
+use polars::prelude::{Column, DataFrame};
+#[cfg(feature = "python")]
+use pyo3::{pyfunction, PyResult};
+#[cfg(feature = "python")]
+use pyo3_polars::PyDataFrame;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    error::{BlitzBeaverError, Result},
+    search::{build_candidates, filter_candidates, read_results, SearchRunner},
+};
+
+use super::{api::cast_to_frames, RecordSchema, TrackingConfig};
+#[cfg(feature = "python")]
+use super::{deserialize_config, ConfigFormat};
+
+/// SearchValues
+///
+/// Values taken by a parameter of the search space, the ranges are inclusive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchValues {
+    Choice(Vec<Value>),
+    Uniform(f64, f64),
+    IntUniform(i64, i64),
+}
+
+/// SearchParameter
+///
+/// Parameter of the search space, the path points to an attribute of the
+/// tracking config, e.g. `tracker.memories[*].memory_strategy`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchParameter {
+    pub path: String,
+    pub values: SearchValues,
+}
+
+/// SearchSpace
+///
+/// Either all the combinations of the parameters values (grid) or
+/// `num_samples` combinations drawn at random given the seed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "strategy")]
+pub enum SearchSpace {
+    Grid {
+        parameters: Vec<SearchParameter>,
+    },
+    Random {
+        parameters: Vec<SearchParameter>,
+        num_samples: usize,
+        seed: u64,
+    },
+}
+
+/// SearchObjective
+///
+/// Score of a tracking graph, the higher the better:
+/// - chain-length: average length of the tracking chains (intrinsic)
+/// - match-ratio: mean ratio of records matched minus the ratio of conflicts (intrinsic)
+/// - ground-truth: F1 score of the links against the true chains, given
+///   as lists of `(frame_idx, record_idx)`
+#[derive(Debug, Clone)]
+pub enum SearchObjective {
+    ChainLength,
+    MatchRatio,
+    GroundTruth(Vec<Vec<(usize, usize)>>),
+}
+
+impl SearchObjective {
+    /// Returns the objective matching the name.
+    ///
+    /// # Errors
+    /// Returns an InvalidConfig error if the name is unknown or if the
+    /// ground-truth objective is missing its chains.
+    pub fn from_name(name: &str, ground_truth: Option<Vec<Vec<(usize, usize)>>>) -> Result<Self> {
+        match (name, ground_truth) {
+            ("chain-length", _) => Ok(Self::ChainLength),
+            ("match-ratio", _) => Ok(Self::MatchRatio),
+            ("ground-truth", Some(ground_truth)) => Ok(Self::GroundTruth(ground_truth)),
+            ("ground-truth", None) => Err(BlitzBeaverError::InvalidConfig(
+                "objective: the ground-truth objective requires the true chains".to_string(),
+            )),
+            (name, _) => Err(BlitzBeaverError::InvalidConfig(format!(
+                "objective: invalid value \"{}\", expected one of \"chain-length\", \"match-ratio\", \"ground-truth\"",
+                name
+            ))),
+        }
+    }
+}
+
+/// SearchOptions
+///
+/// Number of tracking processes run at the same time (they share the
+/// `num_threads` of their configuration) and path of the results file, if
+/// given the results are appended to it as JSON lines and the configurations
+/// already present are not run again, such that an interrupted search can be resumed.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub parallel_runs: usize,
+    pub results_path: Option<PathBuf>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            parallel_runs: 1,
+            results_path: None,
+        }
+    }
+}
+
+/// SearchResult
+///
+/// Score of a configuration of the search space.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub params: Vec<(String, Value)>,
+    pub config: TrackingConfig,
+    pub score: f64,
+    pub duration_secs: f64,
+}
+
+/// Runs the tracking process with each configuration of the search space and
+/// scores it, returns the results ranked from the best score to the worst.
+///
+/// The dataframes are cast once and the distances between the frequent words of
+/// consecutive frames are precomputed once per distance metric, all the runs share them.
+/// The configurations that are invalid, as well as the runs with a non-finite score,
+/// are skipped with a warning.
+///
+/// # Errors
+/// Returns an error if the record schema or the search space is invalid,
+/// if the dataframes don't match the record schema or if the results file
+/// can't be read or written.
+pub fn execute_search(
+    base_config: &TrackingConfig,
+    record_schema: &RecordSchema,
+    dataframes: &[DataFrame],
+    search_space: &SearchSpace,
+    objective: &SearchObjective,
+    options: &SearchOptions,
+) -> Result<Vec<SearchResult>> {
+    record_schema.validate()?;
+
+    let mut candidates = build_candidates(base_config, search_space)?;
+    candidates.retain(|candidate| match candidate.config.validate(record_schema) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("skipping the configuration {:?}: {}", candidate.params, e);
+            false
+        }
+    });
+
+    let previous_results = match &options.results_path {
+        Some(path) => read_results(path)?,
+        None => Vec::new(),
+    };
+    let (candidates, mut results) = filter_candidates(candidates, previous_results)?;
+    log::info!(
+        "running {} configurations ({} already done)",
+        candidates.len(),
+        results.len()
+    );
+
+    if !candidates.is_empty() {
+        let frames = Arc::new(cast_to_frames(record_schema, dataframes)?);
+        let mut runner = SearchRunner::new(record_schema, frames, objective);
+        results.extend(runner.run(
+            candidates,
+            options.parallel_runs,
+            options.results_path.as_deref(),
+        )?);
+    }
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(results)
+}
+
+/// Builds a dataframe of the ranked results with the columns: rank, score,
+/// duration_secs, one column per parameter (values as JSON) and the configuration (as JSON).
+///
+/// # Errors
+/// Returns an error if a configuration can't be serialized.
+pub fn search_results_to_dataframe(results: &[SearchResult]) -> Result<DataFrame> {
+    let mut columns = vec![
+        Column::new(
+            "rank".into(),
+            (1..=results.len() as u32).collect::<Vec<u32>>(),
+        ),
+        Column::new(
+            "score".into(),
+            results.iter().map(|r| r.score).collect::<Vec<f64>>(),
+        ),
+        Column::new(
+            "duration_secs".into(),
+            results
+                .iter()
+                .map(|r| r.duration_secs)
+                .collect::<Vec<f64>>(),
+        ),
+    ];
+
+    let mut paths: Vec<&str> = Vec::new();
+    for result in results.iter() {
+        for (path, _) in result.params.iter() {
+            if !paths.contains(&path.as_str()) {
+                paths.push(path);
+            }
+        }
+    }
+    for path in paths {
+        let values = results
+            .iter()
+            .map(|r| {
+                r.params
+                    .iter()
+                    .find(|(p, _)| p == path)
+                    .map(|(_, value)| value.to_string())
+            })
+            .collect::<Vec<Option<String>>>();
+        columns.push(Column::new(path.into(), values));
+    }
+
+    let configs = results
+        .iter()
+        .map(|r| serde_json::to_string(&r.config))
+        .collect::<std::result::Result<Vec<String>, _>>()
+        .map_err(|e| BlitzBeaverError::Serialization(e.to_string()))?;
+    columns.push(Column::new("config".into(), configs));
+
+    Ok(DataFrame::new(columns)?)
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (base_config, record_schema, dataframes, search_space, objective, ground_truth=None, parallel_runs=1, results_path=None))]
+pub fn execute_search_process(
+    base_config: &TrackingConfig,
+    record_schema: &RecordSchema,
+    dataframes: Vec<PyDataFrame>,
+    search_space: &str,
+    objective: &str,
+    ground_truth: Option<Vec<Vec<(usize, usize)>>>,
+    parallel_runs: usize,
+    results_path: Option<PathBuf>,
+) -> PyResult<PyDataFrame> {
+    let search_space: SearchSpace = deserialize_config(search_space, ConfigFormat::Json)?;
+    let objective = SearchObjective::from_name(objective, ground_truth)?;
+    let dataframes = dataframes.into_iter().map(|d| d.0).collect::<Vec<_>>();
+
+    let results = execute_search(
+        base_config,
+        record_schema,
+        &dataframes,
+        &search_space,
+        &objective,
+        &SearchOptions {
+            parallel_runs,
+            results_path,
+        },
+    )?;
+    Ok(PyDataFrame(search_results_to_dataframe(&results)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::df;
+    use serde_json::json;
+
+    use crate::api::{
+        DistanceMetricConfig, ElementType, FieldSchema, MemoryConfig, RecordScorerConfig,
+        ResolverConfig, TrackerConfig,
+    };
+
+    use super::*;
+
+    fn build_base_config() -> TrackingConfig {
+        TrackingConfig {
+            num_threads: 2,
+            tracker: TrackerConfig {
+                interest_threshold: 0.6,
+                limit_no_match_streak: 3,
+                memories: vec![MemoryConfig {
+                    memory_strategy: "median".to_string(),
+                    multiword_threshold_match: None,
+                    multiword_distance_metric: None,
+                    median_strategy: None,
+                    recency_decay: None,
+                }],
+                record_scorer: RecordScorerConfig {
+                    record_scorer: "average".to_string(),
+                    weights: None,
+                    min_weight_ratio: None,
+                    missing_record_distances: None,
+                    missing_memory_distances: None,
+                    missing_both_distances: None,
+                    composite_features: None,
+                },
+            },
+            distance_metric: DistanceMetricConfig {
+                metric: "lv".to_string(),
+                caching_threshold: 2,
+                use_sigmoid: false,
+                lv_edit_weights: None,
+                lv_substring_weight: None,
+                lv_multiword_separator: None,
+                persistent_cache_bytes: None,
+            },
+            resolver: ResolverConfig {
+                resolving_strategy: "best-match".to_string(),
+                num_hypotheses: None,
+                hypotheses_depth: None,
+                ambiguity_margin: None,
+            },
+            engine_mode: None,
+            chunk_size: None,
+        }
+    }
+
+    #[test]
+    fn test_execute_search() {
+        let record_schema = RecordSchema::new(vec![FieldSchema::new(
+            "name".to_string(),
+            ElementType::String,
+        )]);
+        let dataframes = [
+            df!("name" => ["alice", "bob", "carol"]).unwrap(),
+            df!("name" => ["alice", "bobb", "carol"]).unwrap(),
+            df!("name" => ["alyce", "bobb", "karol"]).unwrap(),
+        ];
+
+        let search_space = SearchSpace::Grid {
+            parameters: vec![
+                SearchParameter {
+                    path: "tracker.interest_threshold".to_string(),
+                    values: SearchValues::Choice(vec![json!(0.1), json!(0.99)]),
+                },
+                SearchParameter {
+                    path: "tracker.memories[*].memory_strategy".to_string(),
+                    values: SearchValues::Choice(vec![json!("median"), json!("unknown")]),
+                },
+            ],
+        };
+        let ground_truth = (0..3)
+            .map(|record_idx| (0..3).map(|frame_idx| (frame_idx, record_idx)).collect())
+            .collect();
+
+        let results_path =
+            std::env::temp_dir().join(format!("blitzbeaver-search-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&results_path);
+        let options = SearchOptions {
+            parallel_runs: 2,
+            results_path: Some(results_path.clone()),
+        };
+
+        let results = execute_search(
+            &build_base_config(),
+            &record_schema,
+            &dataframes,
+            &search_space,
+            &SearchObjective::GroundTruth(ground_truth),
+            &options,
+        )
+        .unwrap();
+
+        // the unknown memory strategy is skipped
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].params[0].1, json!(0.1));
+        assert!((results[0].score - 1.0).abs() < 1e-9);
+        assert!(results[0].score > results[1].score);
+
+        // resumed search: nothing is run again
+        let resumed = execute_search(
+            &build_base_config(),
+            &record_schema,
+            &dataframes,
+            &search_space,
+            &SearchObjective::ChainLength,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(resumed.len(), 2);
+        assert_eq!(resumed[0].score, results[0].score);
+
+        let df = search_results_to_dataframe(&results).unwrap();
+        assert_eq!(df.height(), 2);
+        assert_eq!(
+            df.get_column_names(),
+            vec![
+                "rank",
+                "score",
+                "duration_secs",
+                "tracker.interest_threshold",
+                "tracker.memories[*].memory_strategy",
+                "config"
+            ]
+        );
+        std::fs::remove_file(&results_path).unwrap();
+    }
+
+    #[test]
+    fn test_resume_truncated_search() {
+        let record_schema = RecordSchema::new(vec![FieldSchema::new(
+            "name".to_string(),
+            ElementType::String,
+        )]);
+        let dataframes = [
+            df!("name" => ["alice", "bob"]).unwrap(),
+            df!("name" => ["alise", "bob"]).unwrap(),
+        ];
+        let search_space = SearchSpace::Grid {
+            parameters: vec![SearchParameter {
+                path: "tracker.interest_threshold".to_string(),
+                values: SearchValues::Choice(vec![json!(0.1), json!(0.99)]),
+            }],
+        };
+
+        let results_path = std::env::temp_dir().join(format!(
+            "blitzbeaver-search-truncated-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&results_path);
+        let options = SearchOptions {
+            parallel_runs: 1,
+            results_path: Some(results_path.clone()),
+        };
+        let search = || {
+            execute_search(
+                &build_base_config(),
+                &record_schema,
+                &dataframes,
+                &search_space,
+                &SearchObjective::ChainLength,
+                &options,
+            )
+            .unwrap()
+        };
+
+        let results = search();
+        assert_eq!(results.len(), 2);
+
+        // interrupted while writing the second result
+        let content = std::fs::read_to_string(&results_path).unwrap();
+        let second_line_start = content.find('\n').unwrap() + 1;
+        std::fs::write(&results_path, &content[..second_line_start + 10]).unwrap();
+        assert_eq!(read_results(&results_path).unwrap().len(), 1);
+
+        // the second run is done again, once
+        for _ in 0..2 {
+            let resumed = search();
+            assert_eq!(resumed.len(), 2);
+            assert_eq!(read_results(&results_path).unwrap().len(), 2);
+            let content = std::fs::read_to_string(&results_path).unwrap();
+            assert_eq!(content.lines().count(), 2);
+            assert!(content.ends_with('\n'));
+        }
+        std::fs::remove_file(&results_path).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::{BlitzBeaverError, Result};
+
+/// ConfigFormat
+///
+/// Text format of a serialized configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Returns the format matching the extension of the path, if any.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Some(Self::Json),
+            Some("toml") => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// Deserializes a configuration (or a record schema) from a string.
+///
+/// # Errors
+/// Returns an InvalidConfig error naming the path of the invalid attribute,
+/// e.g. `tracker.memories[3].multiword_distance_metric: missing field `metric``.
+pub fn deserialize_config<T: DeserializeOwned>(content: &str, format: ConfigFormat) -> Result<T> {
+    let result = match format {
+        ConfigFormat::Json => {
+            let mut deserializer = serde_json::Deserializer::from_str(content);
+            serde_path_to_error::deserialize(&mut deserializer)
+                .map_err(|e| (e.path().to_string(), e.into_inner().to_string()))
+        }
+        ConfigFormat::Toml => {
+            let deserializer = toml::Deserializer::new(content);
+            serde_path_to_error::deserialize(deserializer)
+                .map_err(|e| (e.path().to_string(), e.into_inner().message().to_string()))
+        }
+    };
+    result.map_err(|(path, message)| {
+        BlitzBeaverError::InvalidConfig(match path.as_str() {
+            "." => message,
+            _ => format!("{}: {}", path, message),
+        })
+    })
+}
+
+/// Serializes a configuration (or a record schema) to a string.
+///
+/// # Errors
+/// Returns a Serialization error if the configuration can't be represented
+/// in the format, e.g. TOML doesn't support missing values in lists.
+pub fn serialize_config<T: Serialize>(config: &T, format: ConfigFormat) -> Result<String> {
+    match format {
+        ConfigFormat::Json => serde_json::to_string_pretty(config).map_err(|e| e.to_string()),
+        ConfigFormat::Toml => toml::to_string(config).map_err(|e| e.to_string()),
+    }
+    .map_err(BlitzBeaverError::Serialization)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{DistanceMetricConfig, TrackingConfig};
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_config() {
+        let content = r#"
+            num_threads = 2
+
+            [tracker]
+            interest_threshold = 0.6
+            limit_no_match_streak = 3
+            memories = [
+                { memory_strategy = "median" },
+                { memory_strategy = "mw-median", multiword_distance_metric = { use_sigmoid = false } },
+            ]
+            record_scorer = { record_scorer = "average" }
+
+            [distance_metric]
+            metric = "lv_opti"
+            caching_threshold = 4
+            use_sigmoid = false
+
+            [resolver]
+            resolving_strategy = "best-match"
+        "#;
+        let result = deserialize_config::<TrackingConfig>(content, ConfigFormat::Toml);
+        match result {
+            Err(BlitzBeaverError::InvalidConfig(e)) => assert_eq!(
+                e,
+                "tracker.memories[1].multiword_distance_metric: missing field `metric`"
+            ),
+            r => panic!("expected an invalid config error, got {:?}", r),
+        }
+
+        let config: DistanceMetricConfig = deserialize_config(
+            r#"{"metric": "lv_edit", "caching_threshold": 4, "use_sigmoid": true, "lv_edit_weights": [1, 1, 1]}"#,
+            ConfigFormat::Json,
+        )
+        .unwrap();
+        let content = serialize_config(&config, ConfigFormat::Toml).unwrap();
+        let other: DistanceMetricConfig = deserialize_config(&content, ConfigFormat::Toml).unwrap();
+        assert_eq!(other.lv_edit_weights, Some(vec![1.0, 1.0, 1.0]));
+        assert!(other.use_sigmoid);
+    }
+}
@@ -1,19 +1,36 @@
-use pyo3::{pyclass, pymethods};
+use std::collections::HashMap;
+#[cfg(feature = "python")]
+use std::collections::HashSet;
+
+use polars::frame::DataFrame;
+#[cfg(feature = "python")]
+use pyo3::{pyclass, pymethods, PyResult};
+#[cfg(feature = "python")]
+use pyo3_polars::PyDataFrame;
 use serde::{Deserialize, Serialize};
 
-use crate::{frame::Frame, id::ID, trackers::TrackingChain};
+use crate::{
+    error::{BlitzBeaverError, Result},
+    frame::Frame,
+    id::{self, ID},
+    trackers::TrackingChain,
+};
+
+use super::{
+    casting,
+    graph_export::{ExportGraph, GraphExportFilter},
+    RecordSchema,
+};
 
 /// ChainNode
 ///
 /// References a record in a frame.
 ///
 /// Note: this doesn't hold the record itself, but only the indices to access it.
-#[pyclass(frozen)]
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ChainNode {
-    #[pyo3(get)]
     pub frame_idx: usize,
-    #[pyo3(get)]
     pub record_idx: usize,
 }
 
@@ -26,109 +43,728 @@ impl ChainNode {
     }
 }
 
+/// ChainLink
+///
+/// Matching information of a link between two chain nodes,
+/// that is how confident the tracker was when it matched the record.
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChainLink {
+    /// Score of the record
+    pub score: f32,
+    /// Distances of each feature between the record and the tracker's memory
+    pub distances: Vec<Option<f32>>,
+    /// Difference between the score of the record and the best score of the
+    /// other records of interest of the tracker, None if there was no other record.
+    pub margin: Option<f32>,
+}
+
+impl ChainLink {
+    pub fn new(score: f32, distances: Vec<Option<f32>>, margin: Option<f32>) -> Self {
+        Self {
+            score,
+            distances,
+            margin,
+        }
+    }
+}
+
 /// GraphNode
 ///
 /// Node in the tracking graph.
-#[pyclass(frozen)]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "python", pyclass(frozen, get_all))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct GraphNode {
     /// List of incoming edges.
     ///
     /// Each edge represents a link from a previous chain node in a tracking chain.
     /// Each tuple contains the ID of the chain and the chain node.
-    #[pyo3(get)]
     pub ins: Vec<(ID, ChainNode)>,
     /// List of outgoing edges.
     ///
     /// Each edge represents a link to a next chain node in a tracking chain.
     /// Each tuple contains the ID of the chain and the chain node.
-    #[pyo3(get)]
     pub outs: Vec<(ID, ChainNode)>,
+    /// List of the links that lead to this node.
+    ///
+    /// Each tuple contains the ID of the chain and the matching information,
+    /// chains that start at this node or without matching information are absent.
+    pub links: Vec<(ID, ChainLink)>,
 }
 
-#[pymethods]
-impl GraphNode {}
-
 impl GraphNode {
     pub fn new() -> Self {
         Self {
             ins: Vec::new(),
             outs: Vec::new(),
+            links: Vec::new(),
         }
     }
 }
 
+/// GraphEdit
+///
+/// Manual edit of the tracking graph.
+///
+/// The IDs of the chains created by the edits are part of the edit,
+/// such that replaying the edits produces the same graph.
+#[cfg_attr(feature = "python", pyclass(frozen))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum GraphEdit {
+    /// Splits the chain at the frame, the nodes from the frame onward form the new chain.
+    Split {
+        id: ID,
+        frame_idx: usize,
+        new_id: ID,
+    },
+    /// Merges the other chain into the chain.
+    Merge { id: ID, other_id: ID },
+    /// Detaches the node of the chain at the frame into the new chain.
+    Detach {
+        id: ID,
+        frame_idx: usize,
+        new_id: ID,
+    },
+    /// Reassigns the node of the chain at the frame to the target chain.
+    Reassign {
+        id: ID,
+        frame_idx: usize,
+        target_id: ID,
+    },
+}
+
 /// TrackingGraph
 ///
 /// Graph representing all tracking chains, each node in the graph
 /// represents a record in a frame. Each edge represents a link between
 /// two chain nodes of a tracking chain.
-#[pyclass(frozen)]
+///
+/// The graph can be edited manually, the edits are recorded in the graph.
+#[cfg_attr(feature = "python", pyclass(get_all))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TrackingGraph {
     /// Source of the graph, all tracking chains start from this node.
-    #[pyo3(get)]
     pub root: GraphNode,
     /// Adjacency matrix of the graph.
     ///
     /// Each row represents a frame, each column represents a record in the frame.
-    #[pyo3(get)]
     pub matrix: Vec<Vec<GraphNode>>,
+    /// Log of the edits applied to the graph, in order.
+    pub edits: Vec<GraphEdit>,
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl TrackingGraph {
     /// Python function
     ///
     /// Builds the tracking chain with the given ID.
-    pub fn get_tracking_chain(&self, id: ID) -> Vec<ChainNode> {
-        self.build_chain_node_vector(id)
+    #[pyo3(name = "get_tracking_chain")]
+    fn py_get_tracking_chain(&self, id: ID) -> Vec<ChainNode> {
+        self.get_tracking_chain(id)
+    }
+
+    /// Python function
+    ///
+    /// Returns all the links with a score below the given threshold,
+    /// as tuples of the chain ID, the chain node the link leads to and the link.
+    #[pyo3(name = "get_links_below")]
+    fn py_get_links_below(&self, threshold: f32) -> Vec<(ID, ChainNode, ChainLink)> {
+        self.get_links_below(threshold)
+    }
+
+    /// Python function
+    ///
+    /// Exports the graph in the GraphML format.
+    #[pyo3(name = "to_graphml", signature = (record_schema=None, dataframes=None, chains_ids=None, frame_range=None))]
+    fn py_to_graphml(
+        &self,
+        record_schema: Option<&RecordSchema>,
+        dataframes: Option<Vec<PyDataFrame>>,
+        chains_ids: Option<HashSet<ID>>,
+        frame_range: Option<(usize, usize)>,
+    ) -> PyResult<String> {
+        let dataframes = dataframes.map(|d| d.into_iter().map(|d| d.0).collect::<Vec<_>>());
+        let filter = GraphExportFilter::new(chains_ids, frame_range);
+        Ok(self.to_graphml(record_schema, dataframes.as_deref(), &filter)?)
+    }
+
+    /// Python function
+    ///
+    /// Exports the graph in the DOT format.
+    #[pyo3(name = "to_dot", signature = (record_schema=None, dataframes=None, chains_ids=None, frame_range=None))]
+    fn py_to_dot(
+        &self,
+        record_schema: Option<&RecordSchema>,
+        dataframes: Option<Vec<PyDataFrame>>,
+        chains_ids: Option<HashSet<ID>>,
+        frame_range: Option<(usize, usize)>,
+    ) -> PyResult<String> {
+        let dataframes = dataframes.map(|d| d.into_iter().map(|d| d.0).collect::<Vec<_>>());
+        let filter = GraphExportFilter::new(chains_ids, frame_range);
+        Ok(self.to_dot(record_schema, dataframes.as_deref(), &filter)?)
+    }
+
+    /// Python function
+    ///
+    /// Exports the graph in the JSON-lines format.
+    #[pyo3(name = "to_jsonl", signature = (record_schema=None, dataframes=None, chains_ids=None, frame_range=None))]
+    fn py_to_jsonl(
+        &self,
+        record_schema: Option<&RecordSchema>,
+        dataframes: Option<Vec<PyDataFrame>>,
+        chains_ids: Option<HashSet<ID>>,
+        frame_range: Option<(usize, usize)>,
+    ) -> PyResult<String> {
+        let dataframes = dataframes.map(|d| d.into_iter().map(|d| d.0).collect::<Vec<_>>());
+        let filter = GraphExportFilter::new(chains_ids, frame_range);
+        Ok(self.to_jsonl(record_schema, dataframes.as_deref(), &filter)?)
+    }
+
+    /// Python function
+    ///
+    /// Splits the tracking chain at the given frame, returns the ID of the new chain.
+    #[pyo3(name = "split_chain")]
+    fn py_split_chain(&mut self, id: ID, frame_idx: usize) -> PyResult<ID> {
+        Ok(self.split_chain(id, frame_idx)?)
+    }
+
+    /// Python function
+    ///
+    /// Merges the other tracking chain into the tracking chain.
+    #[pyo3(name = "merge_chains")]
+    fn py_merge_chains(&mut self, id: ID, other_id: ID) -> PyResult<()> {
+        Ok(self.merge_chains(id, other_id)?)
+    }
+
+    /// Python function
+    ///
+    /// Detaches the node of the tracking chain at the given frame into a new chain,
+    /// returns the ID of the new chain.
+    #[pyo3(name = "detach_node")]
+    fn py_detach_node(&mut self, id: ID, frame_idx: usize) -> PyResult<ID> {
+        Ok(self.detach_node(id, frame_idx)?)
+    }
+
+    /// Python function
+    ///
+    /// Reassigns the node of the tracking chain at the given frame to the target chain.
+    #[pyo3(name = "reassign_node")]
+    fn py_reassign_node(&mut self, id: ID, frame_idx: usize, target_id: ID) -> PyResult<()> {
+        Ok(self.reassign_node(id, frame_idx, target_id)?)
+    }
+
+    /// Python function
+    ///
+    /// Applies the given edits in order, they are added to the edit log.
+    #[pyo3(name = "replay_edits")]
+    fn py_replay_edits(&mut self, edits: Vec<GraphEdit>) -> PyResult<()> {
+        Ok(self.replay_edits(edits)?)
     }
 }
 
 impl TrackingGraph {
     /// Create a new tracking graph from a list of frames and tracking chains.
     pub fn from_tracking_chains(frames: &Vec<Frame>, chains: Vec<TrackingChain>) -> Self {
-        let mut matrix: Vec<Vec<GraphNode>> = Vec::with_capacity(frames.len());
-        for frame in frames.iter() {
-            let mut column = Vec::with_capacity(frame.num_records());
-            for _ in 0..frame.num_records() {
+        Self::from_frame_sizes(
+            &frames.iter().map(|f| f.num_records()).collect::<Vec<_>>(),
+            chains,
+        )
+    }
+
+    /// Create a new tracking graph from the number of records of each frame
+    /// and tracking chains, the frames themselves don't need to be resident.
+    pub fn from_frame_sizes(frame_sizes: &[usize], chains: Vec<TrackingChain>) -> Self {
+        let mut matrix: Vec<Vec<GraphNode>> = Vec::with_capacity(frame_sizes.len());
+        for &num_records in frame_sizes.iter() {
+            let mut column = Vec::with_capacity(num_records);
+            for _ in 0..num_records {
                 column.push(GraphNode::new());
             }
             matrix.push(column);
         }
 
-        let mut root = GraphNode::new();
+        let mut graph = Self {
+            root: GraphNode::new(),
+            matrix,
+            edits: Vec::new(),
+        };
 
         for chain in chains.iter() {
-            let mut prev_node = &mut root;
-            let mut prev_cn: Option<&ChainNode> = None;
+            graph.insert_chain(chain);
+        }
+
+        graph
+    }
+
+    /// Returns the IDs of all the tracking chains.
+    pub fn chains_ids(&self) -> Vec<ID> {
+        self.root.outs.iter().map(|(id, _)| *id).collect()
+    }
+
+    /// Builds the chain nodes of the tracking chain with the given ID.
+    pub fn get_tracking_chain(&self, id: ID) -> Vec<ChainNode> {
+        self.build_chain_node_vector(self.root.outs.iter().find(|o| o.0 == id))
+    }
+
+    /// Returns all the links with a score below the given threshold,
+    /// as tuples of the chain ID, the chain node the link leads to and the link.
+    pub fn get_links_below(&self, threshold: f32) -> Vec<(ID, ChainNode, ChainLink)> {
+        let mut links = Vec::new();
+        for (frame_idx, column) in self.matrix.iter().enumerate() {
+            for (record_idx, node) in column.iter().enumerate() {
+                for (id, link) in node.links.iter() {
+                    if link.score < threshold {
+                        links.push((*id, ChainNode::new(frame_idx, record_idx), link.clone()));
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    /// Exports the graph to a long-format dataframe, with one row per chain node.
+    ///
+    /// See `casting::cast_tracking_chains_to_dataframe` for the columns.
+    ///
+    /// # Errors
+    /// Returns an error if the records can't be taken from the dataframes.
+    pub fn to_dataframe(
+        &self,
+        record_schema: Option<&RecordSchema>,
+        dataframes: Option<&[DataFrame]>,
+        normalized_dataframes: Option<&[DataFrame]>,
+    ) -> Result<DataFrame> {
+        casting::cast_tracking_chains_to_dataframe(
+            &self.build_tracking_chains(),
+            record_schema,
+            dataframes,
+            normalized_dataframes,
+        )
+    }
+
+    /// Exports the graph in the GraphML format, the node attributes are
+    /// the fields of the records if the dataframes are given.
+    ///
+    /// # Errors
+    /// Returns an error if the dataframes can't be cast to frames.
+    pub fn to_graphml(
+        &self,
+        record_schema: Option<&RecordSchema>,
+        dataframes: Option<&[DataFrame]>,
+        filter: &GraphExportFilter,
+    ) -> Result<String> {
+        let graph = self.build_export_graph(record_schema, dataframes, filter)?;
+        Ok(graph.to_graphml())
+    }
+
+    /// Exports the graph in the DOT format, the node labels include
+    /// the fields of the records if the dataframes are given.
+    ///
+    /// # Errors
+    /// Returns an error if the dataframes can't be cast to frames.
+    pub fn to_dot(
+        &self,
+        record_schema: Option<&RecordSchema>,
+        dataframes: Option<&[DataFrame]>,
+        filter: &GraphExportFilter,
+    ) -> Result<String> {
+        let graph = self.build_export_graph(record_schema, dataframes, filter)?;
+        Ok(graph.to_dot())
+    }
+
+    /// Exports the graph in the JSON-lines format, the node attributes are
+    /// the fields of the records if the dataframes are given.
+    ///
+    /// # Errors
+    /// Returns an error if the dataframes can't be cast to frames.
+    pub fn to_jsonl(
+        &self,
+        record_schema: Option<&RecordSchema>,
+        dataframes: Option<&[DataFrame]>,
+        filter: &GraphExportFilter,
+    ) -> Result<String> {
+        let graph = self.build_export_graph(record_schema, dataframes, filter)?;
+        Ok(graph.to_jsonl())
+    }
+
+    /// Splits the tracking chain at the given frame, the nodes from the frame
+    /// onward form a new chain.
+    ///
+    /// Returns the ID of the new chain.
+    ///
+    /// # Errors
+    /// Returns an error if the edit is invalid.
+    pub fn split_chain(&mut self, id: ID, frame_idx: usize) -> Result<ID> {
+        let new_id = id::new_id();
+        self.apply_edit(GraphEdit::Split {
+            id,
+            frame_idx,
+            new_id,
+        })?;
+        Ok(new_id)
+    }
+
+    /// Merges the other tracking chain into the tracking chain.
+    ///
+    /// # Errors
+    /// Returns an error if the edit is invalid.
+    pub fn merge_chains(&mut self, id: ID, other_id: ID) -> Result<()> {
+        self.apply_edit(GraphEdit::Merge { id, other_id })
+    }
+
+    /// Detaches the node of the tracking chain at the given frame into a new chain.
+    ///
+    /// Returns the ID of the new chain.
+    ///
+    /// # Errors
+    /// Returns an error if the edit is invalid.
+    pub fn detach_node(&mut self, id: ID, frame_idx: usize) -> Result<ID> {
+        let new_id = id::new_id();
+        self.apply_edit(GraphEdit::Detach {
+            id,
+            frame_idx,
+            new_id,
+        })?;
+        Ok(new_id)
+    }
 
-            for cn in chain.nodes.iter() {
-                prev_node.outs.push((chain.id, cn.clone()));
-                let next_node = &mut matrix[cn.frame_idx][cn.record_idx];
+    /// Reassigns the node of the tracking chain at the given frame to the target chain.
+    ///
+    /// # Errors
+    /// Returns an error if the edit is invalid.
+    pub fn reassign_node(&mut self, id: ID, frame_idx: usize, target_id: ID) -> Result<()> {
+        self.apply_edit(GraphEdit::Reassign {
+            id,
+            frame_idx,
+            target_id,
+        })
+    }
 
-                if let Some(prev_cn) = prev_cn {
-                    next_node.ins.push((chain.id, prev_cn.clone()));
+    /// Applies the given edits in order, they are added to the edit log.
+    ///
+    /// # Errors
+    /// Returns an error if an edit is invalid, the previous edits are applied.
+    pub fn replay_edits(&mut self, edits: Vec<GraphEdit>) -> Result<()> {
+        for edit in edits {
+            self.apply_edit(edit)?;
+        }
+        Ok(())
+    }
+
+    /// Builds the graph to export, restricted by the filter.
+    ///
+    /// # Errors
+    /// Returns an error if the dataframes are given without record schema
+    /// or can't be cast to frames.
+    fn build_export_graph(
+        &self,
+        record_schema: Option<&RecordSchema>,
+        dataframes: Option<&[DataFrame]>,
+        filter: &GraphExportFilter,
+    ) -> Result<ExportGraph> {
+        let frames = match dataframes {
+            Some(dataframes) => {
+                let record_schema = record_schema.ok_or_else(|| {
+                    BlitzBeaverError::InvalidData(
+                        "A record schema is required to export the records".to_string(),
+                    )
+                })?;
+                if dataframes.len() != self.matrix.len() {
+                    return Err(BlitzBeaverError::InvalidData(format!(
+                        "Expected {} dataframes, got {}",
+                        self.matrix.len(),
+                        dataframes.len()
+                    )));
                 }
+                let mut frames = Vec::new();
+                for (i, dataframe) in dataframes.iter().enumerate() {
+                    frames.push(casting::cast_to_frame(i, record_schema, dataframe)?);
+                }
+                Some((record_schema, frames))
+            }
+            None => None,
+        };
 
-                prev_node = next_node;
-                prev_cn = Some(cn);
+        Ok(ExportGraph::new(
+            &self.build_tracking_chains(),
+            filter,
+            frames.as_ref().map(|(s, f)| (*s, f.as_slice())),
+        ))
+    }
+
+    /// Inserts the edges of the tracking chain in the graph.
+    fn insert_chain(&mut self, chain: &TrackingChain) {
+        let mut prev_node = &mut self.root;
+        let mut prev_cn: Option<&ChainNode> = None;
+
+        for (cn, link) in chain.nodes.iter().zip(chain.links.iter()) {
+            prev_node.outs.push((chain.id, *cn));
+            let next_node = &mut self.matrix[cn.frame_idx][cn.record_idx];
+
+            if let Some(prev_cn) = prev_cn {
+                next_node.ins.push((chain.id, *prev_cn));
+                if let Some(link) = link {
+                    next_node.links.push((chain.id, link.clone()));
+                }
             }
+
+            prev_node = next_node;
+            prev_cn = Some(cn);
         }
+    }
+
+    /// Removes the edges of the tracking chain from the graph.
+    fn remove_chain(&mut self, chain: &TrackingChain) {
+        self.root.outs.retain(|(id, _)| *id != chain.id);
+        for cn in chain.nodes.iter() {
+            let node = &mut self.matrix[cn.frame_idx][cn.record_idx];
+            node.ins.retain(|(id, _)| *id != chain.id);
+            node.outs.retain(|(id, _)| *id != chain.id);
+            node.links.retain(|(id, _)| *id != chain.id);
+        }
+    }
+
+    /// Returns the tracking chain with the given ID.
+    ///
+    /// # Errors
+    /// Returns an error if the chain doesn't exist.
+    fn get_existing_chain(&self, id: ID) -> Result<TrackingChain> {
+        if !self.root.outs.iter().any(|(i, _)| *i == id) {
+            return Err(BlitzBeaverError::InvalidEdit(format!(
+                "Unknown tracking chain {}",
+                id
+            )));
+        }
+        Ok(self.build_tracking_chain(id))
+    }
+
+    /// Returns an error if a chain with the given ID already exists.
+    fn check_new_chain(&self, id: ID) -> Result<()> {
+        if self.root.outs.iter().any(|(i, _)| *i == id) {
+            return Err(BlitzBeaverError::InvalidEdit(format!(
+                "Tracking chain {} already exists",
+                id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Builds an edited tracking chain from the given nodes, sorted by frame.
+    ///
+    /// The links of the original chains are kept for the nodes
+    /// whose previous node is unchanged.
+    ///
+    /// # Errors
+    /// Returns an error if the chain would have multiple nodes in a frame.
+    fn build_edited_chain(
+        id: ID,
+        mut nodes: Vec<ChainNode>,
+        originals: &[&TrackingChain],
+    ) -> Result<TrackingChain> {
+        nodes.sort_by_key(|cn| cn.frame_idx);
+        for pair in nodes.windows(2) {
+            if pair[0].frame_idx == pair[1].frame_idx {
+                return Err(BlitzBeaverError::InvalidEdit(format!(
+                    "Tracking chain {} would have multiple nodes in frame {}",
+                    id, pair[0].frame_idx
+                )));
+            }
+        }
+
+        let mut previous = HashMap::new();
+        for chain in originals {
+            for (i, (cn, link)) in chain.nodes.iter().zip(chain.links.iter()).enumerate() {
+                if i > 0 {
+                    previous.insert(*cn, (chain.nodes[i - 1], link.clone()));
+                }
+            }
+        }
+
+        let links = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, cn)| match (i, previous.get(cn)) {
+                (0, _) | (_, None) => None,
+                (_, Some((prev, link))) if *prev == nodes[i - 1] => link.clone(),
+                _ => None,
+            })
+            .collect();
+
+        Ok(TrackingChain::new(id, nodes, links))
+    }
 
-        Self { root, matrix }
+    /// Returns the node of the chain at the given frame.
+    ///
+    /// # Errors
+    /// Returns an error if the chain has no node at the frame.
+    fn get_chain_node(chain: &TrackingChain, frame_idx: usize) -> Result<ChainNode> {
+        chain
+            .nodes
+            .iter()
+            .find(|cn| cn.frame_idx == frame_idx)
+            .copied()
+            .ok_or_else(|| {
+                BlitzBeaverError::InvalidEdit(format!(
+                    "Tracking chain {} has no node in frame {}",
+                    chain.id, frame_idx
+                ))
+            })
+    }
+
+    /// Applies an edit to the graph and records it in the edit log.
+    ///
+    /// The graph is left unchanged if the edit is invalid.
+    ///
+    /// # Errors
+    /// Returns an error if the edit is invalid.
+    pub fn apply_edit(&mut self, edit: GraphEdit) -> Result<()> {
+        let (removed, inserted) = match &edit {
+            GraphEdit::Split {
+                id,
+                frame_idx,
+                new_id,
+            } => {
+                let chain = self.get_existing_chain(*id)?;
+                self.check_new_chain(*new_id)?;
+                let (before, after): (Vec<ChainNode>, Vec<ChainNode>) =
+                    chain.nodes.iter().partition(|cn| cn.frame_idx < *frame_idx);
+                if before.is_empty() || after.is_empty() {
+                    return Err(BlitzBeaverError::InvalidEdit(format!(
+                        "Can't split tracking chain {} at frame {}",
+                        id, frame_idx
+                    )));
+                }
+                let inserted = vec![
+                    Self::build_edited_chain(*id, before, &[&chain])?,
+                    Self::build_edited_chain(*new_id, after, &[&chain])?,
+                ];
+                (vec![chain], inserted)
+            }
+            GraphEdit::Merge { id, other_id } => {
+                if id == other_id {
+                    return Err(BlitzBeaverError::InvalidEdit(format!(
+                        "Can't merge tracking chain {} with itself",
+                        id
+                    )));
+                }
+                let chain = self.get_existing_chain(*id)?;
+                let other = self.get_existing_chain(*other_id)?;
+                let nodes = chain
+                    .nodes
+                    .iter()
+                    .chain(other.nodes.iter())
+                    .copied()
+                    .collect();
+                let inserted = vec![Self::build_edited_chain(*id, nodes, &[&chain, &other])?];
+                (vec![chain, other], inserted)
+            }
+            GraphEdit::Detach {
+                id,
+                frame_idx,
+                new_id,
+            } => {
+                let chain = self.get_existing_chain(*id)?;
+                self.check_new_chain(*new_id)?;
+                let node = Self::get_chain_node(&chain, *frame_idx)?;
+                if chain.nodes.len() == 1 {
+                    return Err(BlitzBeaverError::InvalidEdit(format!(
+                        "Can't detach the only node of tracking chain {}",
+                        id
+                    )));
+                }
+                let rest = chain
+                    .nodes
+                    .iter()
+                    .filter(|cn| **cn != node)
+                    .copied()
+                    .collect();
+                let inserted = vec![
+                    Self::build_edited_chain(*id, rest, &[&chain])?,
+                    Self::build_edited_chain(*new_id, vec![node], &[&chain])?,
+                ];
+                (vec![chain], inserted)
+            }
+            GraphEdit::Reassign {
+                id,
+                frame_idx,
+                target_id,
+            } => {
+                if id == target_id {
+                    return Err(BlitzBeaverError::InvalidEdit(format!(
+                        "Can't reassign a node of tracking chain {} to itself",
+                        id
+                    )));
+                }
+                let chain = self.get_existing_chain(*id)?;
+                let target = self.get_existing_chain(*target_id)?;
+                let node = Self::get_chain_node(&chain, *frame_idx)?;
+                let rest: Vec<ChainNode> = chain
+                    .nodes
+                    .iter()
+                    .filter(|cn| **cn != node)
+                    .copied()
+                    .collect();
+                let mut target_nodes = target.nodes.clone();
+                target_nodes.push(node);
+
+                let mut inserted = vec![Self::build_edited_chain(
+                    *target_id,
+                    target_nodes,
+                    &[&chain, &target],
+                )?];
+                // the chain disappears when its only node is reassigned
+                if !rest.is_empty() {
+                    inserted.push(Self::build_edited_chain(*id, rest, &[&chain])?);
+                }
+                (vec![chain, target], inserted)
+            }
+        };
+
+        for chain in removed.iter() {
+            self.remove_chain(chain);
+        }
+        for chain in inserted.iter() {
+            self.insert_chain(chain);
+        }
+        self.edits.push(edit);
+        Ok(())
     }
 
     /// Builds the tracking chain with the given ID.
     pub fn build_tracking_chain(&self, id: ID) -> TrackingChain {
-        let nodes = self.build_chain_node_vector(id);
-        TrackingChain::new(id, nodes)
+        let first = self.root.outs.iter().find(|o| o.0 == id);
+        self.build_tracking_chain_from(id, first)
+    }
+
+    /// Builds all the tracking chains of the graph.
+    pub fn build_tracking_chains(&self) -> Vec<TrackingChain> {
+        self.root
+            .outs
+            .iter()
+            .map(|first| self.build_tracking_chain_from(first.0, Some(first)))
+            .collect()
+    }
+
+    /// Builds the tracking chain starting with the given root edge.
+    fn build_tracking_chain_from(&self, id: ID, first: Option<&(ID, ChainNode)>) -> TrackingChain {
+        let nodes = self.build_chain_node_vector(first);
+        let links = nodes
+            .iter()
+            .map(|cn| {
+                self.matrix[cn.frame_idx][cn.record_idx]
+                    .links
+                    .iter()
+                    .find(|(i, _)| *i == id)
+                    .map(|(_, link)| link.clone())
+            })
+            .collect();
+        TrackingChain::new(id, nodes, links)
     }
 
     /// Builds a vector of chain nodes composing a tracking chain.
-    fn build_chain_node_vector(&self, id: ID) -> Vec<ChainNode> {
-        let mut node = self.root.outs.iter().find(|o| o.0 == id);
+    fn build_chain_node_vector(&self, first: Option<&(ID, ChainNode)>) -> Vec<ChainNode> {
+        let mut node = first;
 
         let mut nodes = Vec::new();
 
@@ -146,3 +782,113 @@ impl TrackingGraph {
         nodes
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::frame::Element;
+
+    use super::*;
+
+    fn build_frame(idx: usize, num_records: usize) -> Frame {
+        Frame::new(idx, vec![(0..num_records).map(|_| Element::None).collect()])
+    }
+
+    #[test]
+    fn test_links() {
+        let frames = vec![build_frame(0, 2), build_frame(1, 2), build_frame(2, 2)];
+        let link_a = ChainLink::new(0.9, vec![Some(0.9)], Some(0.2));
+        let link_b = ChainLink::new(0.7, vec![Some(0.7)], None);
+        let chains = vec![
+            TrackingChain::new(
+                0,
+                vec![
+                    ChainNode::new(0, 0),
+                    ChainNode::new(1, 0),
+                    ChainNode::new(2, 1),
+                ],
+                vec![None, Some(link_a.clone()), Some(link_b.clone())],
+            ),
+            TrackingChain::new(1, vec![ChainNode::new(1, 1)], vec![None]),
+        ];
+
+        let graph = TrackingGraph::from_tracking_chains(&frames, chains);
+
+        let links = graph.get_links_below(0.8);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].0, 0);
+        assert_eq!(links[0].1, ChainNode::new(2, 1));
+        assert_eq!(links[0].2, link_b);
+
+        let chain = graph.build_tracking_chain(0);
+        assert_eq!(chain.links, vec![None, Some(link_a), Some(link_b)]);
+    }
+
+    fn assert_consistent(graph: &TrackingGraph) {
+        for (id, first) in graph.root.outs.iter() {
+            let chain = graph.build_tracking_chain(*id);
+            assert_eq!(chain.nodes[0], *first);
+            for pair in chain.nodes.windows(2) {
+                let node = &graph.matrix[pair[1].frame_idx][pair[1].record_idx];
+                assert!(node.ins.contains(&(*id, pair[0])));
+            }
+        }
+    }
+
+    #[test]
+    fn test_edits() {
+        let frames = vec![build_frame(0, 2), build_frame(1, 2), build_frame(2, 2)];
+        let link = ChainLink::new(0.9, vec![Some(0.9)], None);
+        let chains = vec![
+            TrackingChain::new(
+                0,
+                vec![
+                    ChainNode::new(0, 0),
+                    ChainNode::new(1, 0),
+                    ChainNode::new(2, 0),
+                ],
+                vec![None, Some(link.clone()), Some(link.clone())],
+            ),
+            TrackingChain::new(1, vec![ChainNode::new(0, 1)], vec![None]),
+        ];
+        let mut graph = TrackingGraph::from_tracking_chains(&frames, chains.clone());
+
+        let new_id = graph.split_chain(0, 1).unwrap();
+        assert_eq!(graph.get_tracking_chain(0), vec![ChainNode::new(0, 0)]);
+        let chain = graph.build_tracking_chain(new_id);
+        assert_eq!(
+            chain.nodes,
+            vec![ChainNode::new(1, 0), ChainNode::new(2, 0)]
+        );
+        assert_eq!(chain.links, vec![None, Some(link.clone())]);
+
+        graph.merge_chains(0, new_id).unwrap();
+        let chain = graph.build_tracking_chain(0);
+        assert_eq!(chain.nodes.len(), 3);
+        // the link of the first node of the merged chain is lost
+        assert_eq!(chain.links, vec![None, None, Some(link.clone())]);
+
+        graph.reassign_node(0, 1, 1).unwrap();
+        assert_eq!(
+            graph.get_tracking_chain(1),
+            vec![ChainNode::new(0, 1), ChainNode::new(1, 0)]
+        );
+        assert!(graph.reassign_node(0, 0, 1).is_err());
+        assert!(graph.merge_chains(0, 1).is_err());
+
+        let detached_id = graph.detach_node(0, 2).unwrap();
+        assert_eq!(graph.get_tracking_chain(0), vec![ChainNode::new(0, 0)]);
+        assert!(graph.detach_node(0, 0).is_err());
+        assert_eq!(graph.edits.len(), 4);
+        assert_consistent(&graph);
+
+        // replaying the edits on the original graph gives the same graph
+        let mut replayed = TrackingGraph::from_tracking_chains(&frames, chains);
+        replayed.replay_edits(graph.edits.clone()).unwrap();
+        for id in [0, 1, new_id, detached_id] {
+            let expected = graph.build_tracking_chain(id);
+            let chain = replayed.build_tracking_chain(id);
+            assert_eq!(chain.nodes, expected.nodes);
+            assert_eq!(chain.links, expected.links);
+        }
+    }
+}
@@ -0,0 +1,574 @@
+use std::{collections::HashSet, fmt};
+
+use crate::error::{BlitzBeaverError, Result};
+
+use super::{
+    casting::{
+        DISTANCE_METRICS, ENGINE_MODES, ENGINE_WORKERS, MEDIAN_GRAPHEMES, MEDIAN_STRATEGIES,
+        MEMORY_STRATEGIES, METRIC_LV_EDIT, METRIC_LV_MULTIWORD, METRIC_LV_SUBSTRING,
+        MULTIWORD_MEMORY_STRATEGIES, RECORD_SCORERS, RESOLVING_MULTI_HYPOTHESIS,
+        RESOLVING_STRATEGIES, SCORER_AVERAGE, WEIGHTED_MEMORY_STRATEGIES,
+    },
+    DistanceMetricConfig, GeneratorConfig, MemoryConfig, NormalizationConfig, RecordSchema,
+    RecordScorerConfig, ResolverConfig, TrackerConfig, TrackingConfig,
+};
+
+/// ConfigPath
+///
+/// Path of an attribute in a configuration, e.g. `tracker.memories[3].multiword_distance_metric`,
+/// used to locate the validation errors.
+#[derive(Debug, Clone, Default)]
+struct ConfigPath(String);
+
+impl ConfigPath {
+    fn field(&self, name: &str) -> Self {
+        if self.0.is_empty() {
+            Self(name.to_string())
+        } else {
+            Self(format!("{}.{}", self.0, name))
+        }
+    }
+
+    fn index(&self, idx: usize) -> Self {
+        Self(format!("{}[{}]", self.0, idx))
+    }
+
+    fn error(&self, message: impl fmt::Display) -> BlitzBeaverError {
+        BlitzBeaverError::InvalidConfig(format!("{}: {}", self.0, message))
+    }
+
+    /// Returns the value of an optional attribute.
+    ///
+    /// # Errors
+    /// Returns an error if the attribute is missing, `reason` tells why it is required.
+    fn required<'a, T>(&self, value: &'a Option<T>, reason: impl fmt::Display) -> Result<&'a T> {
+        value
+            .as_ref()
+            .ok_or_else(|| self.error(format_args!("missing attribute, required by {}", reason)))
+    }
+
+    fn check_one_of(&self, value: &str, values: &[&str]) -> Result<()> {
+        if values.contains(&value) {
+            return Ok(());
+        }
+        Err(self.error(format_args!(
+            "invalid value \"{}\", expected one of {}",
+            value,
+            values.join(", ")
+        )))
+    }
+
+    /// Checks that the median strategy, if any, is the graphemes one,
+    /// the only one in which the words vote with their weight.
+    fn check_weighted_median_strategy(
+        &self,
+        median_strategy: &Option<String>,
+        reason: impl fmt::Display,
+    ) -> Result<()> {
+        match median_strategy.as_deref() {
+            None | Some(MEDIAN_GRAPHEMES) => Ok(()),
+            Some(value) => Err(self.error(format_args!(
+                "invalid value \"{}\", only {} is supported by {}",
+                value, MEDIAN_GRAPHEMES, reason
+            ))),
+        }
+    }
+
+    fn check_ratio(&self, value: f32) -> Result<()> {
+        if !(0.0..=1.0).contains(&value) {
+            return Err(self.error(format_args!("{} is not between 0 and 1", value)));
+        }
+        Ok(())
+    }
+
+    fn check_length<T>(&self, values: &[T], expected: usize, unit: &str) -> Result<()> {
+        if values.len() != expected {
+            return Err(self.error(format_args!(
+                "expected {} values, {}, got {}",
+                expected,
+                unit,
+                values.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Validates the record schema: it must have at least one field and
+/// the names of the fields must be unique.
+///
+/// # Errors
+/// Returns an InvalidConfig error naming the path of the invalid attribute.
+pub fn validate_record_schema(record_schema: &RecordSchema) -> Result<()> {
+    let path = ConfigPath::default().field("fields");
+    if record_schema.fields.is_empty() {
+        return Err(path.error("at least one field is required"));
+    }
+
+    let mut names = HashSet::new();
+    for (i, field_schema) in record_schema.fields.iter().enumerate() {
+        if !names.insert(field_schema.name.as_str()) {
+            return Err(path
+                .index(i)
+                .field("name")
+                .error(format_args!("duplicated field \"{}\"", field_schema.name)));
+        }
+    }
+    Ok(())
+}
+
+/// Validates the tracking configuration against the record schema,
+/// such that the tracking process doesn't fail on the configuration.
+///
+/// # Errors
+/// Returns an InvalidConfig error naming the path of the invalid attribute.
+pub fn validate_tracking_config(
+    tracking_config: &TrackingConfig,
+    record_schema: &RecordSchema,
+) -> Result<()> {
+    let path = ConfigPath::default();
+
+    let engine_mode = tracking_config
+        .engine_mode
+        .as_deref()
+        .unwrap_or(ENGINE_WORKERS);
+    path.field("engine_mode")
+        .check_one_of(engine_mode, &ENGINE_MODES)?;
+    // in the workers mode, one thread runs the engine, the others the workers
+    if engine_mode == ENGINE_WORKERS && tracking_config.num_threads < 2 {
+        return Err(path
+            .field("num_threads")
+            .error("must be at least 2 in the \"workers\" engine mode"));
+    }
+    if tracking_config.num_threads < 1 {
+        return Err(path.field("num_threads").error("must be at least 1"));
+    }
+    if tracking_config.chunk_size == Some(0) {
+        return Err(path.field("chunk_size").error("must be at least 1"));
+    }
+    validate_tracker_config(
+        &tracking_config.tracker,
+        record_schema,
+        &path.field("tracker"),
+    )?;
+    validate_distance_metric_config(
+        &tracking_config.distance_metric,
+        &path.field("distance_metric"),
+    )?;
+    validate_resolver_config(&tracking_config.resolver, &path.field("resolver"))
+}
+
+/// Validates the normalization configuration.
+///
+/// # Errors
+/// Returns an InvalidConfig error naming the path of the invalid attribute.
+pub fn validate_normalization_config(normalization_config: &NormalizationConfig) -> Result<()> {
+    let path = ConfigPath::default();
+
+    path.field("threshold_cluster_match")
+        .check_ratio(normalization_config.threshold_cluster_match)?;
+    if let Some(median_strategy) = &normalization_config.median_strategy {
+        path.field("median_strategy")
+            .check_one_of(median_strategy, &MEDIAN_STRATEGIES)?;
+    }
+    if normalization_config.weighted_median == Some(true) {
+        path.field("median_strategy")
+            .check_weighted_median_strategy(
+                &normalization_config.median_strategy,
+                "the weighted median",
+            )?;
+    }
+    validate_distance_metric_config(
+        &normalization_config.distance_metric,
+        &path.field("distance_metric"),
+    )
+}
+
+/// Validates the configuration of the census generator.
+///
+/// # Errors
+/// Returns an InvalidConfig error naming the path of the invalid attribute.
+pub fn validate_generator_config(generator_config: &GeneratorConfig) -> Result<()> {
+    let path = ConfigPath::default();
+
+    if generator_config.num_frames == 0 {
+        return Err(path.field("num_frames").error("must be at least 1"));
+    }
+    if generator_config.num_households == 0 {
+        return Err(path.field("num_households").error("must be at least 1"));
+    }
+
+    let events = &generator_config.events;
+    let events_path = path.field("events");
+    for (name, rate) in [
+        ("birth_rate", events.birth_rate),
+        ("death_rate", events.death_rate),
+        ("marriage_rate", events.marriage_rate),
+        ("move_rate", events.move_rate),
+        ("occupation_change_rate", events.occupation_change_rate),
+        ("leave_home_rate", events.leave_home_rate),
+        ("immigration_rate", events.immigration_rate),
+    ] {
+        events_path.field(name).check_ratio(rate)?;
+    }
+
+    let noise = &generator_config.noise;
+    let noise_path = path.field("noise");
+    for (name, rate) in [
+        ("ocr_confusion_rate", noise.ocr_confusion_rate),
+        ("letter_drop_rate", noise.letter_drop_rate),
+        ("missing_field_rate", noise.missing_field_rate),
+    ] {
+        noise_path.field(name).check_ratio(rate)?;
+    }
+    Ok(())
+}
+
+fn validate_tracker_config(
+    tracker_config: &TrackerConfig,
+    record_schema: &RecordSchema,
+    path: &ConfigPath,
+) -> Result<()> {
+    path.field("interest_threshold")
+        .check_ratio(tracker_config.interest_threshold)?;
+
+    let memories_path = path.field("memories");
+    memories_path.check_length(
+        &tracker_config.memories,
+        record_schema.fields.len(),
+        "one per field",
+    )?;
+    for (i, memory_config) in tracker_config.memories.iter().enumerate() {
+        validate_memory_config(memory_config, &memories_path.index(i))?;
+    }
+
+    validate_record_scorer_config(
+        &tracker_config.record_scorer,
+        record_schema,
+        &path.field("record_scorer"),
+    )
+}
+
+fn validate_memory_config(memory_config: &MemoryConfig, path: &ConfigPath) -> Result<()> {
+    let strategy = memory_config.memory_strategy.as_str();
+    path.field("memory_strategy")
+        .check_one_of(strategy, &MEMORY_STRATEGIES)?;
+    if let Some(median_strategy) = &memory_config.median_strategy {
+        path.field("median_strategy")
+            .check_one_of(median_strategy, &MEDIAN_STRATEGIES)?;
+    }
+    if WEIGHTED_MEMORY_STRATEGIES.contains(&strategy) {
+        path.field("median_strategy")
+            .check_weighted_median_strategy(
+                &memory_config.median_strategy,
+                format_args!("the \"{}\" memory strategy", strategy),
+            )?;
+    }
+    if let Some(recency_decay) = memory_config.recency_decay {
+        path.field("recency_decay").check_ratio(recency_decay)?;
+    }
+
+    if MULTIWORD_MEMORY_STRATEGIES.contains(&strategy) {
+        let reason = format!("the \"{}\" memory strategy", strategy);
+        let threshold_path = path.field("multiword_threshold_match");
+        threshold_path.check_ratio(
+            *threshold_path.required(&memory_config.multiword_threshold_match, &reason)?,
+        )?;
+
+        let distance_metric_path = path.field("multiword_distance_metric");
+        validate_distance_metric_config(
+            distance_metric_path.required(&memory_config.multiword_distance_metric, &reason)?,
+            &distance_metric_path,
+        )?;
+    }
+    Ok(())
+}
+
+fn validate_distance_metric_config(
+    distance_metric_config: &DistanceMetricConfig,
+    path: &ConfigPath,
+) -> Result<()> {
+    let metric = distance_metric_config.metric.as_str();
+    path.field("metric")
+        .check_one_of(metric, &DISTANCE_METRICS)?;
+
+    let reason = format!("the \"{}\" metric", metric);
+    match metric {
+        METRIC_LV_EDIT => {
+            let weights_path = path.field("lv_edit_weights");
+            let weights =
+                weights_path.required(&distance_metric_config.lv_edit_weights, &reason)?;
+            weights_path.check_length(
+                weights,
+                3,
+                "the deletion, insertion and substitution weights",
+            )?;
+        }
+        METRIC_LV_SUBSTRING => {
+            path.field("lv_substring_weight")
+                .required(&distance_metric_config.lv_substring_weight, &reason)?;
+        }
+        METRIC_LV_MULTIWORD => {
+            path.field("lv_multiword_separator")
+                .required(&distance_metric_config.lv_multiword_separator, &reason)?;
+        }
+        _ => {}
+    }
+    if distance_metric_config.persistent_cache_bytes == Some(0) {
+        return Err(path
+            .field("persistent_cache_bytes")
+            .error("must be at least 1"));
+    }
+    Ok(())
+}
+
+fn validate_resolver_config(resolver_config: &ResolverConfig, path: &ConfigPath) -> Result<()> {
+    let strategy = resolver_config.resolving_strategy.as_str();
+    path.field("resolving_strategy")
+        .check_one_of(strategy, &RESOLVING_STRATEGIES)?;
+
+    if strategy == RESOLVING_MULTI_HYPOTHESIS {
+        let reason = format!("the \"{}\" resolving strategy", strategy);
+        for (attribute, value) in [
+            ("num_hypotheses", &resolver_config.num_hypotheses),
+            ("hypotheses_depth", &resolver_config.hypotheses_depth),
+        ] {
+            let attribute_path = path.field(attribute);
+            if *attribute_path.required(value, &reason)? == 0 {
+                return Err(attribute_path.error("must be at least 1"));
+            }
+        }
+    }
+    if let Some(margin) = resolver_config.ambiguity_margin {
+        if margin < 0.0 {
+            return Err(path.field("ambiguity_margin").error("must be positive"));
+        }
+    }
+    Ok(())
+}
+
+fn validate_record_scorer_config(
+    record_scorer_config: &RecordScorerConfig,
+    record_schema: &RecordSchema,
+    path: &ConfigPath,
+) -> Result<()> {
+    let num_fields = record_schema.fields.len();
+    let record_scorer = record_scorer_config.record_scorer.as_str();
+    path.field("record_scorer")
+        .check_one_of(record_scorer, &RECORD_SCORERS)?;
+
+    if record_scorer != SCORER_AVERAGE {
+        let reason = format!("the \"{}\" record scorer", record_scorer);
+        path.field("weights")
+            .required(&record_scorer_config.weights, &reason)?;
+        path.field("min_weight_ratio")
+            .required(&record_scorer_config.min_weight_ratio, &reason)?;
+    }
+    if let Some(weights) = &record_scorer_config.weights {
+        path.field("weights")
+            .check_length(weights, num_fields, "one per field")?;
+    }
+
+    for (attribute, values) in [
+        (
+            "missing_record_distances",
+            &record_scorer_config.missing_record_distances,
+        ),
+        (
+            "missing_memory_distances",
+            &record_scorer_config.missing_memory_distances,
+        ),
+        (
+            "missing_both_distances",
+            &record_scorer_config.missing_both_distances,
+        ),
+    ] {
+        if let Some(values) = values {
+            path.field(attribute)
+                .check_length(values, num_fields, "one per field")?;
+        }
+    }
+
+    let features_path = path.field("composite_features");
+    for (i, composite_feature_config) in record_scorer_config
+        .composite_features
+        .iter()
+        .flatten()
+        .enumerate()
+    {
+        let fields_path = features_path.index(i).field("fields");
+        for (j, field) in composite_feature_config.fields.iter().enumerate() {
+            if !record_schema.fields.iter().any(|f| f.name == *field) {
+                return Err(fields_path
+                    .index(j)
+                    .error(format_args!("unknown field \"{}\"", field)));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{ElementType, FieldSchema};
+
+    use super::*;
+
+    fn build_distance_metric_config(metric: &str) -> DistanceMetricConfig {
+        DistanceMetricConfig {
+            metric: metric.to_string(),
+            caching_threshold: 4,
+            use_sigmoid: false,
+            lv_edit_weights: None,
+            lv_substring_weight: None,
+            lv_multiword_separator: None,
+            persistent_cache_bytes: None,
+        }
+    }
+
+    fn build_memory_config(memory_strategy: &str) -> MemoryConfig {
+        MemoryConfig {
+            memory_strategy: memory_strategy.to_string(),
+            multiword_threshold_match: Some(0.6),
+            multiword_distance_metric: None,
+            median_strategy: None,
+            recency_decay: None,
+        }
+    }
+
+    fn assert_invalid(result: Result<()>, expected: &str) {
+        match result {
+            Err(BlitzBeaverError::InvalidConfig(e)) => assert_eq!(e, expected),
+            r => panic!("expected an invalid config error, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_validate_tracking_config() {
+        let record_schema = RecordSchema::new(vec![
+            FieldSchema::new("name".to_string(), ElementType::String),
+            FieldSchema::new("firstnames".to_string(), ElementType::MultiStrings),
+        ]);
+        let mut tracking_config = TrackingConfig {
+            num_threads: 2,
+            tracker: TrackerConfig {
+                interest_threshold: 0.6,
+                limit_no_match_streak: 3,
+                memories: vec![build_memory_config("median"), build_memory_config("median")],
+                record_scorer: RecordScorerConfig {
+                    record_scorer: "average".to_string(),
+                    weights: None,
+                    min_weight_ratio: None,
+                    missing_record_distances: None,
+                    missing_memory_distances: None,
+                    missing_both_distances: None,
+                    composite_features: None,
+                },
+            },
+            distance_metric: build_distance_metric_config("lv_opti"),
+            resolver: ResolverConfig {
+                resolving_strategy: "best-match".to_string(),
+                num_hypotheses: None,
+                hypotheses_depth: None,
+                ambiguity_margin: None,
+            },
+            engine_mode: None,
+            chunk_size: None,
+        };
+        assert!(validate_tracking_config(&tracking_config, &record_schema).is_ok());
+
+        tracking_config.num_threads = 1;
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "num_threads: must be at least 2 in the \"workers\" engine mode",
+        );
+
+        tracking_config.engine_mode = Some("work-stealing".to_string());
+        assert!(validate_tracking_config(&tracking_config, &record_schema).is_ok());
+
+        tracking_config.chunk_size = Some(0);
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "chunk_size: must be at least 1",
+        );
+
+        tracking_config.num_threads = 2;
+        tracking_config.engine_mode = None;
+        tracking_config.chunk_size = None;
+
+        tracking_config.tracker.memories[0].median_strategy = Some("mean".to_string());
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "tracker.memories[0].median_strategy: invalid value \"mean\", \
+            expected one of graphemes, set-median, refined-median",
+        );
+        tracking_config.tracker.memories[0].median_strategy = Some("set-median".to_string());
+        assert!(validate_tracking_config(&tracking_config, &record_schema).is_ok());
+
+        tracking_config.tracker.memories[0].memory_strategy = "weighted-median".to_string();
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "tracker.memories[0].median_strategy: invalid value \"set-median\", \
+            only graphemes is supported by the \"weighted-median\" memory strategy",
+        );
+        tracking_config.tracker.memories[0].median_strategy = None;
+        tracking_config.tracker.memories[0].recency_decay = Some(1.5);
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "tracker.memories[0].recency_decay: 1.5 is not between 0 and 1",
+        );
+        tracking_config.tracker.memories[0].recency_decay = Some(0.8);
+        assert!(validate_tracking_config(&tracking_config, &record_schema).is_ok());
+
+        tracking_config.tracker.memories[1] = build_memory_config("mw-median");
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "tracker.memories[1].multiword_distance_metric: missing attribute, \
+            required by the \"mw-median\" memory strategy",
+        );
+
+        tracking_config.tracker.memories[1].multiword_distance_metric =
+            Some(build_distance_metric_config("lv_edit"));
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "tracker.memories[1].multiword_distance_metric.lv_edit_weights: missing attribute, \
+            required by the \"lv_edit\" metric",
+        );
+
+        tracking_config.tracker.memories.pop();
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "tracker.memories: expected 2 values, one per field, got 1",
+        );
+
+        tracking_config
+            .tracker
+            .memories
+            .push(build_memory_config("median"));
+        tracking_config.tracker.record_scorer.weights = Some(vec![1.0]);
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "tracker.record_scorer.weights: expected 2 values, one per field, got 1",
+        );
+
+        tracking_config.tracker.record_scorer.weights = None;
+        tracking_config.resolver.resolving_strategy = "greedy".to_string();
+        assert_invalid(
+            validate_tracking_config(&tracking_config, &record_schema),
+            "resolver.resolving_strategy: invalid value \"greedy\", \
+            expected one of simple, best-match, multi-hypothesis",
+        );
+    }
+
+    #[test]
+    fn test_validate_record_schema() {
+        let record_schema = RecordSchema::new(vec![
+            FieldSchema::new("name".to_string(), ElementType::String),
+            FieldSchema::new("name".to_string(), ElementType::String),
+        ]);
+        assert_invalid(
+            validate_record_schema(&record_schema),
+            "fields[1].name: duplicated field \"name\"",
+        );
+    }
+}
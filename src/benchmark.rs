@@ -1,2 +1,21 @@
-#[cfg(feature = "benchmark")]
-pub mod distance;
+mod harness;
+mod workloads;
+
+pub use harness::{measure, Benchmark, BenchmarkOptions, BenchmarkResult};
+pub use workloads::{benchmarks, BenchmarkData};
+
+/// Runs the benchmarks of the suite whose name contains the filter (all if None).
+pub fn run_benchmark_suite(
+    data: &BenchmarkData,
+    options: &BenchmarkOptions,
+    filter: Option<&str>,
+) -> Vec<BenchmarkResult> {
+    benchmarks(data)
+        .into_iter()
+        .filter(|benchmark| filter.is_none_or(|filter| benchmark.name.contains(filter)))
+        .map(|mut benchmark| {
+            log::info!("running benchmark {}...", benchmark.name);
+            measure(&mut benchmark, options)
+        })
+        .collect()
+}
@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+/// Benchmark
+///
+/// Named routine to measure, the setup is done when the benchmark is built
+/// such that only the routine is measured.
+pub struct Benchmark<'a> {
+    pub name: String,
+    routine: Box<dyn FnMut() + 'a>,
+}
+
+impl<'a> Benchmark<'a> {
+    pub fn new(name: impl Into<String>, routine: impl FnMut() + 'a) -> Self {
+        Self {
+            name: name.into(),
+            routine: Box::new(routine),
+        }
+    }
+
+    /// Runs the routine once.
+    pub fn run(&mut self) {
+        (self.routine)();
+    }
+}
+
+/// BenchmarkOptions
+///
+/// The routine is first run for `warm_up_time`, which also estimates its duration,
+/// then `num_samples` samples are measured, each sample runs the routine
+/// enough times for all the samples to take about `measurement_time`.
+#[derive(Debug, Clone)]
+pub struct BenchmarkOptions {
+    pub num_samples: usize,
+    pub warm_up_time: Duration,
+    pub measurement_time: Duration,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self {
+            num_samples: 20,
+            warm_up_time: Duration::from_millis(500),
+            measurement_time: Duration::from_secs(2),
+        }
+    }
+}
+
+/// BenchmarkResult
+///
+/// Statistics of the duration of one run of the routine, in nanoseconds.
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub name: String,
+    pub num_samples: usize,
+    pub iterations_per_sample: u64,
+    pub mean_ns: f64,
+    pub median_ns: f64,
+    pub std_dev_ns: f64,
+    pub min_ns: f64,
+    pub max_ns: f64,
+}
+
+/// Measures the duration of the routine of the benchmark.
+pub fn measure(benchmark: &mut Benchmark, options: &BenchmarkOptions) -> BenchmarkResult {
+    let start = Instant::now();
+    let mut warm_up_iterations = 0u64;
+    while warm_up_iterations == 0 || start.elapsed() < options.warm_up_time {
+        benchmark.run();
+        warm_up_iterations += 1;
+    }
+    let estimate_ns = start.elapsed().as_nanos() as f64 / warm_up_iterations as f64;
+
+    let num_samples = options.num_samples.max(1);
+    let sample_ns = options.measurement_time.as_nanos() as f64 / num_samples as f64;
+    let iterations_per_sample = ((sample_ns / estimate_ns.max(1.0)) as u64).max(1);
+
+    let mut samples = Vec::with_capacity(num_samples);
+    for _ in 0..num_samples {
+        let start = Instant::now();
+        for _ in 0..iterations_per_sample {
+            benchmark.run();
+        }
+        samples.push(start.elapsed().as_nanos() as f64 / iterations_per_sample as f64);
+    }
+    samples.sort_by(|a, b| a.total_cmp(b));
+
+    let mean_ns = samples.iter().sum::<f64>() / num_samples as f64;
+    let variance = samples.iter().map(|s| (s - mean_ns).powi(2)).sum::<f64>() / num_samples as f64;
+    let median_ns = if num_samples.is_multiple_of(2) {
+        (samples[num_samples / 2 - 1] + samples[num_samples / 2]) / 2.0
+    } else {
+        samples[num_samples / 2]
+    };
+
+    BenchmarkResult {
+        name: benchmark.name.clone(),
+        num_samples,
+        iterations_per_sample,
+        mean_ns,
+        median_ns,
+        std_dev_ns: variance.sqrt(),
+        min_ns: samples[0],
+        max_ns: samples[num_samples - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let mut count = 0;
+        let mut benchmark = Benchmark::new("count", || count += 1);
+        let options = BenchmarkOptions {
+            num_samples: 5,
+            warm_up_time: Duration::from_millis(1),
+            measurement_time: Duration::from_millis(5),
+        };
+        let result = measure(&mut benchmark, &options);
+        drop(benchmark);
+
+        assert_eq!(result.name, "count");
+        assert_eq!(result.num_samples, 5);
+        assert!(count as u64 >= 5 * result.iterations_per_sample);
+        assert!(result.min_ns <= result.median_ns && result.median_ns <= result.max_ns);
+        assert!(result.min_ns <= result.mean_ns && result.mean_ns <= result.max_ns);
+    }
+}
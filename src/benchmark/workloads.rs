@@ -0,0 +1,252 @@
+use std::{hint::black_box, sync::Arc};
+
+use crate::{
+    api::{
+        build_tracking_engine, cast_generator_config, census_record_schema, run_tracking_engine,
+        DistanceMetricConfig, GeneratorConfig, GeneratorEventsConfig, GeneratorNoiseConfig,
+        MemoryConfig, RecordSchema, RecordScorerConfig, ResolverConfig, TrackerConfig,
+        TrackingConfig,
+    },
+    distances::{compute_median_word, CachedDistanceCalculator, InternalDistanceMetricConfig},
+    engine::FrameStore,
+    error::Result,
+    frame::{Element, Frame},
+    generator,
+    normalization::compute_words_clusters,
+    resolvers::ResolvingConstraints,
+    word::Word,
+};
+
+use super::harness::Benchmark;
+
+/// Number of words of the distance metrics benchmarks, all the pairs are computed.
+const NUM_DISTANCE_WORDS: usize = 100;
+/// Number of words clustered by the UPGMA benchmark.
+const NUM_CLUSTERING_WORDS: usize = 200;
+/// Number of words of each median word computation.
+const MEDIAN_GROUP_SIZE: usize = 10;
+
+/// Returns the distance metrics benchmarked, with their names.
+fn distance_metrics() -> Vec<(&'static str, InternalDistanceMetricConfig)> {
+    vec![
+        ("lv", InternalDistanceMetricConfig::Lv(false)),
+        ("lv_opti", InternalDistanceMetricConfig::LvOpti(false)),
+        (
+            "lv_bitparallel",
+            InternalDistanceMetricConfig::LvBitParallel(false),
+        ),
+        (
+            "lv_edit",
+            InternalDistanceMetricConfig::LvEdit(1.0, 1.0, 1.0, false),
+        ),
+        (
+            "lv_substring",
+            InternalDistanceMetricConfig::LvSubstring(0.5, false),
+        ),
+        (
+            "lv_multiword",
+            InternalDistanceMetricConfig::LvMultiWord(Word::string_to_grapheme(" "), false),
+        ),
+    ]
+}
+
+/// BenchmarkData
+///
+/// Synthetic census the benchmarks run on, generated once.
+pub struct BenchmarkData {
+    metrics: Vec<(&'static str, InternalDistanceMetricConfig)>,
+    record_schema: RecordSchema,
+    frames: Arc<Vec<Frame>>,
+    words: Vec<Word>,
+}
+
+impl BenchmarkData {
+    /// Generates a census of `num_households` households over `num_frames` frames
+    /// (at least 2), with the default events and noise.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn generate(num_households: usize, num_frames: usize, seed: u64) -> Result<Self> {
+        let (rates, noise) = cast_generator_config(&GeneratorConfig {
+            num_frames: num_frames.max(2),
+            num_households,
+            seed,
+            events: GeneratorEventsConfig::default(),
+            noise: GeneratorNoiseConfig::default(),
+        });
+        let synthetic =
+            generator::generate_frames(num_frames.max(2), num_households, rates, &noise, seed)?;
+
+        // firstnames and lastnames of the first frame
+        let words = [1, 2]
+            .iter()
+            .flat_map(|&feature_idx| synthetic.frames[0].column(feature_idx).iter())
+            .filter_map(|element| element.as_word().cloned())
+            .collect();
+
+        Ok(Self {
+            metrics: distance_metrics(),
+            record_schema: census_record_schema(),
+            frames: Arc::new(synthetic.frames),
+            words,
+        })
+    }
+
+    fn column(&self, frame_idx: usize, feature_idx: usize) -> Vec<&Element> {
+        self.frames[frame_idx].column(feature_idx).iter().collect()
+    }
+}
+
+/// Distances between all the pairs of the first words.
+fn bench_distance_metric<'a>(
+    data: &'a BenchmarkData,
+    name: &str,
+    config: &InternalDistanceMetricConfig,
+) -> Benchmark<'a> {
+    let words = &data.words[..NUM_DISTANCE_WORDS.min(data.words.len())];
+    let mut metric = config.make_metric();
+    Benchmark::new(format!("distance/{}", name), move || {
+        let mut total = 0.0;
+        for w1 in words.iter() {
+            for w2 in words.iter() {
+                total += metric.dist(w1, w2);
+            }
+        }
+        black_box(total);
+    })
+}
+
+/// Precomputation of the cache between the lastnames of the first two frames.
+fn bench_precompute<'a>(
+    data: &'a BenchmarkData,
+    name: &str,
+    config: &'a InternalDistanceMetricConfig,
+) -> Benchmark<'a> {
+    let serie1 = data.column(0, 2);
+    let serie2 = data.column(1, 2);
+    Benchmark::new(format!("precompute/{}", name), move || {
+        let mut distance_calculator = CachedDistanceCalculator::new(config.make_metric(), 2);
+        distance_calculator.precompute(&serie1, &serie2);
+        black_box(distance_calculator.cache_size());
+    })
+}
+
+/// Median words of groups of words.
+fn bench_median_word(data: &BenchmarkData) -> Benchmark<'_> {
+    let groups = data
+        .words
+        .chunks(MEDIAN_GROUP_SIZE)
+        .map(|chunk| chunk.iter().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    Benchmark::new("median_word", move || {
+        for group in groups.iter() {
+            black_box(compute_median_word(group).unwrap());
+        }
+    })
+}
+
+/// UPGMA clustering of the first words.
+fn bench_words_clustering(data: &BenchmarkData) -> Benchmark<'_> {
+    let words = data.words[..NUM_CLUSTERING_WORDS.min(data.words.len())]
+        .iter()
+        .collect::<Vec<_>>();
+    let config = InternalDistanceMetricConfig::LvOpti(false);
+    Benchmark::new("clustering/upgma", move || {
+        let mut distance_calculator = CachedDistanceCalculator::new(config.make_metric(), 2);
+        black_box(compute_words_clusters(
+            &mut distance_calculator,
+            words.clone(),
+            0.8,
+        ));
+    })
+}
+
+fn build_tracking_config(num_threads: usize, engine_mode: &str) -> TrackingConfig {
+    let distance_metric = DistanceMetricConfig {
+        metric: "lv_opti".to_string(),
+        caching_threshold: 4,
+        use_sigmoid: false,
+        lv_edit_weights: None,
+        lv_substring_weight: None,
+        lv_multiword_separator: None,
+        persistent_cache_bytes: None,
+    };
+    let memory = |memory_strategy: &str| MemoryConfig {
+        memory_strategy: memory_strategy.to_string(),
+        multiword_threshold_match: Some(0.8),
+        multiword_distance_metric: Some(distance_metric.clone()),
+        median_strategy: None,
+        recency_decay: None,
+    };
+    TrackingConfig {
+        num_threads,
+        tracker: TrackerConfig {
+            interest_threshold: 0.6,
+            limit_no_match_streak: 3,
+            memories: vec![
+                memory("median"),
+                memory("median"),
+                memory("median"),
+                memory("median"),
+                memory("median"),
+                memory("mw-median"),
+            ],
+            record_scorer: RecordScorerConfig {
+                record_scorer: "average".to_string(),
+                weights: None,
+                min_weight_ratio: None,
+                missing_record_distances: None,
+                missing_memory_distances: None,
+                missing_both_distances: None,
+                composite_features: None,
+            },
+        },
+        distance_metric: distance_metric.clone(),
+        resolver: ResolverConfig {
+            resolving_strategy: "best-match".to_string(),
+            num_hypotheses: None,
+            hypotheses_depth: None,
+            ambiguity_margin: None,
+        },
+        engine_mode: Some(engine_mode.to_string()),
+        chunk_size: None,
+    }
+}
+
+/// Full run of the tracking engine on all the frames.
+fn bench_tracking_engine<'a>(
+    data: &'a BenchmarkData,
+    num_threads: usize,
+    engine_mode: &str,
+) -> Benchmark<'a> {
+    let tracking_config = build_tracking_config(num_threads, engine_mode);
+    let name = format!("engine/{}/{}-threads", engine_mode, num_threads);
+    Benchmark::new(name, move || {
+        let tracking_engine = build_tracking_engine(
+            &tracking_config,
+            &data.record_schema,
+            FrameStore::in_memory(Arc::clone(&data.frames)),
+            ResolvingConstraints::default(),
+        )
+        .unwrap();
+        black_box(run_tracking_engine(tracking_engine).unwrap());
+    })
+}
+
+/// Returns all the benchmarks of the suite: every distance metric, the precomputation
+/// of the cache, the median word, the UPGMA clustering and a full run of the engine
+/// in each mode.
+pub fn benchmarks(data: &BenchmarkData) -> Vec<Benchmark<'_>> {
+    let mut benchmarks = Vec::new();
+    for (name, config) in data.metrics.iter() {
+        benchmarks.push(bench_distance_metric(data, name, config));
+    }
+    for (name, config) in data.metrics.iter() {
+        benchmarks.push(bench_precompute(data, name, config));
+    }
+    benchmarks.push(bench_median_word(data));
+    benchmarks.push(bench_words_clustering(data));
+    benchmarks.push(bench_tracking_engine(data, 4, "workers"));
+    benchmarks.push(bench_tracking_engine(data, 4, "work-stealing"));
+    benchmarks
+}
@@ -0,0 +1,460 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::Parser;
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    api::{
+        self, BeaverFile, ConfigFormat, Diagnostics, ElementType, NormalizationConfig,
+        RecordSchema, TrackingConfig, TrackingGraph,
+    },
+    error::BlitzBeaverError,
+    logger,
+};
+
+/// Command line arguments
+#[derive(Parser)]
+#[command(
+    name = "blitzbeaver",
+    version,
+    about = "Runs the tracking process on a set of frames"
+)]
+struct Cli {
+    /// Directory containing one Parquet or CSV file per frame,
+    /// the frames are ordered by file name
+    #[arg(long)]
+    input: PathBuf,
+    /// Configuration file (TOML or JSON) holding the record schema, the tracking
+    /// configuration and optionally the normalization configuration
+    #[arg(long)]
+    config: PathBuf,
+    /// Path of the .beaver file to write
+    #[arg(long)]
+    output: PathBuf,
+    /// Directory where to write the normalized frames as Parquet files,
+    /// requires a normalization configuration
+    #[arg(long)]
+    normalized_output: Option<PathBuf>,
+    /// Log level: trace, debug, info, warn or error
+    #[arg(long, default_value = "info")]
+    log_level: String,
+    /// Out-of-core mode, for datasets that don't fit in memory: the frames are
+    /// read on demand and the dead trackers are spilled to temporary files
+    #[arg(long)]
+    out_of_core: bool,
+    /// Number of frames kept in memory in the out-of-core mode
+    #[arg(long, default_value_t = 2)]
+    frame_window: usize,
+    /// Directory of the temporary files of the out-of-core mode,
+    /// defaults to the temporary directory of the system
+    #[arg(long)]
+    spill_dir: Option<PathBuf>,
+}
+
+/// CliConfig
+///
+/// Content of the configuration file, the tracking and normalization
+/// configurations have the same structure as the ones serialized
+/// by the python package.
+#[derive(Deserialize)]
+pub struct CliConfig {
+    pub schema: RecordSchema,
+    pub tracking: TrackingConfig,
+    pub normalization: Option<NormalizationConfig>,
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    Io(PathBuf, std::io::Error),
+    Polars(PolarsError),
+    Config(String),
+    Process(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Self::Polars(e) => write!(f, "{}", e),
+            Self::Config(e) => write!(f, "invalid configuration: {}", e),
+            Self::Process(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<PolarsError> for CliError {
+    fn from(e: PolarsError) -> Self {
+        Self::Polars(e)
+    }
+}
+
+impl From<BlitzBeaverError> for CliError {
+    fn from(e: BlitzBeaverError) -> Self {
+        match e {
+            BlitzBeaverError::InvalidConfig(e) => Self::Config(e),
+            BlitzBeaverError::Polars(e) => Self::Polars(e),
+            e => Self::Process(e.to_string()),
+        }
+    }
+}
+
+/// Entry point of the command line binary.
+pub fn main() -> ExitCode {
+    let cli = Cli::parse();
+    logger::initialize_logger(&cli.log_level);
+    execute(&cli)
+}
+
+/// Runs the command, the errors are logged and turned into a failure exit code.
+fn execute(cli: &Cli) -> ExitCode {
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            log::error!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), CliError> {
+    let config = read_config(&cli.config)?;
+    if cli.normalized_output.is_some() && config.normalization.is_none() {
+        return Err(CliError::Config(
+            "a normalization configuration is required to write normalized frames".to_string(),
+        ));
+    }
+    if cli.out_of_core {
+        return run_out_of_core(cli, &config);
+    }
+
+    let paths = list_frame_files(&cli.input)?;
+    let mut dataframes = Vec::new();
+    for (i, path) in paths.iter().enumerate() {
+        log::info!("reading frame {} from {}", i, path.display());
+        dataframes.push(read_dataframe(path, &config.schema)?);
+    }
+
+    log::info!("tracking {} frames...", dataframes.len());
+    let (tracking_graph, diagnostics) =
+        api::execute_tracking(&config.tracking, &config.schema, &dataframes, &[], &[])?;
+
+    write_beaver_file(&cli.output, tracking_graph.clone(), diagnostics)?;
+
+    if let (Some(output_dir), Some(normalization_config)) =
+        (&cli.normalized_output, &config.normalization)
+    {
+        log::info!("normalizing {} frames...", dataframes.len());
+        let normalized_dataframes = api::execute_normalization(
+            normalization_config,
+            &config.schema,
+            &tracking_graph,
+            &dataframes,
+        )?;
+
+        fs::create_dir_all(output_dir).map_err(|e| CliError::Io(output_dir.clone(), e))?;
+        for (path, mut dataframe) in paths.iter().zip(normalized_dataframes) {
+            let output_path = output_dir
+                .join(path.file_stem().unwrap_or_default())
+                .with_extension("parquet");
+            let file =
+                File::create(&output_path).map_err(|e| CliError::Io(output_path.clone(), e))?;
+            ParquetWriter::new(file).finish(&mut dataframe)?;
+        }
+        log::info!("normalized frames written to {}", output_dir.display());
+    }
+
+    Ok(())
+}
+
+/// Runs the tracking process out-of-core, the frames are read when the
+/// engine reaches them.
+///
+/// The normalization needs all the frames at once, it is not supported in this mode.
+fn run_out_of_core(cli: &Cli, config: &CliConfig) -> Result<(), CliError> {
+    if cli.normalized_output.is_some() {
+        return Err(CliError::Config(
+            "normalized frames can't be written in the out-of-core mode".to_string(),
+        ));
+    }
+
+    let paths = list_frame_files(&cli.input)?;
+    let num_frames = paths.len();
+    let record_schema = config.schema.clone();
+    let read_frame = move |frame_idx: usize| {
+        let path = &paths[frame_idx];
+        log::info!("reading frame {} from {}", frame_idx, path.display());
+        read_dataframe(path, &record_schema)
+            .map_err(|e| BlitzBeaverError::InvalidData(e.to_string()))
+    };
+    let spill_dir = cli.spill_dir.clone().unwrap_or_else(std::env::temp_dir);
+
+    log::info!(
+        "tracking {} frames out-of-core, {} frames in memory...",
+        num_frames,
+        cli.frame_window
+    );
+    let (tracking_graph, diagnostics) = api::execute_tracking_out_of_core(
+        &config.tracking,
+        &config.schema,
+        num_frames,
+        read_frame,
+        &[],
+        &[],
+        cli.frame_window,
+        &spill_dir,
+    )?;
+
+    write_beaver_file(&cli.output, tracking_graph, diagnostics)
+}
+
+/// Writes the tracking graph and diagnostics to a .beaver file.
+fn write_beaver_file(
+    path: &Path,
+    tracking_graph: TrackingGraph,
+    diagnostics: Diagnostics,
+) -> Result<(), CliError> {
+    let beaver_file = BeaverFile::new(tracking_graph, diagnostics);
+    fs::write(path, beaver_file.serialize()?).map_err(|e| CliError::Io(path.to_path_buf(), e))?;
+    log::info!("tracking graph written to {}", path.display());
+    Ok(())
+}
+
+/// Reads the configuration file, either in TOML or JSON depending on its extension,
+/// and validates it.
+fn read_config(path: &Path) -> Result<CliConfig, CliError> {
+    let content = fs::read_to_string(path).map_err(|e| CliError::Io(path.to_path_buf(), e))?;
+    let format = ConfigFormat::from_path(path).ok_or_else(|| {
+        CliError::Config(format!(
+            "{}: expected a .toml or .json file",
+            path.display()
+        ))
+    })?;
+    let config: CliConfig = api::deserialize_config(&content, format)?;
+
+    // the paths of the validation errors are relative to the section
+    let in_section = |section: &str| {
+        let section = section.to_string();
+        move |e: BlitzBeaverError| CliError::Config(format!("{}.{}", section, e))
+    };
+    config.schema.validate().map_err(in_section("schema"))?;
+    config
+        .tracking
+        .validate(&config.schema)
+        .map_err(in_section("tracking"))?;
+    if let Some(normalization_config) = &config.normalization {
+        normalization_config
+            .validate()
+            .map_err(in_section("normalization"))?;
+    }
+    Ok(config)
+}
+
+/// Returns the Parquet and CSV files of the directory, sorted by file name.
+fn list_frame_files(dir: &Path) -> Result<Vec<PathBuf>, CliError> {
+    let entries = fs::read_dir(dir).map_err(|e| CliError::Io(dir.to_path_buf(), e))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| CliError::Io(dir.to_path_buf(), e))?
+            .path();
+        if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("parquet") | Some("csv")
+        ) {
+            paths.push(path);
+        }
+    }
+    if paths.is_empty() {
+        return Err(CliError::Process(format!(
+            "{}: no Parquet or CSV file found",
+            dir.display()
+        )));
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Reads a frame from a Parquet or CSV file.
+///
+/// The fields of the schema are read as strings from the CSV files, whatever
+/// their values look like (e.g. house numbers). CSV files can't hold lists,
+/// the multi-strings fields are read as whitespace-separated words.
+fn read_dataframe(path: &Path, record_schema: &RecordSchema) -> Result<DataFrame, CliError> {
+    if path.extension().and_then(|e| e.to_str()) == Some("parquet") {
+        let file = File::open(path).map_err(|e| CliError::Io(path.to_path_buf(), e))?;
+        return Ok(ParquetReader::new(file).finish()?);
+    }
+
+    let strings_schema = record_schema
+        .fields
+        .iter()
+        .map(|field_schema| Field::new(field_schema.name.as_str().into(), DataType::String))
+        .collect::<Schema>();
+    let mut dataframe = CsvReadOptions::default()
+        .with_has_header(true)
+        .with_schema_overwrite(Some(Arc::new(strings_schema)))
+        .try_into_reader_with_file_path(Some(path.to_path_buf()))?
+        .finish()?;
+
+    for field_schema in record_schema.fields.iter() {
+        if field_schema.dtype != ElementType::MultiStrings {
+            continue;
+        }
+        let column = dataframe.column(&field_schema.name)?.str()?.clone();
+        let words: ListChunked = column
+            .iter()
+            .map(|v| {
+                v.map(|v| Series::new(PlSmallStr::EMPTY, v.split_whitespace().collect::<Vec<_>>()))
+            })
+            .collect();
+        dataframe.with_column(
+            words
+                .into_series()
+                .with_name(field_schema.name.as_str().into()),
+        )?;
+    }
+    Ok(dataframe)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::id;
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [schema]
+        fields = [
+            { name = "name", dtype = "string" },
+            { name = "house", dtype = "string" },
+        ]
+
+        [tracking]
+        num_threads = 2
+
+        [tracking.tracker]
+        interest_threshold = 0.5
+        limit_no_match_streak = 3
+        memories = [{ memory_strategy = "median" }, { memory_strategy = "median" }]
+        record_scorer = { record_scorer = "average" }
+
+        [tracking.distance_metric]
+        metric = "lv_opti"
+        caching_threshold = 4
+        use_sigmoid = false
+
+        [tracking.resolver]
+        resolving_strategy = "best-match"
+    "#;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("blitzbeaver").chain(args.iter().copied()))
+    }
+
+    /// Creates a directory with the configuration and three frames, two Parquet files
+    /// and a CSV file whose house numbers look like integers.
+    fn build_fixture(config: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blitzbeaver-cli-{:016x}", id::new_id()));
+        let input = dir.join("frames");
+        fs::create_dir_all(&input).unwrap();
+        fs::write(dir.join("config.toml"), config).unwrap();
+        for (i, names) in [["alice", "bob"], ["alice", "bobb"]].iter().enumerate() {
+            let mut dataframe = df!("name" => names, "house" => ["12", "7"]).unwrap();
+            let file = File::create(input.join(format!("{}.parquet", 1805 + i))).unwrap();
+            ParquetWriter::new(file).finish(&mut dataframe).unwrap();
+        }
+        fs::write(input.join("1807.csv"), "name,house\nalise,12\nbob,7\n").unwrap();
+        dir
+    }
+
+    fn build_args(dir: &Path) -> Vec<String> {
+        [
+            "--input",
+            dir.join("frames").to_str().unwrap(),
+            "--config",
+            dir.join("config.toml").to_str().unwrap(),
+            "--output",
+            dir.join("graph.beaver").to_str().unwrap(),
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let cli = parse(&[
+            "--input",
+            "frames",
+            "--config",
+            "config.toml",
+            "--output",
+            "graph.beaver",
+            "--out-of-core",
+        ])
+        .unwrap();
+        assert_eq!(cli.input, PathBuf::from("frames"));
+        assert_eq!(cli.config, PathBuf::from("config.toml"));
+        assert_eq!(cli.output, PathBuf::from("graph.beaver"));
+        assert!(cli.out_of_core);
+        assert_eq!(cli.frame_window, 2);
+        assert_eq!(cli.log_level, "info");
+        assert!(cli.normalized_output.is_none());
+
+        // the output is required
+        assert!(parse(&["--input", "frames", "--config", "config.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let dir = build_fixture(CONFIG);
+        let args = build_args(&dir);
+
+        for out_of_core in [false, true] {
+            let mut args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            if out_of_core {
+                args.push("--out-of-core");
+            }
+            assert_eq!(execute(&parse(&args).unwrap()), ExitCode::SUCCESS);
+
+            let bytes = fs::read(dir.join("graph.beaver")).unwrap();
+            let tracking_graph = BeaverFile::from_bytes(&bytes)
+                .unwrap()
+                .take_tracking_graph()
+                .unwrap();
+            let mut chains = tracking_graph
+                .chains_ids()
+                .into_iter()
+                .map(|id| tracking_graph.get_tracking_chain(id).len())
+                .collect::<Vec<_>>();
+            chains.sort();
+            assert_eq!(chains, vec![3, 3]);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_invalid_config() {
+        let dir =
+            build_fixture(&CONFIG.replace("interest_threshold = 0.5", "interest_threshold = 2"));
+        let args = build_args(&dir);
+        let cli = parse(&args.iter().map(String::as_str).collect::<Vec<_>>()).unwrap();
+
+        match read_config(&cli.config) {
+            Err(CliError::Config(e)) => assert_eq!(
+                e,
+                "tracking.tracker.interest_threshold: 2 is not between 0 and 1"
+            ),
+            r => panic!("expected a config error, got {:?}", r.err()),
+        }
+        assert_eq!(execute(&cli), ExitCode::FAILURE);
+        assert!(!dir.join("graph.beaver").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
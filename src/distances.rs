@@ -2,13 +2,23 @@ mod distance_calculator;
 mod distance_matrix;
 mod distance_metric;
 mod median_word;
+mod persistent_cache;
 mod sigmoid;
 
-pub use distance_calculator::{CachedDistanceCalculator, TraceCachedDistanceCalculator};
+pub use distance_calculator::CachedDistanceCalculator;
+pub use distance_calculator::TraceCachedDistanceCalculator;
 pub use distance_matrix::DistanceMatrix;
+#[cfg(test)]
+pub use distance_metric::LvOptiDistanceMetric;
 pub use distance_metric::{
-    DistanceMetric, InternalDistanceMetricConfig, LvDistanceMetric, LvEdit, LvEditDistanceMetric,
-    LvMultiWordDistanceMetric, LvOptiDistanceMetric, LvSubstringDistanceMetric,
+    DistanceMetric, InternalDistanceMetricConfig, LvBitParallelDistanceMetric, LvDistanceMetric,
+    LvEdit, LvEditDistanceMetric, LvMultiWordDistanceMetric, LvSubstringDistanceMetric,
 };
+#[cfg(any(test, feature = "benchmark"))]
 pub use median_word::compute_median_word;
-pub use sigmoid::sigmoid;
+pub use median_word::{
+    compute_median_word_with_strategy, compute_weighted_median_word, IncrementalMedianWord,
+    MedianWordBuilder, MedianWordStrategy,
+};
+pub use persistent_cache::{PersistentCacheStats, PersistentDistanceCache};
+pub use sigmoid::{inverse_sigmoid, sigmoid};
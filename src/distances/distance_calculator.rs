@@ -1,20 +1,22 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{
-    distances::{DistanceMatrix, DistanceMetric},
+    distances::{DistanceMatrix, DistanceMetric, PersistentDistanceCache},
     frame::Element,
     word::Word,
 };
 
-#[cfg(feature = "benchmark")]
-#[derive(Clone)]
+/// Trace of a cached distance calculator
+///
+/// Counts the distances requested, the ones found in the cache and
+/// the size of the caches built by the precomputations.
+#[derive(Debug, Clone, Default)]
 pub struct TraceCachedDistanceCalculator {
     pub computation_count: u64,
     pub cache_hit_count: u64,
     pub cache_size: usize,
 }
 
-#[cfg(feature = "benchmark")]
 impl TraceCachedDistanceCalculator {
     pub fn new() -> Self {
         Self {
@@ -43,11 +45,20 @@ impl TraceCachedDistanceCalculator {
 /// to maximize the cache hit rate. The cache is immutable during the computation of a frame.
 ///
 /// The cache should always be cleared after the computation of a frame.
+///
+/// Optionally, a shared cache, precomputed once and read-only, is looked up
+/// when a distance is not in the cache of the frame.
+///
+/// Optionally, a persistent cache, shared between the workers, is looked up last.
+/// It survives across frames: the distances missing from it are buffered and
+/// must be flushed to it at the end of the frame.
 pub struct CachedDistanceCalculator {
     matrix: DistanceMatrix,
+    shared_matrix: Option<Arc<DistanceMatrix>>,
+    persistent_cache: Option<Arc<PersistentDistanceCache>>,
+    persistent_pending: Vec<(u64, f32)>,
     distance_metric: Box<dyn DistanceMetric<Word> + Send>,
     cache_dist_threshold: u32,
-    #[cfg(feature = "benchmark")]
     pub trace: TraceCachedDistanceCalculator,
 }
 
@@ -55,13 +66,53 @@ impl CachedDistanceCalculator {
     pub fn new(distance: Box<dyn DistanceMetric<Word> + Send>, cache_dist_threshold: u32) -> Self {
         Self {
             matrix: DistanceMatrix::new(),
+            shared_matrix: None,
+            persistent_cache: None,
+            persistent_pending: Vec::new(),
             distance_metric: distance,
             cache_dist_threshold,
-            #[cfg(feature = "benchmark")]
             trace: TraceCachedDistanceCalculator::new(),
         }
     }
 
+    /// Sets the shared cache, looked up after the cache of the frame.
+    pub fn with_shared_cache(mut self, shared_matrix: Arc<DistanceMatrix>) -> Self {
+        self.shared_matrix = Some(shared_matrix);
+        self
+    }
+
+    /// Sets the persistent cache, looked up after the other caches.
+    pub fn with_persistent_cache(mut self, persistent_cache: Arc<PersistentDistanceCache>) -> Self {
+        self.persistent_cache = Some(persistent_cache);
+        self
+    }
+
+    /// Returns the persistent cache, if any.
+    pub fn persistent_cache(&self) -> Option<&Arc<PersistentDistanceCache>> {
+        self.persistent_cache.as_ref()
+    }
+
+    /// Inserts the distances computed since the last flush in the persistent cache.
+    pub fn flush_persistent_cache(&mut self) {
+        if let Some(persistent_cache) = self.persistent_cache.as_ref() {
+            persistent_cache.insert(&self.persistent_pending);
+        }
+        self.persistent_pending.clear();
+    }
+
+    /// Takes the cache, leaving an empty one.
+    pub fn take_cache(&mut self) -> DistanceMatrix {
+        std::mem::replace(&mut self.matrix, DistanceMatrix::new())
+    }
+
+    /// Returns the distance between two words from the cache of the frame
+    /// or from the shared cache.
+    fn get_cached_dist(&self, w1: &Word, w2: &Word) -> Option<f32> {
+        self.matrix
+            .get(w1, w2)
+            .or_else(|| self.shared_matrix.as_ref().and_then(|m| m.get(w1, w2)))
+    }
+
     pub fn get_dist(&mut self, e1: &Element, e2: &Element) -> Option<f32> {
         match (e1, e2) {
             (Element::Word(w1), Element::Word(w2)) => Some(self.get_dist_word(w1, w2)),
@@ -70,27 +121,102 @@ impl CachedDistanceCalculator {
         }
     }
 
+    /// Returns the distance of the most similar pair of words between two elements.
+    ///
+    /// For single words, this is the same as `get_dist`. For multi-words, it indicates
+    /// whether at least one word of the first element matches with a word of the second one.
+    ///
+    /// Returns None if the elements can't be compared or if one of them is empty.
+    pub fn get_max_dist(&mut self, e1: &Element, e2: &Element) -> Option<f32> {
+        match (e1, e2) {
+            (Element::Word(w1), Element::Word(w2)) => Some(self.get_dist_word(w1, w2)),
+            (Element::MultiWords(ws1), Element::MultiWords(ws2)) => {
+                let mut max_dist: Option<f32> = None;
+                for w1 in ws1.iter() {
+                    for w2 in ws2.iter() {
+                        let dist = self.get_dist_word(w1, w2);
+                        max_dist = max_dist.map(|d| d.max(dist)).or(Some(dist));
+                    }
+                }
+                max_dist
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the distance between two words, either from the cache or by computing it.
     ///
     /// Note: this doesn't update the cache.
     pub fn get_dist_word(&mut self, w1: &Word, w2: &Word) -> f32 {
-        #[cfg(feature = "benchmark")]
-        {
-            self.trace.computation_count += 1;
-        }
+        self.trace.computation_count += 1;
 
-        match self.matrix.get(&w1.raw, &w2.raw) {
-            Some(dist) => {
-                #[cfg(feature = "benchmark")]
-                {
+        if let Some(dist) = self.get_cached_dist(w1, w2) {
+            self.trace.cache_hit_count += 1;
+            return dist;
+        }
+        match self.persistent_cache.as_ref() {
+            Some(persistent_cache) => {
+                let key = PersistentDistanceCache::key(w1, w2);
+                if let Some(dist) = persistent_cache.get(key) {
                     self.trace.cache_hit_count += 1;
+                    return dist;
                 }
+                let dist = self.distance_metric.dist(w1, w2);
+                self.persistent_pending.push((key, dist));
                 dist
             }
             None => self.distance_metric.dist(w1, w2),
         }
     }
 
+    /// Returns the distance between two elements if it is at least `min_dist`.
+    ///
+    /// Returns None if the elements can't be compared and Some(None) if
+    /// their distance is below `min_dist`.
+    pub fn get_dist_bounded(
+        &mut self,
+        e1: &Element,
+        e2: &Element,
+        min_dist: f32,
+    ) -> Option<Option<f32>> {
+        match (e1, e2) {
+            (Element::Word(w1), Element::Word(w2)) => {
+                Some(self.get_dist_word_bounded(w1, w2, min_dist))
+            }
+            (Element::MultiWords(ws1), Element::MultiWords(ws2)) => {
+                self.get_dists_words_bounded(ws1, ws2, min_dist)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the distance between two words if it is at least `min_dist`, either
+    /// from the cache or by computing it, stopping early when it falls below the bound.
+    ///
+    /// Note: this doesn't update the cache, only the exact distances are
+    /// buffered for the persistent cache.
+    pub fn get_dist_word_bounded(&mut self, w1: &Word, w2: &Word, min_dist: f32) -> Option<f32> {
+        self.trace.computation_count += 1;
+
+        if let Some(dist) = self.get_cached_dist(w1, w2) {
+            self.trace.cache_hit_count += 1;
+            return (dist >= min_dist).then_some(dist);
+        }
+        match self.persistent_cache.as_ref() {
+            Some(persistent_cache) => {
+                let key = PersistentDistanceCache::key(w1, w2);
+                if let Some(dist) = persistent_cache.get(key) {
+                    self.trace.cache_hit_count += 1;
+                    return (dist >= min_dist).then_some(dist);
+                }
+                let dist = self.distance_metric.dist_bounded(w1, w2, min_dist)?;
+                self.persistent_pending.push((key, dist));
+                Some(dist)
+            }
+            None => self.distance_metric.dist_bounded(w1, w2, min_dist),
+        }
+    }
+
     /// Computes the distance between two vectors of words.
     ///
     /// Compares from the perspective of the first vector to the second one,
@@ -104,13 +230,7 @@ impl CachedDistanceCalculator {
         }
         let mut tot_dist = 0.0;
         for w1 in ws1.iter() {
-            // the dist is 0 if ws2 is empty
-            let dist = ws2
-                .iter()
-                .map(|w2| self.get_dist_word(w1, w2))
-                .reduce(f32::max)
-                .unwrap_or(0.0);
-            tot_dist += dist;
+            tot_dist += self.get_max_dist_word(w1, ws2);
         }
 
         let max_len = usize::max(ws1.len(), ws2.len());
@@ -118,32 +238,96 @@ impl CachedDistanceCalculator {
         Some(agg_dist)
     }
 
+    /// Computes the distance between two vectors of words if it is at least `min_dist`,
+    /// see `get_dists_words`.
+    ///
+    /// Stops as soon as the remaining words of the first vector can't bring the average
+    /// distance up to `min_dist`, each of them adds at most 1.
+    ///
+    /// Returns None if the first vector is empty and Some(None) if the distance
+    /// is below `min_dist`.
+    pub fn get_dists_words_bounded(
+        &mut self,
+        ws1: &[Word],
+        ws2: &[Word],
+        min_dist: f32,
+    ) -> Option<Option<f32>> {
+        if ws1.is_empty() {
+            return None;
+        }
+        let max_len = usize::max(ws1.len(), ws2.len());
+        let mut tot_dist = 0.0;
+        for (i, w1) in ws1.iter().enumerate() {
+            tot_dist += self.get_max_dist_word(w1, ws2);
+
+            // loosened so that rounding errors never exclude a distance
+            let remaining = (ws1.len() - i - 1) as f32;
+            if (tot_dist + remaining) / (max_len as f32) + 1e-6 < min_dist {
+                return Some(None);
+            }
+        }
+
+        let agg_dist = tot_dist / max_len as f32;
+        Some((agg_dist >= min_dist).then_some(agg_dist))
+    }
+
+    /// Returns the maximal distance between the word and the words of the vector,
+    /// 0 if the vector is empty.
+    fn get_max_dist_word(&mut self, w1: &Word, ws2: &[Word]) -> f32 {
+        ws2.iter()
+            .map(|w2| self.get_dist_word(w1, w2))
+            .reduce(f32::max)
+            .unwrap_or(0.0)
+    }
+
     /// Clears the cache
     pub fn clear_cache(&mut self) {
         self.matrix.clear();
     }
 
+    /// Takes the trace, leaving an empty one.
+    pub fn take_trace(&mut self) -> TraceCachedDistanceCalculator {
+        std::mem::take(&mut self.trace)
+    }
+
     /// Returns the size of the cache.
     pub fn cache_size(&self) -> usize {
         self.matrix.size()
     }
 
     /// Computes the count of each unique word in the serie.
-    fn compute_uniques<'a>(&self, serie: &'a Vec<&'a Element>) -> HashMap<&'a Word, u32> {
+    pub fn compute_uniques(serie: &[&Element]) -> HashMap<Word, u32> {
         let mut uniques = HashMap::new();
         for e in serie.iter() {
             if let Element::Word(w) = e {
-                uniques.entry(w).and_modify(|c| *c += 1).or_insert(1);
+                uniques
+                    .entry(w.clone())
+                    .and_modify(|c| *c += 1)
+                    .or_insert(1);
             }
         }
         uniques
     }
 
     /// Pre-computes the distance between the most frequent uniques values to build the cache.
+    ///
+    /// The distances already in the persistent cache are copied instead of computed,
+    /// the computed ones are buffered for the persistent cache.
     pub fn precompute(&mut self, serie1: &Vec<&Element>, serie2: &Vec<&Element>) {
-        let uniques1 = self.compute_uniques(serie1);
-        let uniques2 = self.compute_uniques(serie2);
+        let uniques1 = Self::compute_uniques(serie1);
+        let uniques2 = Self::compute_uniques(serie2);
+        self.precompute_uniques(&uniques1, &uniques2);
+    }
 
+    /// Pre-computes the distance between the most frequent uniques values to build the cache,
+    /// given the count of each unique word of both series (see `compute_uniques`).
+    ///
+    /// This allows to count the uniques of a serie once when it is used multiple times.
+    pub fn precompute_uniques(
+        &mut self,
+        uniques1: &HashMap<Word, u32>,
+        uniques2: &HashMap<Word, u32>,
+    ) {
         for (v1, c1) in uniques1.iter() {
             for (v2, c2) in uniques2.iter() {
                 // only pre-compute and store when a min of occurence is reached
@@ -151,17 +335,31 @@ impl CachedDistanceCalculator {
                     continue;
                 }
 
-                if self.matrix.get(&v1.raw, &v2.raw).is_none() {
-                    let dist = self.distance_metric.dist(v1, v2);
-                    self.matrix.set(&v1.raw, &v2.raw, dist);
+                if self.get_cached_dist(v1, v2).is_some() {
+                    continue;
+                }
+                match self.persistent_cache.as_ref() {
+                    Some(persistent_cache) => {
+                        let key = PersistentDistanceCache::key(v1, v2);
+                        let dist = match persistent_cache.get(key) {
+                            Some(dist) => dist,
+                            None => {
+                                let dist = self.distance_metric.dist(v1, v2);
+                                self.persistent_pending.push((key, dist));
+                                dist
+                            }
+                        };
+                        self.matrix.set(v1, v2, dist);
+                    }
+                    None => {
+                        let dist = self.distance_metric.dist(v1, v2);
+                        self.matrix.set(v1, v2, dist);
+                    }
                 }
             }
         }
 
-        #[cfg(feature = "benchmark")]
-        {
-            self.trace.cache_size = self.matrix.size();
-        }
+        self.trace.cache_size += self.matrix.size();
     }
 }
 
@@ -169,10 +367,52 @@ impl Clone for CachedDistanceCalculator {
     fn clone(&self) -> Self {
         Self {
             matrix: self.matrix.clone(),
+            shared_matrix: self.shared_matrix.clone(),
+            persistent_cache: self.persistent_cache.clone(),
+            persistent_pending: self.persistent_pending.clone(),
             distance_metric: self.distance_metric.clone(),
             cache_dist_threshold: self.cache_dist_threshold,
-            #[cfg(feature = "benchmark")]
             trace: self.trace.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::distances::LvOptiDistanceMetric;
+
+    use super::*;
+
+    #[test]
+    fn test_get_dist_bounded_multiwords() {
+        let mut distance_calculator =
+            CachedDistanceCalculator::new(Box::new(LvOptiDistanceMetric::new(false)), 4);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let names = ["jean", "jeanne", "marie", "mario", "louis", "luis"];
+        let mut random_multiwords = || {
+            (0..rng.random_range(0..4))
+                .map(|_| Word::new(names[rng.random_range(0..names.len())]))
+                .collect::<Vec<_>>()
+        };
+
+        // the distance is returned if and only if it reaches the bound
+        for _ in 0..40 {
+            let e1 = Element::MultiWords(random_multiwords());
+            let e2 = Element::MultiWords(random_multiwords());
+            let dist = distance_calculator.get_dist(&e1, &e2);
+            for min_dist in [0.0, 0.3, 0.5, 0.8, 1.0] {
+                assert_eq!(
+                    distance_calculator.get_dist_bounded(&e1, &e2, min_dist),
+                    dist.map(|dist| (dist >= min_dist).then_some(dist)),
+                    "{:?} {:?} {}",
+                    e1,
+                    e2,
+                    min_dist
+                );
+            }
+        }
+    }
+}
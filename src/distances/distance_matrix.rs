@@ -1,15 +1,14 @@
-use std::{
-    collections::HashMap,
-    hash::{DefaultHasher, Hash, Hasher},
-};
+use std::collections::HashMap;
+
+use crate::word::{Word, WordId};
 
 /// DistanceMatrix
 ///
-/// This is a building block for a cache, it stores the distances between elements.
-/// To work properly, the distance between element must be symmetric, that is dist(a, b) == dist(b, a).
+/// This is a building block for a cache, it stores the distances between words.
+/// To work properly, the distance between words must be symmetric, that is dist(a, b) == dist(b, a).
 ///
-/// Note: the distance matrix computes the hashes of the keys passed to avoid having a reference
-/// as key, to avoid lifetime complications.
+/// Note: the distance matrix is keyed on the IDs of the interned words, which avoids
+/// hashing the strings and having a reference as key.
 #[derive(Clone)]
 pub struct DistanceMatrix {
     values: HashMap<u64, f32>,
@@ -32,28 +31,25 @@ impl DistanceMatrix {
         self.values.len()
     }
 
-    /// Hashes two keys and returns the hash.
+    /// Returns the key of the pair of words.
     ///
-    /// Keys are ordered before hashing, such that hash(a, b) == hash(b, a)
-    fn hash<K: Hash + Eq + Ord>(v1: &K, v2: &K) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        if v2 < v1 {
-            v2.hash(&mut hasher);
-            v1.hash(&mut hasher);
+    /// IDs are ordered before being packed, such that key(a, b) == key(b, a)
+    pub(crate) fn key(w1: &Word, w2: &Word) -> u64 {
+        let (id1, id2): (WordId, WordId) = if w2.id() < w1.id() {
+            (w2.id(), w1.id())
         } else {
-            v1.hash(&mut hasher);
-            v2.hash(&mut hasher);
-        }
-        hasher.finish()
+            (w1.id(), w2.id())
+        };
+        ((id1 as u64) << 32) | id2 as u64
     }
 
-    /// Sets the distance between v1 and v2.
-    pub fn set<K: Hash + Eq + Ord>(&mut self, v1: &K, v2: &K, dist: f32) {
-        self.values.insert(Self::hash(v1, v2), dist);
+    /// Sets the distance between w1 and w2.
+    pub fn set(&mut self, w1: &Word, w2: &Word, dist: f32) {
+        self.values.insert(Self::key(w1, w2), dist);
     }
 
-    /// Returns the distance between v1 and v2.
-    pub fn get<K: Hash + Eq + Ord>(&self, v1: &K, v2: &K) -> Option<f32> {
-        self.values.get(&Self::hash(v1, v2)).copied()
+    /// Returns the distance between w1 and w2.
+    pub fn get(&self, w1: &Word, w2: &Word) -> Option<f32> {
+        self.values.get(&Self::key(w1, w2)).copied()
     }
 }
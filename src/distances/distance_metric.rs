@@ -1,10 +1,10 @@
-use std::{cmp::max, usize};
+use std::{cmp::max, collections::HashMap, usize};
 
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::word::{GraphemeType, Word};
 
-use super::sigmoid;
+use super::{inverse_sigmoid, sigmoid};
 
 #[derive(Debug, Clone)]
 pub enum InternalDistanceMetricConfig {
@@ -13,6 +13,7 @@ pub enum InternalDistanceMetricConfig {
     LvEdit(f32, f32, f32, bool),
     LvSubstring(f32, bool),
     LvMultiWord(GraphemeType, bool),
+    LvBitParallel(bool),
 }
 
 impl InternalDistanceMetricConfig {
@@ -33,6 +34,9 @@ impl InternalDistanceMetricConfig {
             InternalDistanceMetricConfig::LvMultiWord(separator, use_sigmoid) => {
                 Box::new(LvMultiWordDistanceMetric::new(*separator, *use_sigmoid))
             }
+            InternalDistanceMetricConfig::LvBitParallel(use_sigmoid) => {
+                Box::new(LvBitParallelDistanceMetric::new(*use_sigmoid))
+            }
         }
     }
 }
@@ -47,6 +51,15 @@ impl InternalDistanceMetricConfig {
 pub trait DistanceMetric<T: ?Sized> {
     fn dist(&mut self, v1: &T, v2: &T) -> f32;
 
+    /// Computes the distance only if it is at least `min_dist`, returns None otherwise.
+    ///
+    /// Metrics can override it to stop the computation as soon as the distance
+    /// is known to be below the bound, by default the exact distance is computed.
+    fn dist_bounded(&mut self, v1: &T, v2: &T, min_dist: f32) -> Option<f32> {
+        let dist = self.dist(v1, v2);
+        (dist >= min_dist).then_some(dist)
+    }
+
     /// Clone the distance metric
     ///
     /// This is done this way because of restrictions on the trait
@@ -54,6 +67,46 @@ pub trait DistanceMetric<T: ?Sized> {
     fn clone(&self) -> Box<dyn DistanceMetric<T> + Send + Sync>;
 }
 
+/// Converts a number of edits into a distance, given the length of the longest word.
+fn edits_to_dist(edits: u32, len: usize, use_sigmoid: bool) -> f32 {
+    cost_to_dist(edits as f32, len, use_sigmoid)
+}
+
+/// Converts a cost (weighted number of edits) into a distance,
+/// given the length of the longest word.
+fn cost_to_dist(cost: f32, len: usize, use_sigmoid: bool) -> f32 {
+    let dist = 1.0 - cost / len as f32;
+    if use_sigmoid {
+        sigmoid(dist)
+    } else {
+        dist
+    }
+}
+
+/// Returns the maximal cost (weighted number of edits) for the distance to be at
+/// least `min_dist`, given the length of the longest word, or None if any cost is.
+///
+/// The bound is slightly loosened so that rounding errors never exclude a distance,
+/// the distance computed from the cost still has to be checked against `min_dist`.
+fn max_cost_for(min_dist: f32, len: usize, use_sigmoid: bool) -> Option<f32> {
+    let min_dist = if use_sigmoid {
+        inverse_sigmoid(min_dist)
+    } else {
+        min_dist
+    };
+    if min_dist.is_nan() || min_dist <= 0.0 {
+        return None;
+    }
+    Some(((1.0 - min_dist) * len as f32 + 1e-3).max(0.0))
+}
+
+/// Returns the maximal number of edits for the distance to be at least `min_dist`,
+/// given the length of the longest word, or None if any number of edits is,
+/// see `max_cost_for`.
+fn max_edits_for(min_dist: f32, len: usize, use_sigmoid: bool) -> Option<u32> {
+    max_cost_for(min_dist, len, use_sigmoid).map(|max_cost| max_cost.floor() as u32)
+}
+
 /// Levenshtein Distance Metric
 ///
 /// This metric computes the Levenshtein distance between two words.
@@ -71,75 +124,85 @@ impl LvDistanceMetric {
     }
 
     fn compute_edits(&mut self, w1: &Word, w2: &Word) -> u32 {
+        self.compute_edits_within(w1, w2, u32::MAX).unwrap()
+    }
+
+    /// Computes the number of edits between two words, returns None
+    /// as soon as it is known to exceed `max_edits`.
+    ///
+    /// Only the cells of the table within a band around the diagonal are computed:
+    /// a path through the cell (i, j) costs at least |i - j| plus the difference
+    /// of the remaining lengths, so it can't be within `max_edits` outside of it.
+    fn compute_edits_within(&mut self, w1: &Word, w2: &Word, max_edits: u32) -> Option<u32> {
         // Backward compatibility
-        let graphemes1 = w1.raw.graphemes(true).collect::<Vec<&str>>();
-        let graphemes2 = w2.raw.graphemes(true).collect::<Vec<&str>>();
+        let graphemes1 = w1.raw().graphemes(true).collect::<Vec<&str>>();
+        let graphemes2 = w2.raw().graphemes(true).collect::<Vec<&str>>();
 
         let len_w1 = graphemes1.len();
         let len_w2 = graphemes2.len();
 
-        let size = (len_w1 + 1) * (len_w2 + 1);
-        let mut dp = Vec::with_capacity(size);
-        for _ in 0..size {
-            dp.push(0);
+        let diff = len_w2 as i64 - len_w1 as i64;
+        if diff.unsigned_abs() > max_edits as u64 {
+            return None;
         }
+        // the band is the range of offsets j - i of the computed cells
+        let slack = (max_edits as i64 - diff.abs()) / 2;
+        let band_from = i64::min(0, diff) - slack;
+        let band_to = i64::max(0, diff) + slack;
 
-        for i in 1..(len_w1 + 1) {
-            let idx = Self::idx_at(i, 0, len_w2);
-            dp[idx] = i as u32;
-        }
-
-        for j in 1..(len_w2 + 1) {
-            let idx = Self::idx_at(0, j, len_w2);
-            dp[idx] = j as u32;
-        }
+        // the cells out of the band are never reached
+        let size = (len_w1 + 1) * (len_w2 + 1);
+        let mut dp = vec![u32::MAX; size];
 
-        for i in 1..(len_w1 + 1) {
-            let g1 = graphemes1[i - 1];
-            for j in 1..(len_w2 + 1) {
-                let g2 = graphemes2[j - 1];
+        for i in 0..(len_w1 + 1) {
+            let from = i64::max(i as i64 + band_from, 0) as usize;
+            let to = i64::min(i as i64 + band_to, len_w2 as i64) as usize;
 
+            let mut row_min = u32::MAX;
+            for j in from..(to + 1) {
                 let idx_cur = Self::idx_at(i, j, len_w2);
-                if g1 == g2 {
-                    let idx_prev = Self::idx_at(i - 1, j - 1, len_w2);
-                    dp[idx_cur] = dp[idx_prev];
-                    continue;
-                }
-
-                let idx_sub = Self::idx_at(i - 1, j - 1, len_w2);
-                let idx_del = Self::idx_at(i - 1, j, len_w2);
-                let idx_add = Self::idx_at(i, j - 1, len_w2);
-
-                let len_sub = dp[idx_sub];
-                let len_del = dp[idx_del];
-                let len_add = dp[idx_add];
-
-                let min_len = len_sub.min(len_del.min(len_add));
-
-                if min_len == len_sub {
-                    dp[idx_cur] = len_sub + 1;
-                } else if min_len == len_del {
-                    dp[idx_cur] = len_del + 1;
+                if i == 0 || j == 0 {
+                    dp[idx_cur] = (i + j) as u32;
+                } else if graphemes1[i - 1] == graphemes2[j - 1] {
+                    dp[idx_cur] = dp[Self::idx_at(i - 1, j - 1, len_w2)];
                 } else {
-                    dp[idx_cur] = len_add + 1;
+                    let len_sub = dp[Self::idx_at(i - 1, j - 1, len_w2)];
+                    let len_del = dp[Self::idx_at(i - 1, j, len_w2)];
+                    let len_add = dp[Self::idx_at(i, j - 1, len_w2)];
+                    dp[idx_cur] = len_sub.min(len_del.min(len_add)).saturating_add(1);
                 }
+                row_min = row_min.min(dp[idx_cur]);
+            }
+
+            // the number of edits never decreases from one row to the next
+            if row_min > max_edits {
+                return None;
             }
         }
 
-        let idx = Self::idx_at(len_w1, len_w2, len_w2);
-        dp[idx]
+        let edits = dp[Self::idx_at(len_w1, len_w2, len_w2)];
+        (edits <= max_edits).then_some(edits)
     }
 }
 
 impl DistanceMetric<Word> for LvDistanceMetric {
     fn dist(&mut self, v1: &Word, v2: &Word) -> f32 {
         let edits = self.compute_edits(v1, v2);
-        let dist = 1.0 - edits as f32 / usize::max(v1.raw.len(), v2.raw.len()) as f32;
-        if self.use_sigmoid {
-            sigmoid(dist)
-        } else {
-            dist
-        }
+        edits_to_dist(
+            edits,
+            usize::max(v1.raw().len(), v2.raw().len()),
+            self.use_sigmoid,
+        )
+    }
+
+    fn dist_bounded(&mut self, v1: &Word, v2: &Word, min_dist: f32) -> Option<f32> {
+        let len = usize::max(v1.raw().len(), v2.raw().len());
+        let edits = match max_edits_for(min_dist, len, self.use_sigmoid) {
+            Some(max_edits) => self.compute_edits_within(v1, v2, max_edits)?,
+            None => self.compute_edits(v1, v2),
+        };
+        let dist = edits_to_dist(edits, len, self.use_sigmoid);
+        (dist >= min_dist).then_some(dist)
     }
 
     fn clone(&self) -> Box<dyn DistanceMetric<Word> + Send + Sync> {
@@ -183,9 +246,24 @@ impl LvOptiDistanceMetric {
         self.dp.fill(0);
     }
 
-    fn compute_edits<'a>(&mut self, mut w1: &'a Word, mut w2: &'a Word) -> u8 {
-        let mut len_w1 = w1.graphemes.len();
-        let mut len_w2 = w2.graphemes.len();
+    /// Computes the number of edits between the graphemes of two words.
+    fn compute_edits(&mut self, w1: &[GraphemeType], w2: &[GraphemeType]) -> u8 {
+        self.compute_edits_within(w1, w2, usize::MAX).unwrap()
+    }
+
+    /// Computes the number of edits between the graphemes of two words,
+    /// returns None as soon as it is known to exceed `max_edits`.
+    ///
+    /// Only the cells close enough to the diagonal to be on a path
+    /// within `max_edits` are computed.
+    fn compute_edits_within<'a>(
+        &mut self,
+        mut w1: &'a [GraphemeType],
+        mut w2: &'a [GraphemeType],
+        max_edits: usize,
+    ) -> Option<u8> {
+        let mut len_w1 = w1.len();
+        let mut len_w2 = w2.len();
 
         // w1 must be the largest word
         if len_w1 < len_w2 {
@@ -193,6 +271,12 @@ impl LvOptiDistanceMetric {
             (w1, w2) = (w2, w1);
         }
 
+        if len_w1 - len_w2 > max_edits {
+            return None;
+        }
+        // max offset i - j of the computed cells
+        let band = max_edits.saturating_add(len_w1 - len_w2) / 2;
+
         self.setup_dp(len_w1, len_w2);
 
         for i in 1..(len_w1 + 1) {
@@ -207,17 +291,29 @@ impl LvOptiDistanceMetric {
         }
 
         for i in 1..(len_w1 + 1) {
-            let g1 = w1.graphemes[i - 1];
+            let g1 = w1[i - 1];
 
-            // only iter to the diagonal
+            // only iter from the band to the diagonal
+            let from = usize::max(i.saturating_sub(band), 1);
             let to = usize::min(i + 1, len_w2 + 1);
-            for j in 1..(to) {
-                let g2 = w2.graphemes[j - 1];
+
+            // set a boundary before the band
+            let mut row_min = 255;
+            if from > 1 {
+                let idx = Self::idx_at(i, from - 1, len_w2);
+                self.dp[idx] = 255;
+            } else {
+                row_min = self.dp[Self::idx_at(i, 0, len_w2)];
+            }
+
+            for j in from..(to) {
+                let g2 = w2[j - 1];
 
                 let idx_cur = Self::idx_at(i, j, len_w2);
                 if g1 == g2 {
                     let idx_prev = Self::idx_at(i - 1, j - 1, len_w2);
                     self.dp[idx_cur] = self.dp[idx_prev];
+                    row_min = row_min.min(self.dp[idx_cur]);
                     continue;
                 }
 
@@ -236,23 +332,38 @@ impl LvOptiDistanceMetric {
                 } else {
                     self.dp[idx_cur] = len_add + 1;
                 }
+                row_min = row_min.min(self.dp[idx_cur]);
+            }
+
+            // the number of edits never decreases from one row to the next
+            if row_min as usize > max_edits {
+                return None;
             }
         }
 
         let idx = Self::idx_at(len_w1, len_w2, len_w2);
-        self.dp[idx]
+        let edits = self.dp[idx];
+        (edits as usize <= max_edits).then_some(edits)
     }
 }
 
 impl DistanceMetric<Word> for LvOptiDistanceMetric {
     fn dist(&mut self, v1: &Word, v2: &Word) -> f32 {
-        let edits = self.compute_edits(v1, v2);
-        let dist = 1.0 - edits as f32 / usize::max(v1.raw.len(), v2.raw.len()) as f32;
-        if self.use_sigmoid {
-            sigmoid(dist)
-        } else {
-            dist
-        }
+        let edits = self.compute_edits(v1.graphemes(), v2.graphemes());
+        edits_to_dist(
+            edits as u32,
+            usize::max(v1.raw().len(), v2.raw().len()),
+            self.use_sigmoid,
+        )
+    }
+
+    fn dist_bounded(&mut self, v1: &Word, v2: &Word, min_dist: f32) -> Option<f32> {
+        let len = usize::max(v1.raw().len(), v2.raw().len());
+        let max_edits =
+            max_edits_for(min_dist, len, self.use_sigmoid).map_or(usize::MAX, |e| e as usize);
+        let edits = self.compute_edits_within(v1.graphemes(), v2.graphemes(), max_edits)?;
+        let dist = edits_to_dist(edits as u32, len, self.use_sigmoid);
+        (dist >= min_dist).then_some(dist)
     }
 
     fn clone(&self) -> Box<dyn DistanceMetric<Word> + Send + Sync> {
@@ -339,8 +450,8 @@ impl LvEditDistanceMetric {
             }
         }
 
-        // clear all elems
-        self.dp.fill(LvEditNode::default());
+        // clear all elems, the cells out of the band are never reached
+        self.dp.fill(LvEditNode::new(u8::MAX, LvEdit::default(), 0));
     }
 
     /// Get the list of edits to transform the source word into the target word.
@@ -364,8 +475,34 @@ impl LvEditDistanceMetric {
 
     /// Compute the list of edits to transform the source word into the target word.
     pub fn compute_edits<'a>(&mut self, src: &'a Word, trg: &'a Word) -> Vec<LvEdit> {
-        let len_src = src.graphemes.len();
-        let len_trg = trg.graphemes.len();
+        self.compute_edits_within(src, trg, usize::MAX).unwrap()
+    }
+
+    /// Compute the list of edits to transform the source word into the target word,
+    /// returns None as soon as the number of edits is known to exceed `max_edits`.
+    ///
+    /// Only the cells of the table within a band around the diagonal are computed,
+    /// see `LvDistanceMetric::compute_edits_within`. The paths within `max_edits`
+    /// never leave the band, such that the edits are the same as with the full table.
+    pub fn compute_edits_within<'a>(
+        &mut self,
+        src: &'a Word,
+        trg: &'a Word,
+        max_edits: usize,
+    ) -> Option<Vec<LvEdit>> {
+        let len_src = src.graphemes().len();
+        let len_trg = trg.graphemes().len();
+
+        let diff = len_trg as i64 - len_src as i64;
+        // there are never more edits than graphemes in the longest word
+        let max_edits = max_edits.min(usize::max(len_src, len_trg)) as i64;
+        if diff.abs() > max_edits {
+            return None;
+        }
+        // the band is the range of offsets j - i of the computed cells
+        let slack = (max_edits - diff.abs()) / 2;
+        let band_from = i64::min(0, diff) - slack;
+        let band_to = i64::max(0, diff) + slack;
 
         self.setup_dp(len_src, len_trg);
 
@@ -375,24 +512,30 @@ impl LvEditDistanceMetric {
                 LvEditNode::new(i as u8, LvEdit::Del(i - 1), Self::idx_at(i - 1, 0, len_trg));
         }
 
+        self.dp[0] = LvEditNode::default();
         for j in 1..(len_trg + 1) {
             let idx = Self::idx_at(0, j, len_trg);
             self.dp[idx] = LvEditNode::new(
                 j as u8,
-                LvEdit::Add(j - 1, trg.graphemes[j - 1]),
+                LvEdit::Add(j - 1, trg.graphemes()[j - 1]),
                 Self::idx_at(0, j - 1, len_trg),
             );
         }
 
         for i in 1..(len_src + 1) {
-            let g1 = src.graphemes[i - 1];
-            for j in 1..(len_trg + 1) {
-                let g2 = trg.graphemes[j - 1];
+            let g1 = src.graphemes()[i - 1];
+            let from = i64::max(i as i64 + band_from, 1) as usize;
+            let to = i64::min(i as i64 + band_to, len_trg as i64);
+
+            let mut row_min = self.dp[Self::idx_at(i, 0, len_trg)].dist;
+            for j in from..(to + 1) as usize {
+                let g2 = trg.graphemes()[j - 1];
 
                 let idx_cur = Self::idx_at(i, j, len_trg);
                 if g1 == g2 {
                     let idx_prev = Self::idx_at(i - 1, j - 1, len_trg);
                     self.dp[idx_cur] = self.dp[idx_prev].clone();
+                    row_min = row_min.min(self.dp[idx_cur].dist);
                     continue;
                 }
 
@@ -406,25 +549,43 @@ impl LvEditDistanceMetric {
 
                 if node_sub.dist < node_del.dist && node_sub.dist < node_add.dist {
                     self.dp[idx_cur] = LvEditNode::new(
-                        node_sub.dist + 1,
-                        LvEdit::Sub(j - 1, trg.graphemes[j - 1]),
+                        node_sub.dist.saturating_add(1),
+                        LvEdit::Sub(j - 1, trg.graphemes()[j - 1]),
                         idx_sub,
                     );
                 } else if node_del.dist < node_add.dist {
-                    self.dp[idx_cur] =
-                        LvEditNode::new(node_del.dist + 1, LvEdit::Del(i - 1), idx_del);
+                    self.dp[idx_cur] = LvEditNode::new(
+                        node_del.dist.saturating_add(1),
+                        LvEdit::Del(i - 1),
+                        idx_del,
+                    );
                 } else {
                     self.dp[idx_cur] = LvEditNode::new(
-                        node_add.dist + 1,
-                        LvEdit::Add(j - 1, trg.graphemes[j - 1]),
+                        node_add.dist.saturating_add(1),
+                        LvEdit::Add(j - 1, trg.graphemes()[j - 1]),
                         idx_add,
                     );
                 }
+                row_min = row_min.min(self.dp[idx_cur].dist);
+            }
+
+            // the number of edits never decreases from one row to the next
+            if row_min as i64 > max_edits {
+                return None;
             }
         }
 
         let idx = Self::idx_at(len_src, len_trg, len_trg);
-        self.get_edit_list(idx)
+        if self.dp[idx].dist as i64 > max_edits {
+            return None;
+        }
+        Some(self.get_edit_list(idx))
+    }
+
+    /// Returns the cost of the edits, each edit weighted by its weight.
+    fn edits_cost(&self, edits: Vec<LvEdit>) -> f32 {
+        let (sub_count, del_count, add_count) = get_edits_counts(edits);
+        sub_count * self.sub_weight + del_count * self.del_weight + add_count * self.add_weight
     }
 }
 
@@ -450,16 +611,41 @@ fn get_edits_counts(edits: Vec<LvEdit>) -> (f32, f32, f32) {
 
 impl DistanceMetric<Word> for LvEditDistanceMetric {
     fn dist(&mut self, v1: &Word, v2: &Word) -> f32 {
-        let all_edits = self.compute_edits(v1, v2);
-        let (sub_count, del_count, add_count) = get_edits_counts(all_edits);
-        let edit_count =
-            sub_count * self.sub_weight + del_count * self.del_weight + add_count * self.add_weight;
-        let dist = 1.0 - edit_count / usize::max(v1.raw.len(), v2.raw.len()) as f32;
-        if self.use_sigmoid {
-            sigmoid(dist)
-        } else {
-            dist
-        }
+        let edits = self.compute_edits(v1, v2);
+        cost_to_dist(
+            self.edits_cost(edits),
+            usize::max(v1.raw().len(), v2.raw().len()),
+            self.use_sigmoid,
+        )
+    }
+
+    fn dist_bounded(&mut self, v1: &Word, v2: &Word, min_dist: f32) -> Option<f32> {
+        let len = usize::max(v1.raw().len(), v2.raw().len());
+        let min_weight = self.sub_weight.min(self.del_weight).min(self.add_weight);
+        let max_edits = match max_cost_for(min_dist, len, self.use_sigmoid) {
+            // the bounds only hold when no edit lowers the cost
+            Some(max_cost) if min_weight >= 0.0 => {
+                // the difference of length is made of deletions or additions
+                let (len_src, len_trg) = (v1.graphemes().len(), v2.graphemes().len());
+                let diff_cost = if len_src > len_trg {
+                    (len_src - len_trg) as f32 * self.del_weight
+                } else {
+                    (len_trg - len_src) as f32 * self.add_weight
+                };
+                if diff_cost > max_cost {
+                    return None;
+                }
+                if min_weight > 0.0 {
+                    (max_cost / min_weight).floor() as usize
+                } else {
+                    usize::MAX
+                }
+            }
+            _ => usize::MAX,
+        };
+        let edits = self.compute_edits_within(v1, v2, max_edits)?;
+        let dist = cost_to_dist(self.edits_cost(edits), len, self.use_sigmoid);
+        (dist >= min_dist).then_some(dist)
     }
 
     fn clone(&self) -> Box<dyn DistanceMetric<Word> + Send + Sync> {
@@ -512,8 +698,8 @@ impl LvSubstringDistanceMetric {
     }
 
     fn compute_edits<'a>(&mut self, mut w1: &'a Word, mut w2: &'a Word) -> (u8, u8) {
-        let mut len_w1 = w1.graphemes.len();
-        let mut len_w2 = w2.graphemes.len();
+        let mut len_w1 = w1.graphemes().len();
+        let mut len_w2 = w2.graphemes().len();
 
         // w1 must be the largest word
         if len_w1 < len_w2 {
@@ -537,11 +723,11 @@ impl LvSubstringDistanceMetric {
         let mut longest = 0;
 
         for i in 1..(len_w1 + 1) {
-            let g1 = w1.graphemes[i - 1];
+            let g1 = w1.graphemes()[i - 1];
 
             let to = usize::min(i + 1, len_w2 + 1);
             for j in 1..(to) {
-                let g2 = w2.graphemes[j - 1];
+                let g2 = w2.graphemes()[j - 1];
 
                 let idx_cur = Self::idx_at(i, j, len_w2);
                 if g1 == g2 {
@@ -580,12 +766,26 @@ impl DistanceMetric<Word> for LvSubstringDistanceMetric {
         let (edits, longest_substring) = self.compute_edits(v1, v2);
         let bonus = longest_substring as f32 * self.weight;
         let edits = f32::max(edits as f32 - bonus, 0.0);
-        let dist = 1.0 - edits / usize::max(v1.raw.len(), v2.raw.len()) as f32;
-        if self.use_sigmoid {
-            sigmoid(dist)
-        } else {
-            dist
+        cost_to_dist(
+            edits,
+            usize::max(v1.raw().len(), v2.raw().len()),
+            self.use_sigmoid,
+        )
+    }
+
+    fn dist_bounded(&mut self, v1: &Word, v2: &Word, min_dist: f32) -> Option<f32> {
+        let len = usize::max(v1.raw().len(), v2.raw().len());
+        if let Some(max_cost) = max_cost_for(min_dist, len, self.use_sigmoid) {
+            // there are at least as many edits as the difference of length and
+            // the longest substring is at most the shortest word
+            let (len1, len2) = (v1.graphemes().len(), v2.graphemes().len());
+            let max_bonus = usize::min(len1, len2) as f32 * self.weight.max(0.0);
+            if len1.abs_diff(len2) as f32 - max_bonus > max_cost {
+                return None;
+            }
         }
+        let dist = self.dist(v1, v2);
+        (dist >= min_dist).then_some(dist)
     }
 
     fn clone(&self) -> Box<dyn DistanceMetric<Word> + Send + Sync> {
@@ -596,6 +796,261 @@ impl DistanceMetric<Word> for LvSubstringDistanceMetric {
     }
 }
 
+/// Bitmasks of the positions of each grapheme in the pattern, one mask
+/// per block of 64 graphemes.
+///
+/// The ASCII graphemes are looked up in a table, the others in a hashmap.
+struct PatternMasks {
+    ascii: [u32; 128],
+    others: HashMap<GraphemeType, u32>,
+    masks: Vec<u64>,
+    num_blocks: usize,
+}
+
+impl PatternMasks {
+    /// Index of the graphemes absent from the pattern.
+    const ABSENT: u32 = 0;
+
+    fn new() -> Self {
+        Self {
+            ascii: [Self::ABSENT; 128],
+            others: HashMap::new(),
+            masks: Vec::new(),
+            num_blocks: 0,
+        }
+    }
+
+    /// Builds the masks of the pattern, the previous pattern is discarded.
+    fn build(&mut self, pattern: &[GraphemeType]) {
+        self.ascii.fill(Self::ABSENT);
+        self.others.clear();
+        self.num_blocks = pattern.len().div_ceil(64);
+        // the masks of the absent graphemes are all zeros
+        self.masks.clear();
+        self.masks.resize(self.num_blocks, 0);
+
+        for (i, &g) in pattern.iter().enumerate() {
+            let idx = match self.index(g) {
+                Self::ABSENT => {
+                    let idx = (self.masks.len() / self.num_blocks) as u32;
+                    self.masks.resize(self.masks.len() + self.num_blocks, 0);
+                    if g < 128 {
+                        self.ascii[g as usize] = idx;
+                    } else {
+                        self.others.insert(g, idx);
+                    }
+                    idx
+                }
+                idx => idx,
+            };
+            self.masks[idx as usize * self.num_blocks + i / 64] |= 1 << (i % 64);
+        }
+    }
+
+    fn index(&self, g: GraphemeType) -> u32 {
+        if g < 128 {
+            self.ascii[g as usize]
+        } else {
+            self.others.get(&g).copied().unwrap_or(Self::ABSENT)
+        }
+    }
+
+    /// Returns the masks of the blocks of the grapheme.
+    fn get(&self, g: GraphemeType) -> &[u64] {
+        let idx = self.index(g) as usize * self.num_blocks;
+        &self.masks[idx..idx + self.num_blocks]
+    }
+}
+
+/// Bit-parallel Levenshtein Distance Metric
+///
+/// This metric computes the exact Levenshtein distance between the graphemes of
+/// two words with the bit-parallel algorithm of Myers (in the formulation of Hyyrö).
+/// A column of the DP table is encoded as bit vectors of the vertical deltas,
+/// such that a column is computed in a few word operations instead of one
+/// operation per cell.
+///
+/// Words of up to 64 graphemes fit in a single block, longer words are split
+/// in blocks of 64 graphemes, processed one after the other for each column.
+pub struct LvBitParallelDistanceMetric {
+    pattern_masks: PatternMasks,
+    pv: Vec<u64>,
+    mv: Vec<u64>,
+    use_sigmoid: bool,
+}
+
+impl LvBitParallelDistanceMetric {
+    pub fn new(use_sigmoid: bool) -> Self {
+        Self {
+            pattern_masks: PatternMasks::new(),
+            pv: Vec::new(),
+            mv: Vec::new(),
+            use_sigmoid,
+        }
+    }
+
+    /// Computes the number of edits between the graphemes of two words.
+    pub fn compute_edits(&mut self, w1: &[GraphemeType], w2: &[GraphemeType]) -> u32 {
+        self.compute_edits_within(w1, w2, u32::MAX).unwrap()
+    }
+
+    /// Computes the number of edits between the graphemes of two words,
+    /// stops early and returns None as soon as it exceeds `max_edits`.
+    pub fn compute_edits_within(
+        &mut self,
+        w1: &[GraphemeType],
+        w2: &[GraphemeType],
+        max_edits: u32,
+    ) -> Option<u32> {
+        // the shortest word is the pattern, to minimize the number of blocks
+        let (pattern, text) = if w1.len() <= w2.len() {
+            (w1, w2)
+        } else {
+            (w2, w1)
+        };
+        if (text.len() - pattern.len()) as u64 > max_edits as u64 {
+            return None;
+        }
+        if pattern.is_empty() {
+            return Some(text.len() as u32);
+        }
+
+        self.pattern_masks.build(pattern);
+        if pattern.len() <= 64 {
+            self.compute_edits_single_block(pattern.len(), text, max_edits)
+        } else {
+            self.compute_edits_blocks(pattern.len(), text, max_edits)
+        }
+    }
+
+    /// Returns whether the distance can't be within `max_edits` anymore
+    /// given the score after `j` graphemes of the text: each of the
+    /// remaining graphemes decreases the score by at most one.
+    fn exceeds(score: u32, j: usize, len_text: usize, max_edits: u32) -> bool {
+        score as u64 > max_edits as u64 + (len_text - j) as u64
+    }
+
+    fn compute_edits_single_block(
+        &self,
+        len_pattern: usize,
+        text: &[GraphemeType],
+        max_edits: u32,
+    ) -> Option<u32> {
+        let last = 1u64 << (len_pattern - 1);
+        let mut pv = u64::MAX;
+        let mut mv = 0u64;
+        let mut score = len_pattern as u32;
+
+        for (j, &g) in text.iter().enumerate() {
+            let eq = self.pattern_masks.get(g)[0];
+            let xv = eq | mv;
+            let xh = ((eq & pv).wrapping_add(pv) ^ pv) | eq;
+            let mut ph = mv | !(xh | pv);
+            let mut mh = pv & xh;
+
+            if ph & last != 0 {
+                score += 1;
+            } else if mh & last != 0 {
+                score -= 1;
+            }
+            if Self::exceeds(score, j + 1, text.len(), max_edits) {
+                return None;
+            }
+
+            // the first row increases by one at each column
+            ph = (ph << 1) | 1;
+            mh <<= 1;
+            pv = mh | !(xv | ph);
+            mv = ph & xv;
+        }
+        Some(score)
+    }
+
+    fn compute_edits_blocks(
+        &mut self,
+        len_pattern: usize,
+        text: &[GraphemeType],
+        max_edits: u32,
+    ) -> Option<u32> {
+        let num_blocks = self.pattern_masks.num_blocks;
+        let last = 1u64 << ((len_pattern - 1) % 64);
+        self.pv.clear();
+        self.pv.resize(num_blocks, u64::MAX);
+        self.mv.clear();
+        self.mv.resize(num_blocks, 0);
+        let mut score = len_pattern as u32;
+
+        for (j, &g) in text.iter().enumerate() {
+            let masks = self.pattern_masks.get(g);
+            // horizontal delta entering the block, the first row increases by one
+            let mut hin: i8 = 1;
+
+            for (b, &mask) in masks.iter().enumerate() {
+                let pv = self.pv[b];
+                let mv = self.mv[b];
+                let mut eq = mask;
+
+                let xv = eq | mv;
+                if hin < 0 {
+                    eq |= 1;
+                }
+                let xh = ((eq & pv).wrapping_add(pv) ^ pv) | eq;
+                let mut ph = mv | !(xh | pv);
+                let mut mh = pv & xh;
+
+                let out = if b + 1 == num_blocks { last } else { 1 << 63 };
+                let hout = if ph & out != 0 {
+                    1
+                } else if mh & out != 0 {
+                    -1
+                } else {
+                    0
+                };
+
+                ph <<= 1;
+                mh <<= 1;
+                if hin < 0 {
+                    mh |= 1;
+                } else if hin > 0 {
+                    ph |= 1;
+                }
+                self.pv[b] = mh | !(xv | ph);
+                self.mv[b] = ph & xv;
+                hin = hout;
+            }
+
+            score = (score as i64 + hin as i64) as u32;
+            if Self::exceeds(score, j + 1, text.len(), max_edits) {
+                return None;
+            }
+        }
+        Some(score)
+    }
+}
+
+impl DistanceMetric<Word> for LvBitParallelDistanceMetric {
+    fn dist(&mut self, v1: &Word, v2: &Word) -> f32 {
+        let edits = self.compute_edits(v1.graphemes(), v2.graphemes());
+        edits_to_dist(
+            edits,
+            usize::max(v1.raw().len(), v2.raw().len()),
+            self.use_sigmoid,
+        )
+    }
+
+    fn dist_bounded(&mut self, v1: &Word, v2: &Word, min_dist: f32) -> Option<f32> {
+        let len = usize::max(v1.raw().len(), v2.raw().len());
+        let max_edits = max_edits_for(min_dist, len, self.use_sigmoid).unwrap_or(u32::MAX);
+        let edits = self.compute_edits_within(v1.graphemes(), v2.graphemes(), max_edits)?;
+        let dist = edits_to_dist(edits, len, self.use_sigmoid);
+        (dist >= min_dist).then_some(dist)
+    }
+
+    fn clone(&self) -> Box<dyn DistanceMetric<Word> + Send + Sync> {
+        Box::new(LvBitParallelDistanceMetric::new(self.use_sigmoid))
+    }
+}
+
 pub struct LvMultiWordDistanceMetric {
     lvopti: LvOptiDistanceMetric,
     separator: GraphemeType,
@@ -611,27 +1066,24 @@ impl LvMultiWordDistanceMetric {
         }
     }
 
-    fn extract_multiple_words(w: &Word, separator: GraphemeType) -> Vec<Vec<GraphemeType>> {
-        let mut ret: Vec<Vec<GraphemeType>> = Vec::new();
-        let mut temp: Vec<GraphemeType> = Vec::new();
-
-        for g in &w.graphemes {
-            if *g == separator {
-                if !temp.is_empty() {
-                    ret.push(temp);
-                }
-                temp = Vec::new();
-            } else {
-                temp.push(*g);
-            }
-        }
-        if !temp.is_empty() {
-            ret.push(temp);
-        }
-        ret
+    fn extract_multiple_words(w: &Word, separator: GraphemeType) -> Vec<&[GraphemeType]> {
+        w.graphemes()
+            .split(|g| *g == separator)
+            .filter(|graphemes| !graphemes.is_empty())
+            .collect()
     }
 
     fn compute_edits(&mut self, w1: &Word, w2: &Word) -> u8 {
+        self.compute_edits_within(w1, w2, usize::MAX).unwrap()
+    }
+
+    /// Computes the number of edits between two multi-words, returns None
+    /// as soon as it is known to exceed `max_edits`.
+    ///
+    /// The edits of the words are scaled by the ratio of imperfect matches, which
+    /// only grows with the next words, such that the words are compared with a bound
+    /// and the computation stops once the edits so far exceed `max_edits`.
+    fn compute_edits_within(&mut self, w1: &Word, w2: &Word, max_edits: usize) -> Option<u8> {
         let multi_w1 = Self::extract_multiple_words(w1, self.separator);
         let multi_w2 = Self::extract_multiple_words(w2, self.separator);
 
@@ -642,33 +1094,51 @@ impl LvMultiWordDistanceMetric {
         let len2 = multi_w2.len();
 
         if !(len1 == len2 && len1 > 1) {
-            return self.lvopti.compute_edits(w1, w2);
+            return self
+                .lvopti
+                .compute_edits_within(w1.graphemes(), w2.graphemes(), max_edits);
         }
+        // the ratio of imperfect matches is at least 1 / len1 once a word doesn't match
+        let max_total = max_edits.saturating_add(1).saturating_mul(len1);
         for i in 0..len1 {
-            let w1 = &Word::from_graphemes(multi_w1[i].clone());
-            let w2 = &Word::from_graphemes(multi_w2[i].clone());
-
-            let edits = self.lvopti.compute_edits(w1, w2);
+            let edits = self
+                .lvopti
+                .compute_edits_within(multi_w1[i], multi_w2[i], max_total)?;
             if edits == 0 {
                 perfect_matches += 1;
             }
             total += edits;
+
+            // strictly above the bound, such that the rounding can't bring it back
+            let imperfect_matches = i + 1 - perfect_matches;
+            if (total as usize).saturating_mul(imperfect_matches) > max_total {
+                return None;
+            }
         }
 
         let factor = 1.0 - (perfect_matches as f32) / (len1 as f32);
-        return (total as f32 * factor) as u8;
+        let edits = (total as f32 * factor) as u8;
+        (edits as usize <= max_edits).then_some(edits)
     }
 }
 
 impl DistanceMetric<Word> for LvMultiWordDistanceMetric {
     fn dist(&mut self, v1: &Word, v2: &Word) -> f32 {
         let edits = self.compute_edits(v1, v2);
-        let dist = 1.0 - edits as f32 / usize::max(v1.raw.len(), v2.raw.len()) as f32;
-        if self.use_sigmoid {
-            sigmoid(dist)
-        } else {
-            dist
-        }
+        edits_to_dist(
+            edits as u32,
+            usize::max(v1.raw().len(), v2.raw().len()),
+            self.use_sigmoid,
+        )
+    }
+
+    fn dist_bounded(&mut self, v1: &Word, v2: &Word, min_dist: f32) -> Option<f32> {
+        let len = usize::max(v1.raw().len(), v2.raw().len());
+        let max_edits =
+            max_edits_for(min_dist, len, self.use_sigmoid).map_or(usize::MAX, |e| e as usize);
+        let edits = self.compute_edits_within(v1, v2, max_edits)?;
+        let dist = edits_to_dist(edits as u32, len, self.use_sigmoid);
+        (dist >= min_dist).then_some(dist)
     }
 
     fn clone(&self) -> Box<dyn DistanceMetric<Word> + Send + Sync> {
@@ -681,6 +1151,8 @@ impl DistanceMetric<Word> for LvMultiWordDistanceMetric {
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
     use super::*;
 
     // Helper function to create a Word instance from a string
@@ -737,38 +1209,38 @@ mod tests {
         // Test identical words
         let w1 = create_word("hello");
         let w2 = create_word("hello");
-        let distance = metric.compute_edits(&w1, &w2);
+        let distance = metric.compute_edits(w1.graphemes(), w2.graphemes());
         assert_eq!(distance, 0);
         // Test one empty word
         let w2 = create_word("");
-        let distance = metric.compute_edits(&w1, &w2);
+        let distance = metric.compute_edits(w1.graphemes(), w2.graphemes());
         assert_eq!(distance, 5);
 
         // Test single character difference
         let w2 = create_word("hallo");
-        let distance = metric.compute_edits(&w1, &w2);
+        let distance = metric.compute_edits(w1.graphemes(), w2.graphemes());
         assert_eq!(distance, 1);
 
         // Test different lengths
         let w2 = create_word("helloworld");
-        let distance = metric.compute_edits(&w1, &w2);
+        let distance = metric.compute_edits(w1.graphemes(), w2.graphemes());
         assert_eq!(distance, 5);
 
         // Test partial overlap
         let w1 = create_word("bernart");
         let w2 = create_word("jeanbernard");
-        let distance = metric.compute_edits(&w1, &w2);
+        let distance = metric.compute_edits(w1.graphemes(), w2.graphemes());
         assert_eq!(distance, 5);
 
         // Test case sensitivity
         let w1 = create_word("Hello");
         let w2 = create_word("hello");
-        let distance = metric.compute_edits(&w1, &w2);
+        let distance = metric.compute_edits(w1.graphemes(), w2.graphemes());
         assert_eq!(distance, 1); // Case-sensitive comparison
 
         let w1 = create_word("Bernard");
         let w2 = create_word("bBeernard");
-        let distance = metric.compute_edits(&w1, &w2);
+        let distance = metric.compute_edits(w1.graphemes(), w2.graphemes());
         assert_eq!(distance, 2);
     }
 
@@ -881,4 +1353,118 @@ mod tests {
         let distance = metric.compute_edits(&w1, &w2);
         assert_eq!(distance, 7);
     }
+
+    #[test]
+    fn test_lv_bit_parallel_metric() {
+        let mut metric = LvBitParallelDistanceMetric::new(false);
+        let mut reference = LvDistanceMetric::new(false);
+
+        let w1 = create_word("bernart");
+        let w2 = create_word("jeanbernard");
+        assert_eq!(metric.compute_edits(w1.graphemes(), w2.graphemes()), 5);
+        let w2 = create_word("");
+        assert_eq!(metric.compute_edits(w1.graphemes(), w2.graphemes()), 7);
+        let w1 = create_word("xabc");
+        let w2 = create_word("abcx");
+        assert_eq!(metric.compute_edits(w1.graphemes(), w2.graphemes()), 2);
+
+        // random words over a small alphabet, up to 3 blocks
+        let mut rng = StdRng::seed_from_u64(0);
+        let alphabet = ["a", "b", "c", "é"];
+        let mut random_word = |max_len: usize| {
+            let len = rng.random_range(0..max_len);
+            create_word(
+                &(0..len)
+                    .map(|_| alphabet[rng.random_range(0..alphabet.len())])
+                    .collect::<String>(),
+            )
+        };
+        for max_len in [10, 64, 80, 200] {
+            for _ in 0..50 {
+                let w1 = random_word(max_len);
+                let w2 = random_word(max_len);
+                let expected = reference.compute_edits(&w1, &w2);
+                assert_eq!(
+                    metric.compute_edits(w1.graphemes(), w2.graphemes()),
+                    expected,
+                    "{:?} {:?}",
+                    w1,
+                    w2
+                );
+                assert_eq!(
+                    metric.compute_edits_within(w1.graphemes(), w2.graphemes(), expected),
+                    Some(expected)
+                );
+                if expected > 0 {
+                    assert_eq!(
+                        metric.compute_edits_within(w1.graphemes(), w2.graphemes(), expected - 1),
+                        None
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dist_bounded() {
+        let mut metrics: Vec<Box<dyn DistanceMetric<Word> + Send + Sync>> = vec![];
+        for use_sigmoid in [false, true] {
+            metrics.push(Box::new(LvDistanceMetric::new(use_sigmoid)));
+            metrics.push(Box::new(LvOptiDistanceMetric::new(use_sigmoid)));
+            metrics.push(Box::new(LvBitParallelDistanceMetric::new(use_sigmoid)));
+            metrics.push(Box::new(LvSubstringDistanceMetric::new(0.5, use_sigmoid)));
+            metrics.push(Box::new(LvEditDistanceMetric::new(1., 1., 1., use_sigmoid)));
+            metrics.push(Box::new(LvEditDistanceMetric::new(
+                0.5,
+                1.5,
+                1.,
+                use_sigmoid,
+            )));
+            metrics.push(Box::new(LvEditDistanceMetric::new(1., 0., 2., use_sigmoid)));
+            metrics.push(Box::new(LvMultiWordDistanceMetric::new(
+                Word::string_to_grapheme(" "),
+                use_sigmoid,
+            )));
+        }
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let alphabet = ["a", "b", "c", "é"];
+        let mut random_word = |max_len: usize| {
+            (0..rng.random_range(1..max_len))
+                .map(|_| alphabet[rng.random_range(0..alphabet.len())])
+                .collect::<String>()
+        };
+        let mut words = (0..60)
+            .map(|_| create_word(&random_word(20)))
+            .collect::<Vec<_>>();
+        // pairs of multi-words with the same number of words
+        for _ in 0..20 {
+            let num_words = 2 + words.len() % 2;
+            for _ in 0..2 {
+                let multi_word = (0..num_words)
+                    .map(|_| random_word(6))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                words.push(create_word(&multi_word));
+            }
+        }
+
+        // the distance is returned if and only if it reaches the bound
+        for metric in metrics.iter_mut() {
+            for pair in words.chunks(2) {
+                let dist = metric.dist(&pair[0], &pair[1]);
+                for min_dist in [-0.5, 0.0, 0.2, 0.5, 0.7, 0.9, 1.0, dist] {
+                    let expected = (dist >= min_dist).then_some(dist);
+                    assert_eq!(
+                        metric.dist_bounded(&pair[0], &pair[1], min_dist),
+                        expected,
+                        "{:?} {:?} {}",
+                        pair[0],
+                        pair[1],
+                        min_dist
+                    );
+                }
+            }
+        }
+    }
 }
@@ -1,74 +1,469 @@
-use std::{collections::HashMap, hash::Hash};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use crate::word::Word;
+use crate::{
+    error::Result,
+    word::{GraphemeType, Word},
+};
 
-use super::{LvEdit, LvEditDistanceMetric};
+use super::{LvBitParallelDistanceMetric, LvEdit, LvEditDistanceMetric};
+
+/// Maximal number of iterations of the refinement of the median word,
+/// each iteration decreases the sum of distances by at least one edit.
+const MAX_REFINEMENT_ITERATIONS: usize = 8;
+
+/// Strategy to compute the median word of a list of words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MedianWordStrategy {
+    /// Most frequent grapheme at each position once the words are aligned,
+    /// see `compute_median_word`, this is a fast approximation.
+    Graphemes,
+    /// Word of the list with the minimal sum of distances to the words of the list.
+    SetMedian,
+    /// Local search around the set median for a word, not necessarily in the list,
+    /// with a smaller sum of distances, closer to the generalized median.
+    RefinedMedian,
+}
 
 /// Computes the median word of a list of words.
-pub fn compute_median_word(words: &Vec<&Word>) -> Option<Word> {
-    if words.is_empty() {
-        return None;
+///
+/// The words of the most frequent length are kept as is, the other words
+/// are aligned with the first of them (the anchor). Then each grapheme of the
+/// median word is the most frequent grapheme at that position.
+///
+/// The ties are broken by the smallest length and grapheme, such that
+/// the result is deterministic.
+///
+/// # Errors
+/// Returns an InvalidData error if all the word IDs have been used.
+#[cfg(any(test, feature = "benchmark"))]
+pub fn compute_median_word(words: &Vec<&Word>) -> Result<Option<Word>> {
+    let mut median_word = IncrementalMedianWord::new();
+    for word in words.iter() {
+        median_word.push(word);
+    }
+    median_word.median()
+}
+
+/// Computes the median word of a list of weighted words.
+///
+/// Same as `compute_median_word`, except that each word votes with its weight
+/// instead of one, both for the length and the graphemes of the median word.
+/// The weights are typically the confidence of the word (e.g. the score of the
+/// match it comes from) or its recency, the negative weights count as zero.
+///
+/// # Errors
+/// Returns an InvalidData error if all the word IDs have been used.
+pub fn compute_weighted_median_word(words: &[(&Word, f32)]) -> Result<Option<Word>> {
+    let mut median_word = IncrementalMedianWord::new();
+    for (word, weight) in words.iter() {
+        median_word.push_weighted(word, *weight);
+    }
+    median_word.median()
+}
+
+/// Computes the median word of a list of words with the given strategy.
+///
+/// The result is deterministic, the set and refined medians don't depend
+/// on the order of the words.
+///
+/// # Errors
+/// Returns an InvalidData error if all the word IDs have been used.
+pub fn compute_median_word_with_strategy(
+    words: &[&Word],
+    strategy: MedianWordStrategy,
+) -> Result<Option<Word>> {
+    let mut median_word = MedianWordBuilder::new(strategy);
+    for word in words.iter() {
+        median_word.push(word);
     }
+    median_word.median()
+}
 
-    let mut distance_metric = LvEditDistanceMetric::new(1., 1., 1., false);
+/// MedianWordBuilder
+///
+/// Median word of words added one by one, with any strategy.
+#[derive(Clone)]
+pub enum MedianWordBuilder {
+    Graphemes(IncrementalMedianWord),
+    Set(IncrementalSetMedianWord),
+}
 
-    // compute most frequent length
-    let mfl = most_frequent(words.iter().map(|w| w.graphemes.len())).unwrap();
+impl MedianWordBuilder {
+    pub fn new(strategy: MedianWordStrategy) -> Self {
+        match strategy {
+            MedianWordStrategy::Graphemes => Self::Graphemes(IncrementalMedianWord::new()),
+            MedianWordStrategy::SetMedian => Self::Set(IncrementalSetMedianWord::new(false)),
+            MedianWordStrategy::RefinedMedian => Self::Set(IncrementalSetMedianWord::new(true)),
+        }
+    }
 
-    // collect words with most frequent length
-    let mfl_words: Vec<&Word> = words
-        .iter()
-        .filter_map(|w| {
-            if w.graphemes.len() == mfl {
-                Some(*w)
-            } else {
-                None
+    /// Adds a word.
+    pub fn push(&mut self, word: &Word) {
+        match self {
+            Self::Graphemes(median_word) => median_word.push(word),
+            Self::Set(median_word) => median_word.push(word),
+        }
+    }
+
+    /// Adds a word with a weight, see `compute_weighted_median_word`.
+    ///
+    /// # Panics
+    /// Panics if the strategy is not the graphemes strategy, the set medians
+    /// don't support the weights (this is checked by the config validation).
+    pub fn push_weighted(&mut self, word: &Word, weight: f32) {
+        match self {
+            Self::Graphemes(median_word) => median_word.push_weighted(word, weight),
+            Self::Set(_) => panic!("the set medians don't support the weights"),
+        }
+    }
+
+    /// Returns the median word of the words added so far, None if there is none.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn median(&mut self) -> Result<Option<Word>> {
+        match self {
+            Self::Graphemes(median_word) => median_word.median(),
+            Self::Set(median_word) => median_word.median(),
+        }
+    }
+}
+
+/// Votes (sum of the weights of the words) for the graphemes at each position
+/// of words of the same length.
+type PositionCounts = Vec<HashMap<GraphemeType, f32>>;
+
+/// Adds the graphemes of a word with the given weight to the counts,
+/// the placeholder graphemes are ignored.
+fn count_graphemes(counts: &mut PositionCounts, graphemes: &[GraphemeType], weight: f32) {
+    for (position_counts, &grapheme) in counts.iter_mut().zip(graphemes.iter()) {
+        if grapheme != 0 {
+            *position_counts.entry(grapheme).or_insert(0.) += weight;
+        }
+    }
+}
+
+/// Multiplies the votes of the graphemes by the factor.
+fn scale_counts(counts: &mut PositionCounts, factor: f32) {
+    for position_counts in counts.iter_mut() {
+        for count in position_counts.values_mut() {
+            *count *= factor;
+        }
+    }
+}
+
+/// Words of the same length.
+#[derive(Clone)]
+struct LengthGroup {
+    /// First word of this length, the words of the other lengths are aligned
+    /// with it when this length is the most frequent one.
+    anchor: Word,
+    /// Sum of the weights of the words of this length
+    weight: f32,
+    counts: PositionCounts,
+}
+
+/// Counts of the words of the other lengths, aligned with the anchor
+/// of the most frequent length.
+#[derive(Clone)]
+struct AlignedCounts {
+    length: usize,
+    /// Number of words, in insertion order, already aligned
+    num_aligned: usize,
+    counts: PositionCounts,
+}
+
+/// IncrementalMedianWord
+///
+/// Computes the same median word as `compute_median_word` as the words are added,
+/// without recomputing it from scratch.
+///
+/// The words are counted position by position, grouped by length. Each word of
+/// another length is aligned with the most frequent length only once, the alignments
+/// are redone only when the most frequent length changes, which becomes rare
+/// as the number of words grows.
+#[derive(Clone, Default)]
+pub struct IncrementalMedianWord {
+    /// Words added, with their weight
+    words: Vec<(Word, f32)>,
+    groups: BTreeMap<usize, LengthGroup>,
+    aligned: Option<AlignedCounts>,
+}
+
+impl IncrementalMedianWord {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a word.
+    pub fn push(&mut self, word: &Word) {
+        self.push_weighted(word, 1.);
+    }
+
+    /// Adds a word with a weight, see `compute_weighted_median_word`.
+    pub fn push_weighted(&mut self, word: &Word, weight: f32) {
+        let weight = weight.max(0.);
+        let graphemes = word.graphemes();
+        let group = self
+            .groups
+            .entry(graphemes.len())
+            .or_insert_with(|| LengthGroup {
+                anchor: word.clone(),
+                weight: 0.,
+                counts: vec![HashMap::new(); graphemes.len()],
+            });
+        group.weight += weight;
+        count_graphemes(&mut group.counts, graphemes, weight);
+        self.words.push((word.clone(), weight));
+    }
+
+    /// Multiplies the weights of all the words added so far by the factor,
+    /// which doesn't change the median for a positive factor.
+    ///
+    /// This allows to weight the new words relatively to the previous ones
+    /// while keeping the weights within the range of f32.
+    pub fn scale_weights(&mut self, factor: f32) {
+        for (_, weight) in self.words.iter_mut() {
+            *weight *= factor;
+        }
+        for group in self.groups.values_mut() {
+            group.weight *= factor;
+            scale_counts(&mut group.counts, factor);
+        }
+        if let Some(aligned) = self.aligned.as_mut() {
+            scale_counts(&mut aligned.counts, factor);
+        }
+    }
+
+    /// Returns the median word of the words added so far, None if there is none.
+    ///
+    /// Only the words added since the last call are aligned, unless the most
+    /// frequent length changed.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn median(&mut self) -> Result<Option<Word>> {
+        // most frequent length, the smallest one in case of a tie
+        let Some((&length, group)) = self
+            .groups
+            .iter()
+            .max_by(|(l1, g1), (l2, g2)| g1.weight.total_cmp(&g2.weight).then(l2.cmp(l1)))
+        else {
+            return Ok(None);
+        };
+
+        if self.aligned.as_ref().map(|a| a.length) != Some(length) {
+            self.aligned = Some(AlignedCounts {
+                length,
+                num_aligned: 0,
+                counts: vec![HashMap::new(); length],
+            });
+        }
+        let aligned = self.aligned.as_mut().unwrap();
+
+        let mut distance_metric = LvEditDistanceMetric::new(1., 1., 1., false);
+        for (word, weight) in self.words[aligned.num_aligned..].iter() {
+            if word.graphemes().len() != length {
+                let edits = distance_metric.compute_edits(word, &group.anchor);
+                count_graphemes(
+                    &mut aligned.counts,
+                    &perform_add_del_edits(word, &edits),
+                    *weight,
+                );
             }
-        })
-        .collect();
+        }
+        aligned.num_aligned = self.words.len();
 
-    // align words with different length
-    let mut aligned_words: Vec<Word> = Vec::new();
-    for word in words.iter() {
-        if word.graphemes.len() != mfl {
-            let aligned_word = align_word(&mut distance_metric, word, &mfl_words);
-            aligned_words.push(aligned_word);
+        compute_most_frequent_graphemes_word(&group.counts, &aligned.counts).map(Some)
+    }
+}
+
+/// Distinct word of the set, with the sum of its distances to all the words.
+#[derive(Clone)]
+struct SetMember {
+    word: Word,
+    count: u32,
+    sum_distances: u32,
+}
+
+/// IncrementalSetMedianWord
+///
+/// Computes the set median of the words as they are added: the word of the set
+/// with the minimal sum of (Levenshtein) distances to all the words.
+///
+/// The sum of distances of each distinct word is kept up to date, adding a word
+/// only computes its distances to the distinct words.
+///
+/// If `refine` is set, the set median is the starting point of a local search
+/// for a word with a smaller sum of distances, see `refine_median_word`.
+///
+/// The median is cached, it is only recomputed (and refined) when
+/// words have been added since the last call.
+#[derive(Clone)]
+pub struct IncrementalSetMedianWord {
+    members: Vec<SetMember>,
+    refine: bool,
+    median_word: Option<Word>,
+    /// Whether words have been added since the median was computed
+    is_dirty: bool,
+}
+
+impl IncrementalSetMedianWord {
+    pub fn new(refine: bool) -> Self {
+        Self {
+            members: Vec::new(),
+            refine,
+            median_word: None,
+            is_dirty: false,
+        }
+    }
+
+    /// Adds a word.
+    pub fn push(&mut self, word: &Word) {
+        self.is_dirty = true;
+        let mut distance_metric = LvBitParallelDistanceMetric::new(false);
+        let mut sum_distances = 0;
+        let mut member_idx = None;
+        for (idx, member) in self.members.iter_mut().enumerate() {
+            if member.word == *word {
+                member_idx = Some(idx);
+                continue;
+            }
+            let dist = distance_metric.compute_edits(word.graphemes(), member.word.graphemes());
+            member.sum_distances += dist;
+            sum_distances += member.count * dist;
+        }
+        match member_idx {
+            Some(idx) => self.members[idx].count += 1,
+            None => self.members.push(SetMember {
+                word: word.clone(),
+                count: 1,
+                sum_distances,
+            }),
+        }
+    }
+
+    /// Returns the (refined) set median of the words added so far, None if there is none.
+    ///
+    /// The ties are broken by the smallest graphemes.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn median(&mut self) -> Result<Option<Word>> {
+        if self.is_dirty {
+            self.median_word = self.compute_median()?;
+            self.is_dirty = false;
         }
+        Ok(self.median_word.clone())
     }
 
-    // collect all words
-    let mut words = Vec::new();
-    words.extend(mfl_words);
-    words.extend(aligned_words.iter().map(|w| w));
+    fn compute_median(&self) -> Result<Option<Word>> {
+        let Some(set_median) = self.members.iter().min_by(|m1, m2| {
+            m1.sum_distances
+                .cmp(&m2.sum_distances)
+                .then(m1.word.graphemes().cmp(m2.word.graphemes()))
+        }) else {
+            return Ok(None);
+        };
+        if !self.refine {
+            return Ok(Some(set_median.word.clone()));
+        }
 
-    // compute median with most frequent graphemes
-    Some(compute_most_frequent_graphemes_word(words))
+        let graphemes = refine_median_word(
+            &self.members,
+            set_median.word.graphemes().to_vec(),
+            set_median.sum_distances,
+        );
+        Word::from_graphemes(graphemes).map(Some)
+    }
 }
 
-/// Returns the most frequent element in an iterator or None if the iterator is empty.
+/// Returns the sum of distances between the graphemes and the words of the set,
+/// None as soon as it exceeds `max_sum`.
+fn sum_distances_within(
+    distance_metric: &mut LvBitParallelDistanceMetric,
+    members: &[SetMember],
+    graphemes: &[GraphemeType],
+    max_sum: u32,
+) -> Option<u32> {
+    let mut sum = 0;
+    for member in members.iter() {
+        sum += member.count * distance_metric.compute_edits(graphemes, member.word.graphemes());
+        if sum > max_sum {
+            return None;
+        }
+    }
+    Some(sum)
+}
+
+/// Refines the median word by a local search: at each iteration, all the words at
+/// one edit (substitution, insertion or deletion of a grapheme of the set) of the
+/// current word are evaluated, the best one replaces the current word if it has
+/// a smaller sum of distances to the words of the set.
 ///
-/// In there are multiple elements with the same frequency, no guarantee is made about
-/// which one is returned.
-fn most_frequent<T: Hash + Eq + Clone>(iter: impl Iterator<Item = T>) -> Option<T> {
-    let mut max_count = 0;
-    let mut mfv = None;
-    let mut counts = HashMap::new();
-    for v in iter {
-        let mut count = 1;
-        counts
-            .entry(v.clone())
-            .and_modify(|c| {
-                *c += 1;
-                count = *c;
-            })
-            .or_insert(1);
+/// The candidates are evaluated in a fixed order and the ties are broken by the
+/// smallest graphemes, such that the result is deterministic.
+fn refine_median_word(
+    members: &[SetMember],
+    mut current: Vec<GraphemeType>,
+    mut current_sum: u32,
+) -> Vec<GraphemeType> {
+    let alphabet = members
+        .iter()
+        .flat_map(|m| m.word.graphemes().iter().copied())
+        .collect::<BTreeSet<GraphemeType>>();
+    let mut distance_metric = LvBitParallelDistanceMetric::new(false);
+
+    for _ in 0..MAX_REFINEMENT_ITERATIONS {
+        if current_sum == 0 {
+            break;
+        }
+        let mut best: Option<(u32, Vec<GraphemeType>)> = None;
+        let mut evaluate = |candidate: Vec<GraphemeType>| {
+            // candidates with the same sum as the best one are kept for the tie-breaking
+            let max_sum = best.as_ref().map_or(current_sum - 1, |(sum, _)| *sum);
+            let Some(sum) =
+                sum_distances_within(&mut distance_metric, members, &candidate, max_sum)
+            else {
+                return;
+            };
+            let is_better = match &best {
+                None => true,
+                Some((best_sum, best_graphemes)) => (sum, &candidate) < (*best_sum, best_graphemes),
+            };
+            if is_better {
+                best = Some((sum, candidate));
+            }
+        };
+
+        for idx in 0..=current.len() {
+            for &grapheme in alphabet.iter() {
+                let mut candidate = current.clone();
+                candidate.insert(idx, grapheme);
+                evaluate(candidate);
+
+                if idx < current.len() && current[idx] != grapheme {
+                    let mut candidate = current.clone();
+                    candidate[idx] = grapheme;
+                    evaluate(candidate);
+                }
+            }
+            if idx < current.len() {
+                let mut candidate = current.clone();
+                candidate.remove(idx);
+                evaluate(candidate);
+            }
+        }
 
-        if count > max_count {
-            max_count = count;
-            mfv = Some(v);
+        match best {
+            Some((sum, graphemes)) => {
+                current = graphemes;
+                current_sum = sum;
+            }
+            None => break,
         }
     }
-    mfv
+    current
 }
 
 /// Performs the add and delete operations of the edits to the word.
@@ -76,10 +471,13 @@ fn most_frequent<T: Hash + Eq + Clone>(iter: impl Iterator<Item = T>) -> Option<
 /// For the add operation, put a placeholder grapheme (0) instead
 /// of the one that should be added.
 ///
-/// This will result in a new word with the same length as the target word
-/// but not necessarily the same graphemes.
-fn perform_add_del_edits(w: &Word, edits: &Vec<LvEdit>) -> Word {
-    let mut graphemes = w.graphemes.clone();
+/// This will result in the graphemes of a new word with the same length as
+/// the target word but not necessarily the same graphemes.
+///
+/// Note: the graphemes are not converted to a word as the placeholders
+/// must not be interned.
+fn perform_add_del_edits(w: &Word, edits: &Vec<LvEdit>) -> Vec<GraphemeType> {
+    let mut graphemes = w.graphemes().to_vec();
     let mut idx_shift: i64 = 0;
     for edit in edits {
         match edit {
@@ -96,41 +494,217 @@ fn perform_add_del_edits(w: &Word, edits: &Vec<LvEdit>) -> Word {
             LvEdit::Sub(_, _) => {}
         }
     }
-    Word::from_graphemes(graphemes)
+    graphemes
 }
 
-/// Aligns a word with a random word from the most frequent length words.
-///
-/// Alignment is done by adding/deleting graphemes according to the Levenshtein distance.
-fn align_word(
-    distance_metric: &mut LvEditDistanceMetric,
-    word: &Word,
-    mfl_words: &Vec<&Word>,
-) -> Word {
-    let idx = rand::random_range(..mfl_words.len());
-    let target = mfl_words[idx];
-    let edits = distance_metric.compute_edits(word, target);
-    perform_add_del_edits(word, &edits)
-}
-
-/// Computes a new word for which every grapheme is the most frequent grapheme
-/// at that position in the words.
-///
-/// All the words must have the same length.
-fn compute_most_frequent_graphemes_word(words: Vec<&Word>) -> Word {
-    let length = words[0].graphemes.len();
-    let mut graphemes = Vec::with_capacity(length);
-    for i in 0..length {
-        let grapheme = most_frequent(words.iter().filter_map(|w| {
-            // do not take into account the placeholder grapheme
-            if w.graphemes[i] == 0 {
-                None
-            } else {
-                Some(w.graphemes[i])
-            }
-        }))
-        .unwrap();
+/// Computes a new word for which every grapheme is the most voted grapheme
+/// at that position, over the words of the most frequent length and the aligned words.
+///
+/// In case of a tie, the smallest grapheme is taken.
+fn compute_most_frequent_graphemes_word(
+    counts: &PositionCounts,
+    aligned_counts: &PositionCounts,
+) -> Result<Word> {
+    let mut graphemes = Vec::with_capacity(counts.len());
+    for (position_counts, aligned_position_counts) in counts.iter().zip(aligned_counts.iter()) {
+        let (grapheme, _) = position_counts
+            .keys()
+            .chain(aligned_position_counts.keys())
+            .map(|grapheme| {
+                let count = position_counts.get(grapheme).unwrap_or(&0.)
+                    + aligned_position_counts.get(grapheme).unwrap_or(&0.);
+                (*grapheme, count)
+            })
+            .max_by(|(g1, c1), (g2, c2)| c1.total_cmp(c2).then(g2.cmp(g1)))
+            .unwrap();
         graphemes.push(grapheme);
     }
     Word::from_graphemes(graphemes)
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    /// Reference implementation of `compute_median_word`, recomputing the median
+    /// from scratch with the same alignment and tie-breaking.
+    fn compute_median_word_from_scratch(words: &[&Word]) -> Option<Word> {
+        // most frequent length, the smallest one in case of a tie
+        let mut length_counts = BTreeMap::new();
+        for word in words.iter() {
+            *length_counts.entry(word.graphemes().len()).or_insert(0) += 1;
+        }
+        let (&length, _) = length_counts
+            .iter()
+            .max_by(|(l1, c1), (l2, c2)| c1.cmp(c2).then(l2.cmp(l1)))?;
+        let anchor = words.iter().find(|w| w.graphemes().len() == length)?;
+
+        let mut distance_metric = LvEditDistanceMetric::new(1., 1., 1., false);
+        let aligned_words = words
+            .iter()
+            .map(|word| {
+                if word.graphemes().len() == length {
+                    word.graphemes().to_vec()
+                } else {
+                    let edits = distance_metric.compute_edits(word, anchor);
+                    perform_add_del_edits(word, &edits)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let graphemes = (0..length)
+            .map(|idx| {
+                let mut counts = BTreeMap::new();
+                for word in aligned_words.iter().filter(|w| w[idx] != 0) {
+                    *counts.entry(word[idx]).or_insert(0) += 1;
+                }
+                // most frequent grapheme, the smallest one in case of a tie
+                let (grapheme, _) = counts
+                    .into_iter()
+                    .max_by(|(g1, c1), (g2, c2)| c1.cmp(c2).then(g2.cmp(g1)))
+                    .unwrap();
+                grapheme
+            })
+            .collect();
+        Some(Word::from_graphemes(graphemes).unwrap())
+    }
+
+    fn median(words: &[&str]) -> Option<String> {
+        let words = words.iter().map(Word::new).collect::<Vec<_>>();
+        compute_median_word(&words.iter().collect())
+            .unwrap()
+            .map(|w| w.raw().to_string())
+    }
+
+    #[test]
+    fn test_compute_median_word() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&["alice"]), Some("alice".to_string()));
+        assert_eq!(
+            median(&["alice", "alise", "alice", "alce"]),
+            Some("alice".to_string())
+        );
+        // "aliice" is aligned with "alice", the placeholder doesn't vote
+        assert_eq!(
+            median(&["alice", "alise", "aliice", "alyce"]),
+            Some("alice".to_string())
+        );
+        // tie between the lengths 3 and 4, the smallest is taken
+        assert_eq!(median(&["bobb", "bob"]), Some("bob".to_string()));
+    }
+
+    #[test]
+    fn test_compute_weighted_median_word() {
+        let weighted_median = |words: &[(&str, f32)]| {
+            let words = words
+                .iter()
+                .map(|(w, weight)| (Word::new(w), *weight))
+                .collect::<Vec<_>>();
+            compute_weighted_median_word(
+                &words
+                    .iter()
+                    .map(|(w, weight)| (w, *weight))
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap()
+            .map(|w| w.raw().to_string())
+        };
+
+        // unit weights give the unweighted median
+        assert_eq!(
+            weighted_median(&[("alice", 1.), ("alise", 1.), ("alise", 1.)]),
+            median(&["alice", "alise", "alise"])
+        );
+        assert_eq!(
+            weighted_median(&[("alice", 3.), ("alise", 1.), ("alise", 1.)]),
+            Some("alice".to_string())
+        );
+        // the weights also decide the length
+        assert_eq!(
+            weighted_median(&[("bobb", 1.), ("bob", 0.5)]),
+            Some("bobb".to_string())
+        );
+        // the aligned words vote with their weight
+        assert_eq!(
+            weighted_median(&[
+                ("alice", 1.),
+                ("alise", 1.),
+                ("aliise", 0.5),
+                ("aliice", 0.8)
+            ]),
+            Some("alice".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_and_refined_median_word() {
+        let words = ["axc", "aby", "zbc"].map(Word::new);
+        let median = |words: &[Word], strategy| {
+            compute_median_word_with_strategy(&words.iter().collect::<Vec<_>>(), strategy)
+                .unwrap()
+                .map(|w| w.raw().to_string())
+        };
+
+        // all the words have a sum of distances of 4, the smallest is taken
+        assert_eq!(
+            median(&words, MedianWordStrategy::SetMedian),
+            Some("aby".to_string())
+        );
+        // "abc" is at one edit of each word
+        assert_eq!(
+            median(&words, MedianWordStrategy::RefinedMedian),
+            Some("abc".to_string())
+        );
+
+        let mut reversed = words.to_vec();
+        reversed.reverse();
+        for strategy in [
+            MedianWordStrategy::SetMedian,
+            MedianWordStrategy::RefinedMedian,
+        ] {
+            assert_eq!(median(&reversed, strategy), median(&words, strategy));
+        }
+
+        let words = ["bob", "bob", "bobb"].map(Word::new);
+        assert_eq!(
+            median(&words, MedianWordStrategy::RefinedMedian),
+            Some("bob".to_string())
+        );
+        assert_eq!(median(&[], MedianWordStrategy::SetMedian), None);
+
+        // the cached median is recomputed once words are added
+        let mut incremental = IncrementalSetMedianWord::new(true);
+        assert_eq!(incremental.median().unwrap(), None);
+        incremental.push(&Word::new("axc"));
+        incremental.push(&Word::new("aby"));
+        assert_eq!(incremental.median().unwrap(), Some(Word::new("aby")));
+        assert_eq!(incremental.median().unwrap(), Some(Word::new("aby")));
+        incremental.push(&Word::new("zbc"));
+        assert_eq!(incremental.median().unwrap(), Some(Word::new("abc")));
+    }
+
+    #[test]
+    fn test_incremental_median_word() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let alphabet = ['a', 'e', 'l', 'n'];
+
+        let mut incremental = IncrementalMedianWord::new();
+        let mut words = Vec::new();
+        for _ in 0..60 {
+            let length = rng.random_range(3..6);
+            let word = Word::new(
+                (0..length)
+                    .map(|_| alphabet[rng.random_range(0..alphabet.len())])
+                    .collect::<String>(),
+            );
+            incremental.push(&word);
+            words.push(word);
+            assert_eq!(
+                incremental.median().unwrap(),
+                compute_median_word_from_scratch(&words.iter().collect::<Vec<_>>())
+            );
+        }
+    }
+}
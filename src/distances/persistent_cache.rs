@@ -0,0 +1,185 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        RwLock,
+    },
+};
+
+use crate::word::Word;
+
+use super::DistanceMatrix;
+
+/// Estimation of the memory used by an entry of the cache: the slot
+/// (key, distance, reference bit) and the entry of the index.
+const ENTRY_MEMORY: usize = 48;
+
+struct Slot {
+    key: u64,
+    dist: f32,
+    referenced: AtomicBool,
+}
+
+#[derive(Default)]
+struct Entries {
+    index: HashMap<u64, usize>,
+    slots: Vec<Slot>,
+    hand: usize,
+}
+
+/// Statistics of a persistent distance cache
+#[derive(Debug, Clone, Default)]
+pub struct PersistentCacheStats {
+    pub capacity: usize,
+    pub size: usize,
+    pub lookup_count: u64,
+    pub hit_count: u64,
+    pub insertion_count: u64,
+    pub eviction_count: u64,
+}
+
+/// PersistentDistanceCache
+///
+/// Bounded cache of distances that survives across frames, it is shared
+/// between the workers. The distances of frequent values (e.g. common names)
+/// are then computed once for the whole tracking process instead of once per frame.
+///
+/// It is read-mostly: lookups only take a read lock, the distances computed
+/// by a worker are buffered and inserted at once at the end of the frame.
+///
+/// The eviction follows the CLOCK algorithm, an approximation of LRU: each entry
+/// has a reference bit set on lookup, when the cache is full the hand sweeps over
+/// the entries, clearing the reference bits, until it finds an entry not referenced
+/// since its last pass, which is replaced.
+pub struct PersistentDistanceCache {
+    entries: RwLock<Entries>,
+    capacity: usize,
+    lookup_count: AtomicU64,
+    hit_count: AtomicU64,
+    insertion_count: AtomicU64,
+    eviction_count: AtomicU64,
+}
+
+impl PersistentDistanceCache {
+    /// Creates a cache holding at most `capacity` distances.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: RwLock::new(Entries::default()),
+            capacity: capacity.max(1),
+            lookup_count: AtomicU64::new(0),
+            hit_count: AtomicU64::new(0),
+            insertion_count: AtomicU64::new(0),
+            eviction_count: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates a cache holding as many distances as fit in the memory budget, in bytes.
+    pub fn with_memory_budget(memory_budget: usize) -> Self {
+        Self::new(memory_budget / ENTRY_MEMORY)
+    }
+
+    /// Returns the distance of the key and marks it as recently used.
+    pub fn get(&self, key: u64) -> Option<f32> {
+        self.lookup_count.fetch_add(1, Ordering::Relaxed);
+        let entries = self.entries.read().unwrap();
+        let slot = &entries.slots[*entries.index.get(&key)?];
+        slot.referenced.store(true, Ordering::Relaxed);
+        self.hit_count.fetch_add(1, Ordering::Relaxed);
+        Some(slot.dist)
+    }
+
+    /// Inserts the distances, evicting the least recently used ones when full.
+    pub fn insert(&self, values: &[(u64, f32)]) {
+        if values.is_empty() {
+            return;
+        }
+        let mut entries = self.entries.write().unwrap();
+        for &(key, dist) in values.iter() {
+            if entries.index.contains_key(&key) {
+                continue;
+            }
+            self.insertion_count.fetch_add(1, Ordering::Relaxed);
+
+            if entries.slots.len() < self.capacity {
+                let idx = entries.slots.len();
+                entries.slots.push(Slot {
+                    key,
+                    dist,
+                    referenced: AtomicBool::new(false),
+                });
+                entries.index.insert(key, idx);
+                continue;
+            }
+
+            let idx = Self::sweep(&mut entries);
+            let evicted_key = entries.slots[idx].key;
+            entries.index.remove(&evicted_key);
+            entries.slots[idx] = Slot {
+                key,
+                dist,
+                referenced: AtomicBool::new(false),
+            };
+            entries.index.insert(key, idx);
+            self.eviction_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Advances the hand until an entry not referenced is found,
+    /// returns its index.
+    fn sweep(entries: &mut Entries) -> usize {
+        loop {
+            let idx = entries.hand;
+            entries.hand = (entries.hand + 1) % entries.slots.len();
+            if !entries.slots[idx].referenced.swap(false, Ordering::Relaxed) {
+                return idx;
+            }
+        }
+    }
+
+    /// Returns the number of distances in the cache.
+    pub fn size(&self) -> usize {
+        self.entries.read().unwrap().slots.len()
+    }
+
+    /// Returns the statistics of the cache.
+    pub fn stats(&self) -> PersistentCacheStats {
+        PersistentCacheStats {
+            capacity: self.capacity,
+            size: self.size(),
+            lookup_count: self.lookup_count.load(Ordering::Relaxed),
+            hit_count: self.hit_count.load(Ordering::Relaxed),
+            insertion_count: self.insertion_count.load(Ordering::Relaxed),
+            eviction_count: self.eviction_count.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the key of the distance between w1 and w2.
+    pub fn key(w1: &Word, w2: &Word) -> u64 {
+        DistanceMatrix::key(w1, w2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_eviction() {
+        let cache = PersistentDistanceCache::new(2);
+        cache.insert(&[(1, 0.1), (2, 0.2)]);
+        assert_eq!(cache.get(1), Some(0.1));
+
+        // 2 is evicted as 1 was used since its insertion
+        cache.insert(&[(3, 0.3)]);
+        assert_eq!(cache.get(2), None);
+        assert_eq!(cache.get(1), Some(0.1));
+        assert_eq!(cache.get(3), Some(0.3));
+
+        let stats = cache.stats();
+        assert_eq!(stats.size, 2);
+        assert_eq!(stats.lookup_count, 4);
+        assert_eq!(stats.hit_count, 3);
+        assert_eq!(stats.insertion_count, 3);
+        assert_eq!(stats.eviction_count, 1);
+    }
+}
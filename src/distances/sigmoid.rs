@@ -5,3 +5,17 @@ pub fn sigmoid(v: f32) -> f32 {
     let v = v * 8.0 - 4.0;
     1.0 / (1.0 + (-v).exp())
 }
+
+/// Computes the inverse of the sigmoid function.
+///
+/// Returns the value between 0 and 1 that the sigmoid function maps to `v`,
+/// which is out of this range when `v` can't be reached.
+pub fn inverse_sigmoid(v: f32) -> f32 {
+    if v <= 0.0 {
+        return f32::NEG_INFINITY;
+    }
+    if v >= 1.0 {
+        return f32::INFINITY;
+    }
+    ((v / (1.0 - v)).ln() + 4.0) / 8.0
+}
@@ -1,6 +1,11 @@
 mod engine;
 mod exclusive_shared;
+mod frame_store;
+mod spill_store;
+mod work_stealing;
 mod worker;
 
-pub use engine::{EngineConfig, TrackingEngine};
+pub use engine::{EngineConfig, EngineMode, TrackingEngine};
 pub use exclusive_shared::ExclusiveShared;
+pub use frame_store::{FrameLoader, FrameStore};
+pub use spill_store::SpillStore;
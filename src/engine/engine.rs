@@ -1,18 +1,36 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use crate::{
-    api::{ChainNode, Diagnostics, ResolvingDiagnostics},
-    distances::CachedDistanceCalculator,
-    frame::Frame,
-    id::ID,
+    api::{
+        ChainNode, Diagnostics, FramePerformanceDiagnostics, PersistentCacheDiagnostics,
+        ResolvingDiagnostics, WorkerFrameDiagnostics,
+    },
+    distances::{CachedDistanceCalculator, PersistentDistanceCache},
+    error::Result,
+    id::{self, ID},
     resolvers::Resolver,
     trackers::{InternalTrackerConfig, RecordScore, Tracker, TrackingChain},
 };
 
-use super::{exclusive_shared::ExclusiveShared, worker::TrackingWorkerHandler};
+use super::{
+    exclusive_shared::ExclusiveShared, frame_store::FrameStore, spill_store::SpillStore,
+    work_stealing::WorkStealingScheduler, worker::TrackingWorkerHandler,
+};
+
+/// Parallelization mode of the engine
+#[derive(Debug, Clone)]
+pub enum EngineMode {
+    /// Long-lived workers, each managing a fixed share of the trackers,
+    /// the main thread supervises them.
+    Workers,
+    /// Work-stealing over chunks of trackers, with the number of trackers
+    /// per chunk (automatic if None), the main thread takes part in it.
+    WorkStealing(Option<usize>),
+}
 
 pub struct EngineConfig {
     pub num_threads: usize,
+    pub mode: EngineMode,
     pub tracker_config: InternalTrackerConfig,
 }
 
@@ -22,54 +40,92 @@ pub struct EngineConfig {
 ///
 /// It is responsible for managing the trackers, workers, and the resolving process.
 pub struct TrackingEngine {
-    frames: Arc<Vec<Frame>>,
+    frames: Arc<FrameStore>,
     config: EngineConfig,
     workers: Vec<TrackingWorkerHandler>,
+    scheduler: Option<WorkStealingScheduler>,
     resolver: Resolver,
     trackers: HashMap<ID, ExclusiveShared<Tracker>>,
     diagnostics: Diagnostics,
+    persistent_caches: Vec<Option<Arc<PersistentDistanceCache>>>,
     dead_tracking_chains: Vec<TrackingChain>,
+    /// Store of the dead trackers' chains and diagnostics, if None they are kept in memory.
+    spill_store: Option<SpillStore>,
     next_frame_idx: usize,
 }
 
 impl TrackingEngine {
+    /// Creates a new engine, the in-memory frames can be shared with other engines.
+    ///
+    /// # Errors
+    /// Returns an error if the first frame can't be loaded.
     pub fn new(
-        frames: Vec<Frame>,
+        frames: FrameStore,
         config: EngineConfig,
         resolver: Resolver,
         distance_calculators: Vec<CachedDistanceCalculator>,
-    ) -> Self {
+    ) -> Result<Self> {
         let frames = Arc::new(frames);
+        frames.load(0)?;
         let trackers = Self::build_trackers(&frames, &config);
-        let workers = Self::build_workers(&frames, &config, &distance_calculators);
+        let (workers, scheduler) = match config.mode {
+            EngineMode::Workers => (
+                Self::build_workers(&frames, &config, &distance_calculators),
+                None,
+            ),
+            EngineMode::WorkStealing(chunk_size) => (
+                Vec::new(),
+                Some(WorkStealingScheduler::new(
+                    Arc::clone(&frames),
+                    config.num_threads,
+                    chunk_size,
+                    &distance_calculators,
+                )),
+            ),
+        };
+        let persistent_caches = distance_calculators
+            .iter()
+            .map(|d| d.persistent_cache().cloned())
+            .collect();
 
         let mut engine = Self {
             frames,
             config,
             workers,
+            scheduler,
             resolver,
             trackers: HashMap::new(),
             diagnostics: Diagnostics::new(),
+            persistent_caches,
             dead_tracking_chains: Vec::new(),
+            spill_store: None,
             next_frame_idx: 1,
         };
         engine.add_new_trackers(trackers);
 
-        engine
+        Ok(engine)
+    }
+
+    /// Spills the chains and diagnostics of the dead trackers to the store
+    /// instead of keeping them in memory until the engine stops.
+    pub fn with_spill_store(mut self, spill_store: SpillStore) -> Self {
+        self.spill_store = Some(spill_store);
+        self
     }
 
     /// Builds the workers given the frames and configuration.
     ///
     /// The trackers will be added at a later stage.
     fn build_workers(
-        frames: &Arc<Vec<Frame>>,
+        frames: &Arc<FrameStore>,
         config: &EngineConfig,
         distance_calculators: &Vec<CachedDistanceCalculator>,
     ) -> Vec<TrackingWorkerHandler> {
         let n_workers = config.num_threads - 1;
         let mut workers = Vec::new();
-        for _ in 0..n_workers {
+        for worker_idx in 0..n_workers {
             let worker = TrackingWorkerHandler::new(
+                worker_idx,
                 Arc::clone(frames),
                 HashMap::new(),
                 distance_calculators.clone(),
@@ -82,8 +138,8 @@ impl TrackingEngine {
     /// Builds the initial trackers from the first frame.
     ///
     /// Each record in the first frame will be used to initialize a tracker.
-    fn build_trackers(frames: &Arc<Vec<Frame>>, config: &EngineConfig) -> Vec<Tracker> {
-        let frame = &frames[0];
+    fn build_trackers(frames: &FrameStore, config: &EngineConfig) -> Vec<Tracker> {
+        let frame = frames.get(0);
 
         let mut trackers = Vec::new();
 
@@ -102,8 +158,20 @@ impl TrackingEngine {
         trackers
     }
 
+    /// Builds a tracking chain for each node released by the tracker.
+    ///
+    /// This happens when the tracker stops while holding multiple hypotheses,
+    /// the nodes that are only part of the discarded hypotheses form their own chains.
+    fn build_released_chains(tracker: &Tracker) -> Vec<TrackingChain> {
+        tracker
+            .get_released_nodes()
+            .into_iter()
+            .map(|node| TrackingChain::new(id::new_id(), vec![node], vec![None]))
+            .collect()
+    }
+
     /// Returns the frames
-    pub fn frames(&self) -> &Vec<Frame> {
+    pub fn frames(&self) -> &FrameStore {
         &self.frames
     }
 
@@ -111,26 +179,48 @@ impl TrackingEngine {
     ///
     /// This will reset the diagnostics.
     pub fn take_diagnostics(&mut self) -> Diagnostics {
+        self.diagnostics.performance.persistent_caches = self
+            .persistent_caches
+            .iter()
+            .enumerate()
+            .filter_map(|(feature_idx, cache)| {
+                cache
+                    .as_ref()
+                    .map(|c| PersistentCacheDiagnostics::new(feature_idx, c.stats()))
+            })
+            .collect();
         std::mem::replace(&mut self.diagnostics, Diagnostics::new())
     }
 
     /// Checks for dead trackers and removes them from the engine
     /// and workers.
     ///
-    /// Collect the diagnostics from the dead trackers.
-    fn remove_dead_trackers(&mut self) {
+    /// Collect the diagnostics from the dead trackers, they are spilled
+    /// along with the tracking chains if there is a spill store.
+    ///
+    /// # Errors
+    /// Returns an error if the dead trackers can't be spilled.
+    fn remove_dead_trackers(&mut self) -> Result<()> {
         let mut removed_ids = Vec::new();
+        let mut dead_chains = Vec::new();
+        let mut dead_diagnostics = Vec::new();
         for (id, tracker) in self.trackers.iter_mut() {
             if tracker.is_dead() {
-                self.diagnostics
-                    .trackers
-                    .insert(*id, tracker.exclusive().take_diagnostics());
-
-                self.dead_tracking_chains.push(tracker.get_tracking_chain());
+                dead_diagnostics.push((*id, tracker.exclusive().take_diagnostics()));
+                dead_chains.push(tracker.get_tracking_chain());
+                dead_chains.extend(Self::build_released_chains(tracker));
                 removed_ids.push(*id);
             }
         }
 
+        match self.spill_store.as_mut() {
+            Some(spill_store) => spill_store.spill(dead_chains, dead_diagnostics)?,
+            None => {
+                self.dead_tracking_chains.extend(dead_chains);
+                self.diagnostics.trackers.extend(dead_diagnostics);
+            }
+        }
+
         log::debug!(
             "frame: {} dead trackers: {}",
             self.next_frame_idx,
@@ -144,6 +234,7 @@ impl TrackingEngine {
         for worker in self.workers.iter_mut() {
             worker.remove_trackers(removed_ids.clone());
         }
+        Ok(())
     }
 
     fn add_trackers_to_worker(
@@ -160,13 +251,20 @@ impl TrackingEngine {
 
     /// Adds trackers to the engine
     ///
-    /// Distributes the trackers among the workers.
+    /// Distributes the trackers among the workers, if any.
     fn add_new_trackers(&mut self, trackers: Vec<Tracker>) {
         let trackers: Vec<ExclusiveShared<Tracker>> = trackers
             .into_iter()
             .map(|t| ExclusiveShared::new(t))
             .collect();
 
+        // without workers, the trackers are distributed at each frame
+        if self.workers.is_empty() {
+            self.trackers
+                .extend(trackers.into_iter().map(|t| (t.id(), t)));
+            return;
+        }
+
         // computes current average number of trackers per worker
         let avg_tracker_count =
             self.workers.iter().map(|w| w.num_trackers()).sum::<usize>() / self.workers.len();
@@ -233,48 +331,93 @@ impl TrackingEngine {
         }
 
         self.resolver.resolve(
-            &self.frames[self.next_frame_idx],
+            &self.frames.get(self.next_frame_idx),
             self.config.tracker_config.clone(),
             &mut trackers,
             scores,
         )
     }
 
-    /// Processes the next frame
-    pub fn process_next_frame(&mut self) {
+    /// Computes the scores of all the trackers for the next frame
+    ///
+    /// Returns the scores and the performance diagnostics of each worker.
+    fn process_scoring(&mut self) -> (HashMap<ID, Vec<RecordScore>>, Vec<WorkerFrameDiagnostics>) {
+        if let Some(scheduler) = self.scheduler.as_mut() {
+            return scheduler.process_frame(self.next_frame_idx, &self.trackers);
+        }
+
         for worker in self.workers.iter_mut() {
             worker.process_frame(self.next_frame_idx);
         }
 
         let mut trackers_scores = HashMap::with_capacity(self.trackers.len());
+        let mut workers_diagnostics = Vec::with_capacity(self.workers.len());
 
         for worker in self.workers.iter() {
-            let scores = worker.wait_scores();
+            let (scores, diagnostics) = worker.wait_scores();
             trackers_scores.extend(scores.into_iter());
+            workers_diagnostics.push(diagnostics);
         }
+        // the workers are reordered when trackers are added
+        workers_diagnostics.sort_by_key(|d| d.worker_idx);
+
+        (trackers_scores, workers_diagnostics)
+    }
+
+    /// Processes the next frame
+    ///
+    /// # Errors
+    /// Returns an error if the frame can't be loaded or the dead trackers can't be spilled.
+    pub fn process_next_frame(&mut self) -> Result<()> {
+        self.frames.load(self.next_frame_idx)?;
+
+        let start = Instant::now();
+        let (trackers_scores, workers_diagnostics) = self.process_scoring();
+        let scoring_time = start.elapsed().as_secs_f64();
 
+        let start = Instant::now();
         let (new_trackers, resolving_diagnostics) = self.process_resolving(trackers_scores);
 
         self.diagnostics.resolvings.push(resolving_diagnostics);
-
-        self.remove_dead_trackers();
+        self.diagnostics
+            .performance
+            .frames
+            .push(FramePerformanceDiagnostics {
+                frame_idx: self.next_frame_idx,
+                scoring_time,
+                resolving_time: start.elapsed().as_secs_f64(),
+                workers: workers_diagnostics,
+            });
+
+        self.remove_dead_trackers()?;
         self.add_new_trackers(new_trackers);
 
         self.next_frame_idx += 1;
+        Ok(())
     }
 
     /// Collects the state of the trackers
     ///
-    /// This includes the tracking chains and diagnostics.
-    fn collect_trackers_state(&mut self) -> Vec<TrackingChain> {
+    /// This includes the tracking chains and diagnostics, the spilled ones
+    /// are read back.
+    ///
+    /// # Errors
+    /// Returns an error if the spilled trackers can't be read back.
+    fn collect_trackers_state(&mut self) -> Result<Vec<TrackingChain>> {
         let mut tracking_chains = self.dead_tracking_chains.clone();
+        if let Some(spill_store) = self.spill_store.as_ref() {
+            let spilled = spill_store.read_back()?;
+            tracking_chains.extend(spilled.chains);
+            self.diagnostics.trackers.extend(spilled.diagnostics);
+        }
         for (_, tracker) in self.trackers.iter_mut() {
             tracking_chains.push(tracker.get_tracking_chain());
+            tracking_chains.extend(Self::build_released_chains(tracker));
             self.diagnostics
                 .trackers
                 .insert(tracker.id(), tracker.exclusive().take_diagnostics());
         }
-        tracking_chains
+        Ok(tracking_chains)
     }
 
     /// Stops the engine
@@ -282,7 +425,10 @@ impl TrackingEngine {
     /// Stops all workers, collects the diagnostics and tracking chains.
     ///
     /// Returns the tracking chains.
-    pub fn stop(&mut self) -> Vec<TrackingChain> {
+    ///
+    /// # Errors
+    /// Returns an error if the spilled trackers can't be read back.
+    pub fn stop(&mut self) -> Result<Vec<TrackingChain>> {
         for worker in self.workers.iter() {
             worker.stop();
         }
@@ -0,0 +1,218 @@
+use std::{
+    collections::VecDeque,
+    ops::Deref,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use crate::{error::Result, frame::Frame};
+
+/// FrameLoader
+///
+/// Source of the frames of an out-of-core run, each frame is loaded
+/// on demand, when the engine reaches it.
+pub trait FrameLoader: Send + Sync {
+    /// Returns the total number of frames.
+    fn num_frames(&self) -> usize;
+
+    /// Loads the frame at the given index.
+    ///
+    /// # Errors
+    /// Returns an error if the frame can't be read or doesn't match the record schema.
+    fn load_frame(&self, frame_idx: usize) -> Result<Frame>;
+}
+
+/// Reference to a frame of the store, either borrowed from the resident
+/// frames or shared with the window of the lazy frames.
+pub enum FrameRef<'a> {
+    Borrowed(&'a Frame),
+    Shared(Arc<Frame>),
+}
+
+impl Deref for FrameRef<'_> {
+    type Target = Frame;
+
+    fn deref(&self) -> &Frame {
+        match self {
+            Self::Borrowed(frame) => frame,
+            Self::Shared(frame) => frame,
+        }
+    }
+}
+
+/// LazyFrames
+///
+/// Frames loaded on demand, only a sliding window of the last loaded
+/// frames is resident, the older ones are dropped.
+pub struct LazyFrames {
+    loader: Box<dyn FrameLoader>,
+    window_size: usize,
+    window: RwLock<VecDeque<Arc<Frame>>>,
+    /// Number of records of each loaded frame, the frames themselves
+    /// are not needed to assemble the tracking graph.
+    frame_sizes: Mutex<Vec<usize>>,
+}
+
+/// FrameStore
+///
+/// Frames processed by the engine, shared between the main thread and the workers.
+pub enum FrameStore {
+    /// All the frames are resident, they can be shared with other engines.
+    InMemory(Arc<Vec<Frame>>),
+    /// Frames loaded on demand, for datasets that don't fit in memory.
+    Lazy(LazyFrames),
+}
+
+impl FrameStore {
+    pub fn in_memory(frames: Arc<Vec<Frame>>) -> Self {
+        Self::InMemory(frames)
+    }
+
+    /// Creates a store loading the frames on demand, keeping at most
+    /// `window_size` frames resident (at least one).
+    pub fn lazy(loader: Box<dyn FrameLoader>, window_size: usize) -> Self {
+        Self::Lazy(LazyFrames {
+            loader,
+            window_size: window_size.max(1),
+            window: RwLock::new(VecDeque::new()),
+            frame_sizes: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Returns the total number of frames, resident or not.
+    pub fn num_frames(&self) -> usize {
+        match self {
+            Self::InMemory(frames) => frames.len(),
+            Self::Lazy(lazy) => lazy.loader.num_frames(),
+        }
+    }
+
+    /// Makes the frame at the given index resident, evicting the oldest
+    /// frames of the window if needed.
+    ///
+    /// The frames must be loaded in order, this is a no-op for resident frames.
+    ///
+    /// # Errors
+    /// Returns an error if the frame can't be loaded.
+    pub fn load(&self, frame_idx: usize) -> Result<()> {
+        let Self::Lazy(lazy) = self else {
+            return Ok(());
+        };
+        if lazy
+            .window
+            .read()
+            .unwrap()
+            .iter()
+            .any(|f| f.idx() == frame_idx)
+        {
+            return Ok(());
+        }
+
+        log::debug!("loading frame {}...", frame_idx);
+        let frame = lazy.loader.load_frame(frame_idx)?;
+        {
+            let mut frame_sizes = lazy.frame_sizes.lock().unwrap();
+            if frame_sizes.len() <= frame_idx {
+                frame_sizes.resize(frame_idx + 1, 0);
+            }
+            frame_sizes[frame_idx] = frame.num_records();
+        }
+
+        let mut window = lazy.window.write().unwrap();
+        window.push_back(Arc::new(frame));
+        while window.len() > lazy.window_size {
+            window.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Returns the frame at the given index.
+    ///
+    /// # Panics
+    /// Panics if the frame is not resident, i.e. it was not loaded or it
+    /// went out of the window.
+    pub fn get(&self, frame_idx: usize) -> FrameRef<'_> {
+        match self {
+            Self::InMemory(frames) => FrameRef::Borrowed(&frames[frame_idx]),
+            Self::Lazy(lazy) => FrameRef::Shared(Arc::clone(
+                lazy.window
+                    .read()
+                    .unwrap()
+                    .iter()
+                    .find(|f| f.idx() == frame_idx)
+                    .unwrap_or_else(|| panic!("frame {} is not resident", frame_idx)),
+            )),
+        }
+    }
+
+    /// Returns the number of records of each frame, the lazy frames
+    /// that were never loaded count as empty.
+    pub fn frame_sizes(&self) -> Vec<usize> {
+        match self {
+            Self::InMemory(frames) => frames.iter().map(|f| f.num_records()).collect(),
+            Self::Lazy(lazy) => {
+                let mut frame_sizes = lazy.frame_sizes.lock().unwrap().clone();
+                frame_sizes.resize(lazy.loader.num_frames(), 0);
+                frame_sizes
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::frame::Element;
+
+    use super::*;
+
+    struct CountingLoader {
+        num_loads: Arc<AtomicUsize>,
+    }
+
+    impl FrameLoader for CountingLoader {
+        fn num_frames(&self) -> usize {
+            5
+        }
+
+        fn load_frame(&self, frame_idx: usize) -> Result<Frame> {
+            self.num_loads.fetch_add(1, Ordering::Relaxed);
+            Ok(Frame::new(
+                frame_idx,
+                vec![vec![Element::None; frame_idx + 1]],
+            ))
+        }
+    }
+
+    #[test]
+    fn test_lazy_window() {
+        let num_loads = Arc::new(AtomicUsize::new(0));
+        let store = FrameStore::lazy(
+            Box::new(CountingLoader {
+                num_loads: Arc::clone(&num_loads),
+            }),
+            2,
+        );
+        assert_eq!(store.num_frames(), 5);
+
+        for frame_idx in 0..4 {
+            store.load(frame_idx).unwrap();
+            store.load(frame_idx).unwrap();
+            assert_eq!(store.get(frame_idx).num_records(), frame_idx + 1);
+        }
+        assert_eq!(num_loads.load(Ordering::Relaxed), 4);
+
+        let FrameStore::Lazy(lazy) = &store else {
+            unreachable!()
+        };
+        let resident = lazy
+            .window
+            .read()
+            .unwrap()
+            .iter()
+            .map(|f| f.idx())
+            .collect::<Vec<_>>();
+        assert_eq!(resident, vec![2, 3]);
+        assert_eq!(store.frame_sizes(), vec![1, 2, 3, 4, 0]);
+    }
+}
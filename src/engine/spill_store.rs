@@ -0,0 +1,139 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::TrackerDiagnostics,
+    error::{BlitzBeaverError, Result},
+    id::{self, ID},
+    trackers::TrackingChain,
+};
+
+/// Tracking chains and diagnostics of dead trackers, a batch is spilled
+/// at once, usually the dead trackers of a frame.
+#[derive(Serialize, Deserialize, Default)]
+pub struct SpilledTrackers {
+    pub chains: Vec<TrackingChain>,
+    pub diagnostics: Vec<(ID, TrackerDiagnostics)>,
+}
+
+/// SpillStore
+///
+/// Temporary on-disk store of the tracking chains and diagnostics of the
+/// dead trackers, such that they don't pile up in memory during long runs.
+///
+/// Each batch is written to its own file in a private directory,
+/// the directory is removed when the store is dropped.
+pub struct SpillStore {
+    dir: PathBuf,
+    num_batches: usize,
+}
+
+impl SpillStore {
+    /// Creates a store in a new directory under `parent`.
+    ///
+    /// # Errors
+    /// Returns a Serialization error if the directory can't be created.
+    pub fn create(parent: &Path) -> Result<Self> {
+        let dir = parent.join(format!("blitzbeaver-spill-{:016x}", id::new_id()));
+        fs::create_dir_all(&dir)
+            .map_err(|e| BlitzBeaverError::Serialization(format!("{}: {}", dir.display(), e)))?;
+        Ok(Self {
+            dir,
+            num_batches: 0,
+        })
+    }
+
+    fn batch_path(&self, batch_idx: usize) -> PathBuf {
+        self.dir.join(format!("batch-{}.bin", batch_idx))
+    }
+
+    /// Writes the chains and diagnostics of dead trackers to a new batch,
+    /// nothing is written if both are empty.
+    ///
+    /// # Errors
+    /// Returns a Serialization error if the batch can't be written.
+    pub fn spill(
+        &mut self,
+        chains: Vec<TrackingChain>,
+        diagnostics: Vec<(ID, TrackerDiagnostics)>,
+    ) -> Result<()> {
+        if chains.is_empty() && diagnostics.is_empty() {
+            return Ok(());
+        }
+        let path = self.batch_path(self.num_batches);
+        let bytes = bincode::serialize(&SpilledTrackers {
+            chains,
+            diagnostics,
+        })
+        .map_err(|e| BlitzBeaverError::Serialization(format!("{}: {}", path.display(), e)))?;
+        fs::write(&path, bytes)
+            .map_err(|e| BlitzBeaverError::Serialization(format!("{}: {}", path.display(), e)))?;
+        self.num_batches += 1;
+        Ok(())
+    }
+
+    /// Reads back all the spilled chains and diagnostics, in the order they were spilled.
+    ///
+    /// # Errors
+    /// Returns a Serialization error if a batch can't be read.
+    pub fn read_back(&self) -> Result<SpilledTrackers> {
+        let mut spilled = SpilledTrackers::default();
+        for batch_idx in 0..self.num_batches {
+            let path = self.batch_path(batch_idx);
+            let io_error =
+                |e: String| BlitzBeaverError::Serialization(format!("{}: {}", path.display(), e));
+            let bytes = fs::read(&path).map_err(|e| io_error(e.to_string()))?;
+            let batch: SpilledTrackers =
+                bincode::deserialize(&bytes).map_err(|e| io_error(e.to_string()))?;
+            spilled.chains.extend(batch.chains);
+            spilled.diagnostics.extend(batch.diagnostics);
+        }
+        Ok(spilled)
+    }
+}
+
+impl Drop for SpillStore {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            log::warn!("failed to remove {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::ChainNode;
+
+    use super::*;
+
+    #[test]
+    fn test_spill_read_back() {
+        let mut store = SpillStore::create(&std::env::temp_dir()).unwrap();
+        let dir = store.dir.clone();
+
+        store.spill(Vec::new(), Vec::new()).unwrap();
+        for id in 0..3 {
+            let chain = TrackingChain::new(id, vec![ChainNode::new(id as usize, 0)], vec![None]);
+            store.spill(vec![chain], Vec::new()).unwrap();
+        }
+        assert_eq!(store.num_batches, 3);
+
+        let SpilledTrackers {
+            chains,
+            diagnostics,
+        } = store.read_back().unwrap();
+        assert_eq!(
+            chains.iter().map(|c| c.id).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(chains[2].nodes, vec![ChainNode::new(2, 0)]);
+        assert!(diagnostics.is_empty());
+
+        drop(store);
+        assert!(!dir.exists());
+    }
+}
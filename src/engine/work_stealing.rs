@@ -0,0 +1,245 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::{
+    api::{CacheDiagnostics, WorkerFrameDiagnostics},
+    distances::CachedDistanceCalculator,
+    frame::Frame,
+    id::ID,
+    trackers::{RecordScore, Tracker},
+    word::Word,
+};
+
+use super::{
+    exclusive_shared::ExclusiveShared,
+    frame_store::FrameStore,
+    worker::{compute_frame_uniques, setup_caches},
+};
+
+/// Number of chunks per thread when the chunk size is automatic, a few chunks
+/// per thread are needed for the threads finishing early to take over the others.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// Work-stealing scheduler
+///
+/// Alternative to the long-lived workers holding a fixed share of the trackers:
+/// for each frame, the trackers are split into chunks that the threads take
+/// from a shared queue as soon as they are idle, such that the costly trackers
+/// (e.g. with a large memory) don't make one thread the bottleneck.
+///
+/// The trackers are sorted by decreasing memory size before being split,
+/// so that the costliest chunks are taken first and the cheap ones fill the gaps.
+///
+/// The main thread takes chunks as well, along with the threads of the pool.
+/// The caches are built per chunk, from the memories of the trackers of the chunk.
+pub struct WorkStealingScheduler {
+    frames: Arc<FrameStore>,
+    pool: Option<ThreadPool>,
+    chunk_size: Option<usize>,
+    /// Distance calculators of each thread, the first ones are the main thread's
+    distance_calculators: Vec<Vec<CachedDistanceCalculator>>,
+}
+
+impl WorkStealingScheduler {
+    /// Creates a scheduler running on `num_threads` threads, including the main thread.
+    ///
+    /// The chunk size is the number of trackers per chunk, if None it is chosen
+    /// to have a few chunks per thread.
+    pub fn new(
+        frames: Arc<FrameStore>,
+        num_threads: usize,
+        chunk_size: Option<usize>,
+        distance_calculators: &[CachedDistanceCalculator],
+    ) -> Self {
+        let num_threads = num_threads.max(1);
+        // the main thread is not part of the pool
+        let pool = (num_threads > 1).then(|| {
+            ThreadPoolBuilder::new()
+                .num_threads(num_threads - 1)
+                .build()
+                .unwrap()
+        });
+        Self {
+            frames,
+            pool,
+            chunk_size,
+            distance_calculators: (0..num_threads)
+                .map(|_| distance_calculators.to_vec())
+                .collect(),
+        }
+    }
+
+    /// Returns the number of threads, including the main thread.
+    pub fn num_threads(&self) -> usize {
+        self.distance_calculators.len()
+    }
+
+    /// Splits the trackers into chunks, the costliest first.
+    fn build_chunks(
+        &self,
+        num_features: usize,
+        trackers: &HashMap<ID, ExclusiveShared<Tracker>>,
+    ) -> Vec<Vec<ExclusiveShared<Tracker>>> {
+        let mut trackers = trackers
+            .values()
+            .map(|t| {
+                let cost = (0..num_features)
+                    .map(|feature_idx| t.get_memory_elements(feature_idx).len())
+                    .sum::<usize>();
+                (cost, ExclusiveShared::clone(t))
+            })
+            .collect::<Vec<_>>();
+        trackers.sort_by(|(c1, t1), (c2, t2)| c2.cmp(c1).then(t1.id().cmp(&t2.id())));
+
+        let chunk_size = self.chunk_size.unwrap_or_else(|| {
+            usize::max(
+                trackers
+                    .len()
+                    .div_ceil(self.num_threads() * CHUNKS_PER_THREAD),
+                1,
+            )
+        });
+        trackers
+            .chunks(chunk_size)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|(_, t)| ExclusiveShared::clone(t))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Processes a frame for all the trackers.
+    ///
+    /// Returns the scores of each tracker and the performance diagnostics
+    /// of each thread, the main thread being the worker 0.
+    pub fn process_frame(
+        &mut self,
+        frame_idx: usize,
+        trackers: &HashMap<ID, ExclusiveShared<Tracker>>,
+    ) -> (HashMap<ID, Vec<RecordScore>>, Vec<WorkerFrameDiagnostics>) {
+        let frame = &self.frames.get(frame_idx);
+        let chunks = self.build_chunks(frame.num_features(), trackers);
+        let frame_uniques = &compute_frame_uniques(frame);
+        let next_chunk = AtomicUsize::new(0);
+
+        let mut results = (0..self.distance_calculators.len())
+            .map(|_| None)
+            .collect::<Vec<_>>();
+        let (main_calculators, pool_calculators) =
+            self.distance_calculators.split_first_mut().unwrap();
+        let (main_result, pool_results) = results.split_first_mut().unwrap();
+
+        let chunks = &chunks;
+        let next_chunk = &next_chunk;
+        match self.pool.as_ref() {
+            Some(pool) => pool.in_place_scope(|scope| {
+                for (worker_idx, (distance_calculators, result)) in pool_calculators
+                    .iter_mut()
+                    .zip(pool_results.iter_mut())
+                    .enumerate()
+                {
+                    scope.spawn(move |_| {
+                        *result = Some(process_chunks(
+                            worker_idx + 1,
+                            frame,
+                            frame_uniques,
+                            chunks,
+                            next_chunk,
+                            distance_calculators,
+                        ));
+                    });
+                }
+                *main_result = Some(process_chunks(
+                    0,
+                    frame,
+                    frame_uniques,
+                    chunks,
+                    next_chunk,
+                    main_calculators,
+                ));
+            }),
+            None => {
+                *main_result = Some(process_chunks(
+                    0,
+                    frame,
+                    frame_uniques,
+                    chunks,
+                    next_chunk,
+                    main_calculators,
+                ));
+            }
+        }
+
+        let mut trackers_scores = HashMap::with_capacity(trackers.len());
+        let mut workers_diagnostics = Vec::with_capacity(results.len());
+        for (scores, diagnostics) in results.into_iter().flatten() {
+            trackers_scores.extend(scores);
+            workers_diagnostics.push(diagnostics);
+        }
+        (trackers_scores, workers_diagnostics)
+    }
+}
+
+/// Takes chunks from the queue and processes them until the queue is empty.
+///
+/// Each chunk is taken by exactly one thread, which then has an exclusive
+/// access to its trackers.
+fn process_chunks(
+    worker_idx: usize,
+    frame: &Frame,
+    frame_uniques: &[HashMap<Word, u32>],
+    chunks: &[Vec<ExclusiveShared<Tracker>>],
+    next_chunk: &AtomicUsize,
+    distance_calculators: &mut Vec<CachedDistanceCalculator>,
+) -> (HashMap<ID, Vec<RecordScore>>, WorkerFrameDiagnostics) {
+    let mut trackers_scores = HashMap::new();
+    let mut diagnostics = WorkerFrameDiagnostics {
+        worker_idx,
+        ..Default::default()
+    };
+
+    loop {
+        let chunk_idx = next_chunk.fetch_add(1, Ordering::Relaxed);
+        let Some(chunk) = chunks.get(chunk_idx) else {
+            break;
+        };
+
+        let start = Instant::now();
+        setup_caches(frame_uniques, chunk.iter(), distance_calculators);
+        diagnostics.setup_caches_time += start.elapsed().as_secs_f64();
+
+        let start = Instant::now();
+        for tracker in chunk.iter() {
+            let mut tracker = ExclusiveShared::clone(tracker);
+            let tracker = tracker.exclusive();
+            let scores = tracker.process_frame(frame, distance_calculators);
+            trackers_scores.insert(tracker.id(), scores);
+        }
+        diagnostics.scoring_time += start.elapsed().as_secs_f64();
+
+        diagnostics.num_trackers += chunk.len();
+        diagnostics.num_chunks += 1;
+    }
+
+    for distance_calculator in distance_calculators.iter_mut() {
+        distance_calculator.clear_cache();
+        distance_calculator.flush_persistent_cache();
+    }
+    diagnostics.caches = distance_calculators
+        .iter_mut()
+        .enumerate()
+        .map(|(feature_idx, d)| CacheDiagnostics::new(feature_idx, d.take_trace()))
+        .collect();
+
+    (trackers_scores, diagnostics)
+}
@@ -4,16 +4,19 @@ use std::{
         mpsc::{Receiver, Sender},
         Arc,
     },
+    time::Instant,
 };
 
 use crate::{
+    api::{CacheDiagnostics, WorkerFrameDiagnostics},
     distances::CachedDistanceCalculator,
     frame::Frame,
     id::ID,
     trackers::{RecordScore, Tracker},
+    word::Word,
 };
 
-use super::exclusive_shared::ExclusiveShared;
+use super::{exclusive_shared::ExclusiveShared, frame_store::FrameStore};
 
 /// Tracking worker command
 ///
@@ -33,8 +36,8 @@ pub enum TrackingWorkerCommand {
 ///
 /// Represents a response that the tracking worker can send back.
 pub enum TrackingWorkerResponse {
-    /// Scores of the processed frame
-    ProcessFrame(HashMap<ID, Vec<RecordScore>>),
+    /// Scores and performance diagnostics of the processed frame
+    ProcessFrame(HashMap<ID, Vec<RecordScore>>, WorkerFrameDiagnostics),
 }
 
 /// Tracking worker handler
@@ -55,7 +58,8 @@ pub struct TrackingWorkerHandler {
 
 impl TrackingWorkerHandler {
     pub fn new(
-        frames: Arc<Vec<Frame>>,
+        worker_idx: usize,
+        frames: Arc<FrameStore>,
         trackers: HashMap<ID, ExclusiveShared<Tracker>>,
         distance_calculators: Vec<CachedDistanceCalculator>,
     ) -> Self {
@@ -65,6 +69,7 @@ impl TrackingWorkerHandler {
 
         std::thread::spawn(move || {
             let mut worker = TrackingWorker::new(
+                worker_idx,
                 receiver_cmd,
                 sender_resp,
                 frames,
@@ -118,13 +123,13 @@ impl TrackingWorkerHandler {
         self.sender.send(TrackingWorkerCommand::Stop).unwrap();
     }
 
-    /// Waits for the scores of the processed frame
+    /// Waits for the scores and performance diagnostics of the processed frame
     ///
     /// This should be called after `process_frame`, it is the equivalent of
     /// a join.
-    pub fn wait_scores(&self) -> HashMap<ID, Vec<RecordScore>> {
+    pub fn wait_scores(&self) -> (HashMap<ID, Vec<RecordScore>>, WorkerFrameDiagnostics) {
         match self.receiver.recv() {
-            Ok(TrackingWorkerResponse::ProcessFrame(scores)) => scores,
+            Ok(TrackingWorkerResponse::ProcessFrame(scores, diagnostics)) => (scores, diagnostics),
             _ => panic!("invalid response"),
         }
     }
@@ -139,22 +144,25 @@ impl TrackingWorkerHandler {
 /// The worker itself lives in the other thread and processes the commands
 /// it receives from its handler in the main thread.
 pub struct TrackingWorker {
+    worker_idx: usize,
     receiver: Receiver<TrackingWorkerCommand>,
     sender: Sender<TrackingWorkerResponse>,
-    frames: Arc<Vec<Frame>>,
+    frames: Arc<FrameStore>,
     trackers: HashMap<ID, ExclusiveShared<Tracker>>,
     distance_calculators: Vec<CachedDistanceCalculator>,
 }
 
 impl TrackingWorker {
     pub fn new(
+        worker_idx: usize,
         receiver: Receiver<TrackingWorkerCommand>,
         sender: Sender<TrackingWorkerResponse>,
-        frames: Arc<Vec<Frame>>,
+        frames: Arc<FrameStore>,
         trackers: HashMap<ID, ExclusiveShared<Tracker>>,
         distance_calculators: Vec<CachedDistanceCalculator>,
     ) -> Self {
         Self {
+            worker_idx,
             receiver,
             sender,
             frames,
@@ -176,9 +184,9 @@ impl TrackingWorker {
                     self.remove_trackers(ids);
                 }
                 Ok(TrackingWorkerCommand::ProcessFrame(frame_idx)) => {
-                    let scores = self.process_frame(frame_idx);
+                    let (scores, diagnostics) = self.process_frame(frame_idx);
                     self.sender
-                        .send(TrackingWorkerResponse::ProcessFrame(scores))
+                        .send(TrackingWorkerResponse::ProcessFrame(scores, diagnostics))
                         .unwrap();
                 }
                 Ok(TrackingWorkerCommand::Stop) => return,
@@ -208,24 +216,20 @@ impl TrackingWorker {
     /// - The cache being smaller, it is more likely to fit in the CPU cache, hence
     ///   it has faster access times.
     fn setup_caches(&mut self, frame_idx: usize) {
-        let frame = &self.frames[frame_idx];
-
-        for feature_idx in 0..self.frames[0].num_features() {
-            let distance_calculator = &mut self.distance_calculators[feature_idx];
-            distance_calculator.clear_cache();
-            let mut memory_elements = Vec::new();
-            for tracker in self.trackers.values() {
-                memory_elements.extend(tracker.get_memory_elements(feature_idx));
-            }
-            distance_calculator.precompute(
-                &memory_elements,
-                &frame.column(feature_idx).iter().map(|e| e).collect(),
-            );
-        }
+        setup_caches(
+            &compute_frame_uniques(&self.frames.get(frame_idx)),
+            self.trackers.values(),
+            &mut self.distance_calculators,
+        );
     }
 
-    fn process_frame(&mut self, frame_idx: usize) -> HashMap<ID, Vec<RecordScore>> {
+    fn process_frame(
+        &mut self,
+        frame_idx: usize,
+    ) -> (HashMap<ID, Vec<RecordScore>>, WorkerFrameDiagnostics) {
+        let start = Instant::now();
         self.setup_caches(frame_idx);
+        let setup_caches_time = start.elapsed().as_secs_f64();
 
         log::debug!(
             "[worker {:?}] process frame {}:  trackers: {}  cache size: {}",
@@ -241,16 +245,68 @@ impl TrackingWorker {
                 .sum::<usize>(),
         );
 
-        let frame = &self.frames[frame_idx];
+        let start = Instant::now();
+        let frame = self.frames.get(frame_idx);
         let mut trackers_scores = HashMap::with_capacity(self.trackers.len());
 
         for (_, tracker) in self.trackers.iter_mut() {
             let tracker = tracker.exclusive();
-            let scores = tracker.process_frame(frame, &mut self.distance_calculators);
+            let scores = tracker.process_frame(&frame, &mut self.distance_calculators);
 
             trackers_scores.insert(tracker.id(), scores);
         }
+        let scoring_time = start.elapsed().as_secs_f64();
+
+        for distance_calculator in self.distance_calculators.iter_mut() {
+            distance_calculator.flush_persistent_cache();
+        }
+
+        let diagnostics = WorkerFrameDiagnostics {
+            worker_idx: self.worker_idx,
+            num_trackers: self.trackers.len(),
+            num_chunks: 1,
+            setup_caches_time,
+            scoring_time,
+            caches: self
+                .distance_calculators
+                .iter_mut()
+                .enumerate()
+                .map(|(feature_idx, d)| CacheDiagnostics::new(feature_idx, d.take_trace()))
+                .collect(),
+        };
 
-        trackers_scores
+        (trackers_scores, diagnostics)
+    }
+}
+
+/// Counts the unique values of each feature of the frame, to be shared
+/// by the precomputations of the caches.
+pub fn compute_frame_uniques(frame: &Frame) -> Vec<HashMap<Word, u32>> {
+    (0..frame.num_features())
+        .map(|feature_idx| {
+            CachedDistanceCalculator::compute_uniques(
+                &frame.column(feature_idx).iter().collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
+
+/// Precomputes the caches of the distance calculators from the memories
+/// of the trackers and the unique values of the frame, the previous caches are cleared.
+pub fn setup_caches<'a>(
+    frame_uniques: &[HashMap<Word, u32>],
+    trackers: impl Iterator<Item = &'a ExclusiveShared<Tracker>> + Clone,
+    distance_calculators: &mut [CachedDistanceCalculator],
+) {
+    for (feature_idx, distance_calculator) in distance_calculators.iter_mut().enumerate() {
+        distance_calculator.clear_cache();
+        let mut memory_elements = Vec::new();
+        for tracker in trackers.clone() {
+            memory_elements.extend(tracker.get_memory_elements(feature_idx));
+        }
+        distance_calculator.precompute_uniques(
+            &CachedDistanceCalculator::compute_uniques(&memory_elements),
+            &frame_uniques[feature_idx],
+        );
     }
 }
@@ -0,0 +1,52 @@
+use std::fmt;
+
+use polars::error::PolarsError;
+
+/// BlitzBeaverError
+///
+/// Error returned by the library API.
+#[derive(Debug)]
+pub enum BlitzBeaverError {
+    /// The configuration is invalid.
+    InvalidConfig(String),
+    /// The input data is invalid, e.g. a field is missing or a record doesn't exist.
+    InvalidData(String),
+    /// The edit of the tracking graph is invalid.
+    InvalidEdit(String),
+    /// A beaver file can't be serialized or deserialized.
+    Serialization(String),
+    /// Error raised by polars.
+    Polars(PolarsError),
+}
+
+pub type Result<T> = std::result::Result<T, BlitzBeaverError>;
+
+impl fmt::Display for BlitzBeaverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidConfig(e)
+            | Self::InvalidData(e)
+            | Self::InvalidEdit(e)
+            | Self::Serialization(e) => write!(f, "{}", e),
+            Self::Polars(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for BlitzBeaverError {}
+
+impl From<PolarsError> for BlitzBeaverError {
+    fn from(e: PolarsError) -> Self {
+        Self::Polars(e)
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<BlitzBeaverError> for pyo3::PyErr {
+    fn from(e: BlitzBeaverError) -> Self {
+        match e {
+            BlitzBeaverError::Polars(e) => pyo3_polars::error::PyPolarsErr::from(e).into(),
+            e => pyo3::exceptions::PyValueError::new_err(e.to_string()),
+        }
+    }
+}
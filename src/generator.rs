@@ -0,0 +1,126 @@
+mod noise;
+mod population;
+
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{error::Result, frame::Frame};
+
+pub use noise::NoiseModel;
+pub use population::{EventRates, Population};
+
+/// Names of the fields of the generated records, in order,
+/// all are single words except the children.
+pub const CENSUS_FIELDS: [&str; 6] = [
+    "address",
+    "firstname",
+    "lastname",
+    "origin",
+    "occupation",
+    "children",
+];
+
+/// SyntheticFrames
+///
+/// Frames generated from a simulated population, along with the true chains:
+/// the `(frame_idx, record_idx)` of the records of each person.
+pub struct SyntheticFrames {
+    pub frames: Vec<Frame>,
+    pub chains: Vec<Vec<(usize, usize)>>,
+}
+
+/// Simulates a population of `num_households` households over `num_frames` frames,
+/// each frame holds one record per household (its head) with the noise applied.
+///
+/// The generation is deterministic given the seed.
+///
+/// # Errors
+/// Returns an InvalidData error if all the word IDs have been used.
+pub fn generate_frames(
+    num_frames: usize,
+    num_households: usize,
+    rates: EventRates,
+    noise: &NoiseModel,
+    seed: u64,
+) -> Result<SyntheticFrames> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut population = Population::new(rates, num_households, &mut rng);
+
+    let mut frames = Vec::with_capacity(num_frames);
+    let mut chains: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    for frame_idx in 0..num_frames {
+        if frame_idx > 0 {
+            population.step(&mut rng);
+        }
+        let (columns, heads) = population.records(&mut rng);
+        let columns = columns
+            .iter()
+            .map(|column| column.iter().map(|e| noise.apply(e, &mut rng)).collect())
+            .collect::<Result<_>>()?;
+        frames.push(Frame::new(frame_idx, columns));
+
+        for (record_idx, head) in heads.into_iter().enumerate() {
+            chains
+                .entry(head)
+                .or_default()
+                .push((frame_idx, record_idx));
+        }
+    }
+
+    let mut chains = chains.into_values().collect::<Vec<_>>();
+    chains.sort();
+    Ok(SyntheticFrames { frames, chains })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn build_rates() -> EventRates {
+        EventRates {
+            birth_rate: 0.1,
+            death_rate: 0.05,
+            marriage_rate: 0.1,
+            move_rate: 0.1,
+            occupation_change_rate: 0.1,
+            leave_home_rate: 0.2,
+            immigration_rate: 0.05,
+        }
+    }
+
+    #[test]
+    fn test_generate_frames() {
+        let noise = NoiseModel {
+            ocr_confusion_rate: 0.05,
+            letter_drop_rate: 0.02,
+            missing_field_rate: 0.05,
+        };
+        let synthetic = generate_frames(8, 50, build_rates(), &noise, 42).unwrap();
+        assert_eq!(synthetic.frames.len(), 8);
+        assert_eq!(synthetic.frames[0].num_records(), 50);
+
+        // each record belongs to exactly one chain, in chronological order
+        let mut nodes = HashSet::new();
+        for chain in synthetic.chains.iter() {
+            assert!(chain.windows(2).all(|w| w[0].0 < w[1].0));
+            for &(frame_idx, record_idx) in chain.iter() {
+                assert!(record_idx < synthetic.frames[frame_idx].num_records());
+                assert!(nodes.insert((frame_idx, record_idx)));
+            }
+        }
+        let num_records = synthetic
+            .frames
+            .iter()
+            .map(|f| f.num_records())
+            .sum::<usize>();
+        assert_eq!(nodes.len(), num_records);
+
+        // deterministic given the seed
+        let other = generate_frames(8, 50, build_rates(), &noise, 42).unwrap();
+        assert_eq!(other.chains, synthetic.chains);
+        assert_eq!(other.frames[7].column(2), synthetic.frames[7].column(2));
+    }
+}
@@ -0,0 +1,143 @@
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    error::Result,
+    frame::Element,
+    word::{GraphemeType, Word},
+};
+
+/// Letters commonly confused when transcribing handwritten records.
+const OCR_CONFUSIONS: [(&str, &str); 10] = [
+    ("e", "c"),
+    ("i", "l"),
+    ("n", "u"),
+    ("m", "n"),
+    ("o", "a"),
+    ("h", "b"),
+    ("t", "f"),
+    ("r", "v"),
+    ("a", "e"),
+    ("s", "f"),
+];
+
+/// NoiseModel
+///
+/// Transcription noise applied to the records: letters confused with similar
+/// letters, letters dropped and fields missing altogether.
+#[derive(Debug, Clone)]
+pub struct NoiseModel {
+    pub ocr_confusion_rate: f32,
+    pub letter_drop_rate: f32,
+    pub missing_field_rate: f32,
+}
+
+impl NoiseModel {
+    /// Returns the letters the grapheme can be confused with.
+    fn confusions(grapheme: GraphemeType) -> Vec<GraphemeType> {
+        let letter = Word::grapheme_to_string(grapheme);
+        OCR_CONFUSIONS
+            .iter()
+            .filter_map(|(a, b)| {
+                if *a == letter {
+                    Some(b)
+                } else if *b == letter {
+                    Some(a)
+                } else {
+                    None
+                }
+            })
+            .map(|l| Word::string_to_grapheme(l))
+            .collect()
+    }
+
+    /// Applies the letter confusions and drops to the word,
+    /// at least one letter is kept.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn apply_word(&self, word: &Word, rng: &mut StdRng) -> Result<Word> {
+        let mut graphemes = Vec::with_capacity(word.graphemes().len());
+        for &grapheme in word.graphemes().iter() {
+            if rng.random_bool(self.letter_drop_rate as f64) {
+                continue;
+            }
+            if rng.random_bool(self.ocr_confusion_rate as f64) {
+                let confusions = Self::confusions(grapheme);
+                if !confusions.is_empty() {
+                    graphemes.push(confusions[rng.random_range(..confusions.len())]);
+                    continue;
+                }
+            }
+            graphemes.push(grapheme);
+        }
+        if graphemes.is_empty() {
+            return Ok(word.clone());
+        }
+        Word::from_graphemes(graphemes)
+    }
+
+    /// Applies the noise to the element, a missing multi-words element is empty.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn apply(&self, element: &Element, rng: &mut StdRng) -> Result<Element> {
+        let missing = rng.random_bool(self.missing_field_rate as f64);
+        Ok(match element {
+            Element::None => Element::None,
+            Element::Word(_) if missing => Element::None,
+            Element::Word(word) => Element::Word(self.apply_word(word, rng)?),
+            Element::MultiWords(_) if missing => Element::MultiWords(Vec::new()),
+            Element::MultiWords(words) => Element::MultiWords(
+                words
+                    .iter()
+                    .map(|w| self.apply_word(w, rng))
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_apply_noise() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let word = Element::Word(Word::new("chippis".to_string()));
+
+        let noise = NoiseModel {
+            ocr_confusion_rate: 0.0,
+            letter_drop_rate: 0.0,
+            missing_field_rate: 0.0,
+        };
+        assert_eq!(noise.apply(&word, &mut rng).unwrap(), word);
+
+        let noise = NoiseModel {
+            ocr_confusion_rate: 1.0,
+            letter_drop_rate: 0.0,
+            missing_field_rate: 0.0,
+        };
+        // p has no confusion, the other letters have a single one
+        assert_eq!(
+            noise.apply(&word, &mut rng).unwrap(),
+            Element::Word(Word::new("eblpplf".to_string()))
+        );
+
+        let noise = NoiseModel {
+            ocr_confusion_rate: 0.0,
+            letter_drop_rate: 1.0,
+            missing_field_rate: 0.0,
+        };
+        assert_eq!(noise.apply(&word, &mut rng).unwrap(), word);
+
+        let noise = NoiseModel {
+            ocr_confusion_rate: 0.0,
+            letter_drop_rate: 0.0,
+            missing_field_rate: 1.0,
+        };
+        assert_eq!(noise.apply(&word, &mut rng).unwrap(), Element::None);
+    }
+}
@@ -0,0 +1,467 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+use crate::{frame::Element, word::Word};
+
+const MALE_FIRSTNAMES: [&str; 16] = [
+    "jean",
+    "louis",
+    "pierre",
+    "françois",
+    "jacques",
+    "henri",
+    "charles",
+    "jules",
+    "emile",
+    "auguste",
+    "david",
+    "samuel",
+    "abram",
+    "marc",
+    "daniel",
+    "gustave",
+];
+const FEMALE_FIRSTNAMES: [&str; 16] = [
+    "marie",
+    "louise",
+    "jeanne",
+    "julie",
+    "susanne",
+    "elise",
+    "sophie",
+    "rose",
+    "françoise",
+    "henriette",
+    "charlotte",
+    "marguerite",
+    "anne",
+    "emma",
+    "catherine",
+    "lucie",
+];
+const LASTNAMES: [&str; 24] = [
+    "favre",
+    "bovet",
+    "chappuis",
+    "mercier",
+    "rochat",
+    "blanc",
+    "pittet",
+    "cuénod",
+    "gonin",
+    "dubois",
+    "jaccard",
+    "bonzon",
+    "martin",
+    "perret",
+    "chevalley",
+    "rouge",
+    "cherix",
+    "morel",
+    "vuilleumier",
+    "monod",
+    "secretan",
+    "reymond",
+    "mayor",
+    "bugnion",
+];
+const ORIGINS: [&str; 16] = [
+    "lausanne",
+    "vevey",
+    "morges",
+    "moudon",
+    "yverdon",
+    "nyon",
+    "aigle",
+    "orbe",
+    "payerne",
+    "genève",
+    "fribourg",
+    "neuchâtel",
+    "berne",
+    "savoie",
+    "france",
+    "allemagne",
+];
+const OCCUPATIONS: [&str; 20] = [
+    "agriculteur",
+    "vigneron",
+    "charpentier",
+    "cordonnier",
+    "tailleur",
+    "boulanger",
+    "boucher",
+    "menuisier",
+    "maçon",
+    "serrurier",
+    "négociant",
+    "instituteur",
+    "journalier",
+    "domestique",
+    "couturière",
+    "lingère",
+    "horloger",
+    "aubergiste",
+    "voiturier",
+    "rentier",
+];
+const STREETS: [&str; 16] = [
+    "rue de bourg",
+    "rue du pont",
+    "place de la palud",
+    "rue de la cité",
+    "rue du pré",
+    "chemin de montbenon",
+    "rue de l'ale",
+    "rue de la madeleine",
+    "avenue d'ouchy",
+    "rue saint-françois",
+    "rue saint-laurent",
+    "rue de la barre",
+    "chemin de chailly",
+    "rue de la mercerie",
+    "route de berne",
+    "chemin de la sallaz",
+];
+
+/// Age at which a child can leave home or marry.
+const ADULT_AGE: u32 = 18;
+/// Age after which a woman has no more children.
+const MAX_BIRTH_AGE: u32 = 45;
+
+/// EventRates
+///
+/// Probabilities of the events of the population between two frames,
+/// each one applies to each person (or household) independently.
+#[derive(Debug, Clone)]
+pub struct EventRates {
+    pub birth_rate: f32,
+    pub death_rate: f32,
+    pub marriage_rate: f32,
+    pub move_rate: f32,
+    pub occupation_change_rate: f32,
+    pub leave_home_rate: f32,
+    pub immigration_rate: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Person {
+    female: bool,
+    firstname: &'static str,
+    lastname: &'static str,
+    origin: &'static str,
+    occupation: Option<&'static str>,
+    age: u32,
+}
+
+/// Household
+///
+/// Head of the household and the people living with them, the head is the
+/// person appearing in the records.
+#[derive(Debug, Clone)]
+struct Household {
+    head: usize,
+    spouse: Option<usize>,
+    children: Vec<usize>,
+    address: String,
+}
+
+/// Population
+///
+/// Simulated population of households, evolving from one frame to the next with
+/// births, deaths, marriages, moves, occupation changes, children leaving home
+/// and immigration.
+///
+/// Each person is identified by its index, which is stable across the frames.
+pub struct Population {
+    rates: EventRates,
+    people: Vec<Person>,
+    households: Vec<Household>,
+}
+
+impl Population {
+    /// Creates a population of `num_households` households.
+    pub fn new(rates: EventRates, num_households: usize, rng: &mut StdRng) -> Self {
+        let mut population = Self {
+            rates,
+            people: Vec::new(),
+            households: Vec::new(),
+        };
+        for _ in 0..num_households {
+            population.add_immigrant_household(rng);
+        }
+        population
+    }
+
+    fn new_person(&mut self, person: Person) -> usize {
+        self.people.push(person);
+        self.people.len() - 1
+    }
+
+    fn random_person(female: bool, age: u32, rng: &mut StdRng) -> Person {
+        let firstnames = if female {
+            &FEMALE_FIRSTNAMES
+        } else {
+            &MALE_FIRSTNAMES
+        };
+        Person {
+            female,
+            firstname: firstnames[rng.random_range(..firstnames.len())],
+            lastname: LASTNAMES[rng.random_range(..LASTNAMES.len())],
+            origin: ORIGINS[rng.random_range(..ORIGINS.len())],
+            occupation: Some(OCCUPATIONS[rng.random_range(..OCCUPATIONS.len())]),
+            age,
+        }
+    }
+
+    fn random_address(rng: &mut StdRng) -> String {
+        format!(
+            "{} {}",
+            STREETS[rng.random_range(..STREETS.len())],
+            rng.random_range(1..=40)
+        )
+    }
+
+    fn new_child(
+        &mut self,
+        lastname: &'static str,
+        origin: &'static str,
+        age: u32,
+        rng: &mut StdRng,
+    ) -> usize {
+        let mut child = Self::random_person(rng.random_bool(0.5), age, rng);
+        child.lastname = lastname;
+        child.origin = origin;
+        child.occupation = None;
+        self.new_person(child)
+    }
+
+    /// Adds a household coming from outside: a head, possibly married, with children.
+    fn add_immigrant_household(&mut self, rng: &mut StdRng) {
+        let head = Self::random_person(rng.random_bool(0.2), rng.random_range(ADULT_AGE..60), rng);
+        let (lastname, origin, head_age, female) =
+            (head.lastname, head.origin, head.age, head.female);
+        let head = self.new_person(head);
+
+        let mut spouse = None;
+        if !female && rng.random_bool(0.6) {
+            let mut person = Self::random_person(
+                true,
+                head_age
+                    .saturating_sub(rng.random_range(0..6))
+                    .max(ADULT_AGE),
+                rng,
+            );
+            person.lastname = lastname;
+            person.occupation = None;
+            spouse = Some(self.new_person(person));
+        }
+
+        let mut children = Vec::new();
+        if head_age > ADULT_AGE + 1 {
+            for _ in 0..rng.random_range(0..=4) {
+                let age = rng.random_range(0..(head_age - ADULT_AGE).min(ADULT_AGE));
+                children.push(self.new_child(lastname, origin, age, rng));
+            }
+        }
+
+        self.households.push(Household {
+            head,
+            spouse,
+            children,
+            address: Self::random_address(rng),
+        });
+    }
+
+    /// Moves the population to the next frame.
+    pub fn step(&mut self, rng: &mut StdRng) {
+        for person in self.people.iter_mut() {
+            person.age += 1;
+        }
+        self.apply_deaths(rng);
+        self.apply_leave_home(rng);
+        self.apply_marriages(rng);
+        self.apply_births(rng);
+
+        let rates = &self.rates;
+        for household in self.households.iter_mut() {
+            if rng.random_bool(rates.move_rate as f64) {
+                household.address = Self::random_address(rng);
+            }
+            if rng.random_bool(rates.occupation_change_rate as f64) {
+                self.people[household.head].occupation =
+                    Some(OCCUPATIONS[rng.random_range(..OCCUPATIONS.len())]);
+            }
+        }
+
+        let num_immigrants = (0..self.households.len())
+            .filter(|_| rng.random_bool(self.rates.immigration_rate as f64))
+            .count();
+        for _ in 0..num_immigrants {
+            self.add_immigrant_household(rng);
+        }
+    }
+
+    /// The spouse of a dead head becomes the head, without spouse the household dissolves.
+    fn apply_deaths(&mut self, rng: &mut StdRng) {
+        let death_rate = self.rates.death_rate as f64;
+        let mut households = Vec::new();
+        for mut household in self.households.drain(..) {
+            household.children.retain(|_| !rng.random_bool(death_rate));
+            if household.spouse.is_some() && rng.random_bool(death_rate) {
+                household.spouse = None;
+            }
+            if rng.random_bool(death_rate) {
+                match household.spouse.take() {
+                    Some(spouse) => household.head = spouse,
+                    None => continue,
+                }
+            }
+            households.push(household);
+        }
+        self.households = households;
+    }
+
+    /// Adult children leave home and become the head of a new household.
+    fn apply_leave_home(&mut self, rng: &mut StdRng) {
+        let mut new_households = Vec::new();
+        for household in self.households.iter_mut() {
+            let people = &mut self.people;
+            household.children.retain(|&child| {
+                if people[child].age < ADULT_AGE
+                    || !rng.random_bool(self.rates.leave_home_rate as f64)
+                {
+                    return true;
+                }
+                people[child].occupation = Some(OCCUPATIONS[rng.random_range(..OCCUPATIONS.len())]);
+                new_households.push(Household {
+                    head: child,
+                    spouse: None,
+                    children: Vec::new(),
+                    address: Self::random_address(rng),
+                });
+                false
+            });
+        }
+        self.households.extend(new_households);
+    }
+
+    /// Unmarried heads marry: a woman head marrying a man head joins his household,
+    /// otherwise the partner comes from outside. A woman takes the lastname of
+    /// her husband and is no longer the head of the household.
+    fn apply_marriages(&mut self, rng: &mut StdRng) {
+        let mut men = Vec::new();
+        let mut women = Vec::new();
+        for (i, household) in self.households.iter().enumerate() {
+            let head = &self.people[household.head];
+            if household.spouse.is_none()
+                && head.age >= ADULT_AGE
+                && rng.random_bool(self.rates.marriage_rate as f64)
+            {
+                if head.female {
+                    women.push(i);
+                } else {
+                    men.push(i);
+                }
+            }
+        }
+        men.shuffle(rng);
+        women.shuffle(rng);
+
+        let mut removed = Vec::new();
+        for (i, &man_idx) in men.iter().enumerate() {
+            let lastname = self.people[self.households[man_idx].head].lastname;
+            let wife = match women.get(i) {
+                Some(&woman_idx) => {
+                    let woman_household = self.households[woman_idx].clone();
+                    self.households[man_idx]
+                        .children
+                        .extend(woman_household.children);
+                    removed.push(woman_idx);
+                    woman_household.head
+                }
+                None => {
+                    let age = self.people[self.households[man_idx].head].age;
+                    let person = Self::random_person(true, age, rng);
+                    self.new_person(person)
+                }
+            };
+            self.people[wife].lastname = lastname;
+            self.people[wife].occupation = None;
+            self.households[man_idx].spouse = Some(wife);
+        }
+
+        for &woman_idx in women.iter().skip(men.len()) {
+            let wife = self.households[woman_idx].head;
+            let age = self.people[wife].age;
+            let husband = Self::random_person(false, age, rng);
+            self.people[wife].lastname = husband.lastname;
+            self.people[wife].occupation = None;
+
+            let household = &mut self.households[woman_idx];
+            household.head = self.people.len();
+            household.spouse = Some(wife);
+            self.people.push(husband);
+        }
+
+        removed.sort_unstable();
+        for idx in removed.into_iter().rev() {
+            self.households.remove(idx);
+        }
+    }
+
+    fn apply_births(&mut self, rng: &mut StdRng) {
+        for i in 0..self.households.len() {
+            let household = &self.households[i];
+            let spouse = match household.spouse {
+                Some(spouse) => spouse,
+                None => continue,
+            };
+            let mother = if self.people[spouse].female {
+                spouse
+            } else {
+                household.head
+            };
+            if self.people[mother].age > MAX_BIRTH_AGE
+                || !rng.random_bool(self.rates.birth_rate as f64)
+            {
+                continue;
+            }
+            let father = &self.people[household.head];
+            let (lastname, origin) = (father.lastname, father.origin);
+            let child = self.new_child(lastname, origin, 0, rng);
+            self.households[i].children.push(child);
+        }
+    }
+
+    /// Returns the records of the households, in a random order, along with the
+    /// person (the head) of each record.
+    ///
+    /// The records have the fields: address, firstname, lastname, origin,
+    /// occupation and children (firstnames).
+    pub fn records(&self, rng: &mut StdRng) -> (Vec<Vec<Element>>, Vec<usize>) {
+        let mut order = (0..self.households.len()).collect::<Vec<_>>();
+        order.shuffle(rng);
+
+        let word = |s: &str| Element::Word(Word::new(s));
+        let mut columns = vec![Vec::new(); 6];
+        let mut heads = Vec::with_capacity(order.len());
+        for idx in order {
+            let household = &self.households[idx];
+            let head = &self.people[household.head];
+            columns[0].push(word(&household.address));
+            columns[1].push(word(head.firstname));
+            columns[2].push(word(head.lastname));
+            columns[3].push(word(head.origin));
+            columns[4].push(head.occupation.map_or(Element::None, word));
+            columns[5].push(Element::MultiWords(
+                household
+                    .children
+                    .iter()
+                    .map(|&child| Word::new(self.people[child].firstname))
+                    .collect(),
+            ));
+            heads.push(household.head);
+        }
+        (columns, heads)
+    }
+}
@@ -1,19 +1,49 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 mod api;
-mod benchmark;
+#[cfg(feature = "benchmark")]
+pub mod benchmark;
+#[cfg(feature = "cli")]
+pub mod cli;
 mod distances;
 mod engine;
+mod error;
 mod evaluation;
 mod frame;
+mod generator;
 mod histogram;
 mod id;
 mod logger;
 mod normalization;
 mod resolvers;
+mod search;
+#[cfg(test)]
+mod test_utils;
 mod trackers;
 mod word;
 
+#[cfg(feature = "benchmark")]
+pub use api::benchmark_results_to_dataframe;
+pub use api::{
+    census_record_schema, compute_median_word, compute_words_clusters, deserialize_config,
+    evaluate_tracking_chain_length, evaluate_tracking_graph_properties, execute_normalization,
+    execute_search, execute_tracking, execute_tracking_out_of_core, generate_census,
+    initialize_logger, normalize_words, search_results_to_dataframe, serialize_config,
+    AmbiguousMatchDiagnostics, BeaverFile, CacheDiagnostics, ChainLink, ChainNode,
+    CompositeFeatureConfig, ConfigFormat, Diagnostics, DistanceMetricConfig, ElementType,
+    EvalMetricChainLength, EvalMetricGraphProperties, FieldSchema, FramePerformanceDiagnostics,
+    GeneratorConfig, GeneratorEventsConfig, GeneratorNoiseConfig, GraphEdit, GraphExportFilter,
+    GraphNode, MemoryConfig, NormalizationConfig, PerformanceDiagnostics,
+    PersistentCacheDiagnostics, RawLink, RecordSchema, RecordScorerConfig, ResolverConfig,
+    ResolvingDiagnostics, SearchObjective, SearchOptions, SearchParameter, SearchResult,
+    SearchSpace, SearchValues, SyntheticCensus, TrackerConfig, TrackerDiagnostics,
+    TrackerFrameDiagnostics, TrackerRecordDiagnostics, TrackingConfig, TrackingGraph,
+    WorkerFrameDiagnostics,
+};
+pub use error::{BlitzBeaverError, Result};
+
+#[cfg(feature = "python")]
 #[pymodule]
 fn blitzbeaver(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // frame
@@ -27,29 +57,46 @@ fn blitzbeaver(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<api::DistanceMetricConfig>()?;
     m.add_class::<api::MemoryConfig>()?;
     m.add_class::<api::RecordScorerConfig>()?;
+    m.add_class::<api::CompositeFeatureConfig>()?;
     m.add_class::<api::TrackerConfig>()?;
     m.add_class::<api::NormalizationConfig>()?;
+    m.add_class::<api::GeneratorConfig>()?;
+    m.add_class::<api::GeneratorEventsConfig>()?;
+    m.add_class::<api::GeneratorNoiseConfig>()?;
 
     // tracking graph
     m.add_class::<api::ChainNode>()?;
+    m.add_class::<api::ChainLink>()?;
     m.add_class::<api::GraphNode>()?;
+    m.add_class::<api::GraphEdit>()?;
     m.add_class::<api::TrackingGraph>()?;
 
     // diagnostics
     m.add_class::<api::TrackerRecordDiagnostics>()?;
     m.add_class::<api::TrackerFrameDiagnostics>()?;
     m.add_class::<api::TrackerDiagnostics>()?;
+    m.add_class::<api::AmbiguousMatchDiagnostics>()?;
     m.add_class::<api::ResolvingDiagnostics>()?;
+    m.add_class::<api::CacheDiagnostics>()?;
+    m.add_class::<api::WorkerFrameDiagnostics>()?;
+    m.add_class::<api::FramePerformanceDiagnostics>()?;
+    m.add_class::<api::PersistentCacheDiagnostics>()?;
+    m.add_class::<api::PerformanceDiagnostics>()?;
     m.add_class::<api::Diagnostics>()?;
 
     m.add_class::<api::BeaverFile>()?;
 
     m.add_function(wrap_pyfunction!(api::setup_logger, m)?)?;
     m.add_function(wrap_pyfunction!(api::execute_tracking_process, m)?)?;
+    m.add_function(wrap_pyfunction!(api::export_tracking_graph, m)?)?;
     m.add_function(wrap_pyfunction!(api::execute_normalization_process, m)?)?;
-    m.add_function(wrap_pyfunction!(api::compute_median_word, m)?)?;
-    m.add_function(wrap_pyfunction!(api::compute_words_clusters, m)?)?;
-    m.add_function(wrap_pyfunction!(api::normalize_words, m)?)?;
+    m.add_function(wrap_pyfunction!(api::compute_median_word_process, m)?)?;
+    m.add_function(wrap_pyfunction!(api::compute_words_clusters_process, m)?)?;
+    m.add_function(wrap_pyfunction!(api::normalize_words_process, m)?)?;
+    m.add_function(wrap_pyfunction!(api::execute_search_process, m)?)?;
+    m.add_function(wrap_pyfunction!(api::generate_census_process, m)?)?;
+    #[cfg(feature = "benchmark")]
+    m.add_function(wrap_pyfunction!(api::benchmark_suite, m)?)?;
 
     // evaluation
     m.add_function(wrap_pyfunction!(api::evaluate_tracking_chain_length, m)?)?;
@@ -2,7 +2,7 @@ use env_logger;
 use log::LevelFilter;
 use std::io::Write;
 
-pub fn initialize_logger(log_level: &String) {
+pub fn initialize_logger(log_level: &str) {
     // Set the log level based on the configuration
     let level_filter = match log_level.to_lowercase().as_str() {
         "trace" => LevelFilter::Trace,
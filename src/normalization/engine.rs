@@ -1,5 +1,6 @@
 use crate::{
     api::{ElementType, RecordSchema, TrackingGraph},
+    error::Result,
     frame::{Element, Frame},
     trackers::TrackingChain,
 };
@@ -36,7 +37,21 @@ impl NormalizationEngine {
         }
     }
 
-    fn normalize_word_feature(&mut self, tracking_chain: &TrackingChain, feature_idx: usize) {
+    /// Returns the weight of each node of the chain: the score of its link,
+    /// the first node, without link, is fully trusted.
+    fn compute_nodes_weights(tracking_chain: &TrackingChain) -> Vec<f32> {
+        tracking_chain
+            .links
+            .iter()
+            .map(|link| link.as_ref().map_or(1., |link| link.score))
+            .collect()
+    }
+
+    fn normalize_word_feature(
+        &mut self,
+        tracking_chain: &TrackingChain,
+        feature_idx: usize,
+    ) -> Result<()> {
         let mut words = Vec::new();
         for node in tracking_chain.nodes.iter() {
             let frame = &self.frames[node.frame_idx];
@@ -44,7 +59,9 @@ impl NormalizationEngine {
             words.push(element.as_word());
         }
 
-        let normalized_words = self.normalizer.normalize_words(words);
+        let normalized_words = self
+            .normalizer
+            .normalize_words(words, &Self::compute_nodes_weights(tracking_chain))?;
 
         for (i, node) in tracking_chain.nodes.iter().enumerate() {
             let frame = &mut self.normalized_frames[node.frame_idx];
@@ -53,9 +70,14 @@ impl NormalizationEngine {
                 Some(ref word) => Element::Word(word.clone()),
             };
         }
+        Ok(())
     }
 
-    fn normalize_multiword_feature(&mut self, tracking_chain: &TrackingChain, feature_idx: usize) {
+    fn normalize_multiword_feature(
+        &mut self,
+        tracking_chain: &TrackingChain,
+        feature_idx: usize,
+    ) -> Result<()> {
         let mut words = Vec::new();
         for node in tracking_chain.nodes.iter() {
             let frame = &self.frames[node.frame_idx];
@@ -63,30 +85,38 @@ impl NormalizationEngine {
             words.push(element.as_multiword());
         }
 
-        let normalized_words = self.normalizer.normalize_multi_words(words);
+        let normalized_words = self
+            .normalizer
+            .normalize_multi_words(words, &Self::compute_nodes_weights(tracking_chain))?;
 
         for (i, node) in tracking_chain.nodes.iter().enumerate() {
             let frame = &mut self.normalized_frames[node.frame_idx];
             frame.mut_column(feature_idx)[node.record_idx] =
                 Element::MultiWords(normalized_words[i].clone());
         }
+        Ok(())
     }
 
-    fn normalize_tracking_chain(&mut self, tracking_chain: &TrackingChain) {
+    fn normalize_tracking_chain(&mut self, tracking_chain: &TrackingChain) -> Result<()> {
         let record_schema = self.record_schema.clone();
         for (feature_idx, field) in record_schema.fields.iter().enumerate() {
             match field.dtype {
                 ElementType::String => {
-                    self.normalize_word_feature(tracking_chain, feature_idx);
+                    self.normalize_word_feature(tracking_chain, feature_idx)?;
                 }
                 ElementType::MultiStrings => {
-                    self.normalize_multiword_feature(tracking_chain, feature_idx);
+                    self.normalize_multiword_feature(tracking_chain, feature_idx)?;
                 }
             }
         }
+        Ok(())
     }
 
-    pub fn normalize(mut self) -> Vec<Frame> {
+    /// Normalizes the frames, chain by chain.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn normalize(mut self) -> Result<Vec<Frame>> {
         let tracker_ids = self
             .tracking_graph
             .root
@@ -96,9 +126,9 @@ impl NormalizationEngine {
             .collect::<Vec<u64>>();
         for id in tracker_ids {
             let tracking_chain = self.tracking_graph.build_tracking_chain(id);
-            self.normalize_tracking_chain(&tracking_chain);
+            self.normalize_tracking_chain(&tracking_chain)?;
         }
 
-        self.normalized_frames
+        Ok(self.normalized_frames)
     }
 }
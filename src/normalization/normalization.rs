@@ -1,5 +1,9 @@
 use crate::{
-    distances::{compute_median_word, CachedDistanceCalculator, InternalDistanceMetricConfig},
+    distances::{
+        CachedDistanceCalculator, InternalDistanceMetricConfig, MedianWordBuilder,
+        MedianWordStrategy,
+    },
+    error::Result,
     word::Word,
 };
 
@@ -9,6 +13,10 @@ pub struct InternalNormalizationConfig {
     pub threshold_cluster_match: f32,
     pub min_cluster_size: usize,
     pub infer_missing_clusters: bool,
+    pub median_strategy: MedianWordStrategy,
+    /// If the words vote for the median of their cluster with their weight,
+    /// see `Normalizer::normalize_words`
+    pub weighted_median: bool,
 }
 
 /// Normalizer
@@ -34,7 +42,16 @@ impl Normalizer {
     /// Builds clusters of words based on their distances and computes the median word for each cluster.
     /// Returns a vector of median words and a mapping of original words to their respective cluster indices.
     /// The mapping is `None` for words that are not part of any cluster.
-    fn build_clusters(&mut self, words: &Vec<Option<&Word>>) -> (Vec<Word>, Vec<Option<usize>>) {
+    ///
+    /// The weights of the words are only used with the weighted median.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    fn build_clusters(
+        &mut self,
+        words: &Vec<Option<&Word>>,
+        weights: &[f32],
+    ) -> Result<(Vec<Word>, Vec<Option<usize>>)> {
         let mut map_idx = Vec::with_capacity(words.len());
         let mut non_null_words = Vec::with_capacity(words.len());
         for (i, word) in words.iter().enumerate() {
@@ -56,18 +73,20 @@ impl Normalizer {
         let mut medians = Vec::new();
         let mut cluster_map = vec![None; words.len()];
         for (cluster_idx, cluster_set) in clusters_sets.iter().enumerate() {
-            let mut cluster_words = Vec::new();
+            let mut median_word = MedianWordBuilder::new(self.config.median_strategy);
             for i in cluster_set.iter() {
                 let idx = map_idx[i];
                 cluster_map[idx] = Some(cluster_idx);
-                cluster_words.push(words[idx].unwrap());
+                if self.config.weighted_median {
+                    median_word.push_weighted(words[idx].unwrap(), weights[idx]);
+                } else {
+                    median_word.push(words[idx].unwrap());
+                }
             }
-
-            let median = compute_median_word(&cluster_words).unwrap();
-            medians.push(median);
+            medians.push(median_word.median()?.unwrap());
         }
 
-        (medians, cluster_map)
+        Ok((medians, cluster_map))
     }
 
     fn get_right_cluster(&self, cluster_map: &Vec<Option<usize>>, mut idx: usize) -> Option<usize> {
@@ -112,14 +131,25 @@ impl Normalizer {
     /// This approach assign a single cluster to each frame.
     ///
     /// In case no clusters are found, the original words are returned.
-    pub fn normalize_words(&mut self, words: Vec<Option<&Word>>) -> Vec<Option<Word>> {
-        let (medians, cluster_map) = self.build_clusters(&words);
+    ///
+    /// Each word has a weight (e.g. the score of the link to its record), with the
+    /// weighted median, it votes for the median of its cluster with its weight,
+    /// otherwise the weights are ignored.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn normalize_words(
+        &mut self,
+        words: Vec<Option<&Word>>,
+        weights: &[f32],
+    ) -> Result<Vec<Option<Word>>> {
+        let (medians, cluster_map) = self.build_clusters(&words, weights)?;
 
         if medians.len() == 0 {
-            return words.into_iter().map(|w| w.map(|w| w.clone())).collect();
+            return Ok(words.into_iter().map(|w| w.cloned()).collect());
         }
 
-        if self.config.infer_missing_clusters {
+        Ok(if self.config.infer_missing_clusters {
             let cluster_map = self.infer_missing_clusters(cluster_map);
             cluster_map
                 .into_iter()
@@ -134,22 +164,33 @@ impl Normalizer {
                     None => words[i].cloned(),
                 })
                 .collect()
-        }
+        })
     }
 
     /// Normalizes a vector of "multi-words" by clustering them and replacing each word with the median of its cluster.
     ///
     /// Attributes a range to each cluster, filling any missing word in a frame with the median of the cluster.
-    pub fn normalize_multi_words(&mut self, words: Vec<&Vec<Word>>) -> Vec<Vec<Word>> {
+    ///
+    /// Each frame has a weight, shared by all its words, see `normalize_words`.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn normalize_multi_words(
+        &mut self,
+        words: Vec<&Vec<Word>>,
+        weights: &[f32],
+    ) -> Result<Vec<Vec<Word>>> {
         let mut map_flat_word_frame_idx = Vec::with_capacity(words.len());
         let mut flat_words = Vec::new();
+        let mut flat_weights = Vec::new();
         for (frame_idx, frame) in words.iter().enumerate() {
             for word in frame.iter() {
                 flat_words.push(Some(word));
+                flat_weights.push(weights[frame_idx]);
                 map_flat_word_frame_idx.push(frame_idx);
             }
         }
-        let (medians, cluster_map) = self.build_clusters(&flat_words);
+        let (medians, cluster_map) = self.build_clusters(&flat_words, &flat_weights)?;
         let mut clusters_range = vec![(usize::MAX, 0); medians.len()];
         for (i, cluster) in cluster_map.iter().enumerate() {
             if let Some(cluster) = cluster {
@@ -168,7 +209,7 @@ impl Normalizer {
                 inferred_words[i].push(medians[cluster_idx].clone());
             }
         }
-        inferred_words
+        Ok(inferred_words)
     }
 }
 
@@ -200,8 +241,10 @@ mod tests {
             threshold_cluster_match: 0.6,
             min_cluster_size: 2,
             infer_missing_clusters: true,
+            median_strategy: MedianWordStrategy::Graphemes,
+            weighted_median: false,
         });
-        let normalized_words = normalizer.normalize_words(words);
+        let normalized_words = normalizer.normalize_words(words, &[1.; 7]).unwrap();
         assert_eq!(
             normalized_words,
             vec![
@@ -216,6 +259,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_weighted_words() {
+        let words = ["alice", "alise", "alise"].map(Word::new);
+        let words = words.iter().map(Some).collect::<Vec<_>>();
+        let weights = [3., 1., 1.];
+
+        for (weighted_median, expected) in [(false, "alise"), (true, "alice")] {
+            let mut normalizer = make_normalizer(InternalNormalizationConfig {
+                threshold_cluster_match: 0.6,
+                min_cluster_size: 2,
+                infer_missing_clusters: true,
+                median_strategy: MedianWordStrategy::Graphemes,
+                weighted_median,
+            });
+            let normalized_words = normalizer.normalize_words(words.clone(), &weights).unwrap();
+            assert_eq!(normalized_words, vec![Some(Word::new(expected)); 3]);
+        }
+    }
+
     #[test]
     fn test_normalize_multi_words() {
         let words = vec![
@@ -254,8 +316,12 @@ mod tests {
             threshold_cluster_match: 0.6,
             min_cluster_size: 2,
             infer_missing_clusters: true,
+            median_strategy: MedianWordStrategy::Graphemes,
+            weighted_median: false,
         });
-        let normalized_words = normalizer.normalize_multi_words(words.iter().collect());
+        let normalized_words = normalizer
+            .normalize_multi_words(words.iter().collect(), &[1.; 9])
+            .unwrap();
         assert_eq!(
             normalized_words,
             vec![
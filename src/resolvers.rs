@@ -1,7 +1,11 @@
 mod best_match_resolving_strategy;
+mod multi_hypothesis_resolving_strategy;
 mod resolver;
+mod resolving_constraints;
 mod simple_resolving_strategy;
 
 pub use best_match_resolving_strategy::BestMatchResolvingStrategy;
+pub use multi_hypothesis_resolving_strategy::MultiHypothesisResolvingStrategy;
 pub use resolver::{Resolver, ResolvingStrategy, ScoreBucket};
+pub use resolving_constraints::ResolvingConstraints;
 pub use simple_resolving_strategy::SimpleResolvingStrategy;
@@ -87,8 +87,7 @@ impl BestMatchResolvingStrategy {
     /// Return the indexes of the trackers that are still in standby
     fn resolve_trackers(
         &self,
-        frame: &Frame,
-        trackers: &mut Vec<ExclusiveShared<Tracker>>,
+        trackers_ids: &[ID],
         trackers_scores: &Vec<Vec<RecordScore>>,
         buckets: &Vec<ScoreBucket>,
         trackers_idx: &Vec<usize>,
@@ -96,18 +95,13 @@ impl BestMatchResolvingStrategy {
     ) -> Vec<usize> {
         let mut standby_idxs = Vec::new();
         for tracker_idx in trackers_idx {
-            let tracker = trackers[*tracker_idx].exclusive();
+            let tracker_id = trackers_ids[*tracker_idx];
             let tracker_scores = &trackers_scores[*tracker_idx];
-            match self.resolve_tracker(tracker.id(), tracker_scores, &buckets, &resolved_trackers) {
+            match self.resolve_tracker(tracker_id, tracker_scores, &buckets, &resolved_trackers) {
                 TrackerStatus::Resolved(record_idx) => {
                     // the tracker is resolved with this record
-                    // update the resolved_trackers map and signal
-                    // the tracker
-                    resolved_trackers.insert(tracker.id(), record_idx);
-                    tracker.signal_matching_node(
-                        ChainNode::new(frame.idx(), record_idx),
-                        frame.record(record_idx),
-                    );
+                    // update the resolved_trackers map
+                    resolved_trackers.insert(tracker_id, record_idx);
                 }
                 TrackerStatus::StandBy => {
                     // add the tracker to the standby list
@@ -119,33 +113,32 @@ impl BestMatchResolvingStrategy {
                     // still add it to the resolved_trackers with a special
                     // value, this is useful to ignore its scores when resolving
                     // standby trackers
-                    resolved_trackers.insert(tracker.id(), usize::MAX);
-                    tracker.signal_no_matching_node();
+                    resolved_trackers.insert(tracker_id, usize::MAX);
                 }
             }
         }
         standby_idxs
     }
-}
 
-impl ResolvingStrategy for BestMatchResolvingStrategy {
-    fn resolve(
-        &mut self,
-        frame: &Frame,
-        tracker_config: InternalTrackerConfig,
-        trackers: &mut Vec<ExclusiveShared<Tracker>>,
-        buckets: Vec<ScoreBucket>,
-        trackers_scores: Vec<Vec<RecordScore>>,
-    ) -> Vec<Tracker> {
+    /// Computes the best matches between the trackers and the records.
+    ///
+    /// Returns for each tracker ID the index of the matching record,
+    /// `usize::MAX` indicates that the tracker doesn't match with any record.
+    /// Trackers that failed to be resolved are absent.
+    pub fn compute_matches(
+        &self,
+        trackers_ids: &[ID],
+        buckets: &Vec<ScoreBucket>,
+        trackers_scores: &Vec<Vec<RecordScore>>,
+    ) -> HashMap<ID, usize> {
         let mut resolved_trackers = HashMap::new();
-        let mut trackers_idx: Vec<usize> = (0..trackers.len()).collect();
+        let mut trackers_idx: Vec<usize> = (0..trackers_ids.len()).collect();
 
         while trackers_idx.len() > 0 {
             let standby_idxs = self.resolve_trackers(
-                frame,
-                trackers,
-                &trackers_scores,
-                &buckets,
+                trackers_ids,
+                trackers_scores,
+                buckets,
                 &trackers_idx,
                 &mut resolved_trackers,
             );
@@ -161,9 +154,39 @@ impl ResolvingStrategy for BestMatchResolvingStrategy {
             trackers_idx = standby_idxs;
         }
 
+        resolved_trackers
+    }
+}
+
+impl ResolvingStrategy for BestMatchResolvingStrategy {
+    fn resolve(
+        &mut self,
+        frame: &Frame,
+        tracker_config: InternalTrackerConfig,
+        trackers: &mut Vec<ExclusiveShared<Tracker>>,
+        buckets: Vec<ScoreBucket>,
+        trackers_scores: Vec<Vec<RecordScore>>,
+    ) -> Vec<Tracker> {
+        let trackers_ids = trackers.iter().map(|t| t.id()).collect::<Vec<ID>>();
+        let matches = self.compute_matches(&trackers_ids, &buckets, &trackers_scores);
+
+        // signal the trackers, the trackers that failed to be resolved
+        // are left untouched
+        for tracker in trackers.iter_mut() {
+            let tracker = tracker.exclusive();
+            match matches.get(&tracker.id()) {
+                Some(&usize::MAX) => tracker.signal_no_matching_node(),
+                Some(&record_idx) => tracker.signal_matching_node(
+                    ChainNode::new(frame.idx(), record_idx),
+                    frame.record(record_idx),
+                ),
+                None => {}
+            }
+        }
+
         // build a map that indicate for each record if it matched with some tracker
         let mut records_match: Vec<bool> = (0..buckets.len()).map(|_| false).collect();
-        for (_, idx) in resolved_trackers.iter() {
+        for (_, idx) in matches.iter() {
             if *idx == usize::MAX {
                 continue;
             }
@@ -192,8 +215,8 @@ impl ResolvingStrategy for BestMatchResolvingStrategy {
 mod tests {
     use crate::{
         frame::Element,
-        resolvers::Resolver,
-        trackers::{TrackerMemoryConfig, TrackerRecordScorerConfig},
+        resolvers::{Resolver, ResolvingConstraints},
+        test_utils::build_tracker_config,
     };
 
     use super::*;
@@ -217,14 +240,9 @@ mod tests {
     ) -> (Vec<ExclusiveShared<Tracker>>, Vec<ExclusiveShared<Tracker>>) {
         let strategy = BestMatchResolvingStrategy {};
 
-        let mut resolver = Resolver::new(Box::new(strategy));
+        let mut resolver = Resolver::new(Box::new(strategy), None, ResolvingConstraints::default());
 
-        let tracker_config = InternalTrackerConfig {
-            interest_threshold: 0.7,
-            limit_no_match_streak: 5,
-            memory_configs: vec![TrackerMemoryConfig::BruteForce; num_features],
-            record_scorer: TrackerRecordScorerConfig::Average,
-        };
+        let tracker_config = build_tracker_config(0.7, 5, num_features);
 
         let mut trackers: Vec<ExclusiveShared<Tracker>> = trackers_scores
             .iter()
@@ -0,0 +1,183 @@
+use crate::{
+    api::ChainNode,
+    engine::ExclusiveShared,
+    frame::Frame,
+    id::ID,
+    trackers::{InternalTrackerConfig, RecordScore, Tracker},
+};
+
+use super::{BestMatchResolvingStrategy, ResolvingStrategy, ScoreBucket};
+
+/// MultiHypothesisResolvingStrategy
+///
+/// The multi-hypothesis strategy defers the matching decisions of each tracker
+/// for a few frames. Instead of committing to a single record per frame, each tracker
+/// keeps the `num_hypotheses` best hypotheses, that is possible paths over the
+/// last frames, ranked by their cumulative score. Once the best hypothesis is more
+/// than `depth` frames ahead of the tracker's chain, its oldest node is committed
+/// and the hypotheses that disagree with it are discarded.
+///
+/// This allows a tracker facing two similar records to wait for the next frames
+/// to decide which one was the right one.
+///
+/// Each record is owned by at most one tracker, only the owner can use the record in
+/// its hypotheses. The owner is the tracker that matched with the record according to the
+/// best match strategy, or if none, the tracker with the highest score for the record.
+/// New trackers are created for the records without owner and for the records
+/// released by their owner, that is records that are not part of any of its hypotheses
+/// anymore nor of its chain.
+pub struct MultiHypothesisResolvingStrategy {
+    num_hypotheses: usize,
+    depth: usize,
+    best_match: BestMatchResolvingStrategy,
+}
+
+impl MultiHypothesisResolvingStrategy {
+    pub fn new(num_hypotheses: usize, depth: usize) -> Self {
+        Self {
+            num_hypotheses,
+            depth,
+            best_match: BestMatchResolvingStrategy {},
+        }
+    }
+}
+
+impl ResolvingStrategy for MultiHypothesisResolvingStrategy {
+    fn resolve(
+        &mut self,
+        frame: &Frame,
+        tracker_config: InternalTrackerConfig,
+        trackers: &mut Vec<ExclusiveShared<Tracker>>,
+        buckets: Vec<ScoreBucket>,
+        trackers_scores: Vec<Vec<RecordScore>>,
+    ) -> Vec<Tracker> {
+        let trackers_ids = trackers.iter().map(|t| t.id()).collect::<Vec<ID>>();
+        let matches = self
+            .best_match
+            .compute_matches(&trackers_ids, &buckets, &trackers_scores);
+
+        // for each record, the tracker it matched with in the best match strategy,
+        // or the tracker with the highest score for it
+        let mut records_owner: Vec<Option<ID>> = buckets
+            .iter()
+            .map(|bucket| bucket.scores().first().map(|(_, id)| *id))
+            .collect();
+        for (id, idx) in matches.iter() {
+            if *idx != usize::MAX {
+                records_owner[*idx] = Some(*id);
+            }
+        }
+
+        let mut released = Vec::new();
+        for tracker in trackers.iter_mut() {
+            let tracker = tracker.exclusive();
+            let tracker_id = tracker.id();
+            released.extend(tracker.signal_hypotheses(
+                frame,
+                |record_idx| records_owner[record_idx] == Some(tracker_id),
+                self.num_hypotheses,
+                self.depth,
+            ));
+        }
+
+        // build new trackers
+        let mut new_trackers = Vec::new();
+
+        for (node, record) in released.into_iter() {
+            let mut new_tracker = Tracker::new(tracker_config.clone());
+            new_tracker.signal_matching_node(node, record);
+            new_trackers.push(new_tracker);
+        }
+        for (record_idx, owner) in records_owner.iter().enumerate() {
+            if owner.is_none() {
+                let mut new_tracker = Tracker::new(tracker_config.clone());
+                new_tracker.signal_matching_node(
+                    ChainNode::new(frame.idx(), record_idx),
+                    frame.record(record_idx),
+                );
+                new_trackers.push(new_tracker);
+            }
+        }
+
+        new_trackers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        frame::Element,
+        resolvers::{Resolver, ResolvingConstraints},
+        test_utils::{build_distance_calculators, build_tracker_config},
+        word::Word,
+    };
+
+    use super::*;
+
+    fn build_frame(idx: usize, words: &[&str]) -> Frame {
+        Frame::new(
+            idx,
+            vec![words
+                .iter()
+                .map(|w| Element::Word(Word::new(w.to_string())))
+                .collect()],
+        )
+    }
+
+    #[test]
+    fn test_deferred_decision() {
+        let tracker_config = build_tracker_config(0.5, 5, 1);
+        let mut distance_calculators = build_distance_calculators(1);
+        let mut resolver = Resolver::new(
+            Box::new(MultiHypothesisResolvingStrategy::new(3, 1)),
+            None,
+            ResolvingConstraints::default(),
+        );
+
+        // in frame 1, record 0 is closer than record 1 but record 1
+        // is the one that leads to the record of frame 2
+        let frames = [
+            build_frame(0, &["abcdef"]),
+            build_frame(1, &["abcdex", "abcdxy"]),
+            build_frame(2, &["abcdxy"]),
+        ];
+
+        let mut tracker = Tracker::new(tracker_config.clone());
+        tracker.signal_matching_node(ChainNode::new(0, 0), frames[0].record(0));
+        let mut trackers = vec![ExclusiveShared::new(tracker)];
+
+        let mut new_trackers = Vec::new();
+        for frame in frames[1..].iter() {
+            let trackers_scores = trackers
+                .iter_mut()
+                .map(|t| {
+                    t.exclusive()
+                        .process_frame(frame, &mut distance_calculators)
+                })
+                .collect();
+            let (frame_new_trackers, _) = resolver.resolve(
+                frame,
+                tracker_config.clone(),
+                &mut trackers,
+                trackers_scores,
+            );
+            new_trackers.push(frame_new_trackers);
+        }
+
+        // the decision is deferred in frame 1, then record 0 of frame 1 is released
+        assert!(new_trackers[0].is_empty());
+        assert_eq!(new_trackers[1].len(), 1);
+        assert_eq!(
+            new_trackers[1][0].get_tracking_chain().nodes,
+            vec![ChainNode::new(1, 0)]
+        );
+        assert_eq!(
+            trackers[0].get_tracking_chain().nodes,
+            vec![
+                ChainNode::new(0, 0),
+                ChainNode::new(1, 1),
+                ChainNode::new(2, 0)
+            ]
+        );
+    }
+}
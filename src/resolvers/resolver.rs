@@ -1,5 +1,7 @@
+use std::collections::HashSet;
+
 use crate::{
-    api::ResolvingDiagnostics,
+    api::{AmbiguousMatchDiagnostics, ResolvingDiagnostics},
     engine::ExclusiveShared,
     frame::Frame,
     histogram::Histogram,
@@ -7,6 +9,8 @@ use crate::{
     trackers::{InternalTrackerConfig, RecordScore, Tracker},
 };
 
+use super::ResolvingConstraints;
+
 /// ScoreBucket
 ///
 /// Specific to a record, it contains a list of score, ID tuples of
@@ -35,7 +39,7 @@ impl ScoreBucket {
         for (i, (s, _)) in self.scores.iter().enumerate() {
             if score > *s {
                 self.scores.insert(i, (score, id));
-                break;
+                return;
             }
         }
         self.scores.push((score, id));
@@ -73,13 +77,113 @@ pub trait ResolvingStrategy {
 /// Resolver
 ///
 /// Responsible for applying the resolving strategy given the trackers scores.
+///
+/// The manual constraints are applied to the trackers scores before applying
+/// the resolving strategy.
+///
+/// When an ambiguity margin is set, the matches for which the best and second-best
+/// scores (either for a record or for a tracker) are closer than the margin are rejected
+/// before applying the resolving strategy. The records rejected because of an ambiguity
+/// between trackers are left out of the frame, no tracker is created for them, such that
+/// they are sent to manual review instead of starting a duplicate chain.
 pub struct Resolver {
     resolving_strategy: Box<dyn ResolvingStrategy>,
+    ambiguity_margin: Option<f32>,
+    constraints: ResolvingConstraints,
 }
 
 impl Resolver {
-    pub fn new(resolving_strategy: Box<dyn ResolvingStrategy>) -> Self {
-        Self { resolving_strategy }
+    pub fn new(
+        resolving_strategy: Box<dyn ResolvingStrategy>,
+        ambiguity_margin: Option<f32>,
+        constraints: ResolvingConstraints,
+    ) -> Self {
+        Self {
+            resolving_strategy,
+            ambiguity_margin,
+            constraints,
+        }
+    }
+
+    /// Builds the score buckets of the records from the trackers scores.
+    fn build_buckets(
+        num_records: usize,
+        trackers: &[ExclusiveShared<Tracker>],
+        trackers_scores: &[Vec<RecordScore>],
+    ) -> Vec<ScoreBucket> {
+        let mut buckets = (0..num_records)
+            .map(|_| ScoreBucket::new())
+            .collect::<Vec<ScoreBucket>>();
+
+        for (tracker_scores, tracker) in trackers_scores.iter().zip(trackers.iter()) {
+            for score in tracker_scores.iter() {
+                buckets[score.idx].push(score.score, tracker.id());
+            }
+        }
+        buckets
+    }
+
+    /// Rejects the ambiguous matches, that is:
+    /// * For a record, when the two best scores of its bucket are closer than the margin,
+    ///   the record is removed from the scores of all trackers and the best tracker
+    ///   won't match with any record.
+    /// * For a tracker, when its two best scores are closer than the margin,
+    ///   the tracker won't match with any record.
+    ///
+    /// The trackers that won't match with any record have their scores cleared,
+    /// such that they signal no matching node whatever the resolving strategy.
+    ///
+    /// Returns the rejected matches and the indexes of the rejected records.
+    fn reject_ambiguous_matches(
+        margin: f32,
+        trackers: &[ExclusiveShared<Tracker>],
+        trackers_scores: &mut [Vec<RecordScore>],
+        buckets: &[ScoreBucket],
+    ) -> (Vec<AmbiguousMatchDiagnostics>, HashSet<usize>) {
+        let mut ambiguous_matches = Vec::new();
+        let mut rejected_records = HashSet::new();
+        let mut rejected_trackers = HashSet::new();
+
+        for (record_idx, bucket) in buckets.iter().enumerate() {
+            if let [(best_score, id), (second_score, _), ..] = bucket.scores().as_slice() {
+                if best_score - second_score < margin {
+                    rejected_records.insert(record_idx);
+                    rejected_trackers.insert(*id);
+                    ambiguous_matches.push(AmbiguousMatchDiagnostics::new(
+                        *id,
+                        record_idx,
+                        "record",
+                        *best_score,
+                        *second_score,
+                    ));
+                }
+            }
+        }
+
+        for (tracker_scores, tracker) in trackers_scores.iter().zip(trackers.iter()) {
+            if let [best, second, ..] = tracker_scores.as_slice() {
+                if best.score - second.score < margin {
+                    rejected_trackers.insert(tracker.id());
+                    ambiguous_matches.push(AmbiguousMatchDiagnostics::new(
+                        tracker.id(),
+                        best.idx,
+                        "tracker",
+                        best.score,
+                        second.score,
+                    ));
+                }
+            }
+        }
+
+        for (tracker_scores, tracker) in trackers_scores.iter_mut().zip(trackers.iter()) {
+            if rejected_trackers.contains(&tracker.id()) {
+                tracker_scores.clear();
+            } else {
+                tracker_scores.retain(|score| !rejected_records.contains(&score.idx));
+            }
+        }
+
+        (ambiguous_matches, rejected_records)
     }
 
     /// Collects diagnostics on the resolving process.
@@ -121,22 +225,25 @@ impl Resolver {
         frame: &Frame,
         tracker_config: InternalTrackerConfig,
         trackers: &mut Vec<ExclusiveShared<Tracker>>,
-        trackers_scores: Vec<Vec<RecordScore>>,
+        mut trackers_scores: Vec<Vec<RecordScore>>,
     ) -> (Vec<Tracker>, ResolvingDiagnostics) {
-        let mut buckets = (0..frame.num_records())
-            .into_iter()
-            .map(|_| ScoreBucket::new())
-            .collect::<Vec<ScoreBucket>>();
+        self.constraints
+            .apply(frame.idx(), trackers, &mut trackers_scores);
 
-        for (tracker_scores, tracker) in trackers_scores.iter().zip(trackers.iter()) {
-            for score in tracker_scores.iter() {
-                buckets[score.idx].push(score.score, tracker.id());
+        let mut buckets = Self::build_buckets(frame.num_records(), trackers, &trackers_scores);
+
+        let mut diagnostics = self.collect_diagnostics(&trackers_scores, &buckets);
+
+        let mut rejected_records = HashSet::new();
+        if let Some(margin) = self.ambiguity_margin {
+            (diagnostics.ambiguous_matches, rejected_records) =
+                Self::reject_ambiguous_matches(margin, trackers, &mut trackers_scores, &buckets);
+            if !diagnostics.ambiguous_matches.is_empty() {
+                buckets = Self::build_buckets(frame.num_records(), trackers, &trackers_scores);
             }
         }
 
-        let diagnostics = self.collect_diagnostics(&trackers_scores, &buckets);
-
-        let new_trackers = self.resolving_strategy.resolve(
+        let mut new_trackers = self.resolving_strategy.resolve(
             frame,
             tracker_config,
             trackers,
@@ -144,6 +251,111 @@ impl Resolver {
             trackers_scores,
         );
 
+        // the rejected records are not in any scores anymore, so the resolving
+        // strategy considers them unmatched, their new trackers are dropped
+        if !rejected_records.is_empty() {
+            new_trackers.retain(|tracker| {
+                !tracker.last_node().is_some_and(|node| {
+                    node.frame_idx == frame.idx() && rejected_records.contains(&node.record_idx)
+                })
+            });
+        }
+
         (new_trackers, diagnostics)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::ChainNode,
+        frame::Element,
+        resolvers::{BestMatchResolvingStrategy, ResolvingConstraints},
+        test_utils::build_tracker_config,
+        word::Word,
+    };
+
+    use super::*;
+
+    fn build_trackers(num_trackers: usize) -> Vec<ExclusiveShared<Tracker>> {
+        let tracker_config = build_tracker_config(0.5, 5, 1);
+        (0..num_trackers)
+            .map(|_| ExclusiveShared::new(Tracker::new(tracker_config.clone())))
+            .collect()
+    }
+
+    #[test]
+    fn test_score_bucket() {
+        let mut bucket = ScoreBucket::new();
+        bucket.push(0.6, 0);
+        bucket.push(0.8, 1);
+        bucket.push(0.7, 2);
+        assert_eq!(bucket.scores(), &vec![(0.8, 1), (0.7, 2), (0.6, 0)]);
+    }
+
+    #[test]
+    fn test_reject_ambiguous_matches() {
+        let trackers = build_trackers(3);
+        let mut trackers_scores = vec![
+            // ambiguous with tracker 1 on record 0
+            vec![RecordScore::new(0, 0.9)],
+            vec![RecordScore::new(0, 0.88), RecordScore::new(1, 0.6)],
+            // ambiguous between records 1 and 2
+            vec![RecordScore::new(1, 0.8), RecordScore::new(2, 0.78)],
+        ];
+        let buckets = Resolver::build_buckets(3, &trackers, &trackers_scores);
+
+        let (ambiguous_matches, rejected_records) =
+            Resolver::reject_ambiguous_matches(0.05, &trackers, &mut trackers_scores, &buckets);
+
+        assert_eq!(ambiguous_matches.len(), 2);
+        assert_eq!(ambiguous_matches[0].tracker_id, trackers[0].id());
+        assert_eq!(ambiguous_matches[0].record_idx, 0);
+        assert_eq!(ambiguous_matches[0].ambiguity, "record");
+        assert_eq!(ambiguous_matches[1].tracker_id, trackers[2].id());
+        assert_eq!(ambiguous_matches[1].record_idx, 1);
+        assert_eq!(ambiguous_matches[1].ambiguity, "tracker");
+
+        assert!(trackers_scores[0].is_empty());
+        assert_eq!(trackers_scores[1], vec![RecordScore::new(1, 0.6)]);
+        assert!(trackers_scores[2].is_empty());
+        assert_eq!(rejected_records, HashSet::from([0]));
+    }
+
+    #[test]
+    fn test_rejected_record_without_new_tracker() {
+        let tracker_config = build_tracker_config(0.5, 5, 1);
+        let mut trackers = build_trackers(2);
+        let frame = Frame::new(
+            1,
+            vec![vec![
+                Element::Word(Word::new("alice")),
+                Element::Word(Word::new("bob")),
+            ]],
+        );
+        // both trackers are as close to record 0, record 1 is unknown
+        let trackers_scores = vec![
+            vec![RecordScore::new(0, 0.9)],
+            vec![RecordScore::new(0, 0.88)],
+        ];
+
+        let mut resolver = Resolver::new(
+            Box::new(BestMatchResolvingStrategy {}),
+            Some(0.05),
+            ResolvingConstraints::default(),
+        );
+        let (new_trackers, diagnostics) =
+            resolver.resolve(&frame, tracker_config, &mut trackers, trackers_scores);
+
+        assert_eq!(diagnostics.ambiguous_matches.len(), 1);
+        // no tracker matched with record 0 and no new tracker was created for it
+        for tracker in trackers.iter_mut() {
+            assert_eq!(tracker.exclusive().last_node(), None);
+        }
+        let new_nodes = new_trackers
+            .iter()
+            .map(|tracker| tracker.last_node())
+            .collect::<Vec<_>>();
+        assert_eq!(new_nodes, vec![Some(ChainNode::new(1, 1))]);
+    }
+}
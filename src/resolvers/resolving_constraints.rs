@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    api::ChainNode,
+    engine::ExclusiveShared,
+    trackers::{RecordScore, Tracker},
+};
+
+/// ResolvingConstraints
+///
+/// Manual constraints on the links between consecutive chain nodes,
+/// typically decided by a reviewer.
+///
+/// The constraints apply to the trackers whose last chain node is the
+/// source of the link:
+/// * A must-link forces the tracker to match with the target record, whatever
+///   the scores. The tracker doesn't match with any record in the frames between
+///   the source and the target, it stays alive meanwhile whatever its no match
+///   streak, and no other tracker can match with the target.
+/// * A cannot-link removes the target record from the candidates of the tracker.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvingConstraints {
+    must_links: HashMap<ChainNode, ChainNode>,
+    cannot_links: HashSet<(ChainNode, ChainNode)>,
+}
+
+impl ResolvingConstraints {
+    pub fn new(
+        must_links: HashMap<ChainNode, ChainNode>,
+        cannot_links: HashSet<(ChainNode, ChainNode)>,
+    ) -> Self {
+        Self {
+            must_links,
+            cannot_links,
+        }
+    }
+
+    /// Returns if there are no constraints.
+    pub fn is_empty(&self) -> bool {
+        self.must_links.is_empty() && self.cannot_links.is_empty()
+    }
+
+    /// Applies the constraints to the trackers scores of the frame.
+    ///
+    /// The trackers that must match with a record are pinned to it and their
+    /// scores only contain this record.
+    pub fn apply(
+        &self,
+        frame_idx: usize,
+        trackers: &mut [ExclusiveShared<Tracker>],
+        trackers_scores: &mut [Vec<RecordScore>],
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        // for each record, the tracker that must match with it
+        let mut pinned_records = HashMap::new();
+
+        for (tracker_idx, tracker) in trackers.iter_mut().enumerate() {
+            let last_node = match tracker.last_node() {
+                Some(node) => node,
+                None => continue,
+            };
+            let tracker_scores = &mut trackers_scores[tracker_idx];
+
+            if let Some(target) = self.must_links.get(&last_node) {
+                if target.frame_idx == frame_idx {
+                    let record_score = tracker_scores
+                        .iter()
+                        .find(|s| s.idx == target.record_idx)
+                        .copied()
+                        .unwrap_or(RecordScore::new(target.record_idx, 0.0));
+                    *tracker_scores = vec![record_score];
+                    tracker.exclusive().pin_record(record_score);
+                    pinned_records.insert(target.record_idx, tracker_idx);
+                } else if target.frame_idx > frame_idx {
+                    // the tracker waits for the target record
+                    tracker_scores.clear();
+                    tracker.exclusive().await_record();
+                }
+                continue;
+            }
+
+            tracker_scores.retain(|s| {
+                !self
+                    .cannot_links
+                    .contains(&(last_node, ChainNode::new(frame_idx, s.idx)))
+            });
+        }
+
+        if pinned_records.is_empty() {
+            return;
+        }
+        for (tracker_idx, tracker_scores) in trackers_scores.iter_mut().enumerate() {
+            tracker_scores.retain(|s| match pinned_records.get(&s.idx) {
+                Some(idx) => *idx == tracker_idx,
+                None => true,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        frame::{Element, Frame},
+        test_utils::build_tracker_config,
+    };
+
+    use super::*;
+
+    fn build_tracker(node: ChainNode, limit_no_match_streak: usize) -> ExclusiveShared<Tracker> {
+        let tracker_config = build_tracker_config(0.5, limit_no_match_streak, 1);
+        let frame = Frame::new(
+            node.frame_idx,
+            vec![(0..=node.record_idx).map(|_| Element::None).collect()],
+        );
+        let mut tracker = Tracker::new(tracker_config);
+        tracker.signal_matching_node(node, frame.record(node.record_idx));
+        ExclusiveShared::new(tracker)
+    }
+
+    #[test]
+    fn test_apply_constraints() {
+        let constraints = ResolvingConstraints::new(
+            HashMap::from([
+                (ChainNode::new(0, 0), ChainNode::new(1, 2)),
+                (ChainNode::new(0, 2), ChainNode::new(2, 0)),
+            ]),
+            HashSet::from([(ChainNode::new(0, 1), ChainNode::new(1, 0))]),
+        );
+        let mut trackers = vec![
+            build_tracker(ChainNode::new(0, 0), 5),
+            build_tracker(ChainNode::new(0, 1), 5),
+            build_tracker(ChainNode::new(0, 2), 5),
+        ];
+        let mut trackers_scores = vec![
+            vec![RecordScore::new(0, 0.9)],
+            vec![RecordScore::new(0, 0.9), RecordScore::new(2, 0.8)],
+            vec![RecordScore::new(1, 0.9)],
+        ];
+
+        constraints.apply(1, &mut trackers, &mut trackers_scores);
+
+        // pinned to record 2 whatever the scores
+        assert_eq!(trackers_scores[0], vec![RecordScore::new(2, 0.0)]);
+        // record 0 is a cannot-link and record 2 is pinned to tracker 0
+        assert!(trackers_scores[1].is_empty());
+        // waits for record 0 of frame 2
+        assert!(trackers_scores[2].is_empty());
+    }
+
+    #[test]
+    fn test_distant_must_link() {
+        let constraints = ResolvingConstraints::new(
+            HashMap::from([(ChainNode::new(0, 0), ChainNode::new(4, 1))]),
+            HashSet::new(),
+        );
+        // the first tracker waits for its target, the second one has no constraint
+        let mut trackers = vec![
+            build_tracker(ChainNode::new(0, 0), 1),
+            build_tracker(ChainNode::new(0, 1), 1),
+        ];
+
+        for frame_idx in 1..4 {
+            let mut trackers_scores = vec![vec![RecordScore::new(0, 0.9)], Vec::new()];
+            constraints.apply(frame_idx, &mut trackers, &mut trackers_scores);
+            assert!(trackers_scores[0].is_empty());
+            for tracker in trackers.iter_mut() {
+                tracker.exclusive().signal_no_matching_node();
+            }
+        }
+        // the streak of both trackers is past the limit
+        assert!(!trackers[0].is_dead());
+        assert!(trackers[1].is_dead());
+
+        let mut trackers_scores = vec![vec![RecordScore::new(0, 0.9)], Vec::new()];
+        constraints.apply(4, &mut trackers, &mut trackers_scores);
+        assert_eq!(trackers_scores[0], vec![RecordScore::new(1, 0.0)]);
+    }
+}
@@ -0,0 +1,6 @@
+mod objective;
+mod runner;
+mod space;
+
+pub use runner::{filter_candidates, read_results, SearchRunner};
+pub use space::build_candidates;
@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use crate::{
+    api::{ChainNode, SearchObjective, TrackingGraph},
+    evaluation,
+};
+
+/// Returns the links between consecutive nodes of the chains.
+fn chains_links<'a>(
+    chains: impl Iterator<Item = &'a [ChainNode]>,
+) -> HashSet<(ChainNode, ChainNode)> {
+    chains
+        .flat_map(|nodes| nodes.windows(2).map(|w| (w[0], w[1])))
+        .collect()
+}
+
+/// Computes the F1 score of the links of the tracking graph against
+/// the links of the ground-truth chains.
+fn eval_links_f1(graph: &TrackingGraph, ground_truth: &[Vec<(usize, usize)>]) -> f64 {
+    let chains = graph.build_tracking_chains();
+    let predicted = chains_links(chains.iter().map(|c| c.nodes.as_slice()));
+
+    let true_chains = ground_truth
+        .iter()
+        .map(|chain| {
+            chain
+                .iter()
+                .map(|(frame_idx, record_idx)| ChainNode::new(*frame_idx, *record_idx))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let truth = chains_links(true_chains.iter().map(|c| c.as_slice()));
+
+    let num_correct = predicted.intersection(&truth).count() as f64;
+    if num_correct == 0.0 {
+        return 0.0;
+    }
+    let precision = num_correct / predicted.len() as f64;
+    let recall = num_correct / truth.len() as f64;
+    2.0 * precision * recall / (precision + recall)
+}
+
+/// Computes the mean, over the frames after the first one, of the ratio of records
+/// matched by a chain minus the ratio of records with conflicts.
+fn eval_match_ratio(graph: &TrackingGraph) -> f64 {
+    let properties = evaluation::eval_tracking_graph_properties(graph);
+    let ratios = properties
+        .records_match_ratios
+        .iter()
+        .zip(properties.conflict_ratios.iter())
+        .skip(1)
+        .map(|(matchs, conflicts)| (matchs - conflicts) as f64)
+        .collect::<Vec<_>>();
+
+    if ratios.is_empty() {
+        return 0.0;
+    }
+    ratios.iter().sum::<f64>() / ratios.len() as f64
+}
+
+/// Scores the tracking graph according to the objective, the higher the better.
+pub fn eval_objective(objective: &SearchObjective, graph: &TrackingGraph) -> f64 {
+    match objective {
+        SearchObjective::ChainLength => {
+            evaluation::eval_tracking_chain_length(graph).average as f64
+        }
+        SearchObjective::MatchRatio => eval_match_ratio(graph),
+        SearchObjective::GroundTruth(ground_truth) => eval_links_f1(graph, ground_truth),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        frame::{Element, Frame},
+        trackers::TrackingChain,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_eval_links_f1() {
+        let frames = (0..3)
+            .map(|i| Frame::new(i, vec![vec![Element::None, Element::None]]))
+            .collect::<Vec<_>>();
+        let graph = TrackingGraph::from_tracking_chains(
+            &frames,
+            vec![
+                TrackingChain::new(
+                    1,
+                    vec![
+                        ChainNode::new(0, 0),
+                        ChainNode::new(1, 0),
+                        ChainNode::new(2, 1),
+                    ],
+                    vec![None; 3],
+                ),
+                TrackingChain::new(2, vec![ChainNode::new(2, 0)], vec![None]),
+            ],
+        );
+
+        // 1 of the 2 predicted links and 1 of the 3 true links are correct
+        let ground_truth = vec![vec![(0, 0), (1, 0), (2, 0)], vec![(0, 1), (1, 1)]];
+        let f1 = eval_objective(&SearchObjective::GroundTruth(ground_truth), &graph);
+        assert!((f1 - 0.4).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use crate::{
+    api::{
+        build_distance_calculators, build_tracking_engine_with_shared_caches, min_num_threads,
+        run_tracking_engine, DistanceMetricConfig, RecordSchema, SearchObjective, SearchResult,
+        TrackingConfig,
+    },
+    distances::DistanceMatrix,
+    engine::FrameStore,
+    error::{BlitzBeaverError, Result},
+    frame::Frame,
+    resolvers::ResolvingConstraints,
+};
+
+use super::{objective::eval_objective, space::Candidate};
+
+/// Returns the key identifying the configuration in the results file.
+pub fn config_key(config: &TrackingConfig) -> Result<String> {
+    serde_json::to_string(config).map_err(|e| BlitzBeaverError::Serialization(e.to_string()))
+}
+
+/// Reads the results of a previous (possibly interrupted) search.
+///
+/// The last line is ignored if it is incomplete, as the search may have been
+/// interrupted while writing it.
+///
+/// # Errors
+/// Returns a Serialization error if the file can't be read or a line is invalid.
+pub fn read_results(path: &Path) -> Result<Vec<SearchResult>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let io_error =
+        |e: std::io::Error| BlitzBeaverError::Serialization(format!("{}: {}", path.display(), e));
+    let file = File::open(path).map_err(io_error)?;
+    let lines = BufReader::new(file)
+        .lines()
+        .collect::<std::io::Result<Vec<String>>>()
+        .map_err(io_error)?;
+
+    let mut results = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(result) => results.push(result),
+            Err(_) if i == lines.len() - 1 => {
+                log::warn!("ignoring the incomplete last line of {}", path.display())
+            }
+            Err(e) => {
+                return Err(BlitzBeaverError::Serialization(format!(
+                    "{}: line {}: {}",
+                    path.display(),
+                    i + 1,
+                    e
+                )))
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Opens the results file to append the new results.
+///
+/// The incomplete last line, ignored by `read_results`, is removed and a complete
+/// last line without line break is terminated, such that the new results start
+/// on a line of their own.
+///
+/// # Errors
+/// Returns a Serialization error if the file can't be read or written.
+fn open_results_file(path: &Path) -> Result<File> {
+    let io_error =
+        |e: std::io::Error| BlitzBeaverError::Serialization(format!("{}: {}", path.display(), e));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .write(true)
+        .truncate(false)
+        .open(path)
+        .map_err(io_error)?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content).map_err(io_error)?;
+
+    let last_line_start = content
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |i| i + 1);
+    let last_line = &content[last_line_start..];
+    if !last_line.is_empty() {
+        if serde_json::from_slice::<SearchResult>(last_line).is_ok() {
+            file.write_all(b"\n").map_err(io_error)?;
+        } else {
+            file.set_len(last_line_start as u64).map_err(io_error)?;
+        }
+    }
+    file.seek(SeekFrom::End(0)).map_err(io_error)?;
+    Ok(file)
+}
+
+/// SearchRunner
+///
+/// Runs the candidates of a search in parallel, all the runs share the frames
+/// and, for each distance metric, the distances between the frequent words of
+/// consecutive frames, precomputed once.
+pub struct SearchRunner<'a> {
+    record_schema: &'a RecordSchema,
+    frames: Arc<Vec<Frame>>,
+    objective: &'a SearchObjective,
+    shared_caches: HashMap<String, Vec<Arc<DistanceMatrix>>>,
+}
+
+impl<'a> SearchRunner<'a> {
+    pub fn new(
+        record_schema: &'a RecordSchema,
+        frames: Arc<Vec<Frame>>,
+        objective: &'a SearchObjective,
+    ) -> Self {
+        Self {
+            record_schema,
+            frames,
+            objective,
+            shared_caches: HashMap::new(),
+        }
+    }
+
+    /// Precomputes the shared caches of the distance metrics of the candidates.
+    ///
+    /// # Errors
+    /// Returns an error if a distance metric configuration is invalid.
+    fn build_shared_caches(&mut self, candidates: &[Candidate]) -> Result<()> {
+        for candidate in candidates.iter() {
+            let distance_metric = &candidate.config.distance_metric;
+            let key = distance_metric_key(distance_metric)?;
+            if self.shared_caches.contains_key(&key) {
+                continue;
+            }
+            log::info!("precomputing the shared cache of {}...", key);
+
+            let mut caches = Vec::new();
+            for (feature_idx, mut distance_calculator) in
+                build_distance_calculators(distance_metric, self.record_schema)?
+                    .into_iter()
+                    .enumerate()
+            {
+                for frames in self.frames.windows(2) {
+                    distance_calculator.precompute(
+                        &frames[0].column(feature_idx).iter().collect(),
+                        &frames[1].column(feature_idx).iter().collect(),
+                    );
+                }
+                caches.push(Arc::new(distance_calculator.take_cache()));
+            }
+            self.shared_caches.insert(key, caches);
+        }
+        Ok(())
+    }
+
+    /// Runs the tracking process with the configuration of the candidate and scores it.
+    ///
+    /// The parallel runs share the threads, the engine uses its part of the
+    /// `num_threads` of the configuration.
+    fn run_candidate(&self, candidate: Candidate, parallel_runs: usize) -> Result<SearchResult> {
+        let start = Instant::now();
+        let shared_caches =
+            &self.shared_caches[&distance_metric_key(&candidate.config.distance_metric)?];
+
+        let mut config = candidate.config.clone();
+        config.num_threads = (config.num_threads / parallel_runs).max(min_num_threads(&config));
+
+        let tracking_engine = build_tracking_engine_with_shared_caches(
+            &config,
+            self.record_schema,
+            FrameStore::in_memory(Arc::clone(&self.frames)),
+            ResolvingConstraints::default(),
+            shared_caches,
+        )?;
+        let (tracking_graph, _) = run_tracking_engine(tracking_engine)?;
+
+        Ok(SearchResult {
+            params: candidate.params,
+            score: eval_objective(self.objective, &tracking_graph),
+            duration_secs: start.elapsed().as_secs_f64(),
+            config: candidate.config,
+        })
+    }
+
+    /// Runs the candidates, `parallel_runs` at a time, each result is appended
+    /// to the results file (if any) as soon as it is available.
+    ///
+    /// The runs with a non-finite score are skipped with a warning, as their
+    /// score can't be written to the results file.
+    ///
+    /// Returns the results in the order of completion.
+    ///
+    /// # Errors
+    /// Returns an error if a run fails or the results file can't be written,
+    /// the remaining runs are not started.
+    pub fn run(
+        &mut self,
+        candidates: Vec<Candidate>,
+        parallel_runs: usize,
+        results_path: Option<&Path>,
+    ) -> Result<Vec<SearchResult>> {
+        self.build_shared_caches(&candidates)?;
+
+        let mut results_file = results_path.map(open_results_file).transpose()?;
+
+        let num_candidates = candidates.len();
+        let parallel_runs = parallel_runs.max(1);
+        let queue = Mutex::new(candidates.into_iter());
+        let stopped = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel();
+        let this = &*self;
+
+        std::thread::scope(|scope| {
+            for _ in 0..parallel_runs {
+                let sender = sender.clone();
+                let (queue, stopped) = (&queue, &stopped);
+                scope.spawn(move || {
+                    while !stopped.load(Ordering::Relaxed) {
+                        let candidate = match queue.lock().unwrap().next() {
+                            Some(candidate) => candidate,
+                            None => break,
+                        };
+                        if sender
+                            .send(this.run_candidate(candidate, parallel_runs))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            let mut results = Vec::with_capacity(num_candidates);
+            for result in receiver {
+                let result = result.and_then(|result| {
+                    // JSON has no NaN nor infinity, the score would be read back as null
+                    if !result.score.is_finite() {
+                        log::warn!(
+                            "skipping the configuration {:?}: the score {} is not finite",
+                            result.params,
+                            result.score
+                        );
+                        return Ok(None);
+                    }
+                    if let Some(file) = results_file.as_mut() {
+                        write_result(file, &result)?;
+                    }
+                    Ok(Some(result))
+                });
+                match result {
+                    Ok(None) => {}
+                    Ok(Some(result)) => {
+                        log::info!(
+                            "run {}/{}: score {:.4}",
+                            results.len() + 1,
+                            num_candidates,
+                            result.score
+                        );
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        stopped.store(true, Ordering::Relaxed);
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(results)
+        })
+    }
+}
+
+fn distance_metric_key(distance_metric: &DistanceMetricConfig) -> Result<String> {
+    serde_json::to_string(distance_metric)
+        .map_err(|e| BlitzBeaverError::Serialization(e.to_string()))
+}
+
+fn write_result(file: &mut File, result: &SearchResult) -> Result<()> {
+    let line = serde_json::to_string(result)
+        .map_err(|e| BlitzBeaverError::Serialization(e.to_string()))?;
+    writeln!(file, "{}", line)
+        .and_then(|_| file.flush())
+        .map_err(|e| BlitzBeaverError::Serialization(e.to_string()))
+}
+
+/// Removes the candidates whose configuration already has a result, as well as
+/// the duplicated candidates, returns the remaining candidates and the previous
+/// results of the candidates.
+///
+/// # Errors
+/// Returns an error if a configuration can't be serialized.
+pub fn filter_candidates(
+    candidates: Vec<Candidate>,
+    previous_results: Vec<SearchResult>,
+) -> Result<(Vec<Candidate>, Vec<SearchResult>)> {
+    let mut previous = HashMap::new();
+    for result in previous_results {
+        previous.insert(config_key(&result.config)?, result);
+    }
+
+    let mut keys = HashSet::new();
+    let mut pending = Vec::new();
+    let mut done = Vec::new();
+    for candidate in candidates {
+        let key = config_key(&candidate.config)?;
+        if !keys.insert(key.clone()) {
+            continue;
+        }
+        match previous.remove(&key) {
+            Some(result) => done.push(result),
+            None => pending.push(candidate),
+        }
+    }
+    Ok((pending, done))
+}
@@ -0,0 +1,262 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::Value;
+
+use crate::{
+    api::{
+        deserialize_config, ConfigFormat, SearchParameter, SearchSpace, SearchValues,
+        TrackingConfig,
+    },
+    error::{BlitzBeaverError, Result},
+};
+
+/// Candidate
+///
+/// Configuration of the search space, with the values of the parameters it was built from.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub params: Vec<(String, Value)>,
+    pub config: TrackingConfig,
+}
+
+/// Segment of a parameter path: an attribute, optionally followed by
+/// an index (`[3]`) or all the indexes (`[*]`) of a list.
+enum PathSegment<'a> {
+    Attribute(&'a str),
+    Index(usize),
+    All,
+}
+
+fn parse_path(path: &str) -> Result<Vec<PathSegment<'_>>> {
+    let invalid = || BlitzBeaverError::InvalidConfig(format!("{}: invalid parameter path", path));
+
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (name, mut rest) = match part.find('[') {
+            Some(idx) => part.split_at(idx),
+            None => (part, ""),
+        };
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        segments.push(PathSegment::Attribute(name));
+
+        while !rest.is_empty() {
+            let end = rest.find(']').ok_or_else(invalid)?;
+            segments.push(match &rest[1..end] {
+                "*" => PathSegment::All,
+                idx => PathSegment::Index(idx.parse().map_err(|_| invalid())?),
+            });
+            rest = &rest[end + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(invalid());
+            }
+        }
+    }
+    Ok(segments)
+}
+
+/// Sets the value at the segments path, the attributes must already exist.
+///
+/// Returns false if the path doesn't exist.
+fn set_segments(target: &mut Value, segments: &[PathSegment], value: &Value) -> bool {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            *target = value.clone();
+            return true;
+        }
+    };
+    match (segment, target) {
+        (PathSegment::Attribute(name), Value::Object(map)) => match map.get_mut(*name) {
+            Some(target) => set_segments(target, rest, value),
+            None => false,
+        },
+        (PathSegment::Index(idx), Value::Array(values)) => match values.get_mut(*idx) {
+            Some(target) => set_segments(target, rest, value),
+            None => false,
+        },
+        (PathSegment::All, Value::Array(values)) => values
+            .iter_mut()
+            .all(|target| set_segments(target, rest, value)),
+        _ => false,
+    }
+}
+
+/// Sets the value of the parameter in the serialized configuration.
+///
+/// # Errors
+/// Returns an error if the path is invalid or doesn't exist in the configuration.
+pub fn set_parameter(config: &mut Value, path: &str, value: &Value) -> Result<()> {
+    if !set_segments(config, &parse_path(path)?, value) {
+        return Err(BlitzBeaverError::InvalidConfig(format!(
+            "{}: unknown attribute in TrackingConfig",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the values of the parameter to try in a grid search.
+///
+/// # Errors
+/// Returns an error if the parameter is a float range, it can't be enumerated.
+fn grid_values(parameter: &SearchParameter) -> Result<Vec<Value>> {
+    match &parameter.values {
+        SearchValues::Choice(values) => Ok(values.clone()),
+        SearchValues::IntUniform(low, high) => Ok((*low..=*high).map(Value::from).collect()),
+        SearchValues::Uniform(_, _) => Err(BlitzBeaverError::InvalidConfig(format!(
+            "{}: a grid search requires explicit values",
+            parameter.path
+        ))),
+    }
+}
+
+/// Draws a value of the parameter for a random search.
+fn draw_value(parameter: &SearchParameter, rng: &mut StdRng) -> Value {
+    match &parameter.values {
+        SearchValues::Choice(values) => values[rng.random_range(..values.len())].clone(),
+        SearchValues::Uniform(low, high) => Value::from(rng.random_range(*low..=*high)),
+        SearchValues::IntUniform(low, high) => Value::from(rng.random_range(*low..=*high)),
+    }
+}
+
+/// Returns the parameters values of all the combinations of the grid.
+fn grid_combinations(parameters: &[SearchParameter]) -> Result<Vec<Vec<Value>>> {
+    let mut combinations = vec![Vec::new()];
+    for parameter in parameters.iter() {
+        let values = grid_values(parameter)?;
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.push(value.clone());
+                    combination
+                })
+            })
+            .collect();
+    }
+    Ok(combinations)
+}
+
+/// Builds the candidates of the search space from the base configuration.
+///
+/// The random search is deterministic given its seed.
+///
+/// # Errors
+/// Returns an InvalidConfig error if a parameter path doesn't exist or if a candidate
+/// can't be deserialized, e.g. a value has the wrong type.
+pub fn build_candidates(
+    base_config: &TrackingConfig,
+    search_space: &SearchSpace,
+) -> Result<Vec<Candidate>> {
+    let (parameters, combinations) = match search_space {
+        SearchSpace::Grid { parameters } => (parameters, grid_combinations(parameters)?),
+        SearchSpace::Random {
+            parameters,
+            num_samples,
+            seed,
+        } => {
+            for parameter in parameters.iter() {
+                if matches!(&parameter.values, SearchValues::Choice(values) if values.is_empty()) {
+                    return Err(BlitzBeaverError::InvalidConfig(format!(
+                        "{}: at least one value is required",
+                        parameter.path
+                    )));
+                }
+            }
+            let mut rng = StdRng::seed_from_u64(*seed);
+            let combinations = (0..*num_samples)
+                .map(|_| parameters.iter().map(|p| draw_value(p, &mut rng)).collect())
+                .collect();
+            (parameters, combinations)
+        }
+    };
+
+    let base_value = serde_json::to_value(base_config)
+        .map_err(|e| BlitzBeaverError::Serialization(e.to_string()))?;
+
+    let mut candidates = Vec::new();
+    for combination in combinations {
+        let mut value = base_value.clone();
+        let mut params = Vec::new();
+        for (parameter, param_value) in parameters.iter().zip(combination) {
+            set_parameter(&mut value, &parameter.path, &param_value)?;
+            params.push((parameter.path.clone(), param_value));
+        }
+        candidates.push(Candidate {
+            params,
+            config: deserialize_config(&value.to_string(), ConfigFormat::Json)?,
+        });
+    }
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_set_parameter() {
+        let mut config = json!({
+            "tracker": {
+                "interest_threshold": 0.5,
+                "memories": [{"memory_strategy": "median"}, {"memory_strategy": "median"}],
+            },
+        });
+
+        set_parameter(&mut config, "tracker.interest_threshold", &json!(0.7)).unwrap();
+        set_parameter(
+            &mut config,
+            "tracker.memories[*].memory_strategy",
+            &json!("bruteforce"),
+        )
+        .unwrap();
+        set_parameter(
+            &mut config,
+            "tracker.memories[1].memory_strategy",
+            &json!("mostfrequent"),
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            json!({
+                "tracker": {
+                    "interest_threshold": 0.7,
+                    "memories": [
+                        {"memory_strategy": "bruteforce"},
+                        {"memory_strategy": "mostfrequent"},
+                    ],
+                },
+            })
+        );
+
+        assert!(set_parameter(
+            &mut config,
+            "tracker.memories[2].memory_strategy",
+            &json!(1)
+        )
+        .is_err());
+        assert!(set_parameter(&mut config, "tracker.threshold", &json!(1)).is_err());
+        assert!(set_parameter(&mut config, "tracker.memories[x]", &json!(1)).is_err());
+    }
+
+    #[test]
+    fn test_grid_combinations() {
+        let parameters = vec![
+            SearchParameter {
+                path: "a".to_string(),
+                values: SearchValues::Choice(vec![json!("x"), json!("y")]),
+            },
+            SearchParameter {
+                path: "b".to_string(),
+                values: SearchValues::IntUniform(1, 3),
+            },
+        ];
+        let combinations = grid_combinations(&parameters).unwrap();
+        assert_eq!(combinations.len(), 6);
+        assert_eq!(combinations[4], vec![json!("y"), json!(2)]);
+    }
+}
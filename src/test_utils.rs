@@ -0,0 +1,115 @@
+use polars::{df, frame::DataFrame};
+
+use crate::{
+    api::{
+        DistanceMetricConfig, ElementType, FieldSchema, MemoryConfig, RecordSchema,
+        RecordScorerConfig, ResolverConfig, TrackerConfig, TrackingConfig, TrackingGraph,
+    },
+    distances::{CachedDistanceCalculator, LvOptiDistanceMetric},
+    trackers::{
+        InternalTrackerConfig, MissingFieldDistances, TrackerMemoryConfig,
+        TrackerRecordScorerConfig,
+    },
+};
+
+/// Builds a tracker configuration with a brute force memory per feature,
+/// an average record scorer, no missing field distances and no composite features.
+pub fn build_tracker_config(
+    interest_threshold: f32,
+    limit_no_match_streak: usize,
+    num_features: usize,
+) -> InternalTrackerConfig {
+    InternalTrackerConfig {
+        interest_threshold,
+        limit_no_match_streak,
+        memory_configs: vec![TrackerMemoryConfig::BruteForce; num_features],
+        record_scorer: TrackerRecordScorerConfig::Average,
+        missing_distances: MissingFieldDistances::default(),
+        composite_features: Vec::new(),
+    }
+}
+
+/// Builds a lv_opti distance calculator per feature.
+pub fn build_distance_calculators(num_features: usize) -> Vec<CachedDistanceCalculator> {
+    (0..num_features)
+        .map(|_| CachedDistanceCalculator::new(Box::new(LvOptiDistanceMetric::new(false)), 4))
+        .collect()
+}
+
+/// Builds the schema of records made of a single "name" string field.
+pub fn build_name_record_schema() -> RecordSchema {
+    RecordSchema::new(vec![FieldSchema::new(
+        "name".to_string(),
+        ElementType::String,
+    )])
+}
+
+/// Builds a dataframe per frame, with the names of the records of the frame.
+pub fn build_name_dataframes(frames: &[&[&str]]) -> Vec<DataFrame> {
+    frames
+        .iter()
+        .map(|names| df!("name" => names).unwrap())
+        .collect()
+}
+
+/// Builds a tracking configuration with a brute force memory for the
+/// "name" field, the lv metric and the best-match resolving strategy.
+pub fn build_tracking_config(num_threads: usize) -> TrackingConfig {
+    TrackingConfig {
+        num_threads,
+        tracker: TrackerConfig {
+            interest_threshold: 0.5,
+            limit_no_match_streak: 3,
+            memories: vec![MemoryConfig {
+                memory_strategy: "bruteforce".to_string(),
+                multiword_threshold_match: None,
+                multiword_distance_metric: None,
+                median_strategy: None,
+                recency_decay: None,
+            }],
+            record_scorer: RecordScorerConfig {
+                record_scorer: "average".to_string(),
+                weights: None,
+                min_weight_ratio: None,
+                missing_record_distances: None,
+                missing_memory_distances: None,
+                missing_both_distances: None,
+                composite_features: None,
+            },
+        },
+        distance_metric: DistanceMetricConfig {
+            metric: "lv".to_string(),
+            caching_threshold: 4,
+            use_sigmoid: false,
+            lv_edit_weights: None,
+            lv_substring_weight: None,
+            lv_multiword_separator: None,
+            persistent_cache_bytes: None,
+        },
+        resolver: ResolverConfig {
+            resolving_strategy: "best-match".to_string(),
+            num_hypotheses: None,
+            hypotheses_depth: None,
+            ambiguity_margin: None,
+        },
+        engine_mode: None,
+        chunk_size: None,
+    }
+}
+
+/// Returns the chains of the graph as lists of (frame index, record index),
+/// sorted such that graphs with the same chains can be compared.
+pub fn build_sorted_chains(tracking_graph: &TrackingGraph) -> Vec<Vec<(usize, usize)>> {
+    let mut chains = tracking_graph
+        .build_tracking_chains()
+        .into_iter()
+        .map(|c| {
+            c.nodes
+                .iter()
+                .map(|n| (n.frame_idx, n.record_idx))
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    chains.sort();
+    chains
+}
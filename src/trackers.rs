@@ -3,9 +3,10 @@ mod tracker;
 mod tracker_memory;
 
 pub use record_scorer::{
-    AverageRecordScorer, WeightedAverageRecordScorer, WeightedQuadraticRecordScorer,
+    AverageRecordScorer, MissingFieldDistances, WeightedAverageRecordScorer,
+    WeightedQuadraticRecordScorer,
 };
 pub use tracker::{
-    InternalTrackerConfig, RecordScore, Tracker, TrackerMemoryConfig, TrackerRecordScorerConfig,
-    TrackingChain,
+    InternalTrackerConfig, RecordScore, Tracker, TrackerCompositeFeatureConfig,
+    TrackerMemoryConfig, TrackerRecordScorerConfig, TrackingChain,
 };
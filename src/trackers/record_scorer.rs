@@ -15,6 +15,18 @@ impl RecordScorer for AverageRecordScorer {
     fn score(&self, scores: &Vec<Option<f32>>) -> f32 {
         scores.iter().filter_map(|s| *s).sum::<f32>() / scores.len() as f32
     }
+
+    fn min_feature_distance(
+        &self,
+        _feature_idx: usize,
+        num_features: usize,
+        max_distance: f32,
+        min_score: f32,
+    ) -> Option<f32> {
+        // the best score is reached when all other features are at the max distance
+        let min_dist = max_distance - num_features as f32 * (max_distance - min_score);
+        (min_dist > 0.0).then_some(min_dist)
+    }
 }
 
 /// WeightedAverageRecordScorer
@@ -48,6 +60,40 @@ impl RecordScorer for WeightedAverageRecordScorer {
 
         tot_score / f32::max(effective_weight, self.min_weight_ratio * tot_weight)
     }
+
+    fn min_feature_distance(
+        &self,
+        feature_idx: usize,
+        _num_features: usize,
+        max_distance: f32,
+        min_score: f32,
+    ) -> Option<f32> {
+        let min_dist = weighted_min_distance(&self.weights, feature_idx, max_distance, min_score)?;
+        (min_dist > 0.0).then_some(min_dist)
+    }
+}
+
+/// Returns the weighted score of the feature below which the score of a record
+/// can't be above `min_score`, with the weighted scores at most `max_score`.
+///
+/// The effective weight is at most the total weight and the best score is reached
+/// when all the other features are present at the max score. With missing features
+/// the denominator is at least the effective weight (the min weight ratio
+/// only raises it), which can only lower the score further.
+///
+/// Returns None when a weight is negative as no bound can be derived then.
+fn weighted_min_distance(
+    weights: &[f32],
+    feature_idx: usize,
+    max_score: f32,
+    min_score: f32,
+) -> Option<f32> {
+    let weight = *weights.get(feature_idx)?;
+    if weight <= 0.0 || weights.iter().any(|w| *w < 0.0) {
+        return None;
+    }
+    let tot_weight = weights.iter().sum::<f32>();
+    Some(max_score - tot_weight * (max_score - min_score) / weight)
 }
 
 /// WeightedQuadraticRecordScorer
@@ -81,6 +127,77 @@ impl RecordScorer for WeightedQuadraticRecordScorer {
 
         tot_score / f32::max(effective_weight, self.min_weight_ratio * tot_weight)
     }
+
+    fn min_feature_distance(
+        &self,
+        feature_idx: usize,
+        _num_features: usize,
+        max_distance: f32,
+        min_score: f32,
+    ) -> Option<f32> {
+        // the bound applies to the squared distances
+        let min_squared_dist = weighted_min_distance(
+            &self.weights,
+            feature_idx,
+            max_distance * max_distance,
+            min_score,
+        )?;
+        (min_squared_dist > 0.0).then(|| min_squared_dist.sqrt())
+    }
+}
+
+/// MissingFieldDistances
+///
+/// Distances to use for a feature when it can't be compared because the element
+/// is missing on the record side, on the memory side or on both sides.
+///
+/// Each vector contains one optional value per feature, `None` (or an empty vector)
+/// means that the distance stays missing, as if no value was configured.
+/// This allows to distinguish an unknown value from a totally different one.
+#[derive(Debug, Clone, Default)]
+pub struct MissingFieldDistances {
+    pub record: Vec<Option<f32>>,
+    pub memory: Vec<Option<f32>>,
+    pub both: Vec<Option<f32>>,
+}
+
+impl MissingFieldDistances {
+    pub fn new(record: Vec<Option<f32>>, memory: Vec<Option<f32>>, both: Vec<Option<f32>>) -> Self {
+        Self {
+            record,
+            memory,
+            both,
+        }
+    }
+
+    /// Returns the distance to use for the feature given which sides are missing.
+    ///
+    /// Returns None if the feature is not missing on any side or if no value is configured.
+    pub fn get(
+        &self,
+        feature_idx: usize,
+        record_missing: bool,
+        memory_missing: bool,
+    ) -> Option<f32> {
+        let values = match (record_missing, memory_missing) {
+            (true, true) => &self.both,
+            (true, false) => &self.record,
+            (false, true) => &self.memory,
+            (false, false) => return None,
+        };
+        values.get(feature_idx).copied().flatten()
+    }
+
+    /// Returns the highest absolute value of the distances configured, if any.
+    pub fn max_abs_distance(&self) -> Option<f32> {
+        self.record
+            .iter()
+            .chain(self.memory.iter())
+            .chain(self.both.iter())
+            .flatten()
+            .map(|d| d.abs())
+            .reduce(f32::max)
+    }
 }
 
 #[cfg(test)]
@@ -161,4 +278,71 @@ mod tests {
         let scores = vec![None, None, None];
         assert_eq_f32(scorer.score(&scores), 0.0);
     }
+
+    #[test]
+    fn test_min_feature_distance() {
+        let average = AverageRecordScorer::new();
+        let weighted_average = WeightedAverageRecordScorer::new(vec![1.0, 2.0, 3.0], 0.5);
+        let weighted_quadratic = WeightedQuadraticRecordScorer::new(vec![1.0, 2.0, 3.0], 0.5);
+        let scorers: [&dyn RecordScorer; 3] = [&average, &weighted_average, &weighted_quadratic];
+
+        // at the min distance, the best score is exactly the min score
+        for scorer in scorers {
+            for feature_idx in 0..3 {
+                let min_dist = scorer
+                    .min_feature_distance(feature_idx, 3, 1.0, 0.95)
+                    .unwrap();
+                let mut scores = vec![Some(1.0); 3];
+                scores[feature_idx] = Some(min_dist);
+                assert!((scorer.score(&scores) - 0.95).abs() < 1e-5);
+
+                // missing features only lower the score
+                scores[(feature_idx + 1) % 3] = None;
+                assert!(scorer.score(&scores) < 0.95);
+            }
+        }
+
+        // no bound when the score can be reached whatever the distance is
+        assert_eq!(average.min_feature_distance(0, 3, 1.0, 0.5), None);
+        assert_eq!(weighted_average.min_feature_distance(0, 3, 1.0, 0.8), None);
+        assert_eq!(
+            WeightedAverageRecordScorer::new(vec![1.0, -1.0], 0.5)
+                .min_feature_distance(0, 2, 1.0, 0.8),
+            None
+        );
+    }
+
+    #[test]
+    fn test_missing_field_distances() {
+        let missing = MissingFieldDistances::new(
+            vec![Some(0.5), None],
+            vec![Some(0.3), Some(0.2)],
+            vec![Some(-0.1)],
+        );
+
+        // not missing on any side
+        assert_eq!(missing.get(0, false, false), None);
+
+        // missing on the record side
+        assert_eq!(missing.get(0, true, false), Some(0.5));
+        assert_eq!(missing.get(1, true, false), None);
+
+        // missing on the memory side
+        assert_eq!(missing.get(0, false, true), Some(0.3));
+        assert_eq!(missing.get(1, false, true), Some(0.2));
+
+        // missing on both sides, second feature is not configured
+        assert_eq!(missing.get(0, true, true), Some(-0.1));
+        assert_eq!(missing.get(1, true, true), None);
+
+        // no values configured
+        let missing = MissingFieldDistances::default();
+        assert_eq!(missing.get(0, true, true), None);
+
+        // missing distances are scored as regular distances
+        let scorer = AverageRecordScorer::new();
+        let missing = MissingFieldDistances::new(vec![None, Some(0.5), None], vec![], vec![]);
+        let scores = vec![Some(0.5), missing.get(1, true, false), Some(0.7)];
+        assert_eq_f32(scorer.score(&scores), 0.5666666);
+    }
 }
@@ -1,6 +1,15 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    api::{ChainNode, TrackerDiagnostics, TrackerFrameDiagnostics, TrackerRecordDiagnostics},
-    distances::{CachedDistanceCalculator, InternalDistanceMetricConfig},
+    api::{
+        ChainLink, ChainNode, TrackerDiagnostics, TrackerFrameDiagnostics, TrackerRecordDiagnostics,
+    },
+    distances::{CachedDistanceCalculator, InternalDistanceMetricConfig, MedianWordStrategy},
     frame::{Element, Frame, Record},
     id::{self, ID},
 };
@@ -8,30 +17,41 @@ use crate::{
 use super::{
     tracker_memory::{
         BruteForceMemory, LongShortTermMemory, MedianWordMemory, MostFrequentMemory,
-        MultiWordMemory,
+        MultiWordMemory, WeightedMedianWordMemory,
     },
-    AverageRecordScorer, WeightedAverageRecordScorer, WeightedQuadraticRecordScorer,
+    AverageRecordScorer, MissingFieldDistances, WeightedAverageRecordScorer,
+    WeightedQuadraticRecordScorer,
 };
 
+/// Margin subtracted from the min distances of the features, so that
+/// rounding errors never exclude a record of interest.
+const MIN_DISTANCE_MARGIN: f32 = 1e-4;
+
 /// TrackingChain
 ///
 /// Represents a chain of chain nodes.
-#[derive(Debug, Clone)]
+///
+/// Each node has a link holding the matching information of the node,
+/// None for the first node of the chain or when no information is available.
+/// There is always one link per node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackingChain {
     pub id: ID,
     pub nodes: Vec<ChainNode>,
+    pub links: Vec<Option<ChainLink>>,
 }
 
 impl TrackingChain {
-    pub fn new(id: ID, nodes: Vec<ChainNode>) -> Self {
-        Self { id, nodes }
+    pub fn new(id: ID, nodes: Vec<ChainNode>, links: Vec<Option<ChainLink>>) -> Self {
+        debug_assert_eq!(nodes.len(), links.len(), "one link per node");
+        Self { id, nodes, links }
     }
 }
 
 /// RecordScore
 ///
 /// Represents the score of a record.
-#[derive(PartialEq, PartialOrd, Clone, Copy)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
 pub struct RecordScore {
     pub idx: usize,
     pub score: f32,
@@ -57,7 +77,9 @@ impl Ord for RecordScore {
 pub enum TrackerMemoryConfig {
     BruteForce,
     MostFrequent,
-    Median,
+    Median(MedianWordStrategy),
+    /// Median word weighted by the confidence of the matches, with the given recency decay
+    WeightedMedian(f32),
     LongShortTerm(Box<TrackerMemoryConfig>),
     MultiWord(Box<TrackerMemoryConfig>, InternalDistanceMetricConfig, f32),
 }
@@ -69,12 +91,29 @@ pub enum TrackerRecordScorerConfig {
     WeightedQuadratic(Vec<f32>, f32),
 }
 
+/// Composite feature
+///
+/// Combines multiple related features (for example the spouse surname and the children
+/// names of a household), when all of them match with the tracker's memory, the bonus is
+/// added to the score of the record. This allows to treat the agreement between related
+/// features as extra evidence instead of independent features.
+#[derive(Debug, Clone)]
+pub struct TrackerCompositeFeatureConfig {
+    pub feature_idxs: Vec<usize>,
+    /// Minimal distance for a feature to be considered as matching,
+    /// for multi-words features, at least one word must match.
+    pub threshold_match: f32,
+    pub bonus: f32,
+}
+
 #[derive(Clone)]
 pub struct InternalTrackerConfig {
     pub interest_threshold: f32,
     pub limit_no_match_streak: usize,
     pub memory_configs: Vec<TrackerMemoryConfig>,
     pub record_scorer: TrackerRecordScorerConfig,
+    pub missing_distances: MissingFieldDistances,
+    pub composite_features: Vec<TrackerCompositeFeatureConfig>,
 }
 
 /// TrackerMemory
@@ -92,6 +131,15 @@ pub trait TrackerMemory {
     /// Updates the memory with the new element.
     fn signal_matching_element(&mut self, element: Element);
 
+    /// Signals that a matching element has been found in the current frame,
+    /// along with the confidence of the match, i.e. the score of the matching record.
+    ///
+    /// By default, the confidence is ignored.
+    fn signal_matching_element_with_confidence(&mut self, element: Element, confidence: f32) {
+        let _ = confidence;
+        self.signal_matching_element(element);
+    }
+
     /// Returns the relevant elements according to the memory policy.
     ///
     /// This should not be computationally expensive, computation should be done
@@ -102,6 +150,12 @@ pub trait TrackerMemory {
 
     /// Returns a new instance of the memory with the default values.
     fn new_default(&self) -> Box<dyn TrackerMemory + Send + Sync>;
+
+    /// Clone the memory, including the elements it has seen.
+    ///
+    /// This is done this way because of restrictions on the trait
+    /// due to it being used as dyn TrackerMemory.
+    fn clone(&self) -> Box<dyn TrackerMemory + Send + Sync>;
 }
 
 /// RecordScorer
@@ -109,20 +163,151 @@ pub trait TrackerMemory {
 /// Responsible of scoring a record based on the scores of its features.
 pub trait RecordScorer {
     fn score(&self, scores: &Vec<Option<f32>>) -> f32;
+
+    /// Returns the distance of the feature below which the score of a record
+    /// can't be above `min_score`, whatever the distances of the other features are,
+    /// given that no distance is above `max_distance`.
+    ///
+    /// Returns None if there is no such positive distance.
+    fn min_feature_distance(
+        &self,
+        feature_idx: usize,
+        num_features: usize,
+        max_distance: f32,
+        min_score: f32,
+    ) -> Option<f32>;
+}
+
+/// PendingNode
+///
+/// Node of a hypothesis not yet committed to the chain.
+#[derive(Clone)]
+struct PendingNode {
+    node: ChainNode,
+    record: Record,
+    link: Option<ChainLink>,
+}
+
+/// TrackerHypothesis
+///
+/// A possible path of the tracker over the last frames that are not yet
+/// committed to its chain. Each hypothesis has its own memories as they
+/// depend on the records matched along the path.
+struct TrackerHypothesis {
+    memories: Vec<Box<dyn TrackerMemory + Send + Sync>>,
+    /// Nodes not yet committed to the chain, one per frame,
+    /// None when the hypothesis didn't match any record in the frame.
+    pending: Vec<Option<PendingNode>>,
+    /// Cumulative score of the matched records along the path
+    score: f32,
+    /// Scores of the records of interest of the last processed frame
+    scores: Vec<RecordScore>,
+    /// Distances of the records of interest of the last processed frame,
+    /// in the same order as the scores
+    distances: Vec<Vec<Option<f32>>>,
+    no_matching_node_counter: usize,
+}
+
+impl TrackerHypothesis {
+    fn new(memories: Vec<Box<dyn TrackerMemory + Send + Sync>>) -> Self {
+        Self {
+            memories,
+            pending: Vec::new(),
+            score: 0.0,
+            scores: Vec::new(),
+            distances: Vec::new(),
+            no_matching_node_counter: 0,
+        }
+    }
+
+    /// Builds the link of the given record from the scores of the last processed frame.
+    ///
+    /// Returns None if the record is not of interest.
+    fn build_link(&self, record_idx: usize) -> Option<ChainLink> {
+        let pos = self.scores.iter().position(|s| s.idx == record_idx)?;
+        let score = self.scores[pos].score;
+        // best score of the other records, the scores are sorted in descending order
+        let runner_up = self
+            .scores
+            .iter()
+            .enumerate()
+            .find(|(i, _)| *i != pos)
+            .map(|(_, s)| s.score);
+        Some(ChainLink::new(
+            score,
+            self.distances[pos].clone(),
+            runner_up.map(|s| score - s),
+        ))
+    }
+
+    /// Builds a new hypothesis extending this one with the given record
+    /// or with no record at all.
+    fn extend(&self, frame: &Frame, record_score: Option<RecordScore>) -> Self {
+        let mut memories: Vec<Box<dyn TrackerMemory + Send + Sync>> =
+            self.memories.iter().map(|m| (**m).clone()).collect();
+        let mut pending = self.pending.clone();
+        match record_score {
+            Some(record_score) => {
+                let record = frame.record(record_score.idx);
+                for (idx, memory) in memories.iter_mut().enumerate() {
+                    memory.signal_matching_element_with_confidence(
+                        record.element(idx).clone(),
+                        record_score.score,
+                    );
+                }
+                pending.push(Some(PendingNode {
+                    node: ChainNode::new(frame.idx(), record_score.idx),
+                    record,
+                    link: self.build_link(record_score.idx),
+                }));
+                Self {
+                    memories,
+                    pending,
+                    score: self.score + record_score.score,
+                    scores: Vec::new(),
+                    distances: Vec::new(),
+                    no_matching_node_counter: 0,
+                }
+            }
+            None => {
+                for memory in memories.iter_mut() {
+                    memory.signal_no_matching_element();
+                }
+                pending.push(None);
+                Self {
+                    memories,
+                    pending,
+                    score: self.score,
+                    scores: Vec::new(),
+                    distances: Vec::new(),
+                    no_matching_node_counter: self.no_matching_node_counter + 1,
+                }
+            }
+        }
+    }
 }
 
 /// Tracker
 ///
 /// Responsible to track an individual through multiple frames.
 /// Each tracker produces a single tracking chain.
+///
+/// The tracker holds a single hypothesis, except when resolved with the
+/// multi-hypothesis strategy, in which case it keeps multiple hypotheses
+/// sorted in descending order of cumulative score.
 pub struct Tracker {
     id: ID,
     config: InternalTrackerConfig,
     chain: Vec<ChainNode>,
-    memories: Vec<Box<dyn TrackerMemory + Send + Sync>>,
+    /// Links of the nodes of the chain
+    links: Vec<Option<ChainLink>>,
+    hypotheses: Vec<TrackerHypothesis>,
     record_scorer: Box<dyn RecordScorer + Send + Sync>,
     diagnostics: TrackerDiagnostics,
-    no_matching_node_counter: usize,
+    /// Indicates that the tracker must match with its pinned record in the current frame
+    pinned: bool,
+    /// Indicates that the tracker waits for a record it must match with in a later frame
+    awaiting_record: bool,
 }
 
 impl Tracker {
@@ -131,15 +316,19 @@ impl Tracker {
         Self {
             id,
             chain: Vec::new(),
-            memories: config
-                .memory_configs
-                .iter()
-                .map(|conf| Self::build_tracker_memory(conf.clone()))
-                .collect(),
+            links: Vec::new(),
+            hypotheses: vec![TrackerHypothesis::new(
+                config
+                    .memory_configs
+                    .iter()
+                    .map(|conf| Self::build_tracker_memory(conf.clone()))
+                    .collect(),
+            )],
             record_scorer: Self::build_record_scorer(&config.record_scorer),
             config,
             diagnostics: TrackerDiagnostics::new(id),
-            no_matching_node_counter: 0,
+            pinned: false,
+            awaiting_record: false,
         }
     }
 
@@ -149,7 +338,10 @@ impl Tracker {
         match memory_config {
             TrackerMemoryConfig::BruteForce => Box::new(BruteForceMemory::new()),
             TrackerMemoryConfig::MostFrequent => Box::new(MostFrequentMemory::new()),
-            TrackerMemoryConfig::Median => Box::new(MedianWordMemory::new()),
+            TrackerMemoryConfig::Median(strategy) => Box::new(MedianWordMemory::new(strategy)),
+            TrackerMemoryConfig::WeightedMedian(recency_decay) => {
+                Box::new(WeightedMedianWordMemory::new(recency_decay))
+            }
             TrackerMemoryConfig::LongShortTerm(memory_config) => Box::new(
                 LongShortTermMemory::new(Self::build_tracker_memory(*memory_config)),
             ),
@@ -192,27 +384,46 @@ impl Tracker {
     }
 
     /// Builds the tracking chain for the tracker at this time.
+    ///
+    /// The pending nodes of the best hypothesis are included in the chain.
     pub fn get_tracking_chain(&self) -> TrackingChain {
-        TrackingChain::new(self.id, self.chain.clone())
+        let mut nodes = self.chain.clone();
+        let mut links = self.links.clone();
+        for pending in self.hypotheses[0].pending.iter().flatten() {
+            nodes.push(pending.node);
+            links.push(pending.link.clone());
+        }
+        TrackingChain::new(self.id, nodes, links)
     }
 
-    /// Returns the memory elements for a feature.
+    /// Returns the memory elements for a feature, across all hypotheses.
     pub fn get_memory_elements(&self, feature_idx: usize) -> Vec<&Element> {
-        self.memories[feature_idx].get_elements()
+        self.hypotheses
+            .iter()
+            .flat_map(|h| h.memories[feature_idx].get_elements())
+            .collect()
     }
 
     /// Returns if the tracker is considered dead.
     ///
     /// This happens when no matching records have been found for a certain amount of frames.
     /// It is useful to reduce the number of trackers that are being processed.
+    ///
+    /// A tracker awaiting a record it must match with is never dead, whatever
+    /// its no match streak.
     pub fn is_dead(&self) -> bool {
-        self.no_matching_node_counter > self.config.limit_no_match_streak
+        !self.awaiting_record
+            && self.hypotheses[0].no_matching_node_counter > self.config.limit_no_match_streak
     }
 
     /// Signals that no matching node has been found in the current frame.
+    ///
+    /// This must only be used when the tracker holds a single hypothesis.
     pub fn signal_no_matching_node(&mut self) {
-        self.no_matching_node_counter += 1;
-        for memory in self.memories.iter_mut() {
+        debug_assert_eq!(self.hypotheses.len(), 1);
+        let hypothesis = &mut self.hypotheses[0];
+        hypothesis.no_matching_node_counter += 1;
+        for memory in hypothesis.memories.iter_mut() {
             memory.signal_no_matching_element();
         }
     }
@@ -221,28 +432,207 @@ impl Tracker {
     /// and add it to the tracker's chain.
     ///
     /// The matching record is also provided to update the tracker's memory.
+    ///
+    /// This must only be used when the tracker holds a single hypothesis.
     pub fn signal_matching_node(&mut self, node: ChainNode, record: Record) {
+        debug_assert_eq!(self.hypotheses.len(), 1);
         self.chain.push(node);
-        self.no_matching_node_counter = 0;
+        // a new tracker has no scores, thus no link for its first node
+        let link = self.hypotheses[0].build_link(node.record_idx);
+        // the first node is fully trusted
+        let confidence = link.as_ref().map_or(1., |link| link.score);
+        self.links.push(link);
+        let hypothesis = &mut self.hypotheses[0];
+        hypothesis.no_matching_node_counter = 0;
         for idx in 0..record.size() {
-            self.memories[idx].signal_matching_element(record.element(idx).clone());
+            hypothesis.memories[idx]
+                .signal_matching_element_with_confidence(record.element(idx).clone(), confidence);
+        }
+    }
+
+    /// Returns the last node of the tracking chain of the tracker at this time.
+    pub fn last_node(&self) -> Option<ChainNode> {
+        self.hypotheses[0]
+            .pending
+            .iter()
+            .rev()
+            .flatten()
+            .next()
+            .map(|pending| pending.node)
+            .or_else(|| self.chain.last().copied())
+    }
+
+    /// Pins a record for the current frame, the record replaces the records
+    /// of interest of all hypotheses and the hypotheses must match with it.
+    ///
+    /// The score and distances computed by a hypothesis are kept when the record
+    /// is of interest to it, otherwise the given score is used.
+    pub fn pin_record(&mut self, record_score: RecordScore) {
+        for hypothesis in self.hypotheses.iter_mut() {
+            let (score, distances) = match hypothesis
+                .scores
+                .iter()
+                .position(|s| s.idx == record_score.idx)
+            {
+                Some(pos) => (hypothesis.scores[pos], hypothesis.distances[pos].clone()),
+                None => (record_score, vec![None; hypothesis.memories.len()]),
+            };
+            hypothesis.scores = vec![score];
+            hypothesis.distances = vec![distances];
+        }
+        self.pinned = true;
+        self.awaiting_record = false;
+    }
+
+    /// Marks the tracker as waiting for a record it must match with in a later frame,
+    /// until the record is pinned, see `pin_record`.
+    pub fn await_record(&mut self) {
+        self.awaiting_record = true;
+    }
+
+    /// Returns the nodes that are only part of hypotheses other than the best one.
+    ///
+    /// These nodes are not part of the tracking chain of the tracker.
+    pub fn get_released_nodes(&self) -> Vec<ChainNode> {
+        let best_nodes = self.hypotheses[0]
+            .pending
+            .iter()
+            .flatten()
+            .map(|pending| pending.node)
+            .collect::<HashSet<ChainNode>>();
+
+        let mut nodes = Vec::new();
+        for hypothesis in self.hypotheses[1..].iter() {
+            for pending in hypothesis.pending.iter().flatten() {
+                if !best_nodes.contains(&pending.node) && !nodes.contains(&pending.node) {
+                    nodes.push(pending.node);
+                }
+            }
         }
+        nodes
     }
 
-    /// Saves the current elements of the memories the tracker to the frame diagnostics.
+    /// Expands the hypotheses of the tracker with the records of interest
+    /// of the current frame.
+    ///
+    /// Each hypothesis is extended with each of its allowed records of interest and
+    /// with no record, only the `num_hypotheses` best resulting hypotheses are kept.
+    /// Once the best hypothesis has more than `depth` pending nodes, its oldest
+    /// pending node is committed to the chain and the hypotheses that disagree
+    /// with it are discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The current frame.
+    /// * `is_allowed` - Indicates for a record index if the tracker can match with it.
+    /// * `num_hypotheses` - The maximal number of hypotheses to keep.
+    /// * `depth` - The number of frames a decision can be deferred.
+    ///
+    /// # Returns
+    /// The released nodes along with their records, that is the allowed records of interest
+    /// and the previously pending nodes that are not part of any hypothesis anymore nor
+    /// of the chain. The list is sorted by frame and record indexes.
+    pub fn signal_hypotheses(
+        &mut self,
+        frame: &Frame,
+        is_allowed: impl Fn(usize) -> bool,
+        num_hypotheses: usize,
+        depth: usize,
+    ) -> Vec<(ChainNode, Record)> {
+        let mut released = HashMap::new();
+        for hypothesis in self.hypotheses.iter() {
+            for pending in hypothesis.pending.iter().flatten() {
+                released.insert(pending.node, pending.record.clone());
+            }
+            for record_score in hypothesis.scores.iter().filter(|s| is_allowed(s.idx)) {
+                released
+                    .entry(ChainNode::new(frame.idx(), record_score.idx))
+                    .or_insert_with(|| frame.record(record_score.idx));
+            }
+        }
+
+        // candidates as (hypothesis index, record score, cumulative score)
+        let mut candidates: Vec<(usize, Option<RecordScore>, f32)> = Vec::new();
+        for (hypothesis_idx, hypothesis) in self.hypotheses.iter().enumerate() {
+            candidates.push((hypothesis_idx, None, hypothesis.score));
+            for record_score in hypothesis
+                .scores
+                .iter()
+                .filter(|s| is_allowed(s.idx))
+                .take(num_hypotheses)
+            {
+                candidates.push((
+                    hypothesis_idx,
+                    Some(*record_score),
+                    hypothesis.score + record_score.score,
+                ));
+            }
+        }
+
+        // a pinned tracker must match with the pinned record
+        if self.pinned
+            && candidates
+                .iter()
+                .any(|(_, record_score, _)| record_score.is_some())
+        {
+            candidates.retain(|(_, record_score, _)| record_score.is_some());
+        }
+
+        // sort in descending order, stable to favor the best hypotheses on ties
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+        candidates.truncate(num_hypotheses.max(1));
+
+        self.hypotheses = candidates
+            .into_iter()
+            .map(|(hypothesis_idx, record_score, _)| {
+                self.hypotheses[hypothesis_idx].extend(frame, record_score)
+            })
+            .collect();
+
+        while self.hypotheses[0].pending.len() > depth {
+            let committed = self.hypotheses[0].pending[0].clone();
+            let committed_node = committed.as_ref().map(|pending| pending.node);
+            self.hypotheses.retain(|hypothesis| {
+                hypothesis.pending[0].as_ref().map(|pending| pending.node) == committed_node
+            });
+            for hypothesis in self.hypotheses.iter_mut() {
+                hypothesis.pending.remove(0);
+            }
+            if let Some(pending) = committed {
+                released.remove(&pending.node);
+                self.chain.push(pending.node);
+                self.links.push(pending.link);
+            }
+        }
+
+        for hypothesis in self.hypotheses.iter() {
+            for pending in hypothesis.pending.iter().flatten() {
+                released.remove(&pending.node);
+            }
+        }
+
+        self.pinned = false;
+
+        let mut released = released.into_iter().collect::<Vec<_>>();
+        released.sort_unstable_by_key(|(node, _)| (node.frame_idx, node.record_idx));
+        released
+    }
+
+    /// Saves the current elements of the memories of the best hypothesis
+    /// of the tracker to the frame diagnostics.
     fn save_memory_to_diagnostics(&self, diagnostics: &mut TrackerFrameDiagnostics) {
         let mut memories = Vec::new();
-        for memory in self.memories.iter() {
+        for memory in self.hypotheses[0].memories.iter() {
             let mut memory_strings = Vec::new();
             for element in memory.get_elements().iter_mut() {
                 match element {
                     Element::MultiWords(words) => {
                         for word in words {
-                            memory_strings.push(word.raw.clone());
+                            memory_strings.push(word.raw().to_string());
                         }
                     }
                     Element::Word(word) => {
-                        memory_strings.push(word.raw.clone());
+                        memory_strings.push(word.raw().to_string());
                     }
                     Element::None => {}
                 }
@@ -253,72 +643,448 @@ impl Tracker {
         diagnostics.memory = memories;
     }
 
+    /// Computes, for each feature, the distance below which a record can't be of interest,
+    /// whatever the distances of its other features are.
+    ///
+    /// The distances of the metrics are expected to be between 0 and 1, the configured
+    /// missing distances and the bonus of the composite features are accounted for.
+    fn compute_min_distances(
+        &self,
+        num_features: usize,
+        max_composite_bonus: f32,
+    ) -> Vec<Option<f32>> {
+        let max_distance = self
+            .config
+            .missing_distances
+            .max_abs_distance()
+            .map_or(1.0, |d| d.max(1.0));
+        let min_score = self.config.interest_threshold - max_composite_bonus;
+        (0..num_features)
+            .map(|feature_idx| {
+                self.record_scorer
+                    .min_feature_distance(feature_idx, num_features, max_distance, min_score)
+                    .map(|d| d - MIN_DISTANCE_MARGIN)
+            })
+            .collect()
+    }
+
     /// Computes the distances between the tracker's memory and the frame's records.
     ///
     /// Returns a matrix of distances, with one vector per record and one element per feature.
+    ///
+    /// When a distance can't be computed because the element is missing on the memory side
+    /// and/or on the record side, the configured missing distance is used instead.
+    ///
+    /// When a feature has a min distance and the distance of a record is below it,
+    /// the record can't be of interest: the computation of its distances stops
+    /// and None is returned for it.
     fn compute_distances(
         &self,
+        memories: &[Box<dyn TrackerMemory + Send + Sync>],
         frame: &Frame,
         distance_calculators: &mut Vec<CachedDistanceCalculator>,
-    ) -> Vec<Vec<Option<f32>>> {
+        min_distances: &[Option<f32>],
+    ) -> Vec<Option<Vec<Option<f32>>>> {
         let mut distances = (0..frame.num_records())
-            .map(|_| (0..frame.num_features()).map(|_| None).collect())
-            .collect::<Vec<Vec<Option<f32>>>>();
+            .map(|_| Some((0..frame.num_features()).map(|_| None).collect()))
+            .collect::<Vec<Option<Vec<Option<f32>>>>>();
 
         for feature_idx in 0..frame.num_features() {
             let distance_calculator = &mut distance_calculators[feature_idx];
-            let own_elements = self.memories[feature_idx].get_elements();
+            let own_elements = memories[feature_idx].get_elements();
+            let memory_missing = own_elements.is_empty();
+            let min_dist = min_distances[feature_idx];
 
             for (record_idx, element) in frame.column(feature_idx).iter().enumerate() {
+                let Some(record_distances) = distances[record_idx].as_mut() else {
+                    continue;
+                };
+
                 let mut max_dist: Option<f32> = None;
+                let mut is_comparable = false;
                 for own_element in own_elements.iter() {
-                    let dist = distance_calculator.get_dist(own_element, element);
+                    let dist = match min_dist {
+                        Some(min_dist) => {
+                            // past the first match, only greater distances matter
+                            let min_dist = max_dist.map_or(min_dist, |d| d.max(min_dist));
+                            distance_calculator.get_dist_bounded(own_element, element, min_dist)
+                        }
+                        None => distance_calculator.get_dist(own_element, element).map(Some),
+                    };
                     if let Some(dist) = dist {
-                        max_dist = max_dist.map(|d| d.max(dist)).or(Some(dist));
+                        is_comparable = true;
+                        if let Some(dist) = dist {
+                            max_dist = max_dist.map(|d| d.max(dist)).or(Some(dist));
+                        }
                     }
                 }
-                distances[record_idx][feature_idx] = max_dist;
+
+                if is_comparable && max_dist.is_none() {
+                    distances[record_idx] = None;
+                    continue;
+                }
+                record_distances[feature_idx] = max_dist.or_else(|| {
+                    self.config.missing_distances.get(
+                        feature_idx,
+                        element.is_none(),
+                        memory_missing,
+                    )
+                });
             }
         }
 
         distances
     }
 
+    /// Returns if the element matches with at least one of the elements of the memory of the feature.
+    fn is_feature_match(
+        memory: &(dyn TrackerMemory + Send + Sync),
+        element: &Element,
+        threshold_match: f32,
+        distance_calculator: &mut CachedDistanceCalculator,
+    ) -> bool {
+        memory.get_elements().iter().any(|own_element| {
+            distance_calculator
+                .get_max_dist(own_element, element)
+                .is_some_and(|dist| dist >= threshold_match)
+        })
+    }
+
+    /// Computes the total bonus of the composite features that match with the record.
+    fn compute_composite_bonus(
+        &self,
+        memories: &[Box<dyn TrackerMemory + Send + Sync>],
+        frame: &Frame,
+        record_idx: usize,
+        distance_calculators: &mut [CachedDistanceCalculator],
+    ) -> f32 {
+        let mut bonus = 0.0;
+        for composite_feature in self.config.composite_features.iter() {
+            let is_match = composite_feature.feature_idxs.iter().all(|&feature_idx| {
+                Self::is_feature_match(
+                    memories[feature_idx].as_ref(),
+                    &frame.column(feature_idx)[record_idx],
+                    composite_feature.threshold_match,
+                    &mut distance_calculators[feature_idx],
+                )
+            });
+            if is_match {
+                bonus += composite_feature.bonus;
+            }
+        }
+        bonus
+    }
+
+    /// Computes the scores of the records of interest of the frame for the given memories.
+    ///
+    /// Returns the list of record scores along with the distances of each record of interest.
+    fn compute_scores(
+        &self,
+        memories: &[Box<dyn TrackerMemory + Send + Sync>],
+        frame: &Frame,
+        distance_calculators: &mut Vec<CachedDistanceCalculator>,
+    ) -> (Vec<RecordScore>, Vec<Vec<Option<f32>>>) {
+        // upper bound of the bonus of the composite features, used to avoid
+        // evaluating them for records that can't be of interest anyway
+        let max_composite_bonus = self
+            .config
+            .composite_features
+            .iter()
+            .map(|c| c.bonus.max(0.0))
+            .sum::<f32>();
+
+        let min_distances = self.compute_min_distances(frame.num_features(), max_composite_bonus);
+        let distances =
+            self.compute_distances(memories, frame, distance_calculators, &min_distances);
+
+        let mut scores = Vec::new();
+        let mut scores_distances = Vec::new();
+
+        for (record_idx, record_distances) in distances.into_iter().enumerate() {
+            let Some(record_distances) = record_distances else {
+                continue;
+            };
+            let mut score = self.record_scorer.score(&record_distances);
+            if !self.config.composite_features.is_empty()
+                && score + max_composite_bonus > self.config.interest_threshold
+            {
+                score +=
+                    self.compute_composite_bonus(memories, frame, record_idx, distance_calculators);
+            }
+            if score > self.config.interest_threshold {
+                scores.push(RecordScore::new(record_idx, score));
+                scores_distances.push(record_distances);
+            }
+        }
+
+        (scores, scores_distances)
+    }
+
     /// Processes a frame, that is computes the distances between the tracker's memory
     /// and the frame's records to find the "best" records.
     ///
     /// Returns a list of record scores, for the records considered of interest by the tracker.
     /// The list is sorted in descending order of score.
+    ///
+    /// When the tracker holds multiple hypotheses, each hypothesis is scored on its own
+    /// and the score of a record is the best score over the hypotheses.
     pub fn process_frame(
         &mut self,
         frame: &Frame,
         distance_calculators: &mut Vec<CachedDistanceCalculator>,
     ) -> Vec<RecordScore> {
-        let distances = self.compute_distances(frame, distance_calculators);
-
-        let mut scores = Vec::new();
         let mut frame_diagnostics = TrackerFrameDiagnostics::new(frame.idx());
+        let mut best_scores: HashMap<usize, f32> = HashMap::new();
+        self.pinned = false;
 
-        for record_idx in 0..frame.num_records() {
-            let score = self.record_scorer.score(&distances[record_idx]);
-            if score > self.config.interest_threshold {
-                scores.push(RecordScore::new(record_idx, score));
-                frame_diagnostics
-                    .records
-                    .push(TrackerRecordDiagnostics::new(
-                        record_idx,
-                        score,
-                        distances[record_idx].clone(),
-                    ));
+        for hypothesis_idx in 0..self.hypotheses.len() {
+            let (scores, distances) = self.compute_scores(
+                &self.hypotheses[hypothesis_idx].memories,
+                frame,
+                distance_calculators,
+            );
+
+            if hypothesis_idx == 0 {
+                for (score, distances) in scores.iter().zip(distances.iter()) {
+                    frame_diagnostics
+                        .records
+                        .push(TrackerRecordDiagnostics::new(
+                            score.idx,
+                            score.score,
+                            distances.clone(),
+                        ));
+                }
             }
+            for score in scores.iter() {
+                let best_score = best_scores.entry(score.idx).or_insert(score.score);
+                *best_score = best_score.max(score.score);
+            }
+
+            // sort in descending order, keeping the distances in the same order
+            let mut scores_distances = scores.into_iter().zip(distances).collect::<Vec<_>>();
+            scores_distances.sort_unstable_by_key(|(score, _)| Reverse(*score));
+            let (scores, distances) = scores_distances.into_iter().unzip();
+
+            let hypothesis = &mut self.hypotheses[hypothesis_idx];
+            hypothesis.scores = scores;
+            hypothesis.distances = distances;
         }
 
         self.save_memory_to_diagnostics(&mut frame_diagnostics);
 
         self.diagnostics.frames.push(frame_diagnostics);
 
+        let mut scores = best_scores
+            .into_iter()
+            .map(|(idx, score)| RecordScore::new(idx, score))
+            .collect::<Vec<_>>();
+
         // sort in descending order
         scores.sort_unstable_by(|a, b| b.cmp(a));
         scores
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{build_distance_calculators, build_tracker_config},
+        word::Word,
+    };
+
+    fn word(s: &str) -> Element {
+        Element::Word(Word::new(s.to_string()))
+    }
+
+    fn multiwords(ws: &[&str]) -> Element {
+        Element::MultiWords(ws.iter().map(|s| Word::new(s.to_string())).collect())
+    }
+
+    fn build_frame(idx: usize, records: Vec<Vec<Element>>) -> Frame {
+        let num_features = records[0].len();
+        let columns = (0..num_features)
+            .map(|feature_idx| records.iter().map(|r| r[feature_idx].clone()).collect())
+            .collect();
+        Frame::new(idx, columns)
+    }
+
+    /// Builds a tracker initialized with the first record of the first frame and
+    /// returns the scores of the records of the second frame.
+    fn compute_scores(
+        composite_features: Vec<TrackerCompositeFeatureConfig>,
+        frames: &[Frame],
+    ) -> Vec<RecordScore> {
+        let num_features = frames[0].num_features();
+        let mut config = build_tracker_config(0.0, 5, num_features);
+        config.composite_features = composite_features;
+        let mut distance_calculators = build_distance_calculators(num_features);
+
+        let mut tracker = Tracker::new(config);
+        tracker.signal_matching_node(ChainNode::new(0, 0), frames[0].record(0));
+        let mut scores = tracker.process_frame(&frames[1], &mut distance_calculators);
+        scores.sort_unstable_by_key(|s| s.idx);
+        scores
+    }
+
+    #[test]
+    fn test_composite_feature_bonus() {
+        let frames = vec![
+            build_frame(
+                0,
+                vec![vec![
+                    word("dupont"),
+                    word("martin"),
+                    multiwords(&["jean", "marie"]),
+                ]],
+            ),
+            build_frame(
+                1,
+                vec![
+                    // spouse and one child match
+                    vec![word("dupond"), word("martin"), multiwords(&["marie"])],
+                    // spouse match but no child match
+                    vec![word("dupond"), word("martin"), multiwords(&["paul"])],
+                    // child match but spouse doesn't match
+                    vec![word("dupond"), word("bernard"), multiwords(&["marie"])],
+                ],
+            ),
+        ];
+
+        let base_scores = compute_scores(Vec::new(), &frames);
+        let scores = compute_scores(
+            vec![TrackerCompositeFeatureConfig {
+                feature_idxs: vec![1, 2],
+                threshold_match: 0.8,
+                bonus: 0.2,
+            }],
+            &frames,
+        );
+
+        assert_eq!(base_scores.len(), 3);
+        assert_eq!(scores.len(), 3);
+        assert!((scores[0].score - base_scores[0].score - 0.2).abs() < 1e-6);
+        assert_eq!(scores[1].score, base_scores[1].score);
+        assert_eq!(scores[2].score, base_scores[2].score);
+    }
+
+    #[test]
+    fn test_min_distances() {
+        let frames = [
+            build_frame(0, vec![vec![word("dupont"), word("jean")]]),
+            build_frame(
+                1,
+                vec![
+                    vec![word("dupont"), word("jean")],
+                    vec![word("dupond"), word("jeanne")],
+                    vec![word("bernard"), word("jean")],
+                    vec![word("martin"), word("paul")],
+                ],
+            ),
+        ];
+        let process_frame = |interest_threshold: f32| {
+            let config = build_tracker_config(interest_threshold, 5, 2);
+            let mut distance_calculators = build_distance_calculators(2);
+            let mut tracker = Tracker::new(config);
+            tracker.signal_matching_node(ChainNode::new(0, 0), frames[0].record(0));
+            let scores = tracker.process_frame(&frames[1], &mut distance_calculators);
+            let computation_count = distance_calculators
+                .iter()
+                .map(|c| c.trace.computation_count)
+                .sum::<u64>();
+            (scores, computation_count)
+        };
+
+        // no min distance can be derived from a negative threshold
+        let (all_scores, all_count) = process_frame(-1.0);
+        assert_eq!(all_scores.len(), 4);
+        assert_eq!(all_count, 8);
+
+        // the records far on the first feature are dropped before the second one
+        let (scores, count) = process_frame(0.7);
+        let expected = all_scores
+            .into_iter()
+            .filter(|s| s.score > 0.7)
+            .collect::<Vec<_>>();
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores, expected);
+        assert_eq!(count, 6);
+    }
+
+    #[test]
+    fn test_chain_links() {
+        let frames = [
+            build_frame(0, vec![vec![word("dupont"), word("jean")]]),
+            build_frame(
+                1,
+                vec![
+                    vec![word("dupont"), word("jean")],
+                    vec![word("dupond"), word("jean")],
+                ],
+            ),
+        ];
+        let config = build_tracker_config(0.0, 5, 2);
+        let mut distance_calculators = build_distance_calculators(2);
+
+        let mut tracker = Tracker::new(config);
+        tracker.signal_matching_node(ChainNode::new(0, 0), frames[0].record(0));
+        let scores = tracker.process_frame(&frames[1], &mut distance_calculators);
+        tracker.signal_matching_node(ChainNode::new(1, 0), frames[1].record(0));
+
+        let chain = tracker.get_tracking_chain();
+        assert_eq!(chain.links.len(), 2);
+        assert!(chain.links[0].is_none());
+        let link = chain.links[1].as_ref().unwrap();
+        assert_eq!(link.score, 1.0);
+        assert_eq!(link.distances, vec![Some(1.0), Some(1.0)]);
+        assert_eq!(link.margin, Some(scores[0].score - scores[1].score));
+    }
+
+    #[test]
+    fn test_missing_field_distances() {
+        let frames = [
+            build_frame(0, vec![vec![word("dupont"), Element::None]]),
+            build_frame(
+                1,
+                vec![
+                    vec![word("dupont"), word("jean")],
+                    vec![word("dupont"), Element::None],
+                ],
+            ),
+        ];
+        let compute_distances = |missing_distances: MissingFieldDistances| {
+            let mut config = build_tracker_config(0.0, 5, 2);
+            config.missing_distances = missing_distances;
+            let mut distance_calculators = build_distance_calculators(2);
+            let mut tracker = Tracker::new(config);
+            tracker.signal_matching_node(ChainNode::new(0, 0), frames[0].record(0));
+            tracker.compute_distances(
+                &tracker.hypotheses[0].memories,
+                &frames[1],
+                &mut distance_calculators,
+                &[None, None],
+            )
+        };
+
+        // the second feature is missing in the memory, and in the second record
+        let distances = compute_distances(MissingFieldDistances::new(
+            vec![None, Some(0.5)],
+            vec![None, Some(0.3)],
+            vec![None, Some(0.1)],
+        ));
+        assert_eq!(
+            distances,
+            vec![
+                Some(vec![Some(1.0), Some(0.3)]),
+                Some(vec![Some(1.0), Some(0.1)])
+            ]
+        );
+
+        // without configured distances, the missing distances stay missing
+        let distances = compute_distances(MissingFieldDistances::default());
+        assert_eq!(
+            distances,
+            vec![Some(vec![Some(1.0), None]), Some(vec![Some(1.0), None])]
+        );
+    }
+}
@@ -4,7 +4,7 @@ use std::{
 };
 
 use crate::{
-    distances::{compute_median_word, DistanceMetric},
+    distances::{DistanceMetric, IncrementalMedianWord, MedianWordBuilder, MedianWordStrategy},
     frame::Element,
     word::Word,
 };
@@ -14,6 +14,7 @@ use super::tracker::TrackerMemory;
 /// BruteForceMemory
 ///
 /// Always returns all the elements that have been seen.
+#[derive(Clone)]
 pub struct BruteForceMemory {
     elements: Vec<Element>,
 }
@@ -43,11 +44,16 @@ impl TrackerMemory for BruteForceMemory {
     fn new_default(&self) -> Box<dyn TrackerMemory + Send + Sync> {
         Box::new(Self::new())
     }
+
+    fn clone(&self) -> Box<dyn TrackerMemory + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
 }
 
 /// MostFrequentMemory
 ///
 /// Returns the most frequent element that has been seen.
+#[derive(Clone)]
 pub struct MostFrequentMemory {
     mf_count: u32,
     elements: Vec<Element>,
@@ -112,6 +118,10 @@ impl TrackerMemory for MostFrequentMemory {
     fn new_default(&self) -> Box<dyn TrackerMemory + Send + Sync> {
         Box::new(Self::new())
     }
+
+    fn clone(&self) -> Box<dyn TrackerMemory + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
 }
 
 /// LongShortTermMemory
@@ -161,20 +171,37 @@ impl TrackerMemory for LongShortTermMemory {
     fn new_default(&self) -> Box<dyn TrackerMemory + Send + Sync> {
         Box::new(Self::new(self.long_memory.new_default()))
     }
+
+    fn clone(&self) -> Box<dyn TrackerMemory + Send + Sync> {
+        Box::new(Self {
+            long_memory: self.long_memory.clone(),
+            latest_element: self.latest_element.clone(),
+        })
+    }
 }
 
 /// MedianWordMemory
 ///
-/// Computes and returns the median word from the words that have been seen.
+/// Computes and returns the median word from the words that have been seen,
+/// with the given strategy, the median word is updated incrementally at each match.
+///
+/// The median is read once per match and cached until the next match, such that
+/// the refined median is computed at most once per word.
+///
+/// If the median word can't be created (all the word IDs have been used),
+/// the previous one is kept.
+#[derive(Clone)]
 pub struct MedianWordMemory {
-    elements: Vec<Element>,
+    strategy: MedianWordStrategy,
+    words: MedianWordBuilder,
     median_word: Option<Element>,
 }
 
 impl MedianWordMemory {
-    pub fn new() -> Self {
+    pub fn new(strategy: MedianWordStrategy) -> Self {
         Self {
-            elements: Vec::new(),
+            strategy,
+            words: MedianWordBuilder::new(strategy),
             median_word: None,
         }
     }
@@ -184,22 +211,111 @@ impl TrackerMemory for MedianWordMemory {
     fn signal_no_matching_element(&mut self) {}
 
     fn signal_matching_element(&mut self, element: Element) {
-        if element.is_none() {
+        let Element::Word(word) = element else {
+            return;
+        };
+        self.words.push(&word);
+        match self.words.median() {
+            Ok(median_word) => self.median_word = median_word.map(Element::Word),
+            // the memory can't fail, the previous median word is kept
+            Err(e) => log::warn!("keeping the previous median word: {}", e),
+        }
+    }
+
+    fn get_elements(&self) -> Vec<&Element> {
+        match self.median_word {
+            Some(ref w) => vec![w],
+            None => Vec::new(),
+        }
+    }
+
+    fn new_default(&self) -> Box<dyn TrackerMemory + Send + Sync> {
+        Box::new(Self::new(self.strategy))
+    }
+
+    fn clone(&self) -> Box<dyn TrackerMemory + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+}
+
+/// WeightedMedianWordMemory
+///
+/// Computes and returns the median word from the words that have been seen,
+/// each word votes with the confidence of its match, decayed by `recency_decay`
+/// for each frame since it was seen, see `compute_weighted_median_word`.
+///
+/// The decay applies the same factor to all the previous words, which doesn't
+/// change the median, so the new words are instead weighted by the inverse
+/// of the decay since the scale frame and the median word is updated
+/// incrementally at each match. When this weight gets too large, the weights
+/// of the previous words are scaled down and the scale frame is moved.
+///
+/// Note: within a multiword memory, the frames are only counted when the word
+/// of the memory is matched.
+#[derive(Clone)]
+pub struct WeightedMedianWordMemory {
+    recency_decay: f32,
+    words: IncrementalMedianWord,
+    /// Number of frames signaled so far
+    num_frames: usize,
+    /// Frame from which the inverse of the decay is applied to the new words
+    scale_frame: usize,
+    median_word: Option<Element>,
+}
+
+impl WeightedMedianWordMemory {
+    /// Maximal inverse of the decay applied to a new word
+    /// before the weights are scaled down.
+    const MAX_SCALE: f32 = 1e12;
+
+    pub fn new(recency_decay: f32) -> Self {
+        Self {
+            recency_decay,
+            words: IncrementalMedianWord::new(),
+            num_frames: 0,
+            scale_frame: 0,
+            median_word: None,
+        }
+    }
+
+    fn push_word(&mut self, word: &Word, confidence: f32) {
+        if self.recency_decay == 0. {
+            // only the latest word has a weight
+            self.words = IncrementalMedianWord::new();
+            self.words.push_weighted(word, confidence);
             return;
         }
-        self.elements.push(element);
-        let median_word = compute_median_word(
-            &self
-                .elements
-                .iter()
-                .filter_map(|e| match e {
-                    Element::Word(w) => Some(w),
-                    _ => None,
-                })
-                .collect::<Vec<&Word>>(),
-        );
 
-        self.median_word = median_word.map(Element::Word);
+        let age = (self.num_frames - self.scale_frame) as i32;
+        let mut scale = self.recency_decay.powi(-age);
+        if scale > Self::MAX_SCALE {
+            self.words.scale_weights(self.recency_decay.powi(age));
+            self.scale_frame = self.num_frames;
+            scale = 1.;
+        }
+        self.words.push_weighted(word, confidence * scale);
+    }
+}
+
+impl TrackerMemory for WeightedMedianWordMemory {
+    fn signal_no_matching_element(&mut self) {
+        self.num_frames += 1;
+    }
+
+    fn signal_matching_element(&mut self, element: Element) {
+        self.signal_matching_element_with_confidence(element, 1.);
+    }
+
+    fn signal_matching_element_with_confidence(&mut self, element: Element, confidence: f32) {
+        if let Element::Word(word) = element {
+            self.push_word(&word, confidence);
+            match self.words.median() {
+                Ok(median_word) => self.median_word = median_word.map(Element::Word),
+                // the memory can't fail, the previous median word is kept
+                Err(e) => log::warn!("keeping the previous median word: {}", e),
+            }
+        }
+        self.num_frames += 1;
     }
 
     fn get_elements(&self) -> Vec<&Element> {
@@ -210,7 +326,11 @@ impl TrackerMemory for MedianWordMemory {
     }
 
     fn new_default(&self) -> Box<dyn TrackerMemory + Send + Sync> {
-        Box::new(Self::new())
+        Box::new(Self::new(self.recency_decay))
+    }
+
+    fn clone(&self) -> Box<dyn TrackerMemory + Send + Sync> {
+        Box::new(Clone::clone(self))
     }
 }
 
@@ -290,6 +410,10 @@ impl TrackerMemory for MultiWordMemory {
     fn signal_no_matching_element(&mut self) {}
 
     fn signal_matching_element(&mut self, element: Element) {
+        self.signal_matching_element_with_confidence(element, 1.);
+    }
+
+    fn signal_matching_element_with_confidence(&mut self, element: Element, confidence: f32) {
         let words = match element {
             Element::MultiWords(ws) => ws,
             _ => return,
@@ -299,10 +423,11 @@ impl TrackerMemory for MultiWordMemory {
         for word in words.into_iter() {
             let (idx, dist) = self.get_word_dist(&word);
             if dist >= self.threshold_match {
-                self.memories[idx].signal_matching_element(Element::Word(word));
+                self.memories[idx]
+                    .signal_matching_element_with_confidence(Element::Word(word), confidence);
             } else {
                 let mut memory = self.template.new_default();
-                memory.signal_matching_element(Element::Word(word.clone()));
+                memory.signal_matching_element_with_confidence(Element::Word(word), confidence);
                 self.memories.push(memory);
             }
         }
@@ -324,4 +449,60 @@ impl TrackerMemory for MultiWordMemory {
             self.threshold_match,
         ))
     }
+
+    fn clone(&self) -> Box<dyn TrackerMemory + Send + Sync> {
+        Box::new(Self {
+            memories: self.memories.iter().map(|m| (**m).clone()).collect(),
+            template: self.template.new_default(),
+            distance_metric: self.distance_metric.clone(),
+            threshold_match: self.threshold_match,
+            current_element: self.current_element.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::distances::compute_weighted_median_word;
+
+    use super::*;
+
+    #[test]
+    fn test_weighted_median_word_memory() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let alphabet = ['a', 'e', 'l', 'n'];
+        let recency_decay = 0.5;
+
+        let mut memory = WeightedMedianWordMemory::new(recency_decay);
+        let mut words = Vec::new();
+        // enough frames for the weights to be scaled down
+        for frame_idx in 0..120 {
+            if rng.random_bool(0.3) {
+                memory.signal_no_matching_element();
+                continue;
+            }
+            let length = rng.random_range(3..6);
+            let word = Word::new(
+                (0..length)
+                    .map(|_| alphabet[rng.random_range(0..alphabet.len())])
+                    .collect::<String>(),
+            );
+            let confidence = rng.random_range(0.5..1.);
+            memory.signal_matching_element_with_confidence(Element::Word(word.clone()), confidence);
+            words.push((word, confidence, frame_idx));
+
+            let weighted_words = words
+                .iter()
+                .map(|(word, confidence, idx)| {
+                    (word, confidence * recency_decay.powi(frame_idx - idx))
+                })
+                .collect::<Vec<_>>();
+            let expected = compute_weighted_median_word(&weighted_words)
+                .unwrap()
+                .map(Element::Word);
+            assert_eq!(memory.get_elements(), expected.iter().collect::<Vec<_>>());
+        }
+    }
 }
@@ -1,47 +1,89 @@
+mod interner;
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
 use log::warn;
 use unicode_segmentation::UnicodeSegmentation;
 
+use crate::error::Result;
+use interner::WordData;
+pub use interner::WordId;
+
 pub type GraphemeType = u64;
 
 /// Word
 ///
 /// This is a string type that is optimized for distance calculations.
 ///
-/// The raw string is owned and not a reference to the original string because of
-/// complications with lifetime management. Also the array of clusters takes more
-/// memory than the raw string anyway.
+/// The word is a handle to an interned string (see `Interner`): identical strings
+/// are stored once, whatever the number of records, memories or normalized copies
+/// they appear in. This makes the word cheap to clone, to compare and to hash, the
+/// ID can be used as key instead of the string. The string is freed with its last word.
 ///
 /// The graphemes store the same string as a sequence of grapheme clusters.
 /// Each grapheme is stored as a u64, this is an optimization and is not always valid
 /// (that is there exists grapheme that are larger than 8 bytes). However in practice
 /// grapheme will (almost) always be smaller than 8 bytes.
-#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+#[derive(Clone)]
 pub struct Word {
-    pub raw: String,
-    pub graphemes: Vec<GraphemeType>,
+    data: Arc<WordData>,
 }
 
 impl Word {
-    pub fn new(raw: String) -> Self {
-        Self {
-            graphemes: raw
-                .as_str()
-                .graphemes(true)
-                .map(|g| Self::string_to_grapheme(g))
-                .collect(),
-            raw,
-        }
+    /// Creates a word from its string.
+    ///
+    /// # Panics
+    /// Panics if all the word IDs have been used, see `try_new`.
+    pub fn new(raw: impl AsRef<str>) -> Self {
+        Self::try_new(raw).unwrap()
     }
 
-    pub fn from_graphemes(graphemes: Vec<GraphemeType>) -> Self {
-        Self {
-            raw: graphemes
-                .iter()
-                .map(|&g| Self::grapheme_to_string(g))
-                .collect::<Vec<String>>()
-                .join(""),
-            graphemes,
+    /// Creates a word from its string, the input data should be cast with it
+    /// such that running out of word IDs is reported as an error.
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn try_new(raw: impl AsRef<str>) -> Result<Self> {
+        let raw = raw.as_ref();
+        Ok(Self {
+            data: interner::intern(raw, || {
+                raw.graphemes(true).map(Self::string_to_grapheme).collect()
+            })?,
+        })
+    }
+
+    /// Creates a word from its graphemes.
+    ///
+    /// The graphemes are joined and segmented again, such that the graphemes of a
+    /// word only depend on its string, whatever the order in which the words are
+    /// interned. They may differ from the given ones (e.g. a combining character
+    /// given on its own is merged with the previous grapheme).
+    ///
+    /// # Errors
+    /// Returns an InvalidData error if all the word IDs have been used.
+    pub fn from_graphemes(graphemes: Vec<GraphemeType>) -> Result<Self> {
+        let mut raw = Vec::with_capacity(graphemes.len());
+        for &grapheme in graphemes.iter() {
+            raw.extend(Self::unpack_grapheme(grapheme));
         }
+        Self::try_new(String::from_utf8(raw).unwrap())
+    }
+
+    /// Returns the ID of the interned string.
+    pub fn id(&self) -> WordId {
+        self.data.id
+    }
+
+    pub fn raw(&self) -> &str {
+        &self.data.raw
+    }
+
+    pub fn graphemes(&self) -> &[GraphemeType] {
+        &self.data.graphemes
     }
 
     pub fn grapheme_to_string(grapheme: GraphemeType) -> String {
@@ -80,3 +122,80 @@ impl Word {
         bytes
     }
 }
+
+impl PartialEq for Word {
+    fn eq(&self, other: &Self) -> bool {
+        self.data.id == other.data.id
+    }
+}
+
+impl Eq for Word {}
+
+impl Hash for Word {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.id.hash(state);
+    }
+}
+
+impl fmt::Debug for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Word").field(&self.raw()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interned_words() {
+        let w1 = Word::new("jean");
+        let w2 = Word::from_graphemes(w1.graphemes().to_vec()).unwrap();
+        let w3 = Word::new("jeanne");
+
+        assert_eq!(w1, w2);
+        assert_eq!(w1.id(), w2.id());
+        assert!(std::ptr::eq(w1.raw(), w2.raw()));
+        assert_ne!(w1.id(), w3.id());
+        assert_eq!(w3.raw(), "jeanne");
+        assert_eq!(w3.graphemes().len(), 6);
+    }
+
+    #[test]
+    fn test_freed_words() {
+        let raw = "word-test-freed-words";
+        let w1 = Word::new(raw);
+        let w2 = w1.clone();
+        drop(w1);
+        assert!(interner::is_interned(raw));
+
+        let id = w2.id();
+        drop(w2);
+        assert!(!interner::is_interned(raw));
+        // the IDs are not reused
+        assert_ne!(Word::new(raw).id(), id);
+    }
+
+    #[test]
+    fn test_from_graphemes() {
+        let to_graphemes = |graphemes: &[&str]| {
+            graphemes
+                .iter()
+                .map(|g| Word::string_to_grapheme(g))
+                .collect::<Vec<_>>()
+        };
+
+        // the combining accent is merged with the previous grapheme
+        let word = Word::from_graphemes(to_graphemes(&["z", "e", "\u{301}", "z"])).unwrap();
+        assert_eq!(word.raw(), "ze\u{301}z");
+        assert_eq!(word.graphemes(), to_graphemes(&["z", "e\u{301}", "z"]));
+        assert_eq!(word.graphemes(), Word::new("ze\u{301}z").graphemes());
+
+        // same graphemes when the string is interned first
+        let word = Word::new("ze\u{301}zou");
+        let other =
+            Word::from_graphemes(to_graphemes(&["z", "e", "\u{301}", "z", "o", "u"])).unwrap();
+        assert_eq!(other.graphemes(), word.graphemes());
+        assert_eq!(other.graphemes().len(), 5);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock, RwLock, Weak},
+};
+
+use crate::error::{BlitzBeaverError, Result};
+
+use super::GraphemeType;
+
+pub type WordId = u32;
+
+/// Data of an interned word, shared by all the words with the same string.
+///
+/// The data is removed from the interner when the last word using it is dropped.
+pub struct WordData {
+    pub id: WordId,
+    pub raw: Arc<str>,
+    pub graphemes: Box<[GraphemeType]>,
+}
+
+impl Drop for WordData {
+    fn drop(&mut self) {
+        interner().write().unwrap().remove(&self.raw);
+    }
+}
+
+/// Interner
+///
+/// Arena of the raw strings and grapheme arrays of the words, each unique string
+/// is stored once and identified by an ID, the words are handles to it.
+///
+/// The interner is global such that the words created at any stage (casting,
+/// memories, normalization) share the same IDs. It only holds weak references:
+/// the data of a string is freed once no word uses it anymore, that is when the
+/// frames and the engine of a run are dropped.
+///
+/// The IDs are never reused, as the distance caches are keyed on them and may
+/// outlive the words (e.g. when the frames are spilled to disk).
+#[derive(Default)]
+struct Interner {
+    words: HashMap<Arc<str>, Weak<WordData>>,
+    next_id: WordId,
+}
+
+impl Interner {
+    fn get(&self, raw: &str) -> Option<Arc<WordData>> {
+        self.words.get(raw).and_then(Weak::upgrade)
+    }
+
+    fn insert(
+        &mut self,
+        raw: &str,
+        graphemes: impl FnOnce() -> Vec<GraphemeType>,
+    ) -> Result<Arc<WordData>> {
+        let id = self.next_id;
+        self.next_id = id.checked_add(1).ok_or_else(|| {
+            BlitzBeaverError::InvalidData(format!(
+                "too many unique words, at most {} can be interned",
+                WordId::MAX
+            ))
+        })?;
+        let data = Arc::new(WordData {
+            id,
+            raw: raw.into(),
+            graphemes: graphemes().into_boxed_slice(),
+        });
+        self.words
+            .insert(Arc::clone(&data.raw), Arc::downgrade(&data));
+        Ok(data)
+    }
+
+    /// Removes the entry of the string, unless it has been interned again
+    /// since its last word was dropped.
+    fn remove(&mut self, raw: &str) {
+        if self.words.get(raw).is_some_and(|w| w.strong_count() == 0) {
+            self.words.remove(raw);
+        }
+    }
+}
+
+fn interner() -> &'static RwLock<Interner> {
+    static INTERNER: OnceLock<RwLock<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(|| RwLock::new(Interner::default()))
+}
+
+/// Returns the data of the interned string, interning it if needed,
+/// the graphemes are only computed if the string is not interned yet.
+///
+/// # Errors
+/// Returns an InvalidData error if all the IDs have been used.
+pub fn intern(raw: &str, graphemes: impl FnOnce() -> Vec<GraphemeType>) -> Result<Arc<WordData>> {
+    if let Some(data) = interner().read().unwrap().get(raw) {
+        return Ok(data);
+    }
+
+    let mut interner = interner().write().unwrap();
+    // another thread may have interned it in the meantime
+    if let Some(data) = interner.get(raw) {
+        return Ok(data);
+    }
+    interner.insert(raw, graphemes)
+}
+
+#[cfg(test)]
+pub fn is_interned(raw: &str) -> bool {
+    interner().read().unwrap().get(raw).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exhausted_ids() {
+        let mut interner = Interner {
+            words: HashMap::new(),
+            next_id: WordId::MAX,
+        };
+        let result = interner.insert("interner-exhausted-ids", Vec::new);
+        assert!(matches!(result, Err(BlitzBeaverError::InvalidData(_))));
+    }
+}